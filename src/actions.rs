@@ -0,0 +1,135 @@
+//! 命令动作模块
+//!
+//! 为命令表中的每个命令定义 GPUI 动作，并根据快捷键设置生成按键绑定
+
+use std::rc::Rc;
+use gpui::*;
+
+use crate::config::{Keymap, COMMANDS};
+
+actions!(
+    readrs,
+    [
+        NewFile,
+        OpenFile,
+        SaveFile,
+        SaveAs,
+        OpenFolder,
+        AddFolder,
+        MoveFile,
+        QuickOpen,
+        CommandPalette,
+        Find,
+        NextMatch,
+        PreviousMatch,
+        ReplaceNext,
+        ReplaceAll,
+        ReplaceInWorkspace,
+        UndoReplace,
+        CycleTheme,
+        OpenSettings,
+        OpenKeymap,
+        ToggleBold,
+        ToggleItalic,
+        ToggleStrikethrough,
+        ToggleCode,
+        CycleHeading,
+        ToggleBlockquote,
+        ToggleBulletList,
+        ToggleNumberedList,
+        ToggleTaskList,
+        InsertLink,
+        IndentLines,
+        OutdentLines,
+        MoveLinesUp,
+        MoveLinesDown,
+        ContinueList,
+        IndentListItem,
+        OutdentListItem,
+        PasteMarkdown,
+        PastePlainText,
+        ExportHtml,
+        ExportWorkspaceHtml,
+        ExportPdf,
+        ExportDocx,
+        CheckLinks,
+    ]
+);
+
+/// 命令对应的动作
+pub fn command_action(id: &str) -> Option<Box<dyn Action>> {
+    let action: Box<dyn Action> = match id {
+        "new_file" => Box::new(NewFile),
+        "open_file" => Box::new(OpenFile),
+        "save_file" => Box::new(SaveFile),
+        "save_as" => Box::new(SaveAs),
+        "open_folder" => Box::new(OpenFolder),
+        "add_folder" => Box::new(AddFolder),
+        "move_file" => Box::new(MoveFile),
+        "quick_open" => Box::new(QuickOpen),
+        "command_palette" => Box::new(CommandPalette),
+        "find" => Box::new(Find),
+        "next_match" => Box::new(NextMatch),
+        "previous_match" => Box::new(PreviousMatch),
+        "replace_next" => Box::new(ReplaceNext),
+        "replace_all" => Box::new(ReplaceAll),
+        "replace_in_workspace" => Box::new(ReplaceInWorkspace),
+        "undo_replace" => Box::new(UndoReplace),
+        "cycle_theme" => Box::new(CycleTheme),
+        "open_settings" => Box::new(OpenSettings),
+        "open_keymap" => Box::new(OpenKeymap),
+        "toggle_bold" => Box::new(ToggleBold),
+        "toggle_italic" => Box::new(ToggleItalic),
+        "toggle_strikethrough" => Box::new(ToggleStrikethrough),
+        "toggle_code" => Box::new(ToggleCode),
+        "cycle_heading" => Box::new(CycleHeading),
+        "toggle_blockquote" => Box::new(ToggleBlockquote),
+        "toggle_bullet_list" => Box::new(ToggleBulletList),
+        "toggle_numbered_list" => Box::new(ToggleNumberedList),
+        "toggle_task_list" => Box::new(ToggleTaskList),
+        "insert_link" => Box::new(InsertLink),
+        "indent_lines" => Box::new(IndentLines),
+        "outdent_lines" => Box::new(OutdentLines),
+        "move_lines_up" => Box::new(MoveLinesUp),
+        "move_lines_down" => Box::new(MoveLinesDown),
+        "continue_list" => Box::new(ContinueList),
+        "indent_list_item" => Box::new(IndentListItem),
+        "outdent_list_item" => Box::new(OutdentListItem),
+        "paste_markdown" => Box::new(PasteMarkdown),
+        "paste_plain_text" => Box::new(PastePlainText),
+        "export_html" => Box::new(ExportHtml),
+        "export_workspace_html" => Box::new(ExportWorkspaceHtml),
+        "export_pdf" => Box::new(ExportPdf),
+        "export_docx" => Box::new(ExportDocx),
+        "check_links" => Box::new(CheckLinks),
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// 根据快捷键设置生成按键绑定
+///
+/// 快捷键已在读取设置时检查过格式，GPUI 仍无法解析的快捷键被跳过并返回错误信息
+pub fn key_bindings(keymap: &Keymap) -> (Vec<KeyBinding>, Vec<String>) {
+    let mut bindings = Vec::new();
+    let mut errors = Vec::new();
+
+    for command in COMMANDS {
+        for keys in keymap.bindings(command.id) {
+            let Some(action) = command_action(command.id) else {
+                continue;
+            };
+            // 编辑器命令绑定在输入框这一层，优先于输入框自带的回车、Tab 等快捷键
+            let context = command
+                .context
+                .and_then(|context| KeyBindingContextPredicate::parse(&format!("{} > Input", context)).ok())
+                .map(Rc::new);
+            match KeyBinding::load(keys, action, context, false, None, &DummyKeyboardMapper) {
+                Ok(binding) => bindings.push(binding),
+                Err(e) => errors.push(format!("{} 的快捷键无法识别: {}", command.id, e)),
+            }
+        }
+    }
+
+    (bindings, errors)
+}
@@ -0,0 +1,392 @@
+//! 命令行模块
+//!
+//! 不打开窗口，直接在命令行中完成批量任务：
+//! - `readrs export-pdf <文件或文件夹>... [-o <输出文件或文件夹>]`：把 Markdown 文件导出为 PDF
+//! - `readrs export-docx <文件或文件夹>... [-o <输出文件或文件夹>]`：把 Markdown 文件导出为 Word 文档
+//! - `readrs import <文件或文件夹>... [-o <输出文件或文件夹>]`：把 Word 文档和 HTML 页面转换为 Markdown
+//! - `readrs check-links <文件或文件夹>...`：检查失效的内部链接和图片，有问题时以状态码 1 退出
+//!
+//! 使用与编辑器相同的设置文件
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail, Context, Result};
+use walkdir::WalkDir;
+
+use crate::config::{Settings, SettingsFile};
+use crate::export::{DocxExporter, DocxExportOptions, PdfExporter, PdfExportOptions};
+use crate::file_manager::{check_links, is_markdown_path};
+use crate::import::{self, ImportOptions};
+
+/// 导出子命令的用法说明，`{command}` 和 `{format}` 替换为子命令和格式名称
+const EXPORT_USAGE: &str = "用法: readrs {command} <文件或文件夹>... [-o <输出文件或文件夹>]
+
+  导出文件夹时包含其中所有的 Markdown 文件，并保持目录结构。
+  没有指定输出位置时，{format} 文件保存在源文件旁边。";
+
+/// 导入子命令的用法说明
+const IMPORT_USAGE: &str = "用法: readrs import <文件或文件夹>... [-o <输出文件或文件夹>]
+
+  把 Word 文档（.docx）和 HTML 页面转换为 Markdown，图片保存在 Markdown 文件旁边的 assets 文件夹中。
+  导入文件夹时包含其中所有的 Word 文档和 HTML 页面，保持目录结构，并把页面之间的链接改为指向 Markdown 文件。
+  没有指定输出位置时，Markdown 文件保存在源文件旁边。";
+
+/// 链接检查子命令的用法说明
+const CHECK_LINKS_USAGE: &str = "用法: readrs check-links <文件或文件夹>...
+
+  检查 Markdown 文件中指向不存在的文件、标题和图片的链接，以及重复的标题锚点。
+  wiki 链接（[[笔记]]）只在检查的文件中查找笔记，检查整个文件夹时结果最准确。
+  发现问题时以状态码 1 退出，可用于持续集成。";
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Pdf,
+    Docx,
+}
+
+impl ExportFormat {
+    fn command(self) -> &'static str {
+        match self {
+            Self::Pdf => "export-pdf",
+            Self::Docx => "export-docx",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+        }
+    }
+
+    fn usage(self) -> String {
+        let format = match self {
+            Self::Pdf => "PDF",
+            Self::Docx => "Word",
+        };
+        EXPORT_USAGE.replace("{command}", self.command()).replace("{format}", format)
+    }
+}
+
+/// 按格式创建的导出器
+enum Exporter {
+    Pdf(PdfExporter),
+    Docx(DocxExporter),
+}
+
+impl Exporter {
+    fn new(format: ExportFormat, settings: &Settings) -> Result<Self> {
+        Ok(match format {
+            ExportFormat::Pdf => Self::Pdf(PdfExporter::new(PdfExportOptions::from_settings(&settings.pdf)?)?),
+            ExportFormat::Docx => Self::Docx(DocxExporter::new(DocxExportOptions::from_settings(&settings.docx))?),
+        })
+    }
+
+    /// 导出文件，返回警告
+    fn export_file(&self, markdown: &str, source: &Path, target: &Path) -> Result<Vec<String>> {
+        match self {
+            Self::Pdf(exporter) => exporter.export_file(markdown, Some(source), target),
+            Self::Docx(exporter) => exporter.export_file(markdown, Some(source), target).map(|()| Vec::new()),
+        }
+    }
+}
+
+/// 执行命令行中的子命令
+///
+/// 第一个参数不是子命令时返回 `None`，由调用方启动编辑器；否则返回进程退出码
+pub fn run(args: &[OsString]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.to_str()? {
+        "export-pdf" => export(rest, ExportFormat::Pdf),
+        "export-docx" => export(rest, ExportFormat::Docx),
+        "import" => import(rest),
+        "check-links" => check(rest),
+        _ => return None,
+    };
+
+    Some(match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            2
+        }
+    })
+}
+
+/// 子命令的参数：输入文件和输出位置
+struct Arguments {
+    inputs: Vec<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+/// 解析子命令的参数，请求帮助时打印用法并返回 `None`
+fn parse_args(args: &[OsString], usage: &str) -> Result<Option<Arguments>> {
+    let mut inputs = Vec::new();
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("-o" | "--output") => {
+                let path = args.next().ok_or_else(|| anyhow!("-o 后缺少输出位置\n\n{}", usage))?;
+                output = Some(PathBuf::from(path));
+            }
+            Some("-h" | "--help") => {
+                println!("{}", usage);
+                return Ok(None);
+            }
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+    if inputs.is_empty() {
+        bail!("缺少输入文件\n\n{}", usage);
+    }
+    Ok(Some(Arguments { inputs, output }))
+}
+
+/// 导出文件，有文件导出失败时返回 `false`
+fn export(args: &[OsString], format: ExportFormat) -> Result<bool> {
+    let Some(Arguments { inputs, output }) = parse_args(args, &format.usage())? else {
+        return Ok(true);
+    };
+
+    let jobs = plan_jobs(&inputs, output.as_deref(), format.extension(), is_markdown_path)?;
+    if jobs.is_empty() {
+        bail!("没有找到 Markdown 文件");
+    }
+
+    let settings = load_settings();
+    let exporter = Exporter::new(format, &settings)?;
+
+    let mut failed = 0;
+    for (source, target) in &jobs {
+        let result = fs::read_to_string(source)
+            .with_context(|| format!("无法读取文件: {}", source.display()))
+            .and_then(|markdown| {
+                create_parent(target)?;
+                exporter.export_file(&markdown, source, target)
+            });
+        match result {
+            Ok(warnings) => {
+                println!("{} -> {}", source.display(), target.display());
+                for warning in warnings {
+                    eprintln!("警告: {}: {}", source.display(), warning);
+                }
+            }
+            Err(e) => {
+                eprintln!("导出失败: {}: {:#}", source.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("{} 个文件导出失败，共 {} 个", failed, jobs.len());
+    }
+    Ok(failed == 0)
+}
+
+/// 导入文件，有文件导入失败时返回 `false`
+fn import(args: &[OsString]) -> Result<bool> {
+    let Some(Arguments { inputs, output }) = parse_args(args, IMPORT_USAGE)? else {
+        return Ok(true);
+    };
+
+    let jobs = plan_jobs(&inputs, output.as_deref(), "md", import::is_importable)?;
+    if jobs.is_empty() {
+        bail!("没有找到 Word 文档或 HTML 页面");
+    }
+
+    // 导入整个文件夹时页面之间的链接指向导入后的 Markdown 文件
+    let options = ImportOptions { rewrite_links: inputs.iter().any(|input| input.is_dir()) };
+    let mut failed = 0;
+    for (source, target) in &jobs {
+        let result = create_parent(target).and_then(|()| import::import_file(source, target, &options));
+        match result {
+            Ok(()) => println!("{} -> {}", source.display(), target.display()),
+            Err(e) => {
+                eprintln!("导入失败: {}: {:#}", source.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("{} 个文件导入失败，共 {} 个", failed, jobs.len());
+    }
+    Ok(failed == 0)
+}
+
+/// 检查链接并打印问题，有问题时返回 `false`
+fn check(args: &[OsString]) -> Result<bool> {
+    let Some(Arguments { inputs, output }) = parse_args(args, CHECK_LINKS_USAGE)? else {
+        return Ok(true);
+    };
+    if output.is_some() {
+        bail!("check-links 不支持 -o\n\n{}", CHECK_LINKS_USAGE);
+    }
+
+    let files: Vec<PathBuf> = plan_jobs(&inputs, None, "md", is_markdown_path)?
+        .into_iter()
+        .map(|(source, _)| source)
+        .collect();
+    if files.is_empty() {
+        bail!("没有找到 Markdown 文件");
+    }
+
+    let problems = check_links(&files);
+    for problem in &problems {
+        println!("{}:{}: {}", problem.file.display(), problem.line_number, problem.message());
+    }
+    if problems.is_empty() {
+        eprintln!("没有发现问题，共检查 {} 个文件", files.len());
+    } else {
+        eprintln!("发现 {} 个问题，共检查 {} 个文件", problems.len(), files.len());
+    }
+    Ok(problems.is_empty())
+}
+
+/// 创建输出文件所在的文件夹
+fn create_parent(target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("无法创建文件夹: {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// 读取用户设置，失败时打印错误并使用默认设置
+fn load_settings() -> Settings {
+    let Some(mut file) = SettingsFile::open_default() else {
+        return Settings::default();
+    };
+
+    file.load().unwrap_or_else(|e| {
+        eprintln!("读取设置失败，使用默认设置: {:#}", e);
+        Settings::default()
+    })
+}
+
+/// 确定每个源文件的输出位置
+///
+/// - 输出位置是文件（只有一个输入文件且扩展名相同）时直接写入该文件
+/// - 输出位置是文件夹时，文件夹中的文件保持相对路径
+/// - 没有输出位置时保存在源文件旁边
+///
+/// 文件夹中只包含 `is_source` 接受的文件
+fn plan_jobs(
+    inputs: &[PathBuf],
+    output: Option<&Path>,
+    extension: &str,
+    is_source: fn(&Path) -> bool,
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let single_file_output = match (inputs, output) {
+        ([input], Some(output)) => {
+            input.is_file()
+                && !output.is_dir()
+                && output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        }
+        _ => false,
+    };
+
+    let mut jobs = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let files = WalkDir::new(input)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'))
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file() && is_source(entry.path()));
+            for entry in files {
+                let source = entry.path().to_path_buf();
+                let target = match output {
+                    Some(output) => output.join(source.strip_prefix(input).unwrap_or(&source)),
+                    None => source.clone(),
+                };
+                jobs.push((source, target.with_extension(extension)));
+            }
+        } else if input.is_file() {
+            let target = match output {
+                Some(output) if single_file_output => output.to_path_buf(),
+                Some(output) => output
+                    .join(input.file_name().unwrap_or(input.as_os_str()))
+                    .with_extension(extension),
+                None => input.with_extension(extension),
+            };
+            jobs.push((input.clone(), target));
+        } else {
+            bail!("找不到文件或文件夹: {}", input.display());
+        }
+    }
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plan_jobs() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs/sub")).unwrap();
+        fs::create_dir_all(root.join("docs/.hidden")).unwrap();
+        fs::write(root.join("docs/a.md"), "# A").unwrap();
+        fs::write(root.join("docs/sub/b.markdown"), "# B").unwrap();
+        fs::write(root.join("docs/.hidden/c.md"), "# C").unwrap();
+        fs::write(root.join("docs/notes.txt"), "").unwrap();
+
+        // 没有输出位置：保存在源文件旁边
+        let jobs = plan_jobs(&[root.join("docs")], None, "pdf", is_markdown_path).unwrap();
+        assert_eq!(jobs, vec![
+            (root.join("docs/a.md"), root.join("docs/a.pdf")),
+            (root.join("docs/sub/b.markdown"), root.join("docs/sub/b.pdf")),
+        ]);
+
+        // 输出到文件夹：保持相对路径
+        let jobs = plan_jobs(&[root.join("docs")], Some(&root.join("out")), "pdf", is_markdown_path).unwrap();
+        assert_eq!(jobs[1].1, root.join("out/sub/b.pdf"));
+
+        // 单个文件输出到指定文件
+        let jobs = plan_jobs(&[root.join("docs/a.md")], Some(&root.join("book.pdf")), "pdf", is_markdown_path).unwrap();
+        assert_eq!(jobs, vec![(root.join("docs/a.md"), root.join("book.pdf"))]);
+
+        // 多个文件输出到文件夹
+        let inputs = [root.join("docs/a.md"), root.join("docs/sub/b.markdown")];
+        let jobs = plan_jobs(&inputs, Some(&root.join("out")), "pdf", is_markdown_path).unwrap();
+        assert_eq!(jobs[0].1, root.join("out/a.pdf"));
+        assert_eq!(jobs[1].1, root.join("out/b.pdf"));
+
+        assert!(plan_jobs(&[root.join("missing.md")], None, "pdf", is_markdown_path).is_err());
+
+        // 导入：只包含 Word 文档和 HTML 页面
+        fs::write(root.join("docs/sub/page.html"), "<p>页面</p>").unwrap();
+        let jobs = plan_jobs(&[root.join("docs")], None, "md", import::is_importable).unwrap();
+        assert_eq!(jobs, vec![(root.join("docs/sub/page.html"), root.join("docs/sub/page.md"))]);
+    }
+
+    #[test]
+    fn test_run_ignores_paths() {
+        assert_eq!(run(&[]), None);
+        assert_eq!(run(&[OsString::from("notes.md")]), None);
+        assert_eq!(run(&[OsString::from("export-pdf")]), Some(2));
+        assert_eq!(run(&[OsString::from("export-docx"), OsString::from("--help")]), Some(0));
+        assert_eq!(run(&[OsString::from("import"), OsString::from("missing.html")]), Some(2));
+    }
+
+    #[test]
+    fn test_check_links_command() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.md"), "# A\n\n[B](b.md#b)\n").unwrap();
+        fs::write(root.join("b.md"), "# B\n").unwrap();
+        assert_eq!(run(&[OsString::from("check-links"), root.as_os_str().to_owned()]), Some(0));
+
+        fs::write(root.join("b.md"), "# C\n").unwrap();
+        assert_eq!(run(&[OsString::from("check-links"), root.as_os_str().to_owned()]), Some(1));
+        assert_eq!(run(&[OsString::from("check-links"), root.join("c.md").into_os_string()]), Some(2));
+    }
+}
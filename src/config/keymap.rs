@@ -0,0 +1,496 @@
+//! 快捷键模块
+//!
+//! 管理所有命令及其快捷键：
+//! - 命令表：每个命令的标识、名称和默认快捷键
+//! - 用户快捷键文件（`keymap.toml`）覆盖默认快捷键，空数组表示取消绑定
+//! - 检查无法识别的命令、格式错误的快捷键和冲突
+//! - 命令面板的模糊搜索
+//!
+//! 快捷键使用 GPUI 的写法，如 `secondary-shift-p`（`secondary` 在 macOS 上为 Cmd，其他平台为 Ctrl），
+//! 多个按键组合用空格分隔
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::file_manager::fuzzy_match;
+
+use super::settings::modified_time;
+
+/// 命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandInfo {
+    /// 命令标识，用于快捷键文件
+    pub id: &'static str,
+    /// 在命令面板中显示的名称
+    pub title: &'static str,
+    /// 默认快捷键
+    pub default_bindings: &'static [&'static str],
+    /// 快捷键生效的上下文（`None` 表示整个窗口）
+    pub context: Option<&'static str>,
+}
+
+/// 编辑区的快捷键上下文，格式化命令只在编辑区获得焦点时生效
+pub const EDITOR_CONTEXT: &str = "MarkdownEditor";
+
+/// 所有命令
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo { id: "new_file", title: "新建文件", default_bindings: &["secondary-n"], context: None },
+    CommandInfo { id: "open_file", title: "打开文件", default_bindings: &["secondary-o"], context: None },
+    CommandInfo { id: "save_file", title: "保存", default_bindings: &["secondary-s"], context: None },
+    CommandInfo { id: "save_as", title: "另存为", default_bindings: &["secondary-shift-s"], context: None },
+    CommandInfo { id: "open_folder", title: "打开文件夹", default_bindings: &["secondary-shift-o"], context: None },
+    CommandInfo { id: "add_folder", title: "添加文件夹到工作区", default_bindings: &[], context: None },
+    CommandInfo { id: "move_file", title: "移动/重命名文件", default_bindings: &[], context: None },
+    CommandInfo { id: "quick_open", title: "快速打开", default_bindings: &["secondary-p"], context: None },
+    CommandInfo { id: "command_palette", title: "命令面板", default_bindings: &["secondary-shift-p", "f1"], context: None },
+    CommandInfo { id: "find", title: "查找", default_bindings: &["secondary-f"], context: None },
+    CommandInfo { id: "next_match", title: "下一个匹配", default_bindings: &["f3"], context: None },
+    CommandInfo { id: "previous_match", title: "上一个匹配", default_bindings: &["shift-f3"], context: None },
+    CommandInfo { id: "replace_next", title: "替换", default_bindings: &["secondary-alt-f"], context: None },
+    CommandInfo { id: "replace_all", title: "全部替换", default_bindings: &[], context: None },
+    CommandInfo { id: "replace_in_workspace", title: "在工作区中替换", default_bindings: &["secondary-shift-h"], context: None },
+    CommandInfo { id: "undo_replace", title: "撤销工作区替换", default_bindings: &[], context: None },
+    CommandInfo { id: "cycle_theme", title: "切换主题", default_bindings: &[], context: None },
+    CommandInfo { id: "open_settings", title: "打开设置", default_bindings: &["secondary-,"], context: None },
+    CommandInfo { id: "open_keymap", title: "打开快捷键设置", default_bindings: &[], context: None },
+    CommandInfo { id: "export_html", title: "导出为 HTML", default_bindings: &[], context: None },
+    CommandInfo { id: "export_workspace_html", title: "导出工作区为 HTML", default_bindings: &[], context: None },
+    CommandInfo { id: "export_pdf", title: "导出为 PDF", default_bindings: &[], context: None },
+    CommandInfo { id: "export_docx", title: "导出为 Word", default_bindings: &[], context: None },
+    CommandInfo { id: "check_links", title: "检查链接", default_bindings: &[], context: None },
+    CommandInfo { id: "toggle_bold", title: "粗体", default_bindings: &["secondary-b"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "toggle_italic", title: "斜体", default_bindings: &["secondary-i"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo {
+        id: "toggle_strikethrough",
+        title: "删除线",
+        default_bindings: &["secondary-shift-x"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo { id: "toggle_code", title: "行内代码", default_bindings: &["secondary-e"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo {
+        id: "cycle_heading",
+        title: "切换标题级别",
+        default_bindings: &["secondary-alt-h"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo {
+        id: "toggle_blockquote",
+        title: "引用",
+        default_bindings: &["secondary-shift-q"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo {
+        id: "toggle_bullet_list",
+        title: "无序列表",
+        default_bindings: &["secondary-shift-8"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo {
+        id: "toggle_numbered_list",
+        title: "有序列表",
+        default_bindings: &["secondary-shift-7"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo {
+        id: "toggle_task_list",
+        title: "任务列表",
+        default_bindings: &["secondary-shift-9"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo { id: "insert_link", title: "插入链接", default_bindings: &["secondary-k"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "indent_lines", title: "增加缩进", default_bindings: &["secondary-]"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "outdent_lines", title: "减少缩进", default_bindings: &["secondary-["], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "move_lines_up", title: "上移行", default_bindings: &["alt-up"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "move_lines_down", title: "下移行", default_bindings: &["alt-down"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "continue_list", title: "换行并续写列表", default_bindings: &["enter"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "indent_list_item", title: "增加列表层级", default_bindings: &["tab"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo { id: "outdent_list_item", title: "减少列表层级", default_bindings: &["shift-tab"], context: Some(EDITOR_CONTEXT) },
+    CommandInfo {
+        id: "paste_markdown",
+        title: "粘贴（网页和 Word 内容转换为 Markdown）",
+        default_bindings: &["secondary-v"],
+        context: Some(EDITOR_CONTEXT),
+    },
+    CommandInfo {
+        id: "paste_plain_text",
+        title: "粘贴为纯文本",
+        default_bindings: &["secondary-shift-v"],
+        context: Some(EDITOR_CONTEXT),
+    },
+];
+
+/// 快捷键中可以使用的修饰键
+const MODIFIERS: &[&str] = &["secondary", "ctrl", "alt", "shift", "cmd", "super", "win", "fn"];
+
+/// 按标识查找命令
+pub fn command(id: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|command| command.id == id)
+}
+
+/// 快捷键文件中的一项：单个快捷键或快捷键数组
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BindingEntry {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// 命令与快捷键的对应关系
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// 命令标识 -> 快捷键
+    bindings: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = COMMANDS
+            .iter()
+            .map(|command| {
+                let keys = command.default_bindings.iter().map(|key| key.to_string()).collect();
+                (command.id, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// 解析快捷键文件内容，在默认快捷键的基础上覆盖
+    ///
+    /// 文件格式错误时返回错误；无法识别的命令、格式错误的快捷键和冲突作为警告返回，
+    /// 出错的项被忽略
+    pub fn parse(content: &str) -> Result<(Self, Vec<String>)> {
+        let entries: BTreeMap<String, BindingEntry> =
+            toml::from_str(content).map_err(|e| anyhow!("快捷键格式错误: {}", e))?;
+
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+
+        for (id, entry) in entries {
+            let Some(command) = command(&id) else {
+                warnings.push(format!("未知的命令 \"{}\"", id));
+                continue;
+            };
+
+            let keys = match entry {
+                BindingEntry::One(key) if key.trim().is_empty() => Vec::new(),
+                BindingEntry::One(key) => vec![key],
+                BindingEntry::Many(keys) => keys,
+            };
+
+            let mut valid = Vec::new();
+            for key in keys {
+                let key = normalize_keystrokes(&key);
+                if is_valid_keystrokes(&key) {
+                    valid.push(key);
+                } else {
+                    warnings.push(format!("{} 的快捷键 \"{}\" 格式错误", id, key));
+                }
+            }
+            keymap.bindings.insert(command.id, valid);
+        }
+
+        warnings.extend(keymap.conflicts());
+        Ok((keymap, warnings))
+    }
+
+    /// 命令的快捷键
+    pub fn bindings(&self, id: &str) -> &[String] {
+        self.bindings.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// 按命令表的顺序列出所有命令和快捷键
+    pub fn iter(&self) -> impl Iterator<Item = (&'static CommandInfo, &[String])> + '_ {
+        COMMANDS.iter().map(move |command| (command, self.bindings(command.id)))
+    }
+
+    /// 绑定到多个命令的快捷键
+    fn conflicts(&self) -> Vec<String> {
+        let mut owners: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (command, keys) in self.iter() {
+            for key in keys {
+                owners.entry(key.as_str()).or_default().push(command.id);
+            }
+        }
+
+        owners
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(key, ids)| format!("快捷键 \"{}\" 同时绑定到 {}", key, ids.join("、")))
+            .collect()
+    }
+
+    /// 生成列出所有命令和默认快捷键的快捷键文件，方便用户修改
+    pub fn template() -> String {
+        let mut content = String::from(
+            "# ReadRS 快捷键设置，修改后重启生效\n\
+             # 格式：命令 = \"快捷键\" 或 [\"快捷键\", ...]，空数组表示取消绑定\n\
+             # secondary 在 macOS 上为 Cmd，其他平台为 Ctrl\n\n",
+        );
+
+        for command in COMMANDS {
+            let keys: Vec<String> = command.default_bindings.iter().map(|key| format!("\"{}\"", key)).collect();
+            content.push_str(&format!("# {}\n# {} = [{}]\n", command.title, command.id, keys.join(", ")));
+        }
+
+        content
+    }
+}
+
+/// 去掉快捷键中多余的空白
+///
+/// 不转换大小写：GPUI 中单个大写字母表示同时按下 Shift
+fn normalize_keystrokes(keys: &str) -> String {
+    keys.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 检查快捷键格式：每个按键组合由若干修饰键和一个按键组成
+pub fn is_valid_keystrokes(keys: &str) -> bool {
+    let mut chords = keys.split_whitespace().peekable();
+    if chords.peek().is_none() {
+        return false;
+    }
+
+    chords.all(|chord| {
+        let (modifiers, key) = split_chord(chord);
+        !key.is_empty() && modifiers.iter().all(|m| MODIFIERS.contains(&m.to_lowercase().as_str()))
+    })
+}
+
+/// 把按键组合拆分为修饰键和按键（`ctrl--` 的按键为 `-`）
+fn split_chord(chord: &str) -> (Vec<&str>, &str) {
+    if let Some(prefix) = chord.strip_suffix("--") {
+        return (prefix.split('-').collect(), "-");
+    }
+    if chord == "-" {
+        return (Vec::new(), "-");
+    }
+
+    let mut parts: Vec<&str> = chord.split('-').collect();
+    let key = parts.pop().unwrap_or_default();
+    (parts, key)
+}
+
+/// 把快捷键转换为显示形式，如 `secondary-shift-p` -> `Ctrl+Shift+P`
+pub fn format_keystrokes(keys: &str) -> String {
+    keys.split_whitespace()
+        .map(|chord| {
+            let (modifiers, key) = split_chord(chord);
+            let mut parts: Vec<String> = modifiers
+                .iter()
+                .map(|modifier| match modifier.to_lowercase().as_str() {
+                    "secondary" if cfg!(target_os = "macos") => "Cmd".to_string(),
+                    "secondary" | "ctrl" => "Ctrl".to_string(),
+                    "cmd" | "super" | "win" if cfg!(target_os = "macos") => "Cmd".to_string(),
+                    "cmd" | "super" | "win" => "Super".to_string(),
+                    "alt" => "Alt".to_string(),
+                    "shift" => "Shift".to_string(),
+                    "fn" => "Fn".to_string(),
+                    other => other.to_string(),
+                })
+                .collect();
+
+            let mut chars = key.chars();
+            let key = match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            };
+            parts.push(key);
+            parts.join("+")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 命令面板中的一个结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandMatch {
+    /// 匹配的命令
+    pub command: &'static CommandInfo,
+    /// 得分，越高越好
+    pub score: i64,
+    /// 命令名称中匹配字符的字节范围，用于高亮
+    pub title_ranges: Vec<Range<usize>>,
+}
+
+/// 按名称或标识模糊搜索命令，空查询按命令表顺序返回所有命令
+pub fn search_commands(query: &str) -> Vec<CommandMatch> {
+    let mut matches: Vec<CommandMatch> = COMMANDS
+        .iter()
+        .filter_map(|command| {
+            let by_title = fuzzy_match(query, command.title);
+            let by_id = fuzzy_match(query, command.id);
+
+            match (by_title, by_id) {
+                (Some(title), id) if id.as_ref().is_none_or(|id| title.score >= id.score) => Some(CommandMatch {
+                    command,
+                    score: title.score,
+                    title_ranges: title.highlight_ranges(command.title),
+                }),
+                (_, Some(id)) => Some(CommandMatch { command, score: id.score, title_ranges: Vec::new() }),
+                (_, None) => None,
+            }
+        })
+        .collect();
+
+    // 稳定排序，得分相同时保持命令表的顺序
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+/// 快捷键文件
+///
+/// 记录上次读取时的修改时间，用于判断文件是否被修改
+#[derive(Debug, Clone)]
+pub struct KeymapFile {
+    /// 快捷键文件路径
+    path: PathBuf,
+    /// 上次读取时的修改时间（文件不存在时为 `None`）
+    modified: Option<SystemTime>,
+}
+
+impl KeymapFile {
+    /// 使用指定的快捷键文件
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), modified: None }
+    }
+
+    /// 使用配置目录中的 `keymap.toml`；无法确定配置目录时返回 `None`
+    pub fn open_default() -> Option<Self> {
+        dirs::config_dir().map(|dir| Self::new(dir.join("readrs").join("keymap.toml")))
+    }
+
+    /// 快捷键文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取快捷键，返回快捷键和警告；文件不存在时使用默认快捷键
+    pub fn load(&mut self) -> Result<(Keymap, Vec<String>)> {
+        self.modified = modified_time(&self.path);
+
+        if !self.path.exists() {
+            return Ok((Keymap::default(), Vec::new()));
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("无法读取快捷键文件: {}", self.path.display()))?;
+        Keymap::parse(&content).with_context(|| format!("快捷键文件 {}", self.path.display()))
+    }
+
+    /// 文件自上次读取后是否被修改、创建或删除
+    pub fn has_changed(&self) -> bool {
+        modified_time(&self.path) != self.modified
+    }
+
+    /// 快捷键文件不存在时写入模板，方便用户编辑
+    pub fn create_if_missing(&self) -> Result<()> {
+        if self.path.exists() {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("无法创建配置目录: {}", dir.display()))?;
+        }
+        fs::write(&self.path, Keymap::template())
+            .with_context(|| format!("无法写入快捷键文件: {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_keymap() {
+        let keymap = Keymap::default();
+        assert!(keymap.conflicts().is_empty());
+        assert_eq!(keymap.bindings("save_file"), ["secondary-s"]);
+        assert!(keymap.bindings("cycle_theme").is_empty());
+
+        for command in COMMANDS {
+            assert!(command.default_bindings.iter().all(|key| is_valid_keystrokes(key)), "{}", command.id);
+        }
+    }
+
+    #[test]
+    fn test_parse_overrides() -> Result<()> {
+        let (keymap, warnings) = Keymap::parse(
+            "save_file = \"ctrl-alt-s\"\ncommand_palette = []\ncycle_theme = [\"f8\", \"secondary-k secondary-t\"]\n",
+        )?;
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(keymap.bindings("save_file"), ["ctrl-alt-s"]);
+        assert!(keymap.bindings("command_palette").is_empty());
+        assert_eq!(keymap.bindings("cycle_theme"), ["f8", "secondary-k secondary-t"]);
+        assert_eq!(keymap.bindings("open_file"), ["secondary-o"]);
+
+        assert!(Keymap::parse("save_file = 1\n").is_err());
+        assert!(Keymap::parse("save_file = \n").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_warnings() -> Result<()> {
+        let (keymap, warnings) = Keymap::parse("frobnicate = \"f9\"\nfind = [\"hyper-f\", \"f4\"]\nopen_file = \"secondary-s\"\n")?;
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].contains("hyper-f"));
+        assert!(warnings[1].contains("frobnicate"));
+        assert!(warnings[2].contains("open_file") && warnings[2].contains("save_file"));
+        assert_eq!(keymap.bindings("find"), ["f4"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keystroke_format() {
+        assert!(is_valid_keystrokes("ctrl--"));
+        assert!(is_valid_keystrokes("secondary-,"));
+        assert!(!is_valid_keystrokes("ctrl-"));
+        assert!(!is_valid_keystrokes(""));
+
+        assert_eq!(format_keystrokes("ctrl-shift-p"), "Ctrl+Shift+P");
+        assert_eq!(format_keystrokes("shift-f3"), "Shift+F3");
+        assert_eq!(format_keystrokes("ctrl-k ctrl-t"), "Ctrl+K Ctrl+T");
+        assert_eq!(format_keystrokes("alt--"), "Alt+-");
+    }
+
+    #[test]
+    fn test_search_commands() {
+        assert_eq!(search_commands("").len(), COMMANDS.len());
+
+        let results = search_commands("保存");
+        assert_eq!(results[0].command.id, "save_file");
+        assert_eq!(results[0].title_ranges, vec![0.."保存".len()]);
+
+        // 也可以按英文标识搜索
+        let results = search_commands("palette");
+        assert_eq!(results[0].command.id, "command_palette");
+        assert!(results[0].title_ranges.is_empty());
+
+        assert!(search_commands("zzzz").is_empty());
+    }
+
+    #[test]
+    fn test_keymap_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut file = KeymapFile::new(temp_dir.path().join("readrs").join("keymap.toml"));
+        assert_eq!(file.load()?, (Keymap::default(), Vec::new()));
+
+        // 模板中的内容全部被注释，读取后仍为默认快捷键
+        file.create_if_missing()?;
+        assert!(file.has_changed());
+        assert_eq!(file.load()?, (Keymap::default(), Vec::new()));
+        assert!(!file.has_changed());
+
+        Ok(())
+    }
+}
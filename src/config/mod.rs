@@ -0,0 +1,19 @@
+//! 配置模块
+//!
+//! 提供跨会话保存的数据，包括：
+//! - 应用状态（搜索历史、最近文件、窗口位置、编辑位置）
+//! - 用户设置（字体、缩进、换行、预览宽度、主题、自动保存、PDF 导出）
+//! - 主题与用户 CSS
+//! - 快捷键与命令表
+
+mod keymap;
+mod settings;
+mod state;
+mod theme;
+mod user_css;
+
+pub use keymap::*;
+pub use settings::*;
+pub use state::*;
+pub use theme::*;
+pub use user_css::*;
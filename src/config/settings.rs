@@ -0,0 +1,516 @@
+//! 用户设置模块
+//!
+//! 从配置目录中的设置文件读取用户设置：
+//! - 支持 TOML（`settings.toml`）和 JSON（`settings.json`）格式
+//! - 缺少的项使用默认值
+//! - 校验取值范围，错误信息可以直接显示给用户
+//! - 通过修改时间检测文件变化，实现热重载
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// 可选的界面主题（`system` 跟随操作系统的明暗设置）
+pub const THEMES: &[&str] = &["light", "dark", "sepia", "high-contrast", "system"];
+
+/// 可选的代码高亮主题（syntect 内置主题）
+pub const CODE_THEMES: &[&str] = &[
+    "InspiredGitHub",
+    "Solarized (dark)",
+    "Solarized (light)",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+    "base16-ocean.dark",
+    "base16-ocean.light",
+];
+
+/// 可选的纸张大小，也可以写作 `宽x高`（毫米），如 `180x240`
+pub const PAPER_SIZES: &[&str] = &["a3", "a4", "a5", "letter", "legal"];
+
+/// 编辑器设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// 字体
+    pub font_family: String,
+    /// 字号（像素）
+    pub font_size: f64,
+    /// 制表符宽度（空格数）
+    pub tab_width: usize,
+    /// 自动换行
+    pub soft_wrap: bool,
+    /// 自动闭合括号、反引号和粗体标记
+    pub auto_pair: bool,
+    /// 编辑区最少显示的行数
+    pub min_lines: usize,
+    /// 编辑区最多显示的行数（超出后滚动）
+    pub max_lines: usize,
+    /// 粘贴或拖入的图片保存的文件夹，相对于文档所在的文件夹
+    pub image_folder: String,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            font_family: "Menlo".to_string(),
+            font_size: 14.0,
+            tab_width: 4,
+            soft_wrap: true,
+            auto_pair: true,
+            min_lines: 10,
+            max_lines: 50,
+            image_folder: "assets".to_string(),
+        }
+    }
+}
+
+/// 预览设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewSettings {
+    /// 预览区占编辑区和预览区总宽度的比例
+    pub width: f64,
+    /// 字体
+    pub font_family: String,
+    /// 字号（像素）
+    pub font_size: f64,
+    /// 行高（字号的倍数）
+    pub line_height: f64,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            width: 0.5,
+            font_family: "Helvetica Neue".to_string(),
+            font_size: 14.0,
+            line_height: 1.6,
+        }
+    }
+}
+
+/// 外观设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    /// 界面主题，见 [`THEMES`]
+    pub theme: String,
+    /// 代码块高亮主题，见 [`CODE_THEMES`]
+    pub code_theme: String,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self {
+            theme: "light".to_string(),
+            code_theme: "InspiredGitHub".to_string(),
+        }
+    }
+}
+
+/// 自动保存设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutosaveSettings {
+    /// 是否自动保存
+    pub enabled: bool,
+    /// 停止输入多久后保存（毫秒）
+    pub delay_ms: u64,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// PDF 导出设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfSettings {
+    /// 纸张大小，见 [`PAPER_SIZES`]
+    pub paper: String,
+    /// 页边距（毫米），与 CSS 相同可以写 1 个（四边）、2 个（上下、左右）或 4 个（上、右、下、左）值
+    pub margins_mm: Vec<f64>,
+    /// 正文字号（磅）
+    pub font_size: f64,
+    /// 行高（字号的倍数）
+    pub line_height: f64,
+    /// 正文字体（为空时自动选择）
+    pub font_family: String,
+    /// 中日韩文字字体（为空时自动选择）
+    pub cjk_font_family: String,
+    /// 代码字体（为空时自动选择）
+    pub code_font_family: String,
+    /// 额外加载的字体文件
+    pub font_files: Vec<String>,
+    /// 页眉模板，可以使用 `{page}`、`{pages}`、`{title}`，用 `|` 分为左、中、右三部分
+    pub header: String,
+    /// 页脚模板，格式与页眉相同
+    pub footer: String,
+}
+
+impl Default for PdfSettings {
+    fn default() -> Self {
+        Self {
+            paper: "a4".to_string(),
+            margins_mm: vec![20.0],
+            font_size: 11.0,
+            line_height: 1.5,
+            font_family: String::new(),
+            cjk_font_family: String::new(),
+            code_font_family: String::new(),
+            font_files: Vec::new(),
+            header: String::new(),
+            footer: "{page} / {pages}".to_string(),
+        }
+    }
+}
+
+impl PdfSettings {
+    /// 纸张的宽和高（毫米），无法识别时返回 `None`
+    pub fn paper_size_mm(&self) -> Option<(f64, f64)> {
+        let paper = self.paper.trim().to_lowercase();
+        let size = match paper.as_str() {
+            "a3" => (297.0, 420.0),
+            "a4" => (210.0, 297.0),
+            "a5" => (148.0, 210.0),
+            "letter" => (215.9, 279.4),
+            "legal" => (215.9, 355.6),
+            _ => {
+                let (width, height) = paper.split_once('x')?;
+                (width.trim().parse().ok()?, height.trim().parse().ok()?)
+            }
+        };
+        (size.0 > 0.0 && size.1 > 0.0).then_some(size)
+    }
+
+    /// 上、右、下、左的页边距（毫米），值的个数不是 1、2 或 4 时返回 `None`
+    pub fn margins(&self) -> Option<[f64; 4]> {
+        match self.margins_mm[..] {
+            [all] => Some([all; 4]),
+            [vertical, horizontal] => Some([vertical, horizontal, vertical, horizontal]),
+            [top, right, bottom, left] => Some([top, right, bottom, left]),
+            _ => None,
+        }
+    }
+}
+
+/// Word 导出设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocxSettings {
+    /// 参考文档（`.docx`），导出时使用其中的样式和页面设置；为空时使用内置样式
+    pub reference_doc: String,
+}
+
+/// 用户设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub editor: EditorSettings,
+    pub preview: PreviewSettings,
+    pub appearance: AppearanceSettings,
+    pub autosave: AutosaveSettings,
+    pub pdf: PdfSettings,
+    pub docx: DocxSettings,
+}
+
+/// 设置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsFormat {
+    Toml,
+    Json,
+}
+
+impl SettingsFormat {
+    /// 根据扩展名判断格式，`.json` 以外的都按 TOML 处理
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+}
+
+impl Settings {
+    /// 解析设置文件内容并校验
+    pub fn parse(content: &str, format: SettingsFormat) -> Result<Self> {
+        let settings: Settings = match format {
+            SettingsFormat::Toml => toml::from_str(content).map_err(|e| anyhow!("设置格式错误: {}", e))?,
+            SettingsFormat::Json => serde_json::from_str(content).map_err(|e| anyhow!("设置格式错误: {}", e))?,
+        };
+
+        let problems = settings.validate();
+        if !problems.is_empty() {
+            return Err(anyhow!("设置无效: {}", problems.join("；")));
+        }
+
+        Ok(settings)
+    }
+
+    /// 校验设置，返回所有问题的描述
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.editor.font_family.trim().is_empty() {
+            problems.push("editor.font_family 不能为空".to_string());
+        }
+        if !(6.0..=72.0).contains(&self.editor.font_size) {
+            problems.push(format!("editor.font_size 应在 6 到 72 之间，当前为 {}", self.editor.font_size));
+        }
+        if !(1..=16).contains(&self.editor.tab_width) {
+            problems.push(format!("editor.tab_width 应在 1 到 16 之间，当前为 {}", self.editor.tab_width));
+        }
+        if self.editor.min_lines == 0 || self.editor.min_lines > self.editor.max_lines {
+            problems.push(format!(
+                "editor.min_lines 应大于 0 且不超过 editor.max_lines（{}），当前为 {}",
+                self.editor.max_lines, self.editor.min_lines
+            ));
+        }
+        let image_folder = Path::new(self.editor.image_folder.trim());
+        if image_folder.as_os_str().is_empty()
+            || image_folder.has_root()
+            || image_folder.components().any(|part| matches!(part, Component::ParentDir | Component::Prefix(_)))
+        {
+            problems.push(format!(
+                "editor.image_folder 应为文档所在文件夹中的相对路径，当前为 \"{}\"",
+                self.editor.image_folder
+            ));
+        }
+        if !(0.1..=0.9).contains(&self.preview.width) {
+            problems.push(format!("preview.width 应在 0.1 到 0.9 之间，当前为 {}", self.preview.width));
+        }
+        if !(6.0..=72.0).contains(&self.preview.font_size) {
+            problems.push(format!("preview.font_size 应在 6 到 72 之间，当前为 {}", self.preview.font_size));
+        }
+        if !(1.0..=3.0).contains(&self.preview.line_height) {
+            problems.push(format!("preview.line_height 应在 1 到 3 之间，当前为 {}", self.preview.line_height));
+        }
+        if !THEMES.contains(&self.appearance.theme.as_str()) {
+            problems.push(format!(
+                "未知的主题 \"{}\"，可选: {}",
+                self.appearance.theme,
+                THEMES.join(", ")
+            ));
+        }
+        if !CODE_THEMES.contains(&self.appearance.code_theme.as_str()) {
+            problems.push(format!(
+                "未知的代码高亮主题 \"{}\"，可选: {}",
+                self.appearance.code_theme,
+                CODE_THEMES.join(", ")
+            ));
+        }
+        if self.autosave.delay_ms < 100 {
+            problems.push(format!("autosave.delay_ms 不能小于 100，当前为 {}", self.autosave.delay_ms));
+        }
+        let paper = self.pdf.paper_size_mm();
+        if paper.is_none() {
+            problems.push(format!(
+                "未知的纸张大小 \"{}\"，可选: {}，或 宽x高（毫米）",
+                self.pdf.paper,
+                PAPER_SIZES.join(", ")
+            ));
+        }
+        match self.pdf.margins() {
+            None => problems.push("pdf.margins_mm 应有 1、2 或 4 个值".to_string()),
+            Some(margins) if margins.iter().any(|margin| !(0.0..=100.0).contains(margin)) => {
+                problems.push(format!("pdf.margins_mm 应在 0 到 100 之间，当前为 {:?}", self.pdf.margins_mm));
+            }
+            Some([top, right, bottom, left]) => {
+                if paper.is_some_and(|(width, height)| left + right >= width * 0.8 || top + bottom >= height * 0.8) {
+                    problems.push("pdf.margins_mm 过大，页面上没有足够的空间".to_string());
+                }
+            }
+        }
+        if !(6.0..=72.0).contains(&self.pdf.font_size) {
+            problems.push(format!("pdf.font_size 应在 6 到 72 之间，当前为 {}", self.pdf.font_size));
+        }
+        if !(1.0..=3.0).contains(&self.pdf.line_height) {
+            problems.push(format!("pdf.line_height 应在 1 到 3 之间，当前为 {}", self.pdf.line_height));
+        }
+        let reference = self.docx.reference_doc.trim();
+        if !reference.is_empty() && !reference.to_lowercase().ends_with(".docx") {
+            problems.push(format!("docx.reference_doc 应为 .docx 文件，当前为 {}", reference));
+        }
+
+        problems
+    }
+
+    /// 序列化为 TOML（用于生成默认设置文件）
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("无法序列化设置")
+    }
+}
+
+/// 设置文件
+///
+/// 记录上次读取时的修改时间，用于判断文件是否被修改
+#[derive(Debug, Clone)]
+pub struct SettingsFile {
+    /// 设置文件路径
+    path: PathBuf,
+    /// 上次读取时的修改时间（文件不存在时为 `None`）
+    modified: Option<SystemTime>,
+}
+
+impl SettingsFile {
+    /// 使用指定的设置文件
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), modified: None }
+    }
+
+    /// 使用配置目录中的设置文件；无法确定配置目录时返回 `None`
+    pub fn open_default() -> Option<Self> {
+        Self::default_path().map(Self::new)
+    }
+
+    /// 默认的设置文件路径
+    ///
+    /// 优先使用 `settings.toml`，只有 `settings.json` 存在时使用 JSON
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = dirs::config_dir()?.join("readrs");
+        let json = dir.join("settings.json");
+        let toml = dir.join("settings.toml");
+
+        Some(if json.exists() && !toml.exists() { json } else { toml })
+    }
+
+    /// 设置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取设置；文件不存在时返回默认设置
+    pub fn load(&mut self) -> Result<Settings> {
+        self.modified = modified_time(&self.path);
+
+        if !self.path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("无法读取设置文件: {}", self.path.display()))?;
+        Settings::parse(&content, SettingsFormat::from_path(&self.path))
+            .with_context(|| format!("设置文件 {}", self.path.display()))
+    }
+
+    /// 文件自上次读取后是否被修改、创建或删除
+    pub fn has_changed(&self) -> bool {
+        modified_time(&self.path) != self.modified
+    }
+
+    /// 设置文件不存在时写入默认设置，方便用户编辑
+    pub fn create_if_missing(&self) -> Result<()> {
+        if self.path.exists() {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("无法创建配置目录: {}", dir.display()))?;
+        }
+
+        let defaults = Settings::default();
+        let content = match SettingsFormat::from_path(&self.path) {
+            SettingsFormat::Toml => defaults.to_toml()?,
+            SettingsFormat::Json => serde_json::to_string_pretty(&defaults)?,
+        };
+        fs::write(&self.path, content)
+            .with_context(|| format!("无法写入设置文件: {}", self.path.display()))
+    }
+}
+
+/// 文件的修改时间，文件不存在时返回 `None`
+pub(super) fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_are_valid() -> Result<()> {
+        assert!(Settings::default().validate().is_empty());
+
+        // 默认设置序列化后能原样读回
+        let toml = Settings::default().to_toml()?;
+        assert_eq!(Settings::parse(&toml, SettingsFormat::Toml)?, Settings::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_partial() -> Result<()> {
+        let settings = Settings::parse("[editor]\ntab_width = 2\n\n[autosave]\nenabled = true\n", SettingsFormat::Toml)?;
+        assert_eq!(settings.editor.tab_width, 2);
+        assert_eq!(settings.editor.font_size, EditorSettings::default().font_size);
+        assert!(settings.autosave.enabled);
+
+        let settings = Settings::parse("[pdf]\npaper = \"180x240\"\nmargins_mm = [15, 10]\n", SettingsFormat::Toml)?;
+        assert_eq!(settings.pdf.paper_size_mm(), Some((180.0, 240.0)));
+        assert_eq!(settings.pdf.margins(), Some([15.0, 10.0, 15.0, 10.0]));
+
+        let settings = Settings::parse(r#"{"preview": {"width": 0.4}}"#, SettingsFormat::Json)?;
+        assert_eq!(settings.preview.width, 0.4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let error = Settings::parse("[editor]\ntab_width = 0\n[appearance]\ntheme = \"pink\"\n", SettingsFormat::Toml)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("editor.tab_width"));
+        assert!(error.contains("pink"));
+
+        let error = Settings::parse("[pdf]\npaper = \"b9\"\nmargins_mm = [10, 20, 30]\n", SettingsFormat::Toml)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("b9"));
+        assert!(error.contains("pdf.margins_mm"));
+
+        let error = Settings::parse("[docx]\nreference_doc = \"template.dotx\"\n", SettingsFormat::Toml)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("docx.reference_doc"));
+
+        for folder in ["", "../images", "/tmp/images"] {
+            let error = Settings::parse(&format!("[editor]\nimage_folder = \"{}\"\n", folder), SettingsFormat::Toml)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("editor.image_folder"));
+        }
+        assert!(Settings::parse("[editor]\nimage_folder = \"./images/notes\"\n", SettingsFormat::Toml).is_ok());
+
+        assert!(Settings::parse("[editor\n", SettingsFormat::Toml).is_err());
+        assert!(Settings::parse("[editor]\ntab_width = \"four\"\n", SettingsFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn test_settings_file_changes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut file = SettingsFile::new(temp_dir.path().join("readrs").join("settings.toml"));
+
+        assert_eq!(file.load()?, Settings::default());
+        assert!(!file.has_changed());
+
+        file.create_if_missing()?;
+        assert!(file.has_changed());
+        assert_eq!(file.load()?, Settings::default());
+        assert!(!file.has_changed());
+
+        fs::remove_file(file.path())?;
+        assert!(file.has_changed());
+
+        Ok(())
+    }
+}
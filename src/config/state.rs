@@ -0,0 +1,303 @@
+//! 应用状态持久化模块
+//!
+//! 在会话之间保存和恢复应用状态：
+//! - 搜索历史
+//! - 最近打开的文件和工作区
+//! - 文件树的展开状态
+//! - 窗口位置和大小
+//! - 每个文件的光标和滚动位置
+//!
+//! 状态以 JSON 格式保存在 XDG 状态目录中（`$XDG_STATE_HOME/readrs/state.json`）
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::file_manager::SearchHistoryEntry;
+
+/// 当前状态文件的格式版本
+pub const STATE_VERSION: u32 = 1;
+
+/// 最多记住多少个文件的光标和滚动位置
+const MAX_FILE_STATES: usize = 200;
+
+/// 窗口位置和大小（逻辑像素）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// 是否最大化（此时位置和大小为还原后的尺寸）
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// 单个文件的编辑位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileState {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 光标所在行（从1开始）
+    pub cursor_line: usize,
+    /// 光标所在列（从1开始）
+    pub cursor_column: usize,
+    /// 预览区的滚动距离（像素）
+    #[serde(default)]
+    pub scroll_offset: f32,
+    /// 编辑区第一个可见的行（从1开始，0 表示没有记录）
+    #[serde(default)]
+    pub editor_line: usize,
+}
+
+/// 需要在会话之间保存的应用状态
+///
+/// 缺少的字段使用默认值，旧版本的状态文件也能读取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppState {
+    /// 状态文件格式版本
+    pub version: u32,
+    /// 搜索历史（最近的在前）
+    pub search_history: Vec<SearchHistoryEntry>,
+    /// 最近打开的文件（最近的在前）
+    pub recent_files: Vec<PathBuf>,
+    /// 最近打开的工作区（最近的在前）
+    pub recent_workspaces: Vec<PathBuf>,
+    /// 上次关闭时工作区中的根文件夹
+    pub workspace_roots: Vec<PathBuf>,
+    /// 上次关闭时打开的文件
+    pub current_file: Option<PathBuf>,
+    /// 文件树中展开的文件夹
+    pub expanded_dirs: Vec<PathBuf>,
+    /// 窗口位置和大小
+    pub window: Option<WindowGeometry>,
+    /// 各文件的编辑位置（最近的在前）
+    pub files: Vec<FileState>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            search_history: Vec::new(),
+            recent_files: Vec::new(),
+            recent_workspaces: Vec::new(),
+            workspace_roots: Vec::new(),
+            current_file: None,
+            expanded_dirs: Vec::new(),
+            window: None,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl AppState {
+    /// 获取文件上次的编辑位置
+    pub fn file_state(&self, path: &Path) -> Option<&FileState> {
+        self.files.iter().find(|state| state.path == path)
+    }
+
+    /// 记住文件的编辑位置，超出上限时丢弃最久未打开的文件
+    pub fn remember_file(&mut self, state: FileState) {
+        self.files.retain(|existing| existing.path != state.path);
+        self.files.insert(0, state);
+        self.files.truncate(MAX_FILE_STATES);
+    }
+
+    /// 去掉已经不存在的文件和文件夹
+    pub fn prune_missing(&mut self) {
+        self.recent_files.retain(|path| path.is_file());
+        self.recent_workspaces.retain(|path| path.is_dir());
+        self.workspace_roots.retain(|path| path.is_dir());
+        self.expanded_dirs.retain(|path| path.is_dir());
+        self.files.retain(|state| state.path.is_file());
+        if self.current_file.as_ref().is_some_and(|path| !path.is_file()) {
+            self.current_file = None;
+        }
+    }
+}
+
+/// 状态文件的读写
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    /// 状态文件路径
+    path: PathBuf,
+}
+
+impl StateStore {
+    /// 使用指定的状态文件
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 使用默认位置的状态文件；无法确定状态目录时返回 `None`
+    pub fn open_default() -> Option<Self> {
+        Self::default_path().map(Self::new)
+    }
+
+    /// 默认的状态文件路径
+    ///
+    /// 优先使用 XDG 状态目录；没有该目录的平台（macOS、Windows）使用本地数据目录
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("readrs").join("state.json"))
+    }
+
+    /// 状态文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取状态；文件不存在时返回默认状态
+    pub fn load(&self) -> Result<AppState> {
+        if !self.path.exists() {
+            return Ok(AppState::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("无法读取状态文件: {}", self.path.display()))?;
+        let mut state: AppState = serde_json::from_str(&content)
+            .with_context(|| format!("状态文件格式错误: {}", self.path.display()))?;
+        state.prune_missing();
+
+        Ok(state)
+    }
+
+    /// 保存状态
+    ///
+    /// 先写入临时文件再重命名，写入中途退出不会损坏原有的状态文件
+    pub fn save(&self, state: &AppState) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("无法创建状态目录: {}", dir.display()))?;
+        }
+
+        let state = AppState { version: STATE_VERSION, ..state.clone() };
+        let content = serde_json::to_string_pretty(&state)?;
+
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, content)
+            .with_context(|| format!("无法写入状态文件: {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("无法写入状态文件: {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_manager::SearchOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        fs::write(&file, "# A")?;
+
+        let store = StateStore::new(temp_dir.path().join("state").join("state.json"));
+        let mut state = AppState {
+            search_history: vec![SearchHistoryEntry {
+                query: "todo".to_string(),
+                options: SearchOptions { case_sensitive: true, ..Default::default() },
+            }],
+            recent_files: vec![file.clone()],
+            workspace_roots: vec![temp_dir.path().to_path_buf()],
+            current_file: Some(file.clone()),
+            window: Some(WindowGeometry { x: 10.0, y: 20.0, width: 800.0, height: 600.0, maximized: false }),
+            ..Default::default()
+        };
+        state.remember_file(FileState {
+            path: file.clone(),
+            cursor_line: 3,
+            cursor_column: 5,
+            scroll_offset: 120.0,
+            editor_line: 2,
+        });
+
+        store.save(&state)?;
+        assert_eq!(store.load()?, state);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_and_partial() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = StateStore::new(temp_dir.path().join("state.json"));
+        assert_eq!(store.load()?, AppState::default());
+
+        // 旧版本或手动编辑的状态文件可能缺少字段
+        fs::write(store.path(), r#"{"search_history": [{"query": "a", "options": {}}]}"#)?;
+        let state = store.load()?;
+        assert_eq!(state.search_history[0].query, "a");
+        assert_eq!(state.search_history[0].options, SearchOptions::default());
+        assert!(state.window.is_none());
+
+        fs::write(store.path(), "not json")?;
+        assert!(store.load().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_missing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        fs::write(&file, "")?;
+        let missing = temp_dir.path().join("missing.md");
+
+        let mut state = AppState {
+            recent_files: vec![missing.clone(), file.clone()],
+            expanded_dirs: vec![temp_dir.path().join("gone")],
+            current_file: Some(missing.clone()),
+            ..Default::default()
+        };
+        state.remember_file(FileState {
+            path: missing,
+            cursor_line: 1,
+            cursor_column: 1,
+            scroll_offset: 0.0,
+            editor_line: 0,
+        });
+        state.prune_missing();
+
+        assert_eq!(state.recent_files, vec![file]);
+        assert!(state.expanded_dirs.is_empty());
+        assert!(state.current_file.is_none());
+        assert!(state.files.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remember_file() {
+        let mut state = AppState::default();
+        for i in 0..MAX_FILE_STATES + 5 {
+            state.remember_file(FileState {
+                path: PathBuf::from(format!("{}.md", i)),
+                cursor_line: i,
+                cursor_column: 1,
+                scroll_offset: 0.0,
+                editor_line: 0,
+            });
+        }
+        state.remember_file(FileState {
+            path: PathBuf::from("10.md"),
+            cursor_line: 42,
+            cursor_column: 1,
+            scroll_offset: 0.0,
+            editor_line: 30,
+        });
+
+        assert_eq!(state.files.len(), MAX_FILE_STATES);
+        assert_eq!(state.files[0].path, PathBuf::from("10.md"));
+        assert_eq!(state.file_state(Path::new("10.md")).map(|s| s.cursor_line), Some(42));
+        assert!(state.file_state(Path::new("0.md")).is_none());
+    }
+}
@@ -0,0 +1,403 @@
+//! 主题模块
+//!
+//! 定义界面和预览使用的颜色与字体：
+//! - 内置主题：明亮、暗黑、护眼（sepia）、高对比度
+//! - `system` 跟随操作系统的明暗设置
+//! - 主题、用户设置和用户 CSS 合并为预览样式，预览和导出使用同一份样式
+//!
+//! 颜色使用 `0xRRGGBB` 形式的整数，与 `gpui::rgb` 的参数一致
+
+use crate::markdown::HtmlStyle;
+
+use super::{PreviewSettings, UserCss};
+
+/// 跟随操作系统明暗设置的主题名称
+pub const SYSTEM_THEME: &str = "system";
+
+/// 界面颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiColors {
+    /// 窗口背景
+    pub background: u32,
+    /// 工具栏和侧边栏背景
+    pub toolbar: u32,
+    /// 工具栏文字
+    pub toolbar_text: u32,
+    /// 侧边栏文字
+    pub sidebar_text: u32,
+    /// 侧边栏边框
+    pub sidebar_border: u32,
+    /// 次要文字
+    pub muted_text: u32,
+    /// 选中项背景
+    pub selection: u32,
+    /// 鼠标悬停背景
+    pub hover: u32,
+    /// 强调色（如模糊匹配的字符）
+    pub accent: u32,
+    /// 浮动面板背景
+    pub overlay: u32,
+    /// 编辑区和预览区背景
+    pub panel: u32,
+    /// 编辑区和预览区标题文字
+    pub panel_header_text: u32,
+    /// 编辑区和预览区之间的边框
+    pub border: u32,
+    /// 标题栏下方的分隔线
+    pub divider: u32,
+    /// 文件树中的 Markdown 文件
+    pub markdown_file: u32,
+    /// 文件树中的文件夹
+    pub folder: u32,
+    /// 错误提示背景
+    pub error_background: u32,
+    /// 错误提示文字
+    pub error_text: u32,
+}
+
+/// 预览颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewColors {
+    /// 背景
+    pub background: u32,
+    /// 正文
+    pub text: u32,
+    /// 标题
+    pub heading: u32,
+    /// 次要文字（六级标题、占位文字）
+    pub muted: u32,
+    /// 链接和已完成的任务
+    pub link: u32,
+    /// 代码背景
+    pub code_background: u32,
+    /// 引用左侧的竖线
+    pub quote_border: u32,
+    /// 引用文字
+    pub quote_text: u32,
+    /// 表格和分隔线
+    pub border: u32,
+    /// 表头背景
+    pub table_header_background: u32,
+    /// 搜索匹配的背景
+    pub highlight: u32,
+    /// 当前搜索匹配的背景
+    pub current_highlight: u32,
+}
+
+/// 主题
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// 主题名称
+    pub name: &'static str,
+    /// 是否为深色主题（决定组件库使用的明暗模式）
+    pub dark: bool,
+    /// 界面颜色
+    pub ui: UiColors,
+    /// 预览颜色
+    pub preview: PreviewColors,
+    /// 标题字体（`None` 时与正文相同）
+    pub heading_font_family: Option<&'static str>,
+    /// 代码字体
+    pub code_font_family: &'static str,
+}
+
+impl Theme {
+    /// 所有内置主题
+    pub fn all() -> Vec<Theme> {
+        vec![Self::light(), Self::dark(), Self::sepia(), Self::high_contrast()]
+    }
+
+    /// 按名称查找内置主题
+    pub fn named(name: &str) -> Option<Theme> {
+        Self::all().into_iter().find(|theme| theme.name == name)
+    }
+
+    /// 根据设置中的主题名称确定主题
+    ///
+    /// `system` 根据 `system_dark` 选择明亮或暗黑主题，未知名称使用明亮主题
+    pub fn for_setting(name: &str, system_dark: bool) -> Theme {
+        if name == SYSTEM_THEME {
+            return if system_dark { Self::dark() } else { Self::light() };
+        }
+        Self::named(name).unwrap_or_else(Self::light)
+    }
+
+    /// 明亮主题
+    pub fn light() -> Theme {
+        Theme {
+            name: "light",
+            dark: false,
+            ui: UiColors {
+                background: 0xf5f5f5,
+                toolbar: 0x2d2d2d,
+                toolbar_text: 0xffffff,
+                sidebar_text: 0xcccccc,
+                sidebar_border: 0x1a1a1a,
+                muted_text: 0x999999,
+                selection: 0x094771,
+                hover: 0x3d3d3d,
+                accent: 0x4fc1ff,
+                overlay: 0x252526,
+                panel: 0xffffff,
+                panel_header_text: 0x666666,
+                border: 0xdddddd,
+                divider: 0xeeeeee,
+                markdown_file: 0x00ccff,
+                folder: 0xffcc00,
+                error_background: 0xfdecea,
+                error_text: 0xb71c1c,
+            },
+            preview: PreviewColors {
+                background: 0xffffff,
+                text: 0x333333,
+                heading: 0x222222,
+                muted: 0x666666,
+                link: 0x0066cc,
+                code_background: 0xf5f5f5,
+                quote_border: 0xdddddd,
+                quote_text: 0x666666,
+                border: 0xdddddd,
+                table_header_background: 0xf5f5f5,
+                highlight: 0xffe066,
+                current_highlight: 0xff9632,
+            },
+            heading_font_family: None,
+            code_font_family: "Menlo",
+        }
+    }
+
+    /// 暗黑主题
+    pub fn dark() -> Theme {
+        Theme {
+            name: "dark",
+            dark: true,
+            ui: UiColors {
+                background: 0x1e1e1e,
+                toolbar: 0x2d2d2d,
+                toolbar_text: 0xffffff,
+                sidebar_text: 0xcccccc,
+                sidebar_border: 0x1a1a1a,
+                muted_text: 0x858585,
+                selection: 0x094771,
+                hover: 0x3d3d3d,
+                accent: 0x4fc1ff,
+                overlay: 0x252526,
+                panel: 0x1e1e1e,
+                panel_header_text: 0x9d9d9d,
+                border: 0x3c3c3c,
+                divider: 0x2b2b2b,
+                markdown_file: 0x00ccff,
+                folder: 0xffcc00,
+                error_background: 0x5a1d1d,
+                error_text: 0xf48771,
+            },
+            preview: PreviewColors {
+                background: 0x1e1e1e,
+                text: 0xd4d4d4,
+                heading: 0xffffff,
+                muted: 0x9d9d9d,
+                link: 0x4fc1ff,
+                code_background: 0x2d2d2d,
+                quote_border: 0x555555,
+                quote_text: 0xa0a0a0,
+                border: 0x3c3c3c,
+                table_header_background: 0x2a2a2a,
+                highlight: 0x6b5900,
+                current_highlight: 0xb35c00,
+            },
+            heading_font_family: None,
+            code_font_family: "Menlo",
+        }
+    }
+
+    /// 护眼主题（暖色纸张）
+    pub fn sepia() -> Theme {
+        Theme {
+            name: "sepia",
+            dark: false,
+            ui: UiColors {
+                background: 0xf4ecd8,
+                toolbar: 0x5b4636,
+                toolbar_text: 0xfbf5e9,
+                sidebar_text: 0xeadfc8,
+                sidebar_border: 0x4a3829,
+                muted_text: 0x9a8a74,
+                selection: 0x8b6b4a,
+                hover: 0x6e5644,
+                accent: 0xf0c674,
+                overlay: 0x4a3829,
+                panel: 0xfbf5e9,
+                panel_header_text: 0x7a6652,
+                border: 0xd9c9a8,
+                divider: 0xe8dcc0,
+                markdown_file: 0xf0c674,
+                folder: 0xe6a84b,
+                error_background: 0xf6d6c8,
+                error_text: 0x8b2500,
+            },
+            preview: PreviewColors {
+                background: 0xfbf5e9,
+                text: 0x433422,
+                heading: 0x2e2216,
+                muted: 0x8a7660,
+                link: 0x8b4513,
+                code_background: 0xefe4cc,
+                quote_border: 0xd2bf99,
+                quote_text: 0x7a6652,
+                border: 0xd9c9a8,
+                table_header_background: 0xefe4cc,
+                highlight: 0xf2d675,
+                current_highlight: 0xe8a04c,
+            },
+            heading_font_family: Some("Georgia"),
+            code_font_family: "Menlo",
+        }
+    }
+
+    /// 高对比度主题
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "high-contrast",
+            dark: true,
+            ui: UiColors {
+                background: 0x000000,
+                toolbar: 0x000000,
+                toolbar_text: 0xffffff,
+                sidebar_text: 0xffffff,
+                sidebar_border: 0xffffff,
+                muted_text: 0xd0d0d0,
+                selection: 0x1a5fb4,
+                hover: 0x333333,
+                accent: 0xffff00,
+                overlay: 0x000000,
+                panel: 0x000000,
+                panel_header_text: 0xffffff,
+                border: 0xffffff,
+                divider: 0xffffff,
+                markdown_file: 0x00ffff,
+                folder: 0xffff00,
+                error_background: 0x000000,
+                error_text: 0xff6060,
+            },
+            preview: PreviewColors {
+                background: 0x000000,
+                text: 0xffffff,
+                heading: 0xffffff,
+                muted: 0xe0e0e0,
+                link: 0xffff00,
+                code_background: 0x1a1a1a,
+                quote_border: 0xffffff,
+                quote_text: 0xffffff,
+                border: 0xffffff,
+                table_header_background: 0x333333,
+                highlight: 0x005fcc,
+                current_highlight: 0xff8c00,
+            },
+            heading_font_family: None,
+            code_font_family: "Menlo",
+        }
+    }
+}
+
+/// 预览样式：主题、用户设置和用户 CSS 合并后的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewStyle {
+    /// 颜色
+    pub colors: PreviewColors,
+    /// 正文字体
+    pub font_family: String,
+    /// 字号（像素）
+    pub font_size: f64,
+    /// 行高（字号的倍数）
+    pub line_height: f64,
+    /// 标题字体（`None` 时与正文相同）
+    pub heading_font_family: Option<String>,
+    /// 代码字体
+    pub code_font_family: String,
+}
+
+impl PreviewStyle {
+    /// 合并主题、设置和用户 CSS（后者优先）
+    pub fn new(theme: &Theme, settings: &PreviewSettings, user_css: &UserCss) -> Self {
+        let mut style = Self {
+            colors: theme.preview,
+            font_family: settings.font_family.clone(),
+            font_size: settings.font_size,
+            line_height: settings.line_height,
+            heading_font_family: theme.heading_font_family.map(str::to_string),
+            code_font_family: theme.code_font_family.to_string(),
+        };
+        user_css.apply(&mut style);
+        style
+    }
+
+    /// 转换为生成 HTML 时使用的样式，`user_css` 附加在内置样式之后
+    pub fn html_style(&self, user_css: &UserCss) -> HtmlStyle {
+        HtmlStyle {
+            font_family: self.font_family.clone(),
+            font_size: self.font_size,
+            line_height: self.line_height,
+            text_color: css_color(self.colors.text),
+            background_color: css_color(self.colors.background),
+            heading_color: css_color(self.colors.heading),
+            heading_font_family: self.heading_font_family.clone(),
+            link_color: css_color(self.colors.link),
+            code_background: css_color(self.colors.code_background),
+            code_font_family: self.code_font_family.clone(),
+            quote_border_color: css_color(self.colors.quote_border),
+            quote_color: css_color(self.colors.quote_text),
+            border_color: css_color(self.colors.border),
+            extra_css: user_css.to_css(),
+        }
+    }
+}
+
+impl Default for PreviewStyle {
+    fn default() -> Self {
+        Self::new(&Theme::light(), &PreviewSettings::default(), &UserCss::default())
+    }
+}
+
+/// 颜色转换为 CSS 的 `#rrggbb` 形式
+pub fn css_color(color: u32) -> String {
+    format!("#{:06x}", color & 0xffffff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_lookup() {
+        assert_eq!(Theme::named("sepia").map(|theme| theme.name), Some("sepia"));
+        assert!(Theme::named("pink").is_none());
+
+        assert_eq!(Theme::for_setting(SYSTEM_THEME, true).name, "dark");
+        assert_eq!(Theme::for_setting(SYSTEM_THEME, false).name, "light");
+        assert_eq!(Theme::for_setting("high-contrast", false).name, "high-contrast");
+        assert_eq!(Theme::for_setting("pink", true).name, "light");
+    }
+
+    #[test]
+    fn test_preview_style_merges_settings_and_css() {
+        let settings = PreviewSettings { font_size: 18.0, ..Default::default() };
+        let (css, warnings) = UserCss::parse("a { color: #ff0000 } h1 { font-family: Palatino }");
+        assert!(warnings.is_empty());
+
+        let style = PreviewStyle::new(&Theme::sepia(), &settings, &css);
+        assert_eq!(style.font_size, 18.0);
+        assert_eq!(style.colors.link, 0xff0000);
+        assert_eq!(style.colors.text, Theme::sepia().preview.text);
+        assert_eq!(style.heading_font_family.as_deref(), Some("Palatino"));
+
+        let html_style = style.html_style(&css);
+        assert_eq!(html_style.link_color, "#ff0000");
+        assert!(html_style.extra_css.contains(".markdown-preview a"));
+    }
+
+    #[test]
+    fn test_css_color() {
+        assert_eq!(css_color(0x0066cc), "#0066cc");
+        assert_eq!(css_color(0), "#000000");
+    }
+}
@@ -0,0 +1,407 @@
+//! 用户 CSS 模块
+//!
+//! 读取配置目录中的 `preview.css`，用其中的样式覆盖预览和导出的样式。
+//! 预览不是浏览器，只支持 CSS 的一个子集：
+//! - 选择器：`body`/`.markdown-preview`、`h1`~`h6`、`a`、`code`、`pre`、`blockquote`、`table`、`th`、`td`、`mark`
+//! - 属性：`color`、`background`/`background-color`、`font-family`、`font-size`、`line-height`、`border-color`
+//! - 颜色：`#rgb`、`#rrggbb`、`rgb(r, g, b)` 和常用颜色名
+//!
+//! 不支持的选择器、属性或取值会被忽略并给出警告
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::{Context, Result};
+
+use super::settings::modified_time;
+use super::PreviewStyle;
+
+/// 支持的选择器
+const SELECTORS: &[&str] = &[
+    "body", ".markdown-preview", "h1", "h2", "h3", "h4", "h5", "h6",
+    "a", "code", "pre", "blockquote", "table", "th", "td", "mark",
+];
+
+/// 一条样式规则（只含一个选择器，`h1, h2 { }` 会拆成两条）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssRule {
+    /// 选择器
+    pub selector: String,
+    /// 属性和值
+    pub declarations: Vec<(String, String)>,
+}
+
+/// 解析后的用户 CSS
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserCss {
+    /// 支持的规则（按出现顺序，后面的覆盖前面的）
+    rules: Vec<CssRule>,
+}
+
+impl UserCss {
+    /// 解析 CSS，返回支持的规则和被忽略部分的警告
+    pub fn parse(source: &str) -> (Self, Vec<String>) {
+        let source = strip_comments(source);
+        let mut rules = Vec::new();
+        let mut warnings = Vec::new();
+        let mut rest = source.as_str();
+
+        while let Some(open) = rest.find('{') {
+            let selectors = rest[..open].trim();
+            let Some(close) = rest[open..].find('}') else {
+                warnings.push(format!("\"{}\" 缺少右花括号", selectors));
+                rest = "";
+                break;
+            };
+            let body = &rest[open + 1..open + close];
+            rest = &rest[open + close + 1..];
+
+            let declarations = parse_declarations(body, &mut warnings);
+            for selector in selectors.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                if !SELECTORS.contains(&selector) {
+                    warnings.push(format!("不支持的选择器: {}", selector));
+                    continue;
+                }
+
+                let supported: Vec<(String, String)> = declarations
+                    .iter()
+                    .filter(|(property, value)| match check_declaration(selector, property, value) {
+                        Ok(()) => true,
+                        Err(warning) => {
+                            warnings.push(warning);
+                            false
+                        }
+                    })
+                    .cloned()
+                    .collect();
+
+                if !supported.is_empty() {
+                    rules.push(CssRule { selector: selector.to_string(), declarations: supported });
+                }
+            }
+        }
+
+        if !rest.trim().is_empty() {
+            warnings.push(format!("无法解析: {}", rest.trim()));
+        }
+
+        (Self { rules }, warnings)
+    }
+
+    /// 是否没有任何规则
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 所有规则
+    pub fn rules(&self) -> &[CssRule] {
+        &self.rules
+    }
+
+    /// 把规则应用到预览样式
+    pub fn apply(&self, style: &mut PreviewStyle) {
+        for rule in &self.rules {
+            for (property, value) in &rule.declarations {
+                apply_declaration(style, &rule.selector, property, value);
+            }
+        }
+    }
+
+    /// 生成限定在 `.markdown-preview` 内的 CSS，附加到导出的 HTML 中
+    pub fn to_css(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let selector = match rule.selector.as_str() {
+                    "body" | ".markdown-preview" => ".markdown-preview".to_string(),
+                    other => format!(".markdown-preview {}", other),
+                };
+                let declarations: Vec<String> = rule.declarations
+                    .iter()
+                    .map(|(property, value)| format!("{}: {};", property, value))
+                    .collect();
+                format!("{} {{ {} }}\n", selector, declarations.join(" "))
+            })
+            .collect()
+    }
+}
+
+/// 用户 CSS 文件
+///
+/// 记录上次读取时的修改时间，用于判断文件是否被修改
+#[derive(Debug, Clone)]
+pub struct UserCssFile {
+    /// 文件路径
+    path: PathBuf,
+    /// 上次读取时的修改时间（文件不存在时为 `None`）
+    modified: Option<SystemTime>,
+}
+
+impl UserCssFile {
+    /// 使用指定的 CSS 文件
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), modified: None }
+    }
+
+    /// 使用配置目录中的 `preview.css`；无法确定配置目录时返回 `None`
+    pub fn open_default() -> Option<Self> {
+        dirs::config_dir().map(|dir| Self::new(dir.join("readrs").join("preview.css")))
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取并解析 CSS；文件不存在时返回空样式
+    pub fn load(&mut self) -> Result<(UserCss, Vec<String>)> {
+        self.modified = modified_time(&self.path);
+
+        if !self.path.exists() {
+            return Ok((UserCss::default(), Vec::new()));
+        }
+
+        let source = fs::read_to_string(&self.path)
+            .with_context(|| format!("无法读取 CSS 文件: {}", self.path.display()))?;
+        Ok(UserCss::parse(&source))
+    }
+
+    /// 文件自上次读取后是否被修改、创建或删除
+    pub fn has_changed(&self) -> bool {
+        modified_time(&self.path) != self.modified
+    }
+}
+
+/// 去掉 `/* */` 注释
+fn strip_comments(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return result,
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// 解析 `property: value;` 列表，去掉 `!important`
+fn parse_declarations(body: &str, warnings: &mut Vec<String>) -> Vec<(String, String)> {
+    body.split(';')
+        .map(str::trim)
+        .filter(|declaration| !declaration.is_empty())
+        .filter_map(|declaration| match declaration.split_once(':') {
+            Some((property, value)) => Some((
+                property.trim().to_ascii_lowercase(),
+                value.trim().trim_end_matches("!important").trim().to_string(),
+            )),
+            None => {
+                warnings.push(format!("无法解析: {}", declaration));
+                None
+            }
+        })
+        .collect()
+}
+
+/// 检查选择器是否支持该属性，以及取值是否有效
+fn check_declaration(selector: &str, property: &str, value: &str) -> std::result::Result<(), String> {
+    let supported = match property {
+        "color" | "background" | "background-color" | "font-family" => true,
+        "font-size" | "line-height" => matches!(selector, "body" | ".markdown-preview"),
+        "border-color" | "border-left-color" => matches!(selector, "blockquote" | "table" | "th" | "td"),
+        _ => false,
+    };
+    if !supported {
+        return Err(format!("{} 不支持属性 {}", selector, property));
+    }
+
+    let valid = match property {
+        "font-family" => !value.is_empty(),
+        "font-size" => parse_length(value).is_some(),
+        "line-height" => parse_line_height(value).is_some(),
+        _ => parse_color(value).is_some(),
+    };
+    if !valid {
+        return Err(format!("{} {} 的值无效: {}", selector, property, value));
+    }
+
+    Ok(())
+}
+
+/// 把一条已检查过的声明应用到预览样式
+fn apply_declaration(style: &mut PreviewStyle, selector: &str, property: &str, value: &str) {
+    let colors = &mut style.colors;
+    let color = parse_color(value);
+
+    match (selector, property) {
+        ("body" | ".markdown-preview", "color") => colors.text = color.unwrap_or(colors.text),
+        ("body" | ".markdown-preview", "background" | "background-color") => {
+            colors.background = color.unwrap_or(colors.background);
+        }
+        ("body" | ".markdown-preview", "font-family") => style.font_family = value.to_string(),
+        (_, "font-size") => style.font_size = parse_length(value).unwrap_or(style.font_size),
+        (_, "line-height") => style.line_height = parse_line_height(value).unwrap_or(style.line_height),
+        ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", "color") => colors.heading = color.unwrap_or(colors.heading),
+        ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", "font-family") => {
+            style.heading_font_family = Some(value.to_string());
+        }
+        ("a", "color") => colors.link = color.unwrap_or(colors.link),
+        ("code" | "pre", "background" | "background-color") => {
+            colors.code_background = color.unwrap_or(colors.code_background);
+        }
+        ("code" | "pre", "font-family") => style.code_font_family = value.to_string(),
+        ("blockquote", "color") => colors.quote_text = color.unwrap_or(colors.quote_text),
+        ("blockquote", "border-color" | "border-left-color") => {
+            colors.quote_border = color.unwrap_or(colors.quote_border);
+        }
+        ("table" | "th" | "td", "border-color") => colors.border = color.unwrap_or(colors.border),
+        ("th", "background" | "background-color") => {
+            colors.table_header_background = color.unwrap_or(colors.table_header_background);
+        }
+        ("mark", "background" | "background-color") => colors.highlight = color.unwrap_or(colors.highlight),
+        // 其余组合只出现在导出的 HTML 中
+        _ => {}
+    }
+}
+
+/// 解析颜色：`#rgb`、`#rrggbb`、`rgb(r, g, b)` 或颜色名
+pub fn parse_color(value: &str) -> Option<u32> {
+    let value = value.trim().to_ascii_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return match hex.len() {
+            3 => {
+                let expanded: String = hex.chars().flat_map(|c| [c, c]).collect();
+                u32::from_str_radix(&expanded, 16).ok()
+            }
+            6 => u32::from_str_radix(hex, 16).ok(),
+            _ => None,
+        };
+    }
+
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+        let channels: Vec<u32> = args
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>().map(u32::from))
+            .collect::<std::result::Result<_, _>>()
+            .ok()?;
+        return match channels.as_slice() {
+            [r, g, b] => Some((r << 16) | (g << 8) | b),
+            _ => None,
+        };
+    }
+
+    match value.as_str() {
+        "black" => Some(0x000000),
+        "white" => Some(0xffffff),
+        "gray" | "grey" => Some(0x808080),
+        "silver" => Some(0xc0c0c0),
+        "red" => Some(0xff0000),
+        "green" => Some(0x008000),
+        "blue" => Some(0x0000ff),
+        "yellow" => Some(0xffff00),
+        "orange" => Some(0xffa500),
+        "purple" => Some(0x800080),
+        "navy" => Some(0x000080),
+        "teal" => Some(0x008080),
+        "maroon" => Some(0x800000),
+        _ => None,
+    }
+}
+
+/// 解析字号：`16px`、`12pt` 或不带单位的像素值
+fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let pixels = if let Some(number) = value.strip_suffix("px") {
+        number.trim().parse::<f64>().ok()?
+    } else if let Some(number) = value.strip_suffix("pt") {
+        number.trim().parse::<f64>().ok()? * 4.0 / 3.0
+    } else {
+        value.parse::<f64>().ok()?
+    };
+
+    (pixels > 0.0).then_some(pixels)
+}
+
+/// 解析行高：倍数（`1.6`）或百分比（`160%`）
+fn parse_line_height(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let ratio = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()?,
+    };
+
+    (ratio > 0.0).then_some(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Theme;
+
+    #[test]
+    fn test_parse_rules() {
+        let (css, warnings) = UserCss::parse(
+            "/* 标题 */\nh1, h2 { color: #c00; font-family: Georgia; }\nbody { font-size: 12pt; line-height: 150% }",
+        );
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(css.rules().len(), 3);
+        assert_eq!(css.rules()[1].selector, "h2");
+        assert_eq!(css.rules()[0].declarations[0], ("color".to_string(), "#c00".to_string()));
+    }
+
+    #[test]
+    fn test_unsupported_parts_are_reported() {
+        let (css, warnings) = UserCss::parse(
+            "div { color: red }\na { color: nope; text-decoration: underline; }\nh1 { font-size: 30px }\ncode {",
+        );
+
+        assert!(css.is_empty());
+        assert_eq!(warnings.len(), 5, "{:?}", warnings);
+        assert!(warnings[0].contains("div"));
+    }
+
+    #[test]
+    fn test_apply_to_style() {
+        let (css, _) = UserCss::parse(
+            "body { color: rgb(10, 20, 30); font-family: Georgia; line-height: 1.8 }\nmark { background: yellow }\nblockquote { border-left-color: #123456 }",
+        );
+        let mut style = PreviewStyle::default();
+        css.apply(&mut style);
+
+        assert_eq!(style.colors.text, 0x0a141e);
+        assert_eq!(style.font_family, "Georgia");
+        assert_eq!(style.line_height, 1.8);
+        assert_eq!(style.colors.highlight, 0xffff00);
+        assert_eq!(style.colors.quote_border, 0x123456);
+        assert_eq!(style.colors.link, Theme::light().preview.link);
+    }
+
+    #[test]
+    fn test_to_css_is_scoped() {
+        let (css, _) = UserCss::parse("body { color: red } a { color: blue !important }");
+        assert_eq!(
+            css.to_css(),
+            ".markdown-preview { color: red; }\n.markdown-preview a { color: blue; }\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#fff"), Some(0xffffff));
+        assert_eq!(parse_color("#0066CC"), Some(0x0066cc));
+        assert_eq!(parse_color("rgb(255, 0, 0)"), Some(0xff0000));
+        assert_eq!(parse_color("Navy"), Some(0x000080));
+        assert_eq!(parse_color("rgb(256, 0, 0)"), None);
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("hotpink"), None);
+    }
+}
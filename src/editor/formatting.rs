@@ -0,0 +1,575 @@
+//! Markdown 格式化模块
+//!
+//! 对编辑器的选区执行格式化操作：
+//! - 切换粗体、斜体、删除线、行内代码
+//! - 循环切换标题级别
+//! - 切换引用、无序列表、有序列表、任务列表
+//! - 插入链接（优先使用剪贴板中的网址）
+//! - 增加和减少缩进
+//! - 上下移动行
+//!
+//! 每个操作计算出一次替换（[`TextEdit`]），编辑器一次性应用，撤销时作为一步。
+//! 位置和范围均为字节偏移
+
+use std::ops::Range;
+
+/// 一次文本替换
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// 被替换的范围（原文本中的位置）
+    pub range: Range<usize>,
+    /// 替换后的文本
+    pub replacement: String,
+    /// 替换后的选区（新文本中的位置）
+    pub selection: Range<usize>,
+}
+
+impl TextEdit {
+    /// 把 `old` 变为 `new` 的最小替换（去掉相同的开头和结尾），文本相同时返回 `None`
+    ///
+    /// 光标 `cursor` 在替换范围之前或之后时保持在原来的文字旁，在范围之内时移到替换文本末尾
+    pub fn between(old: &str, new: &str, cursor: usize) -> Option<TextEdit> {
+        if old == new {
+            return None;
+        }
+
+        let prefix: usize = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let suffix: usize = old[prefix..]
+            .chars()
+            .rev()
+            .zip(new[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+
+        let range = prefix..old.len() - suffix;
+        let replacement = new[prefix..new.len() - suffix].to_string();
+        let cursor = if cursor <= range.start {
+            cursor
+        } else if cursor >= range.end {
+            cursor - range.end + prefix + replacement.len()
+        } else {
+            prefix + replacement.len()
+        };
+
+        Some(TextEdit { range, replacement, selection: cursor..cursor })
+    }
+
+    /// 应用到文本，返回新文本
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len() + self.replacement.len());
+        result.push_str(&text[..self.range.start]);
+        result.push_str(&self.replacement);
+        result.push_str(&text[self.range.end..]);
+        result
+    }
+
+    /// 扩大替换范围，使新选区落在替换文本之内
+    ///
+    /// 返回原文本中的范围、替换文本和选区在替换文本中的位置，
+    /// 编辑器用它在一次替换中同时设置选区
+    pub fn covering_selection(&self, text: &str) -> (Range<usize>, String, Range<usize>) {
+        let new_text = self.apply(text);
+        let replaced_end = self.range.start + self.replacement.len();
+
+        let start = self.range.start.min(self.selection.start);
+        let new_end = replaced_end.max(self.selection.end);
+        let old_end = self.range.end + (new_end - replaced_end);
+
+        let selection = self.selection.start - start..self.selection.end - start;
+        (start..old_end, new_text[start..new_end].to_string(), selection)
+    }
+}
+
+/// 行内样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineStyle {
+    Bold,
+    Italic,
+    Strikethrough,
+    Code,
+}
+
+impl InlineStyle {
+    /// 样式的标记
+    pub fn marker(self) -> &'static str {
+        match self {
+            Self::Bold => "**",
+            Self::Italic => "*",
+            Self::Strikethrough => "~~",
+            Self::Code => "`",
+        }
+    }
+
+    /// 选区两侧连续的标记字符数量为 `run` 时，是否已有该样式
+    ///
+    /// `***文本***` 同时是粗体和斜体
+    fn is_applied(self, run: usize) -> bool {
+        match self {
+            Self::Bold | Self::Strikethrough => run >= 2,
+            Self::Italic => run % 2 == 1,
+            Self::Code => run >= 1,
+        }
+    }
+}
+
+/// 切换选区的行内样式
+///
+/// 选区两侧或选区内部两端已有标记时去掉标记，否则在选区两侧加上标记；
+/// 没有选中文本时插入一对标记，光标位于中间
+pub fn toggle_inline(text: &str, selection: Range<usize>, style: InlineStyle) -> Option<TextEdit> {
+    let marker = style.marker();
+    let marker_char = marker.chars().next()?;
+    let len = marker.len();
+    let selected = &text[selection.clone()];
+
+    // 标记在选区外侧
+    let outside = count_run_end(&text[..selection.start], marker_char)
+        .min(count_run_start(&text[selection.end..], marker_char));
+    if style.is_applied(outside) {
+        return Some(TextEdit {
+            range: selection.start - len..selection.end + len,
+            replacement: selected.to_string(),
+            selection: selection.start - len..selection.end - len,
+        });
+    }
+
+    // 标记在选区内部两端
+    let inside = count_run_start(selected, marker_char).min(count_run_end(selected, marker_char));
+    if selected.len() > 2 * len && style.is_applied(inside) {
+        return Some(TextEdit {
+            range: selection.clone(),
+            replacement: selected[len..selected.len() - len].to_string(),
+            selection: selection.start..selection.end - 2 * len,
+        });
+    }
+
+    Some(TextEdit {
+        range: selection.clone(),
+        replacement: format!("{}{}{}", marker, selected, marker),
+        selection: selection.start + len..selection.end + len,
+    })
+}
+
+/// 文本开头连续的 `c` 的数量
+fn count_run_start(text: &str, c: char) -> usize {
+    text.chars().take_while(|&ch| ch == c).count()
+}
+
+/// 文本末尾连续的 `c` 的数量
+fn count_run_end(text: &str, c: char) -> usize {
+    text.chars().rev().take_while(|&ch| ch == c).count()
+}
+
+/// 循环切换标题级别：正文 → 一级 → … → 六级 → 正文
+///
+/// 以第一行的级别决定下一个级别，选区中的所有非空行使用同一级别
+pub fn cycle_heading(text: &str, selection: Range<usize>) -> Option<TextEdit> {
+    let range = line_range(text, &selection);
+    let first_line = text[range.clone()].split('\n').next().unwrap_or_default();
+    let next = (heading_level(first_line).0 + 1) % 7;
+    let prefix = if next == 0 { String::new() } else { format!("{} ", "#".repeat(next)) };
+
+    edit_lines(text, selection, |line| {
+        let (_, prefix_len) = heading_level(line);
+        Some((0..prefix_len, prefix.clone()))
+    })
+}
+
+/// 行的标题级别和标题标记的长度（包括后面的空格）
+fn heading_level(line: &str) -> (usize, usize) {
+    let level = count_run_start(line, '#');
+    if !(1..=6).contains(&level) {
+        return (0, 0);
+    }
+    match line[level..].chars().next() {
+        None => (level, level),
+        Some(' ') => (level, level + 1),
+        Some(_) => (0, 0),
+    }
+}
+
+/// 切换引用
+///
+/// 所有非空行都是引用时去掉一层引用，否则给每一行加上引用
+pub fn toggle_blockquote(text: &str, selection: Range<usize>) -> Option<TextEdit> {
+    let quoted = all_non_blank(text, &selection, |line| line.starts_with('>'));
+
+    edit_lines(text, selection, |line| {
+        if quoted {
+            let len = if line.starts_with("> ") { 2 } else { 1 };
+            Some((0..len, String::new()))
+        } else {
+            Some((0..0, "> ".to_string()))
+        }
+    })
+}
+
+/// 列表类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListKind {
+    Bullet,
+    Numbered,
+    Task,
+}
+
+/// 行首的列表标记：类型和标记在行中的范围（包括后面的空格，不包括缩进）
+pub(super) fn list_marker(line: &str) -> Option<(ListKind, Range<usize>)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    if rest.starts_with("- ") || rest.starts_with("* ") || rest.starts_with("+ ") {
+        let task = ["[ ] ", "[x] ", "[X] "].iter().any(|checkbox| rest[2..].starts_with(checkbox));
+        return Some(if task {
+            (ListKind::Task, indent..indent + 6)
+        } else {
+            (ListKind::Bullet, indent..indent + 2)
+        });
+    }
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) {
+        return Some((ListKind::Numbered, indent..indent + digits + 2));
+    }
+
+    None
+}
+
+/// 切换列表
+///
+/// 所有非空行都是该类型的列表时去掉列表标记，否则把每一行改为该类型的列表项，
+/// 有序列表从 1 开始编号
+pub fn toggle_list(text: &str, selection: Range<usize>, kind: ListKind) -> Option<TextEdit> {
+    let all_same = all_non_blank(text, &selection, |line| {
+        list_marker(line).is_some_and(|(existing, _)| existing == kind)
+    });
+
+    let mut number = 0;
+    edit_lines(text, selection, |line| {
+        let indent = line.len() - line.trim_start().len();
+        let marker = list_marker(line).map(|(_, range)| range).unwrap_or(indent..indent);
+        if all_same {
+            return Some((marker, String::new()));
+        }
+
+        number += 1;
+        let new_marker = match kind {
+            ListKind::Bullet => "- ".to_string(),
+            ListKind::Numbered => format!("{}. ", number),
+            ListKind::Task => "- [ ] ".to_string(),
+        };
+        Some((marker, new_marker))
+    })
+}
+
+/// 插入链接
+///
+/// - 选中的文本是网址：`[](网址)`，光标位于方括号中
+/// - `url` 是网址（如剪贴板内容）：`[选中的文本](url)`，选中链接文字
+/// - 其他情况：`[选中的文本]()`，光标位于圆括号中
+pub fn insert_link(text: &str, selection: Range<usize>, url: Option<&str>) -> Option<TextEdit> {
+    let selected = &text[selection.clone()];
+    let start = selection.start;
+
+    if is_url(selected) {
+        return Some(TextEdit {
+            range: selection,
+            replacement: format!("[]({})", selected.trim()),
+            selection: start + 1..start + 1,
+        });
+    }
+
+    if let Some(url) = url.map(str::trim).filter(|url| is_url(url)) {
+        return Some(TextEdit {
+            range: selection,
+            replacement: format!("[{}]({})", selected, url),
+            selection: start + 1..start + 1 + selected.len(),
+        });
+    }
+
+    let cursor = start + selected.len() + 3;
+    Some(TextEdit {
+        range: selection,
+        replacement: format!("[{}]()", selected),
+        selection: cursor..cursor,
+    })
+}
+
+/// 文本是否为网址
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty()
+        && !text.contains(char::is_whitespace)
+        && ["http://", "https://", "ftp://", "mailto:", "www."]
+            .iter()
+            .any(|scheme| text.len() > scheme.len() && text.to_ascii_lowercase().starts_with(scheme))
+}
+
+/// 增加缩进：在每一行前面加上 `unit`
+pub fn indent_lines(text: &str, selection: Range<usize>, unit: &str) -> Option<TextEdit> {
+    edit_lines(text, selection, |_| Some((0..0, unit.to_string())))
+}
+
+/// 减少缩进：去掉每一行前面最多 `unit` 长度的空格或一个制表符
+pub fn outdent_lines(text: &str, selection: Range<usize>, unit: &str) -> Option<TextEdit> {
+    edit_lines(text, selection, |line| {
+        let len = if line.starts_with('\t') {
+            1
+        } else {
+            count_run_start(line, ' ').min(unit.len().max(1))
+        };
+        (len > 0).then(|| (0..len, String::new()))
+    })
+}
+
+/// 把选区所在的行上移或下移一行，已在开头或末尾时返回 `None`
+pub fn move_lines(text: &str, selection: Range<usize>, up: bool) -> Option<TextEdit> {
+    let range = line_range(text, &selection);
+    let block = &text[range.clone()];
+
+    if up {
+        if range.start == 0 {
+            return None;
+        }
+        let previous_start = text[..range.start - 1].rfind('\n').map_or(0, |i| i + 1);
+        let previous = &text[previous_start..range.start - 1];
+        let shift = previous.len() + 1;
+        Some(TextEdit {
+            range: previous_start..range.end,
+            replacement: format!("{}\n{}", block, previous),
+            selection: selection.start - shift..selection.end - shift,
+        })
+    } else {
+        if range.end >= text.len() {
+            return None;
+        }
+        let next_end = text[range.end + 1..].find('\n').map_or(text.len(), |i| range.end + 1 + i);
+        let next = &text[range.end + 1..next_end];
+        let shift = next.len() + 1;
+        Some(TextEdit {
+            range: range.start..next_end,
+            replacement: format!("{}\n{}", next, block),
+            selection: selection.start + shift..selection.end + shift,
+        })
+    }
+}
+
+/// 选区覆盖的完整行（不包括最后的换行符）
+///
+/// 选区结束于下一行行首时不包括下一行
+fn line_range(text: &str, selection: &Range<usize>) -> Range<usize> {
+    let start = text[..selection.start].rfind('\n').map_or(0, |i| i + 1);
+    let last = if selection.end > selection.start && text[..selection.end].ends_with('\n') {
+        selection.end - 1
+    } else {
+        selection.end
+    };
+    let end = text[last..].find('\n').map_or(text.len(), |i| last + i);
+    start..end.max(start)
+}
+
+/// 选区覆盖的行中至少有一个非空行，且所有非空行都满足条件
+pub(super) fn all_non_blank(text: &str, selection: &Range<usize>, predicate: impl Fn(&str) -> bool) -> bool {
+    let mut lines = text[line_range(text, selection)]
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+    lines.peek().is_some() && lines.all(predicate)
+}
+
+/// 逐行修改选区覆盖的行，并相应地调整选区
+///
+/// `f` 返回要替换的行内范围和替换文本；多行时跳过空行
+fn edit_lines(
+    text: &str,
+    selection: Range<usize>,
+    mut f: impl FnMut(&str) -> Option<(Range<usize>, String)>,
+) -> Option<TextEdit> {
+    let range = line_range(text, &selection);
+    let lines: Vec<&str> = text[range.clone()].split('\n').collect();
+    let single = lines.len() == 1;
+
+    let mut replacement = String::new();
+    let mut selection_start = selection.start;
+    let mut selection_end = selection.end;
+    let mut old_offset = range.start;
+    let mut changed = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            replacement.push('\n');
+        }
+        let new_offset = range.start + replacement.len();
+
+        let change = if single || !line.trim().is_empty() { f(line) } else { None };
+        match change {
+            Some((cut, insert)) => {
+                // 把原行中的位置映射到新行中
+                let map = |position: usize| {
+                    let column = position - old_offset;
+                    let column = if column <= cut.start {
+                        column
+                    } else if column >= cut.end {
+                        column - cut.len() + insert.len()
+                    } else {
+                        cut.start + insert.len()
+                    };
+                    new_offset + column
+                };
+                let line_end = old_offset + line.len();
+                if (old_offset..=line_end).contains(&selection.start) {
+                    selection_start = map(selection.start);
+                }
+                if (old_offset..=line_end).contains(&selection.end) {
+                    selection_end = map(selection.end);
+                }
+
+                changed |= !cut.is_empty() || !insert.is_empty();
+                replacement.push_str(&line[..cut.start]);
+                replacement.push_str(&insert);
+                replacement.push_str(&line[cut.end..]);
+            }
+            None => {
+                let line_end = old_offset + line.len();
+                if (old_offset..=line_end).contains(&selection.start) {
+                    selection_start = new_offset + selection.start - old_offset;
+                }
+                if (old_offset..=line_end).contains(&selection.end) {
+                    selection_end = new_offset + selection.end - old_offset;
+                }
+                replacement.push_str(line);
+            }
+        }
+
+        old_offset += line.len() + 1;
+    }
+
+    if !changed {
+        return None;
+    }
+
+    // 选区结束于下一行行首时，该位置在替换范围之后
+    if selection.end > range.end {
+        selection_end = selection.end - range.len() + replacement.len();
+    }
+
+    Some(TextEdit {
+        range,
+        replacement,
+        selection: selection_start..selection_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 应用编辑，返回新文本和选中的文本
+    fn run(text: &str, selection: Range<usize>, f: impl FnOnce(&str, Range<usize>) -> Option<TextEdit>) -> (String, String) {
+        let edit = f(text, selection).expect("应产生编辑");
+        let new_text = edit.apply(text);
+        let selected = new_text[edit.selection.clone()].to_string();
+        (new_text, selected)
+    }
+
+    #[test]
+    fn test_toggle_inline() {
+        let bold = |t: &str, s| toggle_inline(t, s, InlineStyle::Bold);
+        let italic = |t: &str, s| toggle_inline(t, s, InlineStyle::Italic);
+
+        assert_eq!(run("a word b", 2..6, bold), ("a **word** b".to_string(), "word".to_string()));
+        assert_eq!(run("a **word** b", 4..8, bold), ("a word b".to_string(), "word".to_string()));
+        assert_eq!(run("a **word** b", 2..10, bold), ("a word b".to_string(), "word".to_string()));
+
+        // 粗体的标记不会被当作斜体
+        assert_eq!(run("**word**", 2..6, italic), ("***word***".to_string(), "word".to_string()));
+        assert_eq!(run("***word***", 3..7, italic), ("**word**".to_string(), "word".to_string()));
+
+        // 没有选中文本时插入一对标记
+        let (text, _) = run("ab", 1..1, |t, s| toggle_inline(t, s, InlineStyle::Code));
+        assert_eq!(text, "a``b");
+        let (text, _) = run("a~~~~b", 3..3, |t, s| toggle_inline(t, s, InlineStyle::Strikethrough));
+        assert_eq!(text, "ab");
+    }
+
+    #[test]
+    fn test_cycle_heading() {
+        assert_eq!(run("title", 0..0, cycle_heading).0, "# title");
+        assert_eq!(run("## title", 5..5, cycle_heading), ("### title".to_string(), String::new()));
+        assert_eq!(run("###### title", 0..0, cycle_heading).0, "title");
+        assert_eq!(run("#tag", 0..0, cycle_heading).0, "# #tag");
+
+        // 选区随标题标记移动
+        let edit = cycle_heading("# 标题", 2..8).unwrap();
+        assert_eq!(&edit.apply("# 标题")[edit.selection], "标题");
+    }
+
+    #[test]
+    fn test_line_prefixes() {
+        assert_eq!(run("a\n\nb", 0..4, toggle_blockquote).0, "> a\n\n> b");
+        assert_eq!(run("> a\n>b", 0..6, toggle_blockquote).0, "a\nb");
+        assert_eq!(run("", 0..0, toggle_blockquote).0, "> ");
+
+        let numbered = |t: &str, s| toggle_list(t, s, ListKind::Numbered);
+        assert_eq!(run("a\n- b\n  c", 0..9, numbered).0, "1. a\n2. b\n  3. c");
+        assert_eq!(run("1. a\n2. b", 0..9, numbered).0, "a\nb");
+
+        let task = |t: &str, s| toggle_list(t, s, ListKind::Task);
+        assert_eq!(run("- a", 3..3, task), ("- [ ] a".to_string(), String::new()));
+        assert_eq!(run("- [x] a", 0..7, |t, s| toggle_list(t, s, ListKind::Bullet)).0, "- a");
+
+        // 选区结束于下一行行首时不处理下一行
+        assert_eq!(run("a\nb", 0..2, |t, s| toggle_list(t, s, ListKind::Bullet)).0, "- a\nb");
+    }
+
+    #[test]
+    fn test_insert_link() {
+        assert_eq!(
+            run("see docs", 4..8, |t, s| insert_link(t, s, Some("https://example.com\n"))),
+            ("see [docs](https://example.com)".to_string(), "docs".to_string())
+        );
+        let edit = insert_link("docs", 0..4, Some("not a url")).unwrap();
+        assert_eq!(edit.apply("docs"), "[docs]()");
+        assert_eq!(edit.selection, 7..7);
+        let edit = insert_link("https://a.b", 0..11, None).unwrap();
+        assert_eq!(edit.apply("https://a.b"), "[](https://a.b)");
+        assert_eq!(edit.selection, 1..1);
+    }
+
+    #[test]
+    fn test_indent_and_move() {
+        assert_eq!(run("- a\n- b", 0..7, |t, s| indent_lines(t, s, "  ")).0, "  - a\n  - b");
+        assert_eq!(run("    - a\n\t- b\nc", 0..13, |t, s| outdent_lines(t, s, "  ")).0, "  - a\n- b\nc");
+        assert!(outdent_lines("a", 0..0, "  ").is_none());
+
+        assert_eq!(run("a\nb\nc", 2..3, |t, s| move_lines(t, s, true)), ("b\na\nc".to_string(), "b".to_string()));
+        assert_eq!(run("a\nb\nc", 0..3, |t, s| move_lines(t, s, false)), ("c\na\nb".to_string(), "a\nb".to_string()));
+        assert!(move_lines("a\nb", 0..0, true).is_none());
+        assert!(move_lines("a\nb", 2..2, false).is_none());
+    }
+
+    #[test]
+    fn test_covering_selection() {
+        let text = "one two";
+        let edit = TextEdit { range: 0..3, replacement: "1".to_string(), selection: 2..5 };
+        let (range, replacement, selection) = edit.covering_selection(text);
+        assert_eq!((range, replacement.as_str(), selection), (0..7, "1 two", 2..5));
+    }
+
+    #[test]
+    fn test_edit_between() {
+        let edit = TextEdit::between("中文 foo 和 foo。", "中文 bar 和 bar。", 0).unwrap();
+        assert_eq!((edit.range.clone(), edit.replacement.as_str()), (7..18, "bar 和 bar"));
+        assert_eq!(edit.apply("中文 foo 和 foo。"), "中文 bar 和 bar。");
+
+        // 光标在替换范围之后时随文字移动
+        let edit = TextEdit::between("aa x", "a x", 4).unwrap();
+        assert_eq!((edit.range, edit.replacement.as_str(), edit.selection), (1..2, "", 3..3));
+        assert!(TextEdit::between("same", "same", 0).is_none());
+    }
+}
@@ -5,10 +5,16 @@
 //! - 文本编辑状态管理
 //! - 实时内容更新通知
 //! - 代码语法高亮
+//! - Markdown 格式化命令
+//! - 列表续写和自动闭合
 
 mod text_editor;
 mod syntax_highlight;
+mod formatting;
+mod typing;
 
 pub use text_editor::*;
 pub use syntax_highlight::*;
+pub use formatting::*;
+pub use typing::*;
 
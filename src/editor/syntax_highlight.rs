@@ -1,16 +1,27 @@
 //! 代码语法高亮模块
 //!
-//! 使用 syntect 为代码块提供语法高亮
+//! 使用 syntect 为代码块提供语法高亮：
+//! - 预览中使用内联颜色
+//! - 导出时使用 CSS 类名，并生成对应主题的样式表
 
 use syntect::easy::HighlightLines;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
 use syntect::highlighting::{Style, ThemeSet};
 use syntect::parsing::SyntaxSet;
 use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
 
+/// 默认的高亮主题
+const DEFAULT_THEME: &str = "InspiredGitHub";
+
+/// 导出时使用的 CSS 类名前缀，避免与文档中的其他类名冲突
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
 /// 代码语法高亮器
 pub struct SyntaxHighlighter {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
+    /// 当前使用的主题名称
+    theme: String,
 }
 
 impl SyntaxHighlighter {
@@ -19,7 +30,22 @@ impl SyntaxHighlighter {
         Self {
             syntax_set: SyntaxSet::load_defaults_newlines(),
             theme_set: ThemeSet::load_defaults(),
+            theme: DEFAULT_THEME.to_string(),
+        }
+    }
+
+    /// 切换高亮主题，主题不存在时返回 `false` 并保持原主题
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        if !self.theme_set.themes.contains_key(name) {
+            return false;
         }
+        self.theme = name.to_string();
+        true
+    }
+
+    /// 当前使用的主题名称
+    pub fn theme(&self) -> &str {
+        &self.theme
     }
 
     /// 高亮代码
@@ -36,7 +62,7 @@ impl SyntaxHighlighter {
             .find_syntax_by_token(language)
             .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
 
-        let theme = &self.theme_set.themes["InspiredGitHub"];
+        let theme = &self.theme_set.themes[&self.theme];
         let mut highlighter = HighlightLines::new(syntax, theme);
 
         let mut html_output = String::new();
@@ -52,6 +78,27 @@ impl SyntaxHighlighter {
         html_output
     }
 
+    /// 高亮代码，使用 CSS 类名标记颜色，配合 [`Self::theme_css`] 使用
+    pub fn highlight_classed(&self, code: &str, language: &str) -> String {
+        let syntax = self
+            .syntax_set
+            .find_syntax_by_token(language)
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+
+        let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &self.syntax_set, CLASS_STYLE);
+        for line in LinesWithEndings::from(code) {
+            if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+                return format!("<pre class=\"hl-code\"><code>{}</code></pre>", html_escape(code));
+            }
+        }
+        format!("<pre class=\"hl-code\"><code>{}</code></pre>", generator.finalize())
+    }
+
+    /// 当前主题对应的样式表，用于 [`Self::highlight_classed`] 生成的 HTML
+    pub fn theme_css(&self) -> String {
+        css_for_theme_with_class_style(&self.theme_set.themes[&self.theme], CLASS_STYLE).unwrap_or_default()
+    }
+
     /// 获取支持的语言列表
     pub fn supported_languages(&self) -> Vec<String> {
         self.syntax_set
@@ -68,6 +115,14 @@ impl Default for SyntaxHighlighter {
     }
 }
 
+/// HTML 转义函数
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,6 +148,16 @@ mod tests {
         assert!(result.contains("hello"));
     }
 
+    #[test]
+    fn test_highlight_classed() {
+        let highlighter = SyntaxHighlighter::new();
+        let result = highlighter.highlight_classed("let x = \"<a>\";", "rust");
+        assert!(result.starts_with("<pre class=\"hl-code\">"));
+        assert!(result.contains("class=\"hl-"));
+        assert!(result.contains("&lt;a&gt;"));
+        assert!(highlighter.theme_css().contains(".hl-"));
+    }
+
     #[test]
     fn test_supported_languages() {
         let highlighter = SyntaxHighlighter::new();
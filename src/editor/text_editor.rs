@@ -1,5 +1,30 @@
+use std::cell::Cell;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use anyhow::Result;
 use gpui::*;
-use gpui_component::input::{InputState, Input};
+use gpui_component::input::{InputState, Input, Position, TabSize};
+
+use crate::actions;
+use crate::config::{EditorSettings, EDITOR_CONTEXT};
+use crate::markdown::{
+    draft_dir, find_inline_links, find_wiki_links, html_to_markdown, image_extension, is_image_file, move_assets,
+    Assets, HtmlConvertOptions, Inline, WikiLink,
+};
+
+use super::formatting::{self, InlineStyle, ListKind, TextEdit};
+use super::typing;
+
+/// 编辑器发出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextEditorEvent {
+    /// 按住 Ctrl（macOS 上为 Cmd）点击了链接，内容为链接目标
+    OpenLink(String),
+    /// 按住 Ctrl 点击了 wiki 链接（`[[笔记]]`）
+    OpenWikiLink(WikiLink),
+}
 
 /// 文本编辑器视图
 /// 
@@ -9,24 +34,73 @@ pub struct TextEditor {
     input_state: Entity<InputState>,
     /// 当前文本内容
     content: SharedString,
+    /// 编辑器设置
+    settings: EditorSettings,
+    /// 当前文档所在的文件夹，未保存的文档为 `None`
+    document_dir: Option<PathBuf>,
+    /// 未保存的文档中粘贴的图片暂存在这里，第一次保存时移到文档旁边
+    draft_dir: PathBuf,
+    /// 上一帧中输入框的位置和大小
+    input_bounds: Rc<Cell<Option<Bounds<Pixels>>>>,
+    /// 编辑区第一个可见字符的位置（UTF-16 偏移）
+    top_offset: usize,
 }
 
 impl TextEditor {
     /// 创建新的文本编辑器
-    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        // 创建多行输入状态，支持自动增长，不设置占位符
-        let input_state = cx.new(|cx| {
-            InputState::new(window, cx)
-                .multi_line()  // 启用多行模式
-                .auto_grow(10, 50)  // 自动增长，最小 10 行，最大 50 行
-        });
+    pub fn new(settings: EditorSettings, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let input_state = Self::build_input(&settings, window, cx);
 
         Self {
             input_state,
             content: SharedString::default(),
+            settings,
+            document_dir: None,
+            draft_dir: draft_dir(),
+            input_bounds: Rc::new(Cell::new(None)),
+            top_offset: 0,
         }
     }
 
+    /// 按设置创建多行输入状态，支持自动增长，不设置占位符
+    fn build_input(settings: &EditorSettings, window: &mut Window, cx: &mut Context<Self>) -> Entity<InputState> {
+        cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line()  // 启用多行模式
+                .auto_grow(settings.min_lines, settings.max_lines)  // 自动增长
+                .soft_wrap(settings.soft_wrap)
+                .tab_size(TabSize {
+                    tab_size: settings.tab_width,
+                    hard_tabs: false,
+                })
+        })
+    }
+
+    /// 应用新的编辑器设置
+    ///
+    /// 行数、换行和缩进只能在创建输入状态时指定，这些设置变化时会重建输入状态并保留内容和光标；
+    /// 返回 `true` 表示输入状态已重建，需要重新订阅变化事件
+    pub fn apply_settings(&mut self, settings: &EditorSettings, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let rebuild = settings.min_lines != self.settings.min_lines
+            || settings.max_lines != self.settings.max_lines
+            || settings.soft_wrap != self.settings.soft_wrap
+            || settings.tab_width != self.settings.tab_width;
+        self.settings = settings.clone();
+
+        if rebuild {
+            let content = self.content(cx);
+            let cursor = self.input_state.read(cx).cursor_position();
+            self.input_state = Self::build_input(settings, window, cx);
+            self.input_state.update(cx, |state, cx| {
+                state.set_value(content.to_string(), window, cx);
+                state.set_cursor_position(cursor, window, cx);
+            });
+        }
+
+        cx.notify();
+        rebuild
+    }
+
     /// 获取当前文本内容
     pub fn content(&self, cx: &Context<Self>) -> SharedString {
         self.input_state.read(cx).value()
@@ -42,17 +116,432 @@ impl TextEditor {
         });
     }
 
+    /// 设置当前文档的路径，`None` 表示新建的未保存文档
+    pub fn set_document_path(&mut self, path: Option<&Path>) {
+        self.document_dir = path.map(|path| path.parent().unwrap_or(Path::new("")).to_path_buf());
+        if path.is_none() {
+            self.draft_dir = draft_dir();
+        }
+    }
+
+    /// 图片相对路径的起点：当前文档所在的文件夹，未保存的文档为暂存图片的临时文件夹
+    pub fn base_dir(&self) -> &Path {
+        self.document_dir.as_deref().unwrap_or(&self.draft_dir)
+    }
+
+    /// 文档另存为 `path`，未保存的文档中粘贴的图片随文档移动
+    pub fn save_document_as(&mut self, path: &Path) -> Result<()> {
+        let result = match (&self.document_dir, path.parent()) {
+            (None, Some(dir)) => move_assets(&self.draft_dir, dir, &self.settings.image_folder),
+            _ => Ok(()),
+        };
+        self.document_dir = Some(path.parent().unwrap_or(Path::new("")).to_path_buf());
+        result
+    }
+
+    /// 将光标移动到指定行和列（均从1开始）
+    pub fn go_to(&mut self, line: usize, column: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let position = Position {
+            line: line.saturating_sub(1) as u32,
+            character: column.saturating_sub(1) as u32,
+        };
+        self.input_state.update(cx, |state, cx| {
+            state.set_cursor_position(position, window, cx);
+        });
+    }
+
+    /// 获取光标所在的行和列（均从1开始）
+    pub fn cursor_position(&self, cx: &App) -> (usize, usize) {
+        let position = self.input_state.read(cx).cursor_position();
+        (position.line as usize + 1, position.character as usize + 1)
+    }
+
+    /// 编辑区第一个可见的行（从1开始）
+    pub fn first_visible_line(&self, cx: &App) -> usize {
+        let content = self.input_state.read(cx).value();
+        let offset = byte_offset(&content, self.top_offset);
+        content[..offset].matches('\n').count() + 1
+    }
+
+    /// 滚动编辑区，使指定的行（从1开始）显示在顶部
+    ///
+    /// 先把光标移到最后一行再移回该行，向上滚动到可见区域时该行位于顶部；之后需要重新设置光标
+    pub fn scroll_to_line(&mut self, line: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let last_line = self.content(cx).split('\n').count();
+        self.go_to(last_line, 1, window, cx);
+        self.go_to(line, 1, window, cx);
+    }
+
+    /// 将光标移动到文本中的字节位置，并将其滚动到可见区域
+    pub fn set_cursor_offset(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let content = self.content(cx);
+        if offset > content.len() || !content.is_char_boundary(offset) {
+            return;
+        }
+
+        let line_start = content[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+        let position = Position {
+            line: content[..line_start].matches('\n').count() as u32,
+            character: utf16_offset(&content[line_start..], offset - line_start) as u32,
+        };
+        self.input_state.update(cx, |state, cx| {
+            state.set_cursor_position(position, window, cx);
+        });
+    }
+
+    /// 选中文本中的字节范围，并将其滚动到可见区域
+    ///
+    /// 通过在选区开头插入空文本的输入法调用设置选区，不修改文本内容
+    pub fn select_range(&mut self, range: Range<usize>, window: &mut Window, cx: &mut Context<Self>) {
+        let content = self.content(cx);
+        if range.start > range.end
+            || range.end > content.len()
+            || !content.is_char_boundary(range.start)
+            || !content.is_char_boundary(range.end)
+        {
+            return;
+        }
+
+        self.set_cursor_offset(range.start, window, cx);
+        let start = utf16_offset(&content, range.start);
+        let len = content[range].encode_utf16().count();
+        self.input_state.update(cx, |state, cx| {
+            state.replace_and_mark_text_in_range(Some(start..start), "", Some(0..len), window, cx);
+            state.unmark_text(window, cx);
+        });
+    }
+
+    /// 当前选区（字节范围）
+    pub fn selection(&self, window: &mut Window, cx: &mut Context<Self>) -> Range<usize> {
+        let content = self.content(cx);
+        let range = self.input_state
+            .update(cx, |state, cx| state.selected_text_range(false, window, cx))
+            .map(|selection| selection.range)
+            .unwrap_or_default();
+        byte_offset(&content, range.start)..byte_offset(&content, range.end)
+    }
+
+    /// 应用一次编辑并设置新的选区
+    ///
+    /// 替换和选中通过一次输入法调用完成，撤销时作为一步
+    pub fn apply_edit(&mut self, edit: &TextEdit, window: &mut Window, cx: &mut Context<Self>) {
+        let content = self.content(cx);
+        let (range, replacement, selection) = edit.covering_selection(&content);
+        let range = utf16_offset(&content, range.start)..utf16_offset(&content, range.end);
+        let selection = utf16_offset(&replacement, selection.start)..utf16_offset(&replacement, selection.end);
+
+        self.input_state.update(cx, |state, cx| {
+            state.replace_and_mark_text_in_range(Some(range), &replacement, Some(selection), window, cx);
+            state.unmark_text(window, cx);
+        });
+    }
+
+    /// 对当前选区执行格式化操作
+    fn format(
+        &mut self,
+        operation: impl FnOnce(&str, Range<usize>) -> Option<TextEdit>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let content = self.content(cx);
+        let selection = self.selection(window, cx);
+        if let Some(edit) = operation(&content, selection) {
+            self.apply_edit(&edit, window, cx);
+        }
+    }
+
+    /// 切换行内样式
+    fn toggle_inline(&mut self, style: InlineStyle, window: &mut Window, cx: &mut Context<Self>) {
+        self.format(|text, selection| formatting::toggle_inline(text, selection, style), window, cx);
+    }
+
+    /// 切换列表
+    fn toggle_list(&mut self, kind: ListKind, window: &mut Window, cx: &mut Context<Self>) {
+        self.format(|text, selection| formatting::toggle_list(text, selection, kind), window, cx);
+    }
+
+    /// 插入链接，剪贴板中是网址时直接使用
+    fn insert_link(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = cx.read_from_clipboard().and_then(|item| item.text());
+        self.format(|text, selection| formatting::insert_link(text, selection, url.as_deref()), window, cx);
+    }
+
+    /// 保存粘贴或拖入的图片的位置
+    fn assets(&self) -> Assets {
+        Assets::in_folder(self.base_dir(), &self.settings.image_folder)
+    }
+
+    /// 把图片保存到图片文件夹，并在光标处插入图片链接
+    fn insert_images(&mut self, images: Vec<(String, Vec<u8>)>, window: &mut Window, cx: &mut Context<Self>) {
+        let mut assets = self.assets();
+        let mut links = Vec::new();
+        for (name, data) in images {
+            match assets.save(&name, &data) {
+                Ok(url) => links.push(Inline::Image { url, alt: String::new(), title: String::new() }.to_markdown()),
+                Err(e) => eprintln!("保存图片失败: {:#}", e),
+            }
+        }
+        if links.is_empty() {
+            return;
+        }
+
+        let markdown = links.join("\n");
+        self.format(|text, selection| Some(typing::paste(text, selection, &markdown, true)), window, cx);
+    }
+
+    /// 剪贴板中只有图片（如截图）时插入图片，返回是否已处理
+    fn paste_image(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(item) = cx.read_from_clipboard() else {
+            return false;
+        };
+        if item.text().is_some() {
+            return false;
+        }
+        let Some(image) = item.entries().iter().find_map(|entry| match entry {
+            ClipboardEntry::Image(image) => Some(image),
+            _ => None,
+        }) else {
+            return false;
+        };
+
+        let name = format!("image.{}", image_extension(image.format().mime_type()));
+        self.insert_images(vec![(name, image.bytes().to_vec())], window, cx);
+        true
+    }
+
+    /// 拖入图片文件时复制到图片文件夹并插入链接，其他文件忽略
+    fn drop_files(&mut self, paths: &ExternalPaths, window: &mut Window, cx: &mut Context<Self>) {
+        let images = paths
+            .paths()
+            .iter()
+            .filter(|path| is_image_file(path))
+            .filter_map(|path| match fs::read(path) {
+                Ok(data) => Some((path.file_name()?.to_string_lossy().to_string(), data)),
+                Err(e) => {
+                    eprintln!("无法读取图片 {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        self.insert_images(images, window, cx);
+    }
+
+    /// 粘贴，剪贴板中是图片时保存并插入链接，有 HTML（从浏览器或 Word 复制）时转换为 Markdown，否则粘贴纯文本
+    fn paste_markdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.paste_image(window, cx) {
+            return;
+        }
+
+        // HTML 中内嵌的图片同样保存到图片文件夹
+        let mut assets = self.assets();
+        let markdown = read_clipboard_html()
+            .and_then(|html| {
+                let options = HtmlConvertOptions { assets: Some(&mut assets), ..Default::default() };
+                html_to_markdown(&html, options).ok()
+            })
+            .filter(|markdown| !markdown.trim().is_empty());
+        match markdown {
+            Some(markdown) => {
+                self.format(|text, selection| Some(typing::paste(text, selection, &markdown, true)), window, cx)
+            }
+            None => self.paste_plain_text(window, cx),
+        }
+    }
+
+    /// 粘贴剪贴板中的纯文本
+    fn paste_plain_text(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(pasted) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        self.format(|text, selection| Some(typing::paste(text, selection, &pasted, false)), window, cx);
+    }
+
+    /// 增加或减少缩进，缩进宽度与制表符宽度相同
+    fn indent(&mut self, outdent: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let unit = " ".repeat(self.settings.tab_width);
+        self.format(
+            |text, selection| {
+                if outdent {
+                    formatting::outdent_lines(text, selection, &unit)
+                } else {
+                    formatting::indent_lines(text, selection, &unit)
+                }
+            },
+            window,
+            cx,
+        );
+    }
+
+    /// 执行输入辅助操作，不需要处理时交给输入框按默认方式处理（如普通回车）
+    fn assist(
+        &mut self,
+        operation: impl FnOnce(&str, Range<usize>) -> Option<TextEdit>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let content = self.content(cx);
+        let selection = self.selection(window, cx);
+        match operation(&content, selection) {
+            Some(edit) => self.apply_edit(&edit, window, cx),
+            None => cx.propagate(),
+        }
+    }
+
+    /// 输入字符前检查是否需要自动闭合
+    fn handle_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if !self.settings.auto_pair
+            || keystroke.modifiers.control
+            || keystroke.modifiers.platform
+            || keystroke.modifiers.function
+        {
+            return;
+        }
+        let Some(typed) = keystroke.key_char.as_deref().filter(|typed| typing::is_pair_trigger(typed)) else {
+            return;
+        };
+
+        let content = self.content(cx);
+        let selection = self.selection(window, cx);
+        if let Some(edit) = typing::auto_pair(&content, selection, typed) {
+            self.apply_edit(&edit, window, cx);
+            cx.stop_propagation();
+        }
+    }
+
+    /// 按住 Ctrl（macOS 上为 Cmd）点击链接时打开链接
+    ///
+    /// 输入框在按下鼠标时已把光标移到点击的位置，松开时查找光标所在的链接
+    fn handle_mouse_up(&mut self, event: &MouseUpEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if event.button != MouseButton::Left || !event.modifiers.secondary() {
+            return;
+        }
+        let content = self.content(cx);
+        let offset = self.selection(window, cx).start;
+        if let Some(link) = find_inline_links(&content).into_iter().find(|link| link.range.contains(&offset)) {
+            cx.emit(TextEditorEvent::OpenLink(link.dest));
+        } else if let Some(link) = find_wiki_links(&content).into_iter().find(|link| link.range.contains(&offset)) {
+            cx.emit(TextEditorEvent::OpenWikiLink(link));
+        }
+    }
+
     /// 获取输入状态的实体引用，用于订阅变化事件
     pub fn input_state(&self) -> Entity<InputState> {
         self.input_state.clone()
     }
 }
 
+/// 读取剪贴板中的 HTML 内容
+///
+/// GPUI 的剪贴板只提供纯文本和图片，HTML 格式通过系统剪贴板读取
+fn read_clipboard_html() -> Option<String> {
+    arboard::Clipboard::new().ok()?.get().html().ok()
+}
+
+/// 字节偏移转换为 UTF-16 偏移（输入法接口使用 UTF-16）
+fn utf16_offset(text: &str, offset: usize) -> usize {
+    text[..offset].encode_utf16().count()
+}
+
+/// UTF-16 偏移转换为字节偏移
+fn byte_offset(text: &str, offset: usize) -> usize {
+    let mut utf16 = 0;
+    for (index, ch) in text.char_indices() {
+        if utf16 >= offset {
+            return index;
+        }
+        utf16 += ch.len_utf16();
+    }
+    text.len()
+}
+
+impl EventEmitter<TextEditorEvent> for TextEditor {}
+
 impl Render for TextEditor {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        // 使用 Input 组件渲染多行文本编辑器
-        Input::new(&self.input_state)
-            .h_full()  // 占据全部可用高度
-            .w_full()  // 占据全部可用宽度
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // 按上一帧的布局记录第一个可见字符，用于保存滚动位置
+        if let Some(bounds) = self.input_bounds.get() {
+            let top_offset = self.input_state.update(cx, |state, cx| {
+                state.character_index_for_point(bounds.origin, window, cx)
+            });
+            if let Some(top_offset) = top_offset {
+                self.top_offset = top_offset;
+            }
+        }
+        let input_bounds = self.input_bounds.clone();
+
+        // 格式化命令只在编辑区获得焦点时生效
+        div()
+            .key_context(EDITOR_CONTEXT)
+            .size_full()
+            .relative()
+            .capture_key_down(cx.listener(Self::handle_key_down))
+            .capture_any_mouse_up(cx.listener(Self::handle_mouse_up))
+            .on_drop(cx.listener(Self::drop_files))
+            .on_action(cx.listener(|this, _: &actions::ToggleBold, window, cx| {
+                this.toggle_inline(InlineStyle::Bold, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleItalic, window, cx| {
+                this.toggle_inline(InlineStyle::Italic, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleStrikethrough, window, cx| {
+                this.toggle_inline(InlineStyle::Strikethrough, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleCode, window, cx| {
+                this.toggle_inline(InlineStyle::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::CycleHeading, window, cx| {
+                this.format(formatting::cycle_heading, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleBlockquote, window, cx| {
+                this.format(formatting::toggle_blockquote, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleBulletList, window, cx| {
+                this.toggle_list(ListKind::Bullet, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleNumberedList, window, cx| {
+                this.toggle_list(ListKind::Numbered, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ToggleTaskList, window, cx| {
+                this.toggle_list(ListKind::Task, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::InsertLink, window, cx| this.insert_link(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::IndentLines, window, cx| this.indent(false, window, cx)))
+            .on_action(cx.listener(|this, _: &actions::OutdentLines, window, cx| this.indent(true, window, cx)))
+            .on_action(cx.listener(|this, _: &actions::MoveLinesUp, window, cx| {
+                this.format(|text, selection| formatting::move_lines(text, selection, true), window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::MoveLinesDown, window, cx| {
+                this.format(|text, selection| formatting::move_lines(text, selection, false), window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ContinueList, window, cx| {
+                this.assist(typing::continue_list, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::IndentListItem, window, cx| {
+                let unit = " ".repeat(this.settings.tab_width);
+                this.assist(|text, selection| typing::indent_list_items(text, selection, &unit), window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::OutdentListItem, window, cx| {
+                let unit = " ".repeat(this.settings.tab_width);
+                this.assist(|text, selection| typing::outdent_list_items(text, selection, &unit), window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::PasteMarkdown, window, cx| this.paste_markdown(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::PastePlainText, window, cx| {
+                this.paste_plain_text(window, cx)
+            }))
+            .child(
+                // 使用 Input 组件渲染多行文本编辑器
+                Input::new(&self.input_state)
+                    .font_family(self.settings.font_family.clone())
+                    .text_size(px(self.settings.font_size as f32))
+                    .h_full()  // 占据全部可用高度
+                    .w_full()  // 占据全部可用宽度
+            )
+            .child(
+                // 只记录输入框的位置，不绘制内容
+                canvas(move |bounds, _window, _cx| input_bounds.set(Some(bounds)), |_, _, _, _| {})
+                    .absolute()
+                    .size_full()
+            )
     }
 }
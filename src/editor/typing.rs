@@ -0,0 +1,331 @@
+//! 输入辅助模块
+//!
+//! 在编辑器中输入时自动补全 Markdown 结构：
+//! - 在列表项中回车时续写列表，有序列表的后续项重新编号
+//! - 在空列表项中回车时退出列表
+//! - Tab / Shift-Tab 调整列表项的层级
+//! - 自动闭合 `**`、反引号、括号和代码块，光标位于中间
+//! - 粘贴多段 Markdown 时与光标所在行的其他文字用空行隔开
+//!
+//! 与格式化命令相同，每个操作返回一次替换（[`TextEdit`]），不需要处理时返回 `None`，
+//! 由输入框按默认方式处理
+
+use std::ops::Range;
+
+use super::formatting::{all_non_blank, indent_lines, list_marker, outdent_lines, ListKind, TextEdit};
+
+/// 可以自动闭合的字符
+const PAIRS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}'), ('`', '`')];
+
+/// 在光标处回车时续写列表
+///
+/// 光标位于列表项的标记之后时：
+/// - 列表项为空：去掉标记，退出列表
+/// - 否则换行并插入同级的新标记（任务列表的新项未勾选），
+///   有序列表中后续的同级项依次加一
+pub fn continue_list(text: &str, selection: Range<usize>) -> Option<TextEdit> {
+    if !selection.is_empty() {
+        return None;
+    }
+    let cursor = selection.start;
+    let start = line_start(text, cursor);
+    let mut end = line_end(text, cursor);
+    let line = &text[start..end];
+
+    let (kind, marker) = list_marker(line)?;
+    if cursor < start + marker.end {
+        return None;
+    }
+
+    if line[marker.end..].trim().is_empty() {
+        return Some(TextEdit {
+            range: start..end,
+            replacement: String::new(),
+            selection: start..start,
+        });
+    }
+
+    let indent = &line[..marker.start];
+    let marker_text = &line[marker];
+    let (new_marker, next_number) = match kind {
+        ListKind::Bullet => (marker_text.to_string(), None),
+        ListKind::Task => (format!("{}[ ] ", &marker_text[..2]), None),
+        ListKind::Numbered => {
+            let (number, delimiter) = split_number(marker_text)?;
+            (format!("{}{} ", number + 1, delimiter), Some(number + 2))
+        }
+    };
+
+    let mut replacement = format!("\n{}{}", indent, new_marker);
+    let new_cursor = cursor + replacement.len();
+    replacement.push_str(&text[cursor..end]);
+
+    if let Some(mut number) = next_number {
+        while end < text.len() {
+            let next_end = line_end(text, end + 1);
+            let next = &text[end + 1..next_end];
+            let next_indent = next.len() - next.trim_start().len();
+            if next.trim().is_empty() || next_indent < indent.len() {
+                break;
+            }
+
+            replacement.push('\n');
+            if next_indent == indent.len() {
+                // 同级的有序列表项重新编号，遇到其他内容时结束
+                let Some((ListKind::Numbered, marker)) = list_marker(next) else {
+                    replacement.truncate(replacement.len() - 1);
+                    break;
+                };
+                let (_, delimiter) = split_number(&next[marker.clone()])?;
+                replacement.push_str(&format!("{}{}{} ", indent, number, delimiter));
+                replacement.push_str(&next[marker.end..]);
+                number += 1;
+            } else {
+                // 更深层的内容属于当前列表项
+                replacement.push_str(next);
+            }
+            end = next_end;
+        }
+    }
+
+    Some(TextEdit {
+        range: cursor..end,
+        replacement,
+        selection: new_cursor..new_cursor,
+    })
+}
+
+/// 增加选区所在列表项的层级
+///
+/// 选区覆盖的非空行都是列表项时才处理，否则返回 `None`
+pub fn indent_list_items(text: &str, selection: Range<usize>, unit: &str) -> Option<TextEdit> {
+    if !is_list(text, &selection) {
+        return None;
+    }
+    indent_lines(text, selection, unit)
+}
+
+/// 减少选区所在列表项的层级
+pub fn outdent_list_items(text: &str, selection: Range<usize>, unit: &str) -> Option<TextEdit> {
+    if !is_list(text, &selection) {
+        return None;
+    }
+    outdent_lines(text, selection, unit)
+}
+
+/// 输入的字符是否可能触发自动闭合
+pub fn is_pair_trigger(typed: &str) -> bool {
+    let mut chars = typed.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c == '*' || PAIRS.iter().any(|&(open, close)| c == open || c == close),
+        _ => false,
+    }
+}
+
+/// 输入字符时自动闭合
+///
+/// - 有选中文本时用成对的字符包围选区
+/// - 输入左括号或反引号时插入一对，光标位于中间
+/// - 在 `*` 后再输入 `*` 时补全为 `**|**`
+/// - 在行首的两个反引号后输入第三个时补全代码块的结束标记
+/// - 输入的字符与光标后的闭合字符相同时跳过该字符
+pub fn auto_pair(text: &str, selection: Range<usize>, typed: &str) -> Option<TextEdit> {
+    if !is_pair_trigger(typed) {
+        return None;
+    }
+    let c = typed.chars().next()?;
+    let closing = if c == '*' {
+        Some('*')
+    } else {
+        PAIRS.iter().find(|&&(open, _)| open == c).map(|&(_, close)| close)
+    };
+
+    if !selection.is_empty() {
+        let closing = closing?;
+        return Some(TextEdit {
+            range: selection.clone(),
+            replacement: format!("{}{}{}", c, &text[selection.clone()], closing),
+            selection: selection.start + 1..selection.end + 1,
+        });
+    }
+
+    let cursor = selection.start;
+    let before = text[..cursor].chars().next_back();
+    let after = text[cursor..].chars().next();
+    let insert = |replacement: String, offset: usize| TextEdit {
+        range: cursor..cursor,
+        replacement,
+        selection: cursor + offset..cursor + offset,
+    };
+
+    let prefix = &text[line_start(text, cursor)..cursor];
+    if c == '`' && prefix.trim_start() == "``" && text[cursor..line_end(text, cursor)].trim().is_empty() {
+        // 已经打开的代码块正在被关闭时不补全
+        if fence_count(&text[..line_start(text, cursor)]) % 2 == 1 {
+            return None;
+        }
+        let indent = &prefix[..prefix.len() - 2];
+        return Some(insert(format!("`\n{}```", indent), 1));
+    }
+
+    let is_closing = c == '*' || PAIRS.iter().any(|&(_, close)| close == c);
+    if is_closing && after == Some(c) && (c != '*' || before.is_some_and(|b| !b.is_whitespace())) {
+        return Some(TextEdit {
+            range: cursor..cursor + c.len_utf8(),
+            replacement: c.to_string(),
+            selection: cursor + c.len_utf8()..cursor + c.len_utf8(),
+        });
+    }
+
+    let closing = closing?;
+    let free_after = after.is_none_or(|a| a.is_whitespace() || PAIRS.iter().any(|&(_, close)| close == a));
+    if !free_after {
+        return None;
+    }
+
+    match c {
+        '*' => {
+            // 只在输入第二个星号时补全，单个星号可能是列表标记
+            let before_star = text[..cursor].strip_suffix('*')?.chars().next_back();
+            (before_star != Some('*')).then(|| insert("***".to_string(), 1))
+        }
+        '`' => before
+            .is_none_or(|b| !b.is_alphanumeric() && b != '`')
+            .then(|| insert(format!("{}{}", c, closing), 1)),
+        _ => Some(insert(format!("{}{}", c, closing), 1)),
+    }
+}
+
+/// 在选区处粘贴文字，光标移到粘贴内容之后
+///
+/// `markdown` 为 `true` 且粘贴的内容有多行时，按块级内容处理：
+/// 光标前后同一行中还有其他文字时用空行隔开
+pub fn paste(text: &str, selection: Range<usize>, pasted: &str, markdown: bool) -> TextEdit {
+    let mut replacement = pasted.to_string();
+    if markdown && pasted.contains('\n') {
+        if !text[line_start(text, selection.start)..selection.start].trim().is_empty() {
+            replacement.insert_str(0, "\n\n");
+        }
+        if !text[selection.end..line_end(text, selection.end)].trim().is_empty() {
+            replacement.push_str("\n\n");
+        }
+    }
+    let cursor = selection.start + replacement.len();
+    let cursor = if markdown && replacement.ends_with("\n\n") { cursor - 2 } else { cursor };
+    TextEdit { range: selection, replacement, selection: cursor..cursor }
+}
+
+/// 选区覆盖的非空行是否都是列表项
+fn is_list(text: &str, selection: &Range<usize>) -> bool {
+    all_non_blank(text, selection, |line| list_marker(line).is_some())
+}
+
+/// 拆分有序列表标记，如 `12. ` 拆分为 12 和 `.`
+fn split_number(marker: &str) -> Option<(u64, char)> {
+    let marker = marker.trim_end();
+    let delimiter = marker.chars().next_back()?;
+    let number = marker[..marker.len() - 1].parse().ok()?;
+    Some((number, delimiter))
+}
+
+/// 位置所在行的行首
+fn line_start(text: &str, position: usize) -> usize {
+    text[..position].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// 位置所在行的行尾（不包括换行符）
+fn line_end(text: &str, position: usize) -> usize {
+    text[position..].find('\n').map_or(text.len(), |i| position + i)
+}
+
+/// 文本中代码块标记行的数量
+fn fence_count(text: &str) -> usize {
+    text.lines().filter(|line| line.trim_start().starts_with("```")).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 应用编辑，返回新文本并用 `|` 标出光标
+    fn run(text: &str, cursor: usize, f: impl FnOnce(&str, Range<usize>) -> Option<TextEdit>) -> String {
+        let edit = f(text, cursor..cursor).expect("应产生编辑");
+        let mut new_text = edit.apply(text);
+        new_text.insert(edit.selection.start, '|');
+        new_text
+    }
+
+    #[test]
+    fn test_continue_list() {
+        assert_eq!(run("- a", 3, continue_list), "- a\n- |");
+        assert_eq!(run("  * ab", 5, continue_list), "  * a\n  * |b");
+        assert_eq!(run("- [x] done", 10, continue_list), "- [x] done\n- [ ] |");
+
+        // 空列表项回车时退出列表
+        assert_eq!(run("- a\n- ", 6, continue_list), "- a\n|");
+
+        // 光标在标记之前或不在列表中时按普通回车处理
+        assert!(continue_list("- a", 1..1).is_none());
+        assert!(continue_list("text", 4..4).is_none());
+        assert!(continue_list("- a", 0..3).is_none());
+    }
+
+    #[test]
+    fn test_continue_numbered_list() {
+        assert_eq!(
+            run("1. a\n2. b\n   more\n3) c\n  - x\n\n1. d", 4, continue_list),
+            "1. a\n2. |\n3. b\n   more\n4) c\n  - x\n\n1. d"
+        );
+        // 遇到同级的其他内容时停止编号
+        assert_eq!(run("1. a\n2. b\ntext\n3. c", 9, continue_list), "1. a\n2. b\n3. |\ntext\n3. c");
+    }
+
+    #[test]
+    fn test_list_indent() {
+        let edit = indent_list_items("- a\n- b", 5..5, "  ").unwrap();
+        assert_eq!(edit.apply("- a\n- b"), "- a\n  - b");
+        let edit = outdent_list_items("- a\n  - b", 0..9, "  ").unwrap();
+        assert_eq!(edit.apply("- a\n  - b"), "- a\n- b");
+        assert!(indent_list_items("text", 0..0, "  ").is_none());
+    }
+
+    #[test]
+    fn test_auto_pair() {
+        let pair = |typed: &'static str| move |t: &str, s| auto_pair(t, s, typed);
+
+        assert_eq!(run("a ", 2, pair("(")), "a (|)");
+        assert_eq!(run("(a)", 2, pair(")")), "(a)|");
+        assert_eq!(run("", 0, pair("`")), "`|`");
+        assert!(auto_pair("word", 4..4, "`").is_none());
+        assert!(auto_pair("ab", 1..1, "[").is_none());
+
+        // 粗体
+        assert!(auto_pair("- ", 2..2, "*").is_none());
+        assert_eq!(run("a *", 3, pair("*")), "a **|**");
+        assert_eq!(run("**b**", 3, pair("*")), "**b*|*");
+
+        // 代码块
+        assert_eq!(run("``", 2, pair("`")), "```|\n```");
+        assert!(auto_pair("```\ncode\n``", 11..11, "`").is_none());
+
+        // 包围选区
+        let edit = auto_pair("a b", 2..3, "`").unwrap();
+        assert_eq!(edit.apply("a b"), "a `b`");
+        assert_eq!(edit.selection, 3..4);
+        assert!(auto_pair("ab", 0..1, "a").is_none());
+    }
+
+    #[test]
+    fn test_paste() {
+        let paste_markdown = |pasted: &'static str| move |t: &str, s| Some(paste(t, s, pasted, true));
+
+        assert_eq!(run("ab", 1, paste_markdown("**x**")), "a**x**|b");
+        assert_eq!(run("ab", 1, paste_markdown("# x\n\ny")), "a\n\n# x\n\ny|\n\nb");
+        assert_eq!(run("a\n", 2, paste_markdown("- x\n- y")), "a\n- x\n- y|");
+
+        // 纯文本原样粘贴，替换选中的文字
+        let edit = paste("a b c", 2..3, "x\ny", false);
+        assert_eq!(edit.apply("a b c"), "a x\ny c");
+        assert_eq!(edit.selection, 5..5);
+    }
+}
@@ -0,0 +1,654 @@
+//! Word 导出模块
+//!
+//! 生成 Word 文档（`.docx`），保留文档结构，不依赖 Word 或其他程序：
+//! - 标题使用“标题 1”到“标题 6”样式，Word 的导航窗格可以显示文档结构
+//! - 列表、表格、脚注和图片转换为 Word 中对应的元素
+//! - 可以指定参考文档，使用其中的样式、主题和页面设置；参考文档缺少的样式使用内置样式
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Context, Result};
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+use regex::Regex;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::config::DocxSettings;
+
+use super::docx_body::{build, xml_escape, BodyOptions, ListKind, LIST_INDENT};
+
+/// 每缇（1/20 磅）的 EMU 数
+const EMU_PER_TWIP: u64 = 635;
+
+/// WordprocessingML 命名空间
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+/// 文档关系命名空间
+const R_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+/// 包关系类型的前缀
+const REL_TYPE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// 没有参考文档时的页面设置：A4 纸，页边距 1 英寸
+const DEFAULT_SECTION: &str = "<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>\
+    <w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"720\" w:footer=\"720\" w:gutter=\"0\"/>\
+    </w:sectPr>";
+
+/// 文档默认格式
+const DOC_DEFAULTS: &str = "<w:docDefaults><w:rPrDefault><w:rPr>\
+    <w:rFonts w:ascii=\"Calibri\" w:hAnsi=\"Calibri\" w:eastAsia=\"等线\" w:cs=\"Calibri\"/>\
+    <w:sz w:val=\"22\"/><w:szCs w:val=\"22\"/><w:lang w:val=\"en-US\" w:eastAsia=\"zh-CN\"/></w:rPr></w:rPrDefault>\
+    <w:pPrDefault><w:pPr><w:spacing w:after=\"160\" w:line=\"276\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault>\
+    </w:docDefaults>";
+
+/// 内置样式，参考文档中没有同名样式时添加
+const STYLES: &[(&str, &str)] = &[
+    ("Normal", "<w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/></w:style>"),
+    (
+        "DefaultParagraphFont",
+        "<w:style w:type=\"character\" w:default=\"1\" w:styleId=\"DefaultParagraphFont\">\
+         <w:name w:val=\"Default Paragraph Font\"/><w:uiPriority w:val=\"1\"/><w:semiHidden/><w:unhideWhenUsed/></w:style>",
+    ),
+    (
+        "TableNormal",
+        "<w:style w:type=\"table\" w:default=\"1\" w:styleId=\"TableNormal\"><w:name w:val=\"Normal Table\"/>\
+         <w:uiPriority w:val=\"99\"/><w:semiHidden/><w:unhideWhenUsed/><w:tblPr><w:tblInd w:w=\"0\" w:type=\"dxa\"/>\
+         <w:tblCellMar><w:top w:w=\"0\" w:type=\"dxa\"/><w:left w:w=\"108\" w:type=\"dxa\"/>\
+         <w:bottom w:w=\"0\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar></w:tblPr></w:style>",
+    ),
+    ("Heading1", ""),
+    ("Heading2", ""),
+    ("Heading3", ""),
+    ("Heading4", ""),
+    ("Heading5", ""),
+    ("Heading6", ""),
+    (
+        "Quote",
+        "<w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/>\
+         <w:next w:val=\"Normal\"/><w:uiPriority w:val=\"29\"/><w:qFormat/><w:pPr>\
+         <w:pBdr><w:left w:val=\"single\" w:sz=\"18\" w:space=\"8\" w:color=\"D0D7DE\"/></w:pBdr>\
+         <w:ind w:left=\"360\" w:right=\"360\"/></w:pPr><w:rPr><w:color w:val=\"595959\"/></w:rPr></w:style>",
+    ),
+    (
+        "ListParagraph",
+        "<w:style w:type=\"paragraph\" w:styleId=\"ListParagraph\"><w:name w:val=\"List Paragraph\"/>\
+         <w:basedOn w:val=\"Normal\"/><w:uiPriority w:val=\"34\"/><w:qFormat/><w:pPr>\
+         <w:spacing w:after=\"60\"/><w:ind w:left=\"720\"/></w:pPr></w:style>",
+    ),
+    (
+        "SourceCode",
+        "<w:style w:type=\"paragraph\" w:customStyle=\"1\" w:styleId=\"SourceCode\"><w:name w:val=\"Source Code\"/>\
+         <w:basedOn w:val=\"Normal\"/><w:link w:val=\"VerbatimChar\"/><w:qFormat/><w:pPr><w:wordWrap w:val=\"0\"/>\
+         <w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F6F8FA\"/><w:spacing w:after=\"160\" w:line=\"240\" w:lineRule=\"auto\"/>\
+         </w:pPr><w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/>\
+         <w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/></w:rPr></w:style>",
+    ),
+    (
+        "VerbatimChar",
+        "<w:style w:type=\"character\" w:customStyle=\"1\" w:styleId=\"VerbatimChar\"><w:name w:val=\"Verbatim Char\"/>\
+         <w:basedOn w:val=\"DefaultParagraphFont\"/><w:link w:val=\"SourceCode\"/><w:rPr>\
+         <w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/>\
+         <w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F6F8FA\"/></w:rPr></w:style>",
+    ),
+    (
+        "Hyperlink",
+        "<w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/>\
+         <w:basedOn w:val=\"DefaultParagraphFont\"/><w:uiPriority w:val=\"99\"/><w:unhideWhenUsed/><w:rPr>\
+         <w:color w:val=\"0563C1\"/><w:u w:val=\"single\"/></w:rPr></w:style>",
+    ),
+    (
+        "FootnoteText",
+        "<w:style w:type=\"paragraph\" w:styleId=\"FootnoteText\"><w:name w:val=\"footnote text\"/>\
+         <w:basedOn w:val=\"Normal\"/><w:uiPriority w:val=\"99\"/><w:unhideWhenUsed/><w:pPr>\
+         <w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:sz w:val=\"20\"/>\
+         <w:szCs w:val=\"20\"/></w:rPr></w:style>",
+    ),
+    (
+        "FootnoteReference",
+        "<w:style w:type=\"character\" w:styleId=\"FootnoteReference\"><w:name w:val=\"footnote reference\"/>\
+         <w:basedOn w:val=\"DefaultParagraphFont\"/><w:uiPriority w:val=\"99\"/><w:unhideWhenUsed/><w:rPr>\
+         <w:vertAlign w:val=\"superscript\"/></w:rPr></w:style>",
+    ),
+    (
+        "TableGrid",
+        "<w:style w:type=\"table\" w:styleId=\"TableGrid\"><w:name w:val=\"Table Grid\"/><w:basedOn w:val=\"TableNormal\"/>\
+         <w:uiPriority w:val=\"39\"/><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr>\
+         <w:tblPr><w:tblBorders><w:top w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/>\
+         <w:left w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/>\
+         <w:bottom w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/>\
+         <w:right w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/>\
+         <w:insideH w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/>\
+         <w:insideV w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"BFBFBF\"/></w:tblBorders>\
+         <w:tblCellMar><w:top w:w=\"40\" w:type=\"dxa\"/><w:left w:w=\"108\" w:type=\"dxa\"/>\
+         <w:bottom w:w=\"40\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar></w:tblPr></w:style>",
+    ),
+];
+
+/// 各级标题的字号（半磅）
+const HEADING_SIZES: [u32; 6] = [32, 28, 26, 24, 22, 22];
+
+/// 各级无序列表的符号
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+/// 各级有序列表的编号格式
+const NUMBER_FORMATS: [&str; 3] = ["decimal", "lowerLetter", "lowerRoman"];
+
+/// Word 导出选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocxExportOptions {
+    /// 参考文档，使用其中的样式和页面设置
+    pub reference: Option<PathBuf>,
+}
+
+impl DocxExportOptions {
+    /// 根据用户设置创建导出选项
+    pub fn from_settings(settings: &DocxSettings) -> Self {
+        let reference = settings.reference_doc.trim();
+        Self {
+            reference: (!reference.is_empty()).then(|| PathBuf::from(reference)),
+        }
+    }
+}
+
+/// 从参考文档中读取的内容
+#[derive(Debug, Clone, Default)]
+struct Template {
+    /// 样式（已补上缺少的内置样式）
+    styles: String,
+    theme: Option<Vec<u8>>,
+    /// 编号定义中的 `abstractNum`（包括图片符号）和 `num` 元素
+    abstract_nums: String,
+    nums: String,
+    max_abstract_num_id: usize,
+    max_num_id: usize,
+    /// 分节属性（页面大小和页边距）
+    section: String,
+    /// 版心宽度（缇）
+    text_width: u64,
+}
+
+impl Template {
+    /// 内置模板
+    fn builtin() -> Self {
+        let mut template = Self {
+            styles: format!("<w:styles xmlns:w=\"{}\">{}</w:styles>", W_NS, DOC_DEFAULTS),
+            section: DEFAULT_SECTION.to_string(),
+            ..Default::default()
+        };
+        template.add_missing_styles(&HashSet::new());
+        template.text_width = section_text_width(&template.section);
+        template
+    }
+
+    /// 读取参考文档
+    fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("无法打开参考文档: {}", path.display()))?;
+        let mut archive = ZipArchive::new(file).with_context(|| format!("参考文档不是有效的 Word 文档: {}", path.display()))?;
+        let styles = read_entry(&mut archive, "word/styles.xml")?
+            .ok_or_else(|| anyhow!("参考文档中没有样式: {}", path.display()))?;
+        let styles = String::from_utf8(styles).context("参考文档的样式不是 UTF-8 编码")?;
+        let existing = style_ids(&styles).with_context(|| format!("无法解析参考文档的样式: {}", path.display()))?;
+
+        let mut template = Self {
+            styles,
+            theme: read_entry(&mut archive, "word/theme/theme1.xml")?,
+            section: DEFAULT_SECTION.to_string(),
+            ..Default::default()
+        };
+        template.add_missing_styles(&existing);
+
+        if let Some(numbering) = read_entry(&mut archive, "word/numbering.xml")? {
+            template.load_numbering(&String::from_utf8_lossy(&numbering));
+        }
+        if let Some(document) = read_entry(&mut archive, "word/document.xml")? {
+            if let Some(section) = last_section(&String::from_utf8_lossy(&document)) {
+                template.section = section;
+            }
+        }
+        template.text_width = section_text_width(&template.section);
+        Ok(template)
+    }
+
+    /// 在样式表末尾添加缺少的内置样式
+    fn add_missing_styles(&mut self, existing: &HashSet<String>) {
+        let missing: String = STYLES
+            .iter()
+            .filter(|(id, _)| !existing.contains(*id))
+            .map(|(id, xml)| match id.strip_prefix("Heading") {
+                Some(level) => heading_style(level.parse().unwrap_or(1)),
+                None => xml.to_string(),
+            })
+            .collect();
+        if let Some(end) = self.styles.rfind("</w:styles>") {
+            self.styles.insert_str(end, &missing);
+        }
+    }
+
+    /// 保留参考文档中的编号定义，新的编号定义排在其后
+    fn load_numbering(&mut self, numbering: &str) {
+        let element = |name: &str| Regex::new(&format!(r"(?s)<w:{0}\b[^>]*/>|<w:{0}\b.*?</w:{0}>", name)).expect("正则表达式有效");
+        let id = |attribute: &str, xml: &str| -> usize {
+            Regex::new(&format!(r#"w:{}="(\d+)""#, attribute))
+                .expect("正则表达式有效")
+                .captures_iter(xml)
+                .filter_map(|captures| captures[1].parse().ok())
+                .max()
+                .unwrap_or(0)
+        };
+
+        for name in ["numPicBullet", "abstractNum"] {
+            for found in element(name).find_iter(numbering) {
+                self.abstract_nums.push_str(found.as_str());
+            }
+        }
+        for found in element("num").find_iter(numbering) {
+            self.nums.push_str(found.as_str());
+        }
+        self.max_abstract_num_id = id("abstractNumId", &self.abstract_nums);
+        self.max_num_id = id("numId", &self.nums);
+    }
+}
+
+/// Word 导出器
+///
+/// 创建时读取参考文档，同一个导出器可以导出多个文档
+pub struct DocxExporter {
+    template: Template,
+}
+
+impl DocxExporter {
+    /// 创建导出器，参考文档无法读取时返回错误
+    pub fn new(options: DocxExportOptions) -> Result<Self> {
+        let template = match &options.reference {
+            Some(path) => Template::load(path)?,
+            None => Template::builtin(),
+        };
+        Ok(Self { template })
+    }
+
+    /// 生成 Word 文档
+    ///
+    /// `source` 为文档所在的路径，用于查找图片；没有一级标题时用文件名作为标题
+    pub fn render(&self, markdown: &str, source: Option<&Path>) -> Result<Vec<u8>> {
+        let template = &self.template;
+        let bullet_id = template.max_abstract_num_id + 1;
+        let ordered_id = template.max_abstract_num_id + 2;
+        let body = build(
+            markdown,
+            source,
+            BodyOptions {
+                first_num_id: template.max_num_id + 1,
+                max_image_width: template.text_width * EMU_PER_TWIP,
+            },
+        );
+        let title = body
+            .title
+            .clone()
+            .or_else(|| source.and_then(Path::file_stem).map(|stem| stem.to_string_lossy().to_string()))
+            .unwrap_or_default();
+
+        let document = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <w:document xmlns:w=\"{}\" xmlns:r=\"{}\" \
+             xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" \
+             xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+             xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><w:body>{}{}</w:body></w:document>",
+            W_NS, R_NS, body.xml, template.section
+        );
+
+        let mut numbering = format!("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{}\" xmlns:r=\"{}\">", W_NS, R_NS);
+        numbering.push_str(&template.abstract_nums);
+        numbering.push_str(&abstract_num(bullet_id, false));
+        numbering.push_str(&abstract_num(ordered_id, true));
+        numbering.push_str(&template.nums);
+        for (index, list) in body.lists.iter().enumerate() {
+            let num_id = template.max_num_id + 1 + index;
+            numbering.push_str(&match list {
+                ListKind::Bullet => format!("<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/></w:num>", num_id, bullet_id),
+                // 每个有序列表从自己的起始编号开始，不接着上一个列表编号
+                // 嵌套列表使用其他级别，所有级别都从列表的起始编号开始
+                ListKind::Ordered(start) => format!(
+                    "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/>{}</w:num>",
+                    num_id,
+                    ordered_id,
+                    (0..9)
+                        .map(|level| format!(
+                            "<w:lvlOverride w:ilvl=\"{}\"><w:startOverride w:val=\"{}\"/></w:lvlOverride>",
+                            level, start
+                        ))
+                        .collect::<String>()
+                ),
+            });
+        }
+        numbering.push_str("</w:numbering>");
+
+        let mut footnotes = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:footnotes xmlns:w=\"{}\" xmlns:r=\"{}\">\
+             <w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:r><w:separator/></w:r></w:p></w:footnote>\
+             <w:footnote w:type=\"continuationSeparator\" w:id=\"0\"><w:p><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>",
+            W_NS, R_NS
+        );
+        for (id, content) in &body.footnotes {
+            footnotes.push_str(&format!("<w:footnote w:id=\"{}\">{}</w:footnote>", id, content));
+        }
+        footnotes.push_str("</w:footnotes>");
+
+        let settings = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:settings xmlns:w=\"{}\">\
+             <w:footnotePr><w:footnote w:id=\"-1\"/><w:footnote w:id=\"0\"/></w:footnotePr>\
+             <w:compat><w:compatSetting w:name=\"compatibilityMode\" w:uri=\"http://schemas.microsoft.com/office/word\" w:val=\"15\"/></w:compat>\
+             </w:settings>",
+            W_NS
+        );
+
+        let mut relationships = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        );
+        for (id, kind, target) in [
+            ("rStyles", "styles", "styles.xml"),
+            ("rNumbering", "numbering", "numbering.xml"),
+            ("rFootnotes", "footnotes", "footnotes.xml"),
+            ("rSettings", "settings", "settings.xml"),
+        ] {
+            relationships.push_str(&relationship(id, kind, target, false));
+        }
+        if template.theme.is_some() {
+            relationships.push_str(&relationship("rTheme", "theme", "theme/theme1.xml", false));
+        }
+        for (id, target) in &body.links {
+            relationships.push_str(&relationship(id, "hyperlink", target, true));
+        }
+        for media in &body.media {
+            relationships.push_str(&relationship(&media.id, "image", &format!("media/{}", media.name), false));
+        }
+        relationships.push_str("</Relationships>");
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let mut add = |name: &str, data: &[u8]| -> Result<()> {
+            zip.start_file(name, SimpleFileOptions::default().compression_method(CompressionMethod::Deflated))?;
+            zip.write_all(data)?;
+            Ok(())
+        };
+        add("[Content_Types].xml", content_types(template.theme.is_some()).as_bytes())?;
+        add("_rels/.rels", PACKAGE_RELATIONSHIPS.as_bytes())?;
+        add("docProps/core.xml", core_properties(&title).as_bytes())?;
+        add("word/document.xml", document.as_bytes())?;
+        add("word/_rels/document.xml.rels", relationships.as_bytes())?;
+        add("word/styles.xml", template.styles.as_bytes())?;
+        add("word/numbering.xml", numbering.as_bytes())?;
+        add("word/footnotes.xml", footnotes.as_bytes())?;
+        add("word/settings.xml", settings.as_bytes())?;
+        if let Some(theme) = &template.theme {
+            add("word/theme/theme1.xml", theme)?;
+        }
+        for media in &body.media {
+            add(&format!("word/media/{}", media.name), &media.data)?;
+        }
+        let cursor = zip.finish().context("无法生成 Word 文档")?;
+        Ok(cursor.into_inner())
+    }
+
+    /// 导出为 Word 文件
+    pub fn export_file(&self, markdown: &str, source: Option<&Path>, target: &Path) -> Result<()> {
+        let docx = self.render(markdown, source)?;
+        fs::write(target, docx).with_context(|| format!("无法写入文件: {}", target.display()))
+    }
+}
+
+/// 包的关系：主文档和文档属性
+const PACKAGE_RELATIONSHIPS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+    <Relationship Id=\"rDocument\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>\
+    <Relationship Id=\"rCore\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>\
+    </Relationships>";
+
+/// 各部分的内容类型
+fn content_types(theme: bool) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Default Extension=\"png\" ContentType=\"image/png\"/>\
+         <Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/>\
+         <Default Extension=\"gif\" ContentType=\"image/gif\"/>\
+         <Default Extension=\"bmp\" ContentType=\"image/bmp\"/>",
+    );
+    let wordprocessing = "application/vnd.openxmlformats-officedocument.wordprocessingml";
+    for (part, kind) in [
+        ("/word/document.xml", "document.main"),
+        ("/word/styles.xml", "styles"),
+        ("/word/numbering.xml", "numbering"),
+        ("/word/footnotes.xml", "footnotes"),
+        ("/word/settings.xml", "settings"),
+    ] {
+        xml.push_str(&format!("<Override PartName=\"{}\" ContentType=\"{}.{}+xml\"/>", part, wordprocessing, kind));
+    }
+    if theme {
+        xml.push_str("<Override PartName=\"/word/theme/theme1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.theme+xml\"/>");
+    }
+    xml.push_str("<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>");
+    xml.push_str("</Types>");
+    xml
+}
+
+/// 文档属性（标题）
+fn core_properties(title: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\"><dc:title>{}</dc:title></cp:coreProperties>",
+        xml_escape(title)
+    )
+}
+
+/// 文档关系
+fn relationship(id: &str, kind: &str, target: &str, external: bool) -> String {
+    format!(
+        "<Relationship Id=\"{}\" Type=\"{}/{}\" Target=\"{}\"{}/>",
+        id,
+        REL_TYPE,
+        kind,
+        xml_escape(target),
+        if external { " TargetMode=\"External\"" } else { "" }
+    )
+}
+
+/// 标题样式，大纲级别使标题显示在导航窗格中
+fn heading_style(level: usize) -> String {
+    let size = HEADING_SIZES[level.clamp(1, 6) - 1];
+    format!(
+        "<w:style w:type=\"paragraph\" w:styleId=\"Heading{level}\"><w:name w:val=\"heading {level}\"/>\
+         <w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:uiPriority w:val=\"9\"/><w:qFormat/><w:pPr>\
+         <w:keepNext/><w:keepLines/><w:spacing w:before=\"{before}\" w:after=\"120\"/><w:outlineLvl w:val=\"{outline}\"/>\
+         </w:pPr><w:rPr><w:b/><w:bCs/><w:color w:val=\"1F2328\"/><w:sz w:val=\"{size}\"/><w:szCs w:val=\"{size}\"/></w:rPr></w:style>",
+        level = level,
+        before = if level <= 2 { 360 } else { 240 },
+        outline = level - 1,
+        size = size,
+    )
+}
+
+/// 列表的编号定义，共 9 级
+fn abstract_num(id: usize, ordered: bool) -> String {
+    let mut xml = format!(
+        "<w:abstractNum w:abstractNumId=\"{}\"><w:multiLevelType w:val=\"hybridMultilevel\"/>",
+        id
+    );
+    for level in 0..9 {
+        let (format, text) = if ordered {
+            (NUMBER_FORMATS[level % NUMBER_FORMATS.len()], format!("%{}.", level + 1))
+        } else {
+            ("bullet", BULLETS[level % BULLETS.len()].to_string())
+        };
+        xml.push_str(&format!(
+            "<w:lvl w:ilvl=\"{}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{}\"/><w:lvlText w:val=\"{}\"/>\
+             <w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+            level,
+            format,
+            text,
+            LIST_INDENT * (level + 1)
+        ));
+    }
+    xml.push_str("</w:abstractNum>");
+    xml
+}
+
+/// 读取文档包中的文件，文件不存在时返回 `None`
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<Vec<u8>>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("无法读取参考文档中的 {}", name)),
+    };
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .with_context(|| format!("无法读取参考文档中的 {}", name))?;
+    Ok(Some(data))
+}
+
+/// 样式表中已定义的样式编号
+fn style_ids(styles: &str) -> Result<HashSet<String>> {
+    let mut reader = Reader::from_str(styles);
+    let mut ids = HashSet::new();
+    loop {
+        match reader.read_event()? {
+            XmlEvent::Start(element) | XmlEvent::Empty(element) if element.local_name().as_ref() == b"style" => {
+                for attribute in element.attributes().flatten() {
+                    if attribute.key.local_name().as_ref() == b"styleId" {
+                        ids.insert(attribute.unescape_value()?.to_string());
+                    }
+                }
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(ids)
+}
+
+/// 文档最后一节的分节属性，去掉引用参考文档中页眉页脚的部分
+fn last_section(document: &str) -> Option<String> {
+    let section = Regex::new(r"(?s)<w:sectPr\b.*?</w:sectPr>").expect("正则表达式有效");
+    let found = section.find_iter(document).last()?;
+    let references = Regex::new(r"(?s)<w:(header|footer)Reference\b[^>]*/>").expect("正则表达式有效");
+    Some(references.replace_all(found.as_str(), "").to_string())
+}
+
+/// 分节属性中的版心宽度（缇），无法读取时按 A4 纸和 1 英寸页边距计算
+fn section_text_width(section: &str) -> u64 {
+    let value = |element: &str, attribute: &str| -> Option<u64> {
+        let pattern = format!(r#"<w:{}\b[^>]*\bw:{}="(\d+)""#, element, attribute);
+        Regex::new(&pattern).ok()?.captures(section)?[1].parse().ok()
+    };
+    let width = value("pgSz", "w").unwrap_or(11906);
+    let margins = value("pgMar", "left").unwrap_or(1440) + value("pgMar", "right").unwrap_or(1440);
+    width.saturating_sub(margins).max(1440)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 读取生成的文档包中的文件
+    fn entry(docx: &[u8], name: &str) -> Option<String> {
+        let mut archive = ZipArchive::new(Cursor::new(docx)).unwrap();
+        let mut entry = archive.by_name(name).ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        Some(content)
+    }
+
+    #[test]
+    fn test_render_docx() -> Result<()> {
+        let exporter = DocxExporter::new(DocxExportOptions::default())?;
+        let docx = exporter.render("# 标题\n\n- 一\n- 二\n\n正文[^1] [链接](https://example.com?a=1&b=2)\n\n[^1]: 脚注\n", None)?;
+
+        let document = entry(&docx, "word/document.xml").unwrap();
+        assert!(document.contains("<w:pStyle w:val=\"Heading1\"/>"));
+        assert!(document.ends_with(&format!("{}</w:body></w:document>", DEFAULT_SECTION)));
+
+        let styles = entry(&docx, "word/styles.xml").unwrap();
+        assert!(styles.contains("<w:outlineLvl w:val=\"0\"/>"));
+        assert!(styles.contains("w:styleId=\"SourceCode\""));
+
+        let numbering = entry(&docx, "word/numbering.xml").unwrap();
+        assert!(numbering.contains("<w:num w:numId=\"1\"><w:abstractNumId w:val=\"1\"/></w:num>"));
+
+        let footnotes = entry(&docx, "word/footnotes.xml").unwrap();
+        assert!(footnotes.contains("<w:footnote w:id=\"1\">"));
+        assert!(footnotes.contains("脚注"));
+
+        let relationships = entry(&docx, "word/_rels/document.xml.rels").unwrap();
+        assert!(relationships.contains("Target=\"https://example.com?a=1&amp;b=2\" TargetMode=\"External\""));
+        assert!(entry(&docx, "docProps/core.xml").unwrap().contains("<dc:title>标题</dc:title>"));
+        assert!(entry(&docx, "word/theme/theme1.xml").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reference_document() -> Result<()> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reference.docx");
+        let mut zip = ZipWriter::new(File::create(&path)?);
+        zip.start_file("word/styles.xml", SimpleFileOptions::default())?;
+        zip.write_all(
+            format!(
+                "<w:styles xmlns:w=\"{}\"><w:style w:type=\"paragraph\" w:styleId=\"Heading1\">\
+                 <w:name w:val=\"heading 1\"/><w:rPr><w:rFonts w:ascii=\"Georgia\"/></w:rPr></w:style></w:styles>",
+                W_NS
+            )
+            .as_bytes(),
+        )?;
+        zip.start_file("word/numbering.xml", SimpleFileOptions::default())?;
+        zip.write_all(
+            b"<w:numbering><w:abstractNum w:abstractNumId=\"4\"><w:lvl w:ilvl=\"0\"/></w:abstractNum>\
+              <w:num w:numId=\"7\"><w:abstractNumId w:val=\"4\"/></w:num></w:numbering>",
+        )?;
+        zip.start_file("word/document.xml", SimpleFileOptions::default())?;
+        zip.write_all(
+            b"<w:document><w:body><w:p/><w:sectPr><w:headerReference w:type=\"default\" r:id=\"rId8\"/>\
+              <w:pgSz w:w=\"12240\" w:h=\"15840\"/><w:pgMar w:top=\"1440\" w:right=\"1800\" w:bottom=\"1440\" w:left=\"1800\"/>\
+              </w:sectPr></w:body></w:document>",
+        )?;
+        zip.start_file("word/theme/theme1.xml", SimpleFileOptions::default())?;
+        zip.write_all(b"<a:theme/>")?;
+        zip.finish()?;
+
+        let template = Template::load(&path)?;
+        assert_eq!(template.text_width, 12240 - 3600);
+        assert!(!template.section.contains("headerReference"));
+
+        let exporter = DocxExporter::new(DocxExportOptions { reference: Some(path) })?;
+        let docx = exporter.render("# 标题\n\n1. 一\n", None)?;
+
+        // 使用参考文档中的标题样式，只补上缺少的样式
+        let styles = entry(&docx, "word/styles.xml").unwrap();
+        assert!(styles.contains("Georgia"));
+        assert_eq!(styles.matches("w:styleId=\"Heading1\"").count(), 1);
+        assert!(styles.contains("w:styleId=\"Heading2\""));
+
+        // 新的编号定义排在参考文档的编号定义之后
+        let numbering = entry(&docx, "word/numbering.xml").unwrap();
+        assert!(numbering.contains("<w:num w:numId=\"7\">"));
+        assert!(numbering.contains("<w:num w:numId=\"8\"><w:abstractNumId w:val=\"6\"/>"));
+        assert!(numbering.find("<w:abstractNum w:abstractNumId=\"6\"").unwrap() < numbering.find("<w:num ").unwrap());
+        assert!(entry(&docx, "word/document.xml").unwrap().contains("<w:numId w:val=\"8\"/>"));
+
+        assert_eq!(entry(&docx, "word/theme/theme1.xml").as_deref(), Some("<a:theme/>"));
+        assert!(entry(&docx, "[Content_Types].xml").unwrap().contains("/word/theme/theme1.xml"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_reference() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reference.docx");
+        fs::write(&path, "not a zip").unwrap();
+        assert!(DocxExporter::new(DocxExportOptions { reference: Some(path) }).is_err());
+        assert!(DocxExporter::new(DocxExportOptions { reference: Some(dir.path().join("missing.docx")) }).is_err());
+    }
+}
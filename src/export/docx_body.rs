@@ -0,0 +1,755 @@
+//! Word 正文模块
+//!
+//! 把 Markdown 转换为 Word 文档正文（WordprocessingML）：
+//! - 标题使用 Word 的标题样式，并添加书签，文档内的锚点链接可以跳转
+//! - 列表使用编号定义，每个有序列表从自己的起始编号开始
+//! - 表格转换为 Word 表格，表头在每页重复
+//! - 代码使用等宽样式，脚注转换为 Word 脚注
+//! - 本地图片嵌入文档，宽度超出版心时按比例缩小
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use pulldown_cmark::{Alignment, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use crate::file_manager::resolve_link_path;
+use crate::markdown::{heading_slug, is_external_dest};
+
+/// 列表每一级的缩进（缇，1/20 磅）
+pub(super) const LIST_INDENT: usize = 720;
+/// 每像素的 EMU 数（按 96 DPI 计算）
+const EMU_PER_PIXEL: u64 = 9525;
+/// Word 书签名称的最大长度
+const BOOKMARK_NAME_MAX: usize = 40;
+
+/// 列表的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ListKind {
+    Bullet,
+    /// 有序列表及起始编号
+    Ordered(u64),
+}
+
+/// 嵌入的图片
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Media {
+    /// 关系编号
+    pub id: String,
+    /// 在文档包中的文件名，如 `image1.png`
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// 生成正文的选项
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BodyOptions {
+    /// 编号定义的起始编号（参考文档中已有的编号之后）
+    pub first_num_id: usize,
+    /// 图片的最大宽度（EMU）
+    pub max_image_width: u64,
+}
+
+/// 生成的正文
+#[derive(Debug, Default)]
+pub(super) struct Body {
+    /// `<w:body>` 中的内容（不含分节属性）
+    pub xml: String,
+    /// 脚注编号和内容
+    pub footnotes: Vec<(usize, String)>,
+    /// 链接的关系编号和地址
+    pub links: Vec<(String, String)>,
+    pub media: Vec<Media>,
+    /// 每个编号定义对应的列表，编号从 `first_num_id` 开始
+    pub lists: Vec<ListKind>,
+    /// 第一个一级标题
+    pub title: Option<String>,
+}
+
+/// 把 Markdown 转换为 Word 正文
+///
+/// `source` 为文档所在的路径，用于查找相对路径的图片
+pub(super) fn build(markdown: &str, source: Option<&Path>, options: BodyOptions) -> Body {
+    let mut parser_options = Options::empty();
+    parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+    parser_options.insert(Options::ENABLE_TABLES);
+    parser_options.insert(Options::ENABLE_FOOTNOTES);
+    parser_options.insert(Options::ENABLE_TASKLISTS);
+    parser_options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    parser_options.insert(Options::ENABLE_MATH);
+
+    let mut builder = Builder::new(source, options);
+    for event in Parser::new_ext(markdown, parser_options) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+/// 正在生成的段落
+struct Paragraph {
+    props: String,
+    runs: String,
+}
+
+/// 正在生成的图片
+struct Image {
+    dest: String,
+    alt: String,
+}
+
+/// 正在生成的脚注
+struct Footnote {
+    id: usize,
+    /// 进入脚注前的正文
+    body: String,
+    /// 第一个段落开头还没有添加脚注编号
+    needs_ref: bool,
+}
+
+/// 根据 Markdown 事件生成正文
+struct Builder<'a> {
+    source: Option<&'a Path>,
+    options: BodyOptions,
+    body: Body,
+    out: String,
+    paragraph: Option<Paragraph>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    links: usize,
+    quotes: usize,
+    /// 每层列表的编号定义
+    lists: Vec<usize>,
+    /// 列表项的编号还没有添加到段落
+    pending_number: Option<(usize, usize)>,
+    heading: Option<(HeadingLevel, String)>,
+    anchors: HashMap<String, usize>,
+    bookmarks: usize,
+    code: Option<String>,
+    image: Option<Image>,
+    /// 已嵌入的图片及其关系编号和像素尺寸
+    images: HashMap<PathBuf, (String, (u32, u32))>,
+    drawings: usize,
+    table: Option<Vec<Alignment>>,
+    cell: usize,
+    footnote_ids: HashMap<String, usize>,
+    footnote: Option<Footnote>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(source: Option<&'a Path>, options: BodyOptions) -> Self {
+        Self {
+            source,
+            options,
+            body: Body::default(),
+            out: String::new(),
+            paragraph: None,
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            links: 0,
+            quotes: 0,
+            lists: Vec::new(),
+            pending_number: None,
+            heading: None,
+            anchors: HashMap::new(),
+            bookmarks: 0,
+            code: None,
+            image: None,
+            images: HashMap::new(),
+            drawings: 0,
+            table: None,
+            cell: 0,
+            footnote_ids: HashMap::new(),
+            footnote: None,
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.push_text(&text, false),
+            Event::Code(text) => self.push_text(&text, true),
+            Event::InlineMath(formula) | Event::DisplayMath(formula) => self.push_text(&formula, true),
+            Event::SoftBreak => self.push_text(" ", false),
+            Event::HardBreak => {
+                if self.image.is_none() {
+                    self.run_xml("<w:br/>");
+                }
+            }
+            Event::Rule => {
+                self.close_paragraph();
+                self.out.push_str(
+                    "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>",
+                );
+            }
+            Event::TaskListMarker(checked) => self.push_text(if checked { "☒ " } else { "☐ " }, false),
+            Event::FootnoteReference(label) => {
+                let id = self.footnote_id(&label);
+                self.ensure_paragraph();
+                if let Some(paragraph) = &mut self.paragraph {
+                    paragraph.runs.push_str(&format!(
+                        "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteReference w:id=\"{}\"/></w:r>",
+                        id
+                    ));
+                }
+            }
+            Event::Html(_) | Event::InlineHtml(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.open_paragraph(None),
+            Tag::Heading { level, .. } => {
+                self.open_paragraph(Some(&format!("Heading{}", level as usize)));
+                self.heading = Some((level, String::new()));
+            }
+            Tag::BlockQuote(_) => {
+                self.close_paragraph();
+                self.quotes += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.close_paragraph();
+                self.code = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush_pending_number();
+                self.close_paragraph();
+                self.body.lists.push(match start {
+                    Some(start) => ListKind::Ordered(start),
+                    None => ListKind::Bullet,
+                });
+                self.lists.push(self.options.first_num_id + self.body.lists.len() - 1);
+            }
+            Tag::Item => {
+                self.close_paragraph();
+                let level = self.lists.len().saturating_sub(1);
+                self.pending_number = self.lists.last().map(|num| (*num, level));
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.close_paragraph();
+                let id = self.footnote_id(&label);
+                let body = std::mem::take(&mut self.out);
+                self.footnote = Some(Footnote { id, body, needs_ref: true });
+            }
+            Tag::Table(alignments) => {
+                self.close_paragraph();
+                self.out.push_str(
+                    "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"5000\" w:type=\"pct\"/>\
+                     <w:tblLook w:val=\"04A0\" w:firstRow=\"1\" w:lastRow=\"0\" w:firstColumn=\"0\" w:lastColumn=\"0\" \
+                     w:noHBand=\"0\" w:noVBand=\"1\"/></w:tblPr><w:tblGrid>",
+                );
+                for _ in &alignments {
+                    self.out.push_str("<w:gridCol/>");
+                }
+                self.out.push_str("</w:tblGrid>");
+                self.table = Some(alignments);
+            }
+            Tag::TableHead => {
+                // 表头在每一页重复
+                self.out.push_str("<w:tr><w:trPr><w:tblHeader/></w:trPr>");
+                self.cell = 0;
+                self.bold += 1;
+            }
+            Tag::TableRow => {
+                self.out.push_str("<w:tr>");
+                self.cell = 0;
+            }
+            Tag::TableCell => {
+                self.out.push_str("<w:tc><w:tcPr><w:tcW w:w=\"0\" w:type=\"auto\"/></w:tcPr>");
+                let alignment = self
+                    .table
+                    .as_ref()
+                    .and_then(|alignments| alignments.get(self.cell))
+                    .copied()
+                    .unwrap_or(Alignment::None);
+                let props = match alignment {
+                    Alignment::Center => "<w:jc w:val=\"center\"/>",
+                    Alignment::Right => "<w:jc w:val=\"right\"/>",
+                    Alignment::Left | Alignment::None => "",
+                };
+                self.paragraph = Some(Paragraph { props: props.to_string(), runs: String::new() });
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link { dest_url, .. } => {
+                self.ensure_paragraph();
+                let open = self.hyperlink(&dest_url);
+                if let Some(paragraph) = &mut self.paragraph {
+                    paragraph.runs.push_str(&open);
+                }
+                self.links += 1;
+            }
+            Tag::Image { dest_url, .. } => {
+                self.image = Some(Image { dest: dest_url.to_string(), alt: String::new() });
+            }
+            Tag::HtmlBlock | Tag::MetadataBlock(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.close_paragraph(),
+            TagEnd::Heading(_) => {
+                if let Some((level, text)) = self.heading.take() {
+                    self.add_bookmark(&text);
+                    if level == HeadingLevel::H1 && self.body.title.is_none() && !text.trim().is_empty() {
+                        self.body.title = Some(text.trim().to_string());
+                    }
+                }
+                self.close_paragraph();
+            }
+            TagEnd::BlockQuote => {
+                self.close_paragraph();
+                self.quotes = self.quotes.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                let code = self.code.take().unwrap_or_default();
+                let code = code.strip_suffix('\n').unwrap_or(&code);
+                self.open_paragraph(Some("SourceCode"));
+                if let Some(paragraph) = &mut self.paragraph {
+                    paragraph.runs.push_str(&format!("<w:r>{}</w:r>", text_xml(code)));
+                }
+                self.close_paragraph();
+            }
+            TagEnd::List(_) => {
+                self.close_paragraph();
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                self.flush_pending_number();
+                self.close_paragraph();
+            }
+            TagEnd::FootnoteDefinition => {
+                self.close_paragraph();
+                if let Some(footnote) = self.footnote.take() {
+                    if footnote.needs_ref {
+                        self.out.push_str(&format!(
+                            "<w:p><w:pPr><w:pStyle w:val=\"FootnoteText\"/></w:pPr>{}</w:p>",
+                            FOOTNOTE_REF_RUNS
+                        ));
+                    }
+                    let content = std::mem::replace(&mut self.out, footnote.body);
+                    self.body.footnotes.push((footnote.id, content));
+                }
+            }
+            TagEnd::Table => {
+                self.out.push_str("</w:tbl>");
+                self.table = None;
+            }
+            TagEnd::TableHead => {
+                self.out.push_str("</w:tr>");
+                self.bold = self.bold.saturating_sub(1);
+            }
+            TagEnd::TableRow => self.out.push_str("</w:tr>"),
+            TagEnd::TableCell => {
+                self.close_paragraph();
+                self.out.push_str("</w:tc>");
+                self.cell += 1;
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            TagEnd::Link => {
+                if let Some(paragraph) = &mut self.paragraph {
+                    paragraph.runs.push_str("</w:hyperlink>");
+                }
+                self.links = self.links.saturating_sub(1);
+            }
+            TagEnd::Image => {
+                if let Some(image) = self.image.take() {
+                    self.push_image(image);
+                }
+            }
+            TagEnd::HtmlBlock | TagEnd::MetadataBlock(_) => {}
+        }
+    }
+
+    fn finish(mut self) -> Body {
+        self.close_paragraph();
+        self.body.footnotes.sort_by_key(|(id, _)| *id);
+        self.body.xml = self.out;
+        self.body
+    }
+
+    fn push_text(&mut self, text: &str, code: bool) {
+        if let Some(image) = &mut self.image {
+            image.alt.push_str(text);
+            return;
+        }
+        if let Some(block) = &mut self.code {
+            block.push_str(text);
+            return;
+        }
+        if let Some((_, heading)) = &mut self.heading {
+            heading.push_str(text);
+        }
+        let props = self.run_props(code);
+        self.ensure_paragraph();
+        if let Some(paragraph) = &mut self.paragraph {
+            paragraph.runs.push_str(&format!("<w:r>{}{}</w:r>", props, text_xml(text)));
+        }
+    }
+
+    /// 添加只有一个元素（如换行）的文字块
+    fn run_xml(&mut self, content: &str) {
+        self.ensure_paragraph();
+        if let Some(paragraph) = &mut self.paragraph {
+            paragraph.runs.push_str(&format!("<w:r>{}</w:r>", content));
+        }
+    }
+
+    /// 当前文字的格式
+    fn run_props(&self, code: bool) -> String {
+        let mut props = String::new();
+        if code {
+            props.push_str("<w:rStyle w:val=\"VerbatimChar\"/>");
+        } else if self.links > 0 {
+            props.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        }
+        if self.bold > 0 {
+            props.push_str("<w:b/><w:bCs/>");
+        }
+        if self.italic > 0 {
+            props.push_str("<w:i/><w:iCs/>");
+        }
+        if self.strike > 0 {
+            props.push_str("<w:strike/>");
+        }
+        if props.is_empty() {
+            props
+        } else {
+            format!("<w:rPr>{}</w:rPr>", props)
+        }
+    }
+
+    /// 开始新段落，根据所在的列表、引用和脚注确定段落格式
+    fn open_paragraph(&mut self, style: Option<&str>) {
+        self.close_paragraph();
+        let context_style = if self.footnote.is_some() {
+            Some("FootnoteText")
+        } else if !self.lists.is_empty() {
+            Some("ListParagraph")
+        } else if self.quotes > 0 {
+            Some("Quote")
+        } else {
+            None
+        };
+
+        let mut props = String::new();
+        if let Some(style) = style.or(context_style) {
+            props.push_str(&format!("<w:pStyle w:val=\"{}\"/>", style));
+        }
+        if let Some((num, level)) = self.pending_number.take() {
+            props.push_str(&format!("<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{}\"/></w:numPr>", level, num));
+        } else if !self.lists.is_empty() {
+            props.push_str(&format!("<w:ind w:left=\"{}\"/>", LIST_INDENT * self.lists.len()));
+        }
+
+        let mut runs = String::new();
+        if let Some(footnote) = &mut self.footnote {
+            if std::mem::take(&mut footnote.needs_ref) {
+                runs.push_str(FOOTNOTE_REF_RUNS);
+            }
+        }
+        self.paragraph = Some(Paragraph { props, runs });
+    }
+
+    /// 列表项中没有段落标记的文字也放在段落中
+    fn ensure_paragraph(&mut self) {
+        if self.paragraph.is_none() {
+            self.open_paragraph(None);
+        }
+    }
+
+    fn close_paragraph(&mut self) {
+        if let Some(paragraph) = self.paragraph.take() {
+            self.out.push_str("<w:p>");
+            if !paragraph.props.is_empty() {
+                self.out.push_str(&format!("<w:pPr>{}</w:pPr>", paragraph.props));
+            }
+            self.out.push_str(&paragraph.runs);
+            self.out.push_str("</w:p>");
+        }
+    }
+
+    /// 列表项没有文字（如只有嵌套列表）时也显示编号
+    fn flush_pending_number(&mut self) {
+        if self.pending_number.is_some() {
+            self.open_paragraph(None);
+            self.close_paragraph();
+        }
+    }
+
+    /// 为当前标题添加书签，名称与 HTML 导出的锚点相同
+    fn add_bookmark(&mut self, text: &str) {
+        let slug = heading_slug(text);
+        let count = self.anchors.entry(slug.clone()).or_insert(0);
+        let anchor = if *count == 0 { slug } else { format!("{}-{}", slug, count) };
+        *count += 1;
+
+        let id = self.bookmarks;
+        self.bookmarks += 1;
+        if let Some(paragraph) = &mut self.paragraph {
+            paragraph.runs = format!(
+                "<w:bookmarkStart w:id=\"{}\" w:name=\"{}\"/>{}<w:bookmarkEnd w:id=\"{}\"/>",
+                id,
+                xml_escape(&bookmark_name(&anchor)),
+                paragraph.runs,
+                id
+            );
+        }
+    }
+
+    /// 链接的开始标签：`#` 开头的链接指向文档中的书签，其他链接添加为外部关系
+    fn hyperlink(&mut self, dest: &str) -> String {
+        if let Some(anchor) = dest.strip_prefix('#') {
+            return format!("<w:hyperlink w:anchor=\"{}\" w:history=\"1\">", xml_escape(&bookmark_name(anchor)));
+        }
+        let id = format!("rLink{}", self.body.links.len() + 1);
+        self.body.links.push((id.clone(), dest.to_string()));
+        format!("<w:hyperlink r:id=\"{}\" w:history=\"1\">", id)
+    }
+
+    fn footnote_id(&mut self, label: &str) -> usize {
+        let next = self.footnote_ids.len() + 1;
+        *self.footnote_ids.entry(label.to_string()).or_insert(next)
+    }
+
+    /// 嵌入本地图片，无法嵌入时显示替代文字
+    fn push_image(&mut self, image: Image) {
+        let Some((id, (width, height))) = self.embed_image(&image.dest) else {
+            let text = if image.alt.is_empty() { image.dest } else { image.alt };
+            self.push_text(&format!("[{}]", text), false);
+            return;
+        };
+
+        let mut cx = width as u64 * EMU_PER_PIXEL;
+        let mut cy = height as u64 * EMU_PER_PIXEL;
+        if cx > self.options.max_image_width {
+            cy = cy * self.options.max_image_width / cx;
+            cx = self.options.max_image_width;
+        }
+        self.drawings += 1;
+        let drawing = format!(
+            "<w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\"><wp:extent cx=\"{cx}\" cy=\"{cy}\"/>\
+             <wp:docPr id=\"{n}\" name=\"图片 {n}\" descr=\"{alt}\"/><a:graphic><a:graphicData \
+             uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><pic:pic><pic:nvPicPr>\
+             <pic:cNvPr id=\"{n}\" name=\"图片 {n}\"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill>\
+             <a:blip r:embed=\"{id}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr>\
+             <a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>\
+             <a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic>\
+             </wp:inline></w:drawing>",
+            cx = cx,
+            cy = cy,
+            n = self.drawings,
+            alt = xml_escape(&image.alt),
+            id = id,
+        );
+        self.run_xml(&drawing);
+    }
+
+    /// 读取图片并添加到文档包中，同一图片只嵌入一次
+    fn embed_image(&mut self, dest: &str) -> Option<(String, (u32, u32))> {
+        if is_external_dest(dest) || dest.starts_with("data:") {
+            return None;
+        }
+        let path_part = &dest[..dest.find(['#', '?']).unwrap_or(dest.len())];
+        let path = resolve_link_path(self.source?, path_part)?;
+        if let Some(embedded) = self.images.get(&path) {
+            return Some(embedded.clone());
+        }
+
+        let extension = image_extension(&path)?;
+        let data = fs::read(&path).ok()?;
+        let size = image_size(&data)?;
+        let id = format!("rImage{}", self.body.media.len() + 1);
+        self.body.media.push(Media {
+            id: id.clone(),
+            name: format!("image{}.{}", self.body.media.len() + 1, extension),
+            data,
+        });
+        self.images.insert(path, (id.clone(), size));
+        Some((id, size))
+    }
+}
+
+/// 脚注第一个段落开头的脚注编号
+const FOOTNOTE_REF_RUNS: &str =
+    "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteRef/></w:r><w:r><w:t xml:space=\"preserve\"> </w:t></w:r>";
+
+/// 文字块的内容，制表符和换行转换为 Word 的制表符和换行
+fn text_xml(text: &str) -> String {
+    let mut xml = String::new();
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            xml.push_str("<w:br/>");
+        }
+        for (index, part) in line.split('\t').enumerate() {
+            if index > 0 {
+                xml.push_str("<w:tab/>");
+            }
+            if !part.is_empty() {
+                xml.push_str(&format!("<w:t xml:space=\"preserve\">{}</w:t>", xml_escape(part)));
+            }
+        }
+    }
+    xml
+}
+
+/// 转义 XML 特殊字符，并去掉 XML 中不允许的控制字符
+pub(super) fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Word 书签名称不能超过 40 个字符
+fn bookmark_name(anchor: &str) -> String {
+    anchor.chars().take(BOOKMARK_NAME_MAX).collect()
+}
+
+/// 可以嵌入 Word 文档的图片格式
+fn image_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "png",
+        "jpg" | "jpeg" => "jpeg",
+        "gif" => "gif",
+        "bmp" => "bmp",
+        _ => return None,
+    })
+}
+
+/// 从文件头读取图片的像素尺寸
+pub(super) fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes([*data.get(at)?, *data.get(at + 1)?]) as u32);
+    let le16 = |at: usize| Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let le32 = |at: usize| Some(i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?).unsigned_abs());
+
+    let size = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        (be32(16)?, be32(20)?)
+    } else if data.starts_with(b"GIF8") {
+        (le16(6)?, le16(8)?)
+    } else if data.starts_with(b"BM") {
+        (le32(18)?, le32(22)?)
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        // 查找帧开始标记（SOF0-SOF15，不含 DHT、JPG、DAC）
+        let mut at = 2;
+        loop {
+            while *data.get(at)? != 0xFF {
+                at += 1;
+            }
+            while *data.get(at)? == 0xFF {
+                at += 1;
+            }
+            let marker = *data.get(at)?;
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                break (be16(at + 6)?, be16(at + 4)?);
+            }
+            at += 1 + be16(at + 1)? as usize;
+        }
+    } else {
+        return None;
+    };
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const OPTIONS: BodyOptions = BodyOptions { first_num_id: 1, max_image_width: 100 * EMU_PER_PIXEL };
+
+    #[test]
+    fn test_structure() {
+        let body = build(
+            "# 标题\n\n正文 **粗体** [链接](https://example.com) [跳转](#标题)\n\n\
+             1. 一\n2. 二\n   - 子项\n\n3) 三\n\n```\nlet a = 1;\n\tb < 2\n```\n\n| a | b |\n|:-:|--:|\n| 1 | 2 |\n",
+            None,
+            OPTIONS,
+        );
+        assert_eq!(body.title.as_deref(), Some("标题"));
+        assert!(body.xml.contains("<w:pStyle w:val=\"Heading1\"/>"));
+        assert!(body.xml.contains("<w:bookmarkStart w:id=\"0\" w:name=\"标题\"/>"));
+        assert!(body.xml.contains("<w:hyperlink w:anchor=\"标题\""));
+        assert_eq!(body.links, vec![("rLink1".to_string(), "https://example.com".to_string())]);
+        assert!(body.xml.contains("<w:b/><w:bCs/></w:rPr><w:t xml:space=\"preserve\">粗体</w:t>"));
+
+        // 两个有序列表分别编号，嵌套的无序列表在第二级
+        assert_eq!(body.lists, vec![ListKind::Ordered(1), ListKind::Bullet, ListKind::Ordered(3)]);
+        assert!(body.xml.contains("<w:numPr><w:ilvl w:val=\"1\"/><w:numId w:val=\"2\"/></w:numPr>"));
+        assert!(body.xml.contains("<w:numPr><w:ilvl w:val=\"0\"/><w:numId w:val=\"3\"/></w:numPr>"));
+
+        assert!(body.xml.contains(
+            "<w:pStyle w:val=\"SourceCode\"/></w:pPr><w:r><w:t xml:space=\"preserve\">let a = 1;</w:t><w:br/><w:tab/>\
+             <w:t xml:space=\"preserve\">b &lt; 2</w:t></w:r>"
+        ));
+        assert!(body.xml.contains("<w:trPr><w:tblHeader/></w:trPr>"));
+        assert!(body.xml.contains("<w:jc w:val=\"center\"/>"));
+        assert!(body.xml.contains("<w:jc w:val=\"right\"/>"));
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let body = build("正文[^a]，再次[^a]。\n\n[^a]: 脚注 *内容*\n\n[^b]: 没有引用\n", None, OPTIONS);
+        assert_eq!(body.xml.matches("<w:footnoteReference w:id=\"1\"/>").count(), 2);
+        assert!(!body.xml.contains("脚注"));
+        assert_eq!(body.footnotes.len(), 2);
+        assert_eq!(body.footnotes[0].0, 1);
+        assert!(body.footnotes[0].1.starts_with("<w:p><w:pPr><w:pStyle w:val=\"FootnoteText\"/></w:pPr><w:r><w:rPr>"));
+        assert!(body.footnotes[0].1.contains("<w:footnoteRef/>"));
+        assert!(body.footnotes[0].1.contains("内容"));
+        assert_eq!(body.footnotes[1].0, 2);
+    }
+
+    #[test]
+    fn test_images() {
+        let dir = TempDir::new().unwrap();
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&200u32.to_be_bytes());
+        png.extend_from_slice(&50u32.to_be_bytes());
+        fs::write(dir.path().join("a.png"), &png).unwrap();
+        let source = dir.path().join("doc.md");
+
+        let body = build("![图](a.png) ![图](a.png) ![远程](https://example.com/b.png)", Some(&source), OPTIONS);
+        assert_eq!(body.media.len(), 1);
+        assert_eq!(body.media[0].name, "image1.png");
+        // 宽度缩小到 100 像素，高度按比例缩小
+        assert!(body.xml.contains(&format!("<wp:extent cx=\"{}\" cy=\"{}\"/>", 100 * EMU_PER_PIXEL, 25 * EMU_PER_PIXEL)));
+        assert_eq!(body.xml.matches("r:embed=\"rImage1\"").count(), 2);
+        assert!(body.xml.contains("[远程]"));
+    }
+
+    #[test]
+    fn test_image_size() {
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&[10, 0, 20, 0]);
+        assert_eq!(image_size(&gif), Some((10, 20)));
+
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x30, 0x00, 0x40,
+        ];
+        assert_eq!(image_size(&jpeg), Some((64, 48)));
+        assert_eq!(image_size(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("a < b & \"c\"\u{1}"), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,302 @@
+//! 导出字体模块
+//!
+//! 为 PDF 导出查找和读取字体：
+//! - 从系统字体和用户指定的字体文件中按字体名查找正文、粗体、斜体和等宽字体
+//! - 正文字体缺少的字符（如中日韩文字）依次从后备字体中查找
+//! - 记录每个字符对应的字形和宽度，供排版和嵌入字体使用
+//! - 记录所有字体中都没有的字符，导出后提示用户指定字体
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use fontdb::{Database, Family, Query, Stretch, Style, Weight};
+
+/// 未指定正文字体时依次尝试的字体
+const SANS_FAMILIES: &[&str] = &[
+    "Helvetica Neue",
+    "Helvetica",
+    "Arial",
+    "Segoe UI",
+    "Noto Sans",
+    "DejaVu Sans",
+    "Liberation Sans",
+];
+
+/// 未指定等宽字体时依次尝试的字体
+const MONO_FAMILIES: &[&str] = &[
+    "Menlo",
+    "SF Mono",
+    "Consolas",
+    "Noto Sans Mono",
+    "DejaVu Sans Mono",
+    "Liberation Mono",
+    "Courier New",
+];
+
+/// 中日韩文字的后备字体
+const CJK_FAMILIES: &[&str] = &[
+    "PingFang SC",
+    "Hiragino Sans GB",
+    "Microsoft YaHei",
+    "Noto Sans CJK SC",
+    "Noto Sans SC",
+    "Source Han Sans SC",
+    "Source Han Sans CN",
+    "WenQuanYi Micro Hei",
+    "WenQuanYi Zen Hei",
+    "SimSun",
+];
+
+/// 字体选项，字体名为空时自动查找
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontOptions {
+    /// 正文字体
+    pub family: String,
+    /// 中日韩文字字体
+    pub cjk_family: String,
+    /// 代码字体
+    pub mono_family: String,
+    /// 额外加载的字体文件
+    pub files: Vec<PathBuf>,
+}
+
+/// 字体集合中的字体编号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FontId(pub usize);
+
+/// 文字使用的字体样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FontStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub mono: bool,
+}
+
+/// 字体中的字形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    /// 字形编号
+    pub id: u16,
+    /// 对应的字符
+    pub c: char,
+    /// 宽度（字体单位）
+    pub advance: f32,
+}
+
+/// 读取的字体
+pub struct FontFace {
+    /// 字体文件内容
+    pub data: Vec<u8>,
+    /// 在字体集合文件（`.ttc`）中的编号
+    pub index: u32,
+    /// PostScript 名称
+    pub name: String,
+    /// 每 em 的字体单位数
+    pub units_per_em: f32,
+    pub ascender: f32,
+    pub descender: f32,
+    pub cap_height: f32,
+    /// 字形边界（字体单位）
+    pub bbox: [f32; 4],
+    pub italic_angle: f32,
+    /// 字形为 CFF 格式（否则为 TrueType）
+    pub cff: bool,
+    pub monospaced: bool,
+    pub serif: bool,
+    pub italic: bool,
+    /// 字符到字形的缓存
+    glyphs: RefCell<HashMap<char, Option<Glyph>>>,
+}
+
+impl FontFace {
+    /// 解析字体文件，无法解析时返回 `None`
+    fn parse(data: Vec<u8>, index: u32, family: &str) -> Option<Self> {
+        let face = ttf_parser::Face::parse(&data, index).ok()?;
+        let bbox = face.global_bounding_box();
+        let name = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .find_map(|name| name.to_string())
+            .unwrap_or_else(|| family.to_string());
+        let font = Self {
+            name: name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect(),
+            units_per_em: face.units_per_em() as f32,
+            ascender: face.ascender() as f32,
+            descender: face.descender() as f32,
+            cap_height: face.capital_height().unwrap_or(face.ascender()) as f32,
+            bbox: [bbox.x_min as f32, bbox.y_min as f32, bbox.x_max as f32, bbox.y_max as f32],
+            italic_angle: face.italic_angle().unwrap_or(0.0),
+            cff: face.tables().cff.is_some(),
+            monospaced: face.is_monospaced(),
+            serif: family.to_lowercase().contains("serif") && !family.to_lowercase().contains("sans"),
+            italic: face.is_italic(),
+            glyphs: RefCell::new(HashMap::new()),
+            data,
+            index,
+        };
+        Some(font)
+    }
+
+    /// 字符对应的字形，字体中没有该字符时返回 `None`
+    pub fn glyph(&self, c: char) -> Option<Glyph> {
+        if let Some(glyph) = self.glyphs.borrow().get(&c) {
+            return *glyph;
+        }
+        let glyph = ttf_parser::Face::parse(&self.data, self.index).ok().and_then(|face| {
+            let id = face.glyph_index(c)?;
+            let advance = face.glyph_hor_advance(id).unwrap_or(0) as f32;
+            Some(Glyph { id: id.0, c, advance })
+        });
+        self.glyphs.borrow_mut().insert(c, glyph);
+        glyph
+    }
+
+    /// 把字体单位换算为指定字号下的宽度（磅）
+    pub fn scale(&self, units: f32, size: f32) -> f32 {
+        units / self.units_per_em * size
+    }
+}
+
+/// 导出使用的一组字体
+pub struct FontSet {
+    faces: Vec<FontFace>,
+    regular: FontId,
+    bold: FontId,
+    italic: FontId,
+    bold_italic: FontId,
+    mono: FontId,
+    /// 按顺序查找缺少的字符
+    fallbacks: Vec<FontId>,
+    /// 所有字体中都没有的字符（不含空白和控制字符）
+    missing: RefCell<BTreeSet<char>>,
+}
+
+impl FontSet {
+    /// 查找并读取字体，找不到任何正文字体时返回错误
+    pub fn load(options: &FontOptions) -> Result<Self> {
+        let mut db = Database::new();
+        db.load_system_fonts();
+        for file in &options.files {
+            db.load_font_file(file)
+                .map_err(|e| anyhow!("无法读取字体文件 {}: {}", file.display(), e))?;
+        }
+        Self::from_database(&db, options)
+    }
+
+    /// 从字体数据库中选择字体
+    fn from_database(db: &Database, options: &FontOptions) -> Result<Self> {
+        let mut set = Self {
+            faces: Vec::new(),
+            regular: FontId(0),
+            bold: FontId(0),
+            italic: FontId(0),
+            bold_italic: FontId(0),
+            mono: FontId(0),
+            fallbacks: Vec::new(),
+            missing: RefCell::new(BTreeSet::new()),
+        };
+        let mut loaded: HashMap<fontdb::ID, FontId> = HashMap::new();
+
+        let sans = families(&options.family, SANS_FAMILIES, Family::SansSerif);
+        let mono = families(&options.mono_family, MONO_FAMILIES, Family::Monospace);
+        let cjk = families(&options.cjk_family, CJK_FAMILIES, Family::SansSerif);
+
+        let mut find = |families: &[Family], weight: Weight, style: Style| -> Option<FontId> {
+            let id = db.query(&Query { families, weight, stretch: Stretch::Normal, style })?;
+            if let Some(font) = loaded.get(&id) {
+                return Some(*font);
+            }
+            let family = db.face(id)?.families.first().map(|f| f.0.clone()).unwrap_or_default();
+            let face = db.with_face_data(id, |data, index| FontFace::parse(data.to_vec(), index, &family))??;
+            set.faces.push(face);
+            let font = FontId(set.faces.len() - 1);
+            loaded.insert(id, font);
+            Some(font)
+        };
+
+        let regular = find(&sans, Weight::NORMAL, Style::Normal)
+            .or_else(|| find(&cjk, Weight::NORMAL, Style::Normal))
+            .ok_or_else(|| anyhow!("找不到可用的字体，请在设置中指定字体文件"))?;
+        let bold = find(&sans, Weight::BOLD, Style::Normal).unwrap_or(regular);
+        let italic = find(&sans, Weight::NORMAL, Style::Italic).unwrap_or(regular);
+        let bold_italic = find(&sans, Weight::BOLD, Style::Italic).unwrap_or(bold);
+        let mono = find(&mono, Weight::NORMAL, Style::Normal).unwrap_or(regular);
+        let cjk_regular = find(&cjk, Weight::NORMAL, Style::Normal);
+
+        set.regular = regular;
+        set.bold = bold;
+        set.italic = italic;
+        set.bold_italic = bold_italic;
+        set.mono = mono;
+        set.fallbacks = [Some(regular), cjk_regular].into_iter().flatten().collect();
+        set.fallbacks.dedup();
+        Ok(set)
+    }
+
+    /// 按编号取字体
+    pub fn face(&self, id: FontId) -> &FontFace {
+        &self.faces[id.0]
+    }
+
+    /// 样式对应的字体
+    pub fn font(&self, style: FontStyle) -> FontId {
+        match (style.mono, style.bold, style.italic) {
+            (true, _, _) => self.mono,
+            (false, true, true) => self.bold_italic,
+            (false, true, false) => self.bold,
+            (false, false, true) => self.italic,
+            (false, false, false) => self.regular,
+        }
+    }
+
+    /// 为字符选择字体：样式对应的字体中没有该字符时依次查找后备字体
+    ///
+    /// 所有字体都没有该字符时使用样式对应的字体中的缺失字形，并记录该字符
+    pub fn glyph(&self, c: char, style: FontStyle) -> (FontId, Glyph) {
+        let font = self.font(style);
+        std::iter::once(font)
+            .chain(self.fallbacks.iter().copied())
+            .find_map(|id| self.face(id).glyph(c).map(|glyph| (id, glyph)))
+            .unwrap_or_else(|| {
+                if !c.is_whitespace() && !c.is_control() {
+                    self.missing.borrow_mut().insert(c);
+                }
+                (font, Glyph { id: 0, c, advance: self.face(font).units_per_em / 2.0 })
+            })
+    }
+
+    /// 取出上次调用以来遇到的所有字体中都没有的字符
+    pub fn take_missing_chars(&self) -> Vec<char> {
+        std::mem::take(&mut *self.missing.borrow_mut()).into_iter().collect()
+    }
+
+    /// 是否有字体包含该字符
+    pub fn has_char(&self, c: char, style: FontStyle) -> bool {
+        std::iter::once(self.font(style))
+            .chain(self.fallbacks.iter().copied())
+            .any(|id| self.face(id).glyph(c).is_some())
+    }
+
+    /// 文字在指定字号下的宽度（磅）
+    pub fn measure(&self, text: &str, style: FontStyle, size: f32) -> f32 {
+        text.chars()
+            .map(|c| {
+                let (font, glyph) = self.glyph(c, style);
+                self.face(font).scale(glyph.advance, size)
+            })
+            .sum()
+    }
+}
+
+/// 查询时依次尝试的字体：用户指定的字体、常见字体，最后是通用字体
+fn families<'a>(preferred: &'a str, defaults: &[&'a str], generic: Family<'a>) -> Vec<Family<'a>> {
+    let preferred = preferred.trim();
+    (!preferred.is_empty())
+        .then_some(Family::Name(preferred))
+        .into_iter()
+        .chain(defaults.iter().map(|name| Family::Name(name)))
+        .chain(std::iter::once(generic))
+        .collect()
+}
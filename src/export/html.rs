@@ -0,0 +1,491 @@
+//! HTML 导出模块
+//!
+//! 把 Markdown 文档导出为自包含的 HTML 文件：
+//! - 本地图片以 data URI 内嵌
+//! - 代码块使用 CSS 类名高亮，附带代码主题的样式表
+//! - 数学公式和 Mermaid 图表预先渲染为 SVG
+//! - 包含当前主题和用户 CSS 的样式，以及由标题生成的目录
+//! - 可以把整个工作区导出为互相链接的页面
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use walkdir::WalkDir;
+
+use crate::editor::SyntaxHighlighter;
+use crate::file_manager::{is_markdown_path, resolve_link_path, to_link_path};
+use crate::markdown::{heading_slug, is_external_dest, DiagramType, HtmlStyle, LatexRenderer, MarkdownParser, MermaidRenderer};
+
+/// 至少有这么多个标题时才生成目录
+const TOC_MIN_HEADINGS: usize = 2;
+
+/// HTML 导出选项
+#[derive(Debug, Clone)]
+pub struct HtmlExportOptions {
+    /// 排版样式（主题颜色、字体和用户 CSS）
+    pub style: HtmlStyle,
+    /// 代码高亮主题
+    pub code_theme: String,
+    /// 是否在正文前生成目录
+    pub table_of_contents: bool,
+}
+
+impl Default for HtmlExportOptions {
+    fn default() -> Self {
+        Self {
+            style: HtmlStyle::default(),
+            code_theme: "InspiredGitHub".to_string(),
+            table_of_contents: true,
+        }
+    }
+}
+
+/// 文档中的标题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 级别（1-6）
+    pub level: usize,
+    /// 标题文字
+    pub text: String,
+    /// 锚点（同名标题依次加上 `-1`、`-2` 后缀）
+    pub anchor: String,
+}
+
+/// 工作区导出结果
+#[derive(Debug, Default)]
+pub struct WorkspaceExport {
+    /// 生成的页面
+    pub pages: Vec<PathBuf>,
+    /// 无法导出的文件及原因
+    pub errors: Vec<String>,
+}
+
+/// HTML 导出器
+pub struct HtmlExporter {
+    options: HtmlExportOptions,
+    highlighter: SyntaxHighlighter,
+}
+
+impl HtmlExporter {
+    /// 创建导出器
+    pub fn new(options: HtmlExportOptions) -> Self {
+        let mut highlighter = SyntaxHighlighter::new();
+        highlighter.set_theme(&options.code_theme);
+        Self { options, highlighter }
+    }
+
+    /// 生成完整的 HTML 文档
+    ///
+    /// `source` 为文档所在的路径，用于查找相对路径的图片；未保存的文档传入 `None`
+    pub fn render(&self, markdown: &str, source: Option<&Path>) -> String {
+        self.render_page(markdown, source, false).0
+    }
+
+    /// 导出为 HTML 文件
+    pub fn export_file(&self, markdown: &str, source: Option<&Path>, target: &Path) -> Result<()> {
+        fs::write(target, self.render(markdown, source))
+            .with_context(|| format!("无法写入文件: {}", target.display()))
+    }
+
+    /// 把工作区中的所有 Markdown 文件导出为互相链接的页面
+    ///
+    /// 目录结构保持不变，指向 Markdown 文件的链接改为指向对应的页面；
+    /// 没有 `index.md` 时另外生成列出所有页面的 `index.html`
+    pub fn export_workspace(&self, root: &Path, target_dir: &Path) -> Result<WorkspaceExport> {
+        fs::create_dir_all(target_dir)
+            .with_context(|| format!("无法创建导出目录: {}", target_dir.display()))?;
+
+        let mut result = WorkspaceExport::default();
+        let mut entries = Vec::new();
+
+        let files = WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file() && is_markdown_path(entry.path()));
+
+        for entry in files {
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(path).with_extension("html");
+            let target = target_dir.join(&relative);
+
+            let page = fs::read_to_string(path)
+                .with_context(|| format!("无法读取文件: {}", path.display()))
+                .and_then(|markdown| {
+                    let (page, title) = self.render_page(&markdown, Some(path), true);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)
+                            .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+                    }
+                    fs::write(&target, page).with_context(|| format!("无法写入文件: {}", target.display()))?;
+                    Ok(title)
+                });
+
+            match page {
+                Ok(title) => {
+                    entries.push((to_link_path(&relative), title));
+                    result.pages.push(target);
+                }
+                Err(e) => result.errors.push(format!("{:#}", e)),
+            }
+        }
+
+        let index = target_dir.join("index.html");
+        if !result.pages.contains(&index) {
+            let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let mut body = format!("<h1>{}</h1>\n<ul>\n", html_escape(&name));
+            for (link, title) in &entries {
+                body.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", html_escape(link), html_escape(title)));
+            }
+            body.push_str("</ul>\n");
+
+            let content = MarkdownParser::wrap_with_style(&body, &self.options.style);
+            fs::write(&index, self.document(&name, "", &content))
+                .with_context(|| format!("无法写入文件: {}", index.display()))?;
+            result.pages.push(index);
+        }
+
+        Ok(result)
+    }
+
+    /// 生成页面，返回 HTML 和页面标题；`link_pages` 时把指向 Markdown 文件的链接改为指向页面
+    fn render_page(&self, markdown: &str, source: Option<&Path>, link_pages: bool) -> (String, String) {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_FOOTNOTES);
+        options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_SMART_PUNCTUATION);
+        options.insert(Options::ENABLE_MATH);
+        // 与链接检查一致，`# 标题 {#锚点}` 使用指定的锚点
+        options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+
+        let mut events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+        let headings = assign_anchors(&mut events);
+        let events = self.transform(events, source, link_pages);
+
+        let mut body = String::new();
+        html::push_html(&mut body, events.into_iter());
+        let content = MarkdownParser::wrap_with_style(&body, &self.options.style);
+
+        let title = headings
+            .iter()
+            .find(|heading| heading.level == 1)
+            .map(|heading| heading.text.clone())
+            .or_else(|| source.and_then(Path::file_stem).map(|stem| stem.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "未命名".to_string());
+        let toc = if self.options.table_of_contents {
+            table_of_contents(&headings)
+        } else {
+            String::new()
+        };
+
+        (self.document(&title, &toc, &content), title)
+    }
+
+    /// 渲染代码块、公式和图表，内嵌图片，改写页面之间的链接
+    fn transform<'a>(&self, events: Vec<Event<'a>>, source: Option<&Path>, link_pages: bool) -> Vec<Event<'a>> {
+        let mut result = Vec::with_capacity(events.len());
+        let mut code_block: Option<(String, String)> = None;
+
+        for event in events {
+            if let Some((language, code)) = &mut code_block {
+                match event {
+                    Event::Text(text) => code.push_str(&text),
+                    Event::End(TagEnd::CodeBlock) => {
+                        let html = if language == "mermaid" {
+                            let diagram_type = DiagramType::detect(code.lines().next().unwrap_or_default());
+                            format!("<div class=\"diagram\">{}</div>", MermaidRenderer::render(code, diagram_type))
+                        } else {
+                            self.highlighter.highlight_classed(code, language)
+                        };
+                        result.push(Event::Html(html.into()));
+                        code_block = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let event = match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code_block = Some((language, String::new()));
+                    continue;
+                }
+                Event::InlineMath(formula) => Event::InlineHtml(LatexRenderer::render_html(&formula, false).into()),
+                Event::DisplayMath(formula) => {
+                    let formula = LatexRenderer::render_html(&formula, true);
+                    Event::InlineHtml(format!("<span class=\"math-display\">{}</span>", formula).into())
+                }
+                Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                    let dest_url = source
+                        .and_then(|source| data_uri(source, &dest_url))
+                        .map(CowStr::from)
+                        .unwrap_or(dest_url);
+                    Event::Start(Tag::Image { link_type, dest_url, title, id })
+                }
+                Event::Start(Tag::Link { link_type, dest_url, title, id }) if link_pages => {
+                    let dest_url = page_link(&dest_url).map(CowStr::from).unwrap_or(dest_url);
+                    Event::Start(Tag::Link { link_type, dest_url, title, id })
+                }
+                event => event,
+            };
+            result.push(event);
+        }
+
+        result
+    }
+
+    /// 包装为完整的 HTML 文档
+    fn document(&self, title: &str, toc: &str, content: &str) -> String {
+        let style = &self.options.style;
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="ReadRS">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background-color: {background}; }}
+.document {{ max-width: 860px; margin: 0 auto; }}
+.toc {{ padding: 20px 20px 0; font-family: {font}; color: {text}; }}
+.toc ul {{ list-style: none; padding-left: 0; margin: 0.5em 0 0; }}
+.toc li {{ margin: 0.25em 0; }}
+.toc a {{ color: {link}; text-decoration: none; }}
+.toc .toc-h2 {{ padding-left: 1em; }}
+.toc .toc-h3 {{ padding-left: 2em; }}
+.toc .toc-h4, .toc .toc-h5, .toc .toc-h6 {{ padding-left: 3em; }}
+.diagram {{ overflow-x: auto; margin: 1em 0; }}
+.math-display {{ display: block; text-align: center; margin: 1em 0; }}
+{code_css}
+</style>
+</head>
+<body>
+<main class="document">
+{toc}
+{content}
+</main>
+</body>
+</html>
+"#,
+            title = html_escape(title),
+            background = style.background_color,
+            font = style.font_family,
+            text = style.text_color,
+            link = style.link_color,
+            code_css = self.highlighter.theme_css(),
+        )
+    }
+}
+
+/// 为标题生成锚点（已有 `{#id}` 的标题保持不变），返回所有标题
+fn assign_anchors(events: &mut [Event]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    let mut index = 0;
+    while index < events.len() {
+        let Event::Start(Tag::Heading { level, .. }) = &events[index] else {
+            index += 1;
+            continue;
+        };
+        let level = *level as usize;
+
+        let mut text = String::new();
+        let mut end = index + 1;
+        while end < events.len() && !matches!(events[end], Event::End(TagEnd::Heading(_))) {
+            if let Event::Text(part) | Event::Code(part) | Event::InlineMath(part) = &events[end] {
+                text.push_str(part);
+            }
+            end += 1;
+        }
+
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[index] {
+            let anchor = match id {
+                Some(existing) => existing.to_string(),
+                None => {
+                    let slug = heading_slug(&text);
+                    let count = counts.entry(slug.clone()).or_insert(0);
+                    let anchor = if *count == 0 { slug } else { format!("{}-{}", slug, count) };
+                    *count += 1;
+                    *id = Some(anchor.clone().into());
+                    anchor
+                }
+            };
+            headings.push(Heading { level, text, anchor });
+        }
+        index = end;
+    }
+
+    headings
+}
+
+/// 生成目录，标题太少时返回空字符串
+fn table_of_contents(headings: &[Heading]) -> String {
+    if headings.len() < TOC_MIN_HEADINGS {
+        return String::new();
+    }
+
+    let mut html = String::from("<nav class=\"toc\"><strong>目录</strong><ul>");
+    for heading in headings {
+        html.push_str(&format!(
+            "<li class=\"toc-h{}\"><a href=\"#{}\">{}</a></li>",
+            heading.level,
+            html_escape(&heading.anchor),
+            html_escape(&heading.text)
+        ));
+    }
+    html.push_str("</ul></nav>");
+    html
+}
+
+/// 把本地图片转换为 data URI，不是本地图片或无法读取时返回 `None`
+fn data_uri(source: &Path, dest: &str) -> Option<String> {
+    if is_external_dest(dest) || dest.starts_with("data:") {
+        return None;
+    }
+    let path_part = &dest[..dest.find(['#', '?']).unwrap_or(dest.len())];
+    let path = resolve_link_path(source, path_part)?;
+    let mime = image_mime(&path)?;
+    let bytes = fs::read(&path).ok()?;
+    Some(format!("data:{};base64,{}", mime, BASE64.encode(bytes)))
+}
+
+/// 根据扩展名确定图片的 MIME 类型
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        _ => return None,
+    })
+}
+
+/// 把指向 Markdown 文件的相对链接改为指向导出的页面，保留锚点
+fn page_link(dest: &str) -> Option<String> {
+    if is_external_dest(dest) {
+        return None;
+    }
+    let split = dest.find(['#', '?']).unwrap_or(dest.len());
+    let (path, suffix) = dest.split_at(split);
+    let lower = path.to_lowercase();
+    let stem = if lower.ends_with(".md") {
+        &path[..path.len() - 3]
+    } else if lower.ends_with(".markdown") {
+        &path[..path.len() - 9]
+    } else {
+        return None;
+    };
+    Some(format!("{}.html{}", stem, suffix))
+}
+
+/// HTML 转义函数
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_document() {
+        let exporter = HtmlExporter::new(HtmlExportOptions::default());
+        let markdown = "# 标题\n\n## 小节\n\n## 小节\n\n```rust\nfn main() {}\n```\n\n$x^2$\n\n```mermaid\ngraph TD\nA-->B\n```\n";
+        let html = exporter.render(markdown, None);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>标题</title>"));
+        assert!(html.contains("<h2 id=\"小节\">"));
+        assert!(html.contains("<h2 id=\"小节-1\">"));
+        assert!(html.contains("<li class=\"toc-h2\"><a href=\"#小节-1\">小节</a></li>"));
+        assert!(html.contains("<pre class=\"hl-code\">"));
+        assert!(html.contains("<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"math\""));
+        assert!(html.contains("<div class=\"diagram\"><svg"));
+    }
+
+    #[test]
+    fn test_custom_heading_anchor() {
+        let exporter = HtmlExporter::new(HtmlExportOptions::default());
+        let html = exporter.render("# 标题 {#custom}
+
+[跳转](#custom)
+
+## 小节
+", None);
+
+        assert!(html.contains("<h1 id=\"custom\">标题</h1>"));
+        assert!(html.contains("<li class=\"toc-h1\"><a href=\"#custom\">标题</a></li>"));
+        assert!(html.contains("<h2 id=\"小节\">"));
+        assert!(!html.contains("{#custom}"));
+    }
+
+    #[test]
+    fn test_embed_images() -> Result<()> {
+        let dir = TempDir::new()?;
+        fs::create_dir(dir.path().join("img"))?;
+        fs::write(dir.path().join("img/a.png"), [1u8, 2, 3])?;
+        let source = dir.path().join("doc.md");
+
+        let exporter = HtmlExporter::new(HtmlExportOptions { table_of_contents: false, ..Default::default() });
+        let html = exporter.render("![a](img/a.png) ![b](missing.png) ![c](https://example.com/c.png)", Some(&source));
+
+        assert!(html.contains("src=\"data:image/png;base64,AQID\""));
+        assert!(html.contains("src=\"missing.png\""));
+        assert!(html.contains("src=\"https://example.com/c.png\""));
+        assert!(!html.contains("class=\"toc\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_page_link() {
+        assert_eq!(page_link("guide/intro.md#setup").as_deref(), Some("guide/intro.html#setup"));
+        assert_eq!(page_link("a.Markdown").as_deref(), Some("a.html"));
+        assert_eq!(page_link("https://example.com/a.md"), None);
+        assert_eq!(page_link("image.png"), None);
+    }
+
+    #[test]
+    fn test_export_workspace() -> Result<()> {
+        let root = TempDir::new()?;
+        fs::create_dir(root.path().join("guide"))?;
+        fs::write(root.path().join("readme.md"), "# 首页\n\n[介绍](guide/intro.md)")?;
+        fs::write(root.path().join("guide/intro.md"), "# 介绍\n\n[返回](../readme.md)")?;
+        fs::write(root.path().join("notes.txt"), "ignored")?;
+
+        let target = TempDir::new()?;
+        let exporter = HtmlExporter::new(HtmlExportOptions::default());
+        let result = exporter.export_workspace(root.path(), target.path())?;
+
+        assert!(result.errors.is_empty());
+        assert_eq!(result.pages.len(), 3);
+        let readme = fs::read_to_string(target.path().join("readme.html"))?;
+        assert!(readme.contains("href=\"guide/intro.html\""));
+        let intro = fs::read_to_string(target.path().join("guide/intro.html"))?;
+        assert!(intro.contains("href=\"../readme.html\""));
+        let index = fs::read_to_string(target.path().join("index.html"))?;
+        assert!(index.contains("<a href=\"guide/intro.html\">介绍</a>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//! 导出模块
+//!
+//! 把 Markdown 文档导出为其他格式：
+//! - HTML：自包含的单个文件，或整个工作区的互相链接的页面
+//! - PDF：分页、页眉页脚、书签，嵌入字体子集
+//! - Word：保留标题、列表、表格、脚注和图片，可以使用参考文档的样式
+
+mod docx;
+mod docx_body;
+mod fonts;
+mod html;
+mod pdf;
+mod pdf_layout;
+
+pub use docx::*;
+pub use fonts::*;
+pub use html::*;
+pub use pdf::*;
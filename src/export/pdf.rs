@@ -0,0 +1,603 @@
+//! PDF 导出模块
+//!
+//! 使用 pdf-writer 生成 PDF，不依赖浏览器或窗口，可以批量导出：
+//! - 纸张大小和页边距可以设置
+//! - 嵌入字体的子集（包括中日韩文字），复制文字时保持原文
+//! - 根据标题生成书签
+//! - 页眉和页脚模板，支持页码
+//! - 链接可以点击
+//! - 没有字体包含的字符会在导出后给出警告
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Context, Result};
+use pdf_writer::types::{ActionType, AnnotationType, CidFontType, FontFlags, PageMode, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use crate::config::PdfSettings;
+
+use super::fonts::{FontId, FontOptions, FontSet};
+use super::pdf_layout::{layout, text_line, Bookmark, Color, LayoutMetrics, Op};
+
+/// 每毫米的磅数
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+/// 页眉页脚字号（正文字号的倍数）
+const MARGIN_TEXT_SCALE: f32 = 0.8;
+/// 页眉页脚颜色
+const MARGIN_TEXT_COLOR: Color = Color(0.45, 0.45, 0.45);
+/// 缺少字形的警告中最多列出的字符数
+const MISSING_GLYPH_SAMPLE: usize = 20;
+
+/// PDF 导出选项，长度单位为磅
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfExportOptions {
+    /// 纸张宽度
+    pub page_width: f32,
+    /// 纸张高度
+    pub page_height: f32,
+    /// 上、右、下、左页边距
+    pub margins: [f32; 4],
+    /// 正文字号
+    pub font_size: f32,
+    /// 行高（字号的倍数）
+    pub line_height: f32,
+    pub fonts: FontOptions,
+    /// 页眉模板
+    pub header: String,
+    /// 页脚模板
+    pub footer: String,
+}
+
+impl Default for PdfExportOptions {
+    fn default() -> Self {
+        Self::from_settings(&PdfSettings::default()).expect("默认设置有效")
+    }
+}
+
+impl PdfExportOptions {
+    /// 根据用户设置创建导出选项
+    pub fn from_settings(settings: &PdfSettings) -> Result<Self> {
+        let (width, height) = settings
+            .paper_size_mm()
+            .ok_or_else(|| anyhow!("未知的纸张大小: {}", settings.paper))?;
+        let margins = settings
+            .margins()
+            .ok_or_else(|| anyhow!("页边距应有 1、2 或 4 个值"))?;
+
+        Ok(Self {
+            page_width: width as f32 * POINTS_PER_MM,
+            page_height: height as f32 * POINTS_PER_MM,
+            margins: margins.map(|margin| margin as f32 * POINTS_PER_MM),
+            font_size: settings.font_size as f32,
+            line_height: settings.line_height as f32,
+            fonts: FontOptions {
+                family: settings.font_family.clone(),
+                cjk_family: settings.cjk_font_family.clone(),
+                mono_family: settings.code_font_family.clone(),
+                files: settings.font_files.iter().map(PathBuf::from).collect(),
+            },
+            header: settings.header.clone(),
+            footer: settings.footer.clone(),
+        })
+    }
+}
+
+/// 展开页眉页脚模板，返回左、中、右三部分
+///
+/// 模板中没有 `|` 时居中显示
+pub fn expand_template(template: &str, page: usize, pages: usize, title: &str) -> [String; 3] {
+    let expanded = template
+        .replace("{page}", &page.to_string())
+        .replace("{pages}", &pages.to_string())
+        .replace("{title}", title);
+    let mut parts = expanded.splitn(3, '|').map(|part| part.trim().to_string());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(center), None, _) => [String::new(), center, String::new()],
+        (left, center, right) => [left.unwrap_or_default(), center.unwrap_or_default(), right.unwrap_or_default()],
+    }
+}
+
+/// PDF 导出器
+///
+/// 创建时读取字体，同一个导出器可以导出多个文档
+pub struct PdfExporter {
+    options: PdfExportOptions,
+    fonts: FontSet,
+}
+
+impl PdfExporter {
+    /// 创建导出器，找不到字体时返回错误
+    pub fn new(options: PdfExportOptions) -> Result<Self> {
+        let fonts = FontSet::load(&options.fonts)?;
+        Ok(Self { options, fonts })
+    }
+
+    /// 生成 PDF，同时返回警告（如缺少字形的字符）
+    ///
+    /// `source` 为文档所在的路径，没有一级标题时用文件名作为标题
+    pub fn render(&self, markdown: &str, source: Option<&Path>) -> Result<(Vec<u8>, Vec<String>)> {
+        let options = &self.options;
+        // 只报告这次导出中缺少的字符
+        self.fonts.take_missing_chars();
+        let [top, right, bottom, left] = options.margins;
+        let metrics = LayoutMetrics {
+            width: options.page_width - left - right,
+            height: options.page_height - top - bottom,
+            font_size: options.font_size,
+            line_height: options.line_height,
+        };
+        let document = layout(&self.fonts, markdown, metrics);
+        let title = document
+            .title
+            .clone()
+            .or_else(|| source.and_then(Path::file_stem).map(|stem| stem.to_string_lossy().to_string()))
+            .unwrap_or_default();
+
+        let mut writer = Writer::new(&self.fonts, options);
+        let page_count = document.pages.len();
+        for (index, ops) in document.pages.iter().enumerate() {
+            let mut ops = ops.clone();
+            ops.extend(self.margin_text(&options.header, index + 1, page_count, &title, top / 2.0 - top));
+            ops.extend(self.margin_text(
+                &options.footer,
+                index + 1,
+                page_count,
+                &title,
+                metrics.height + bottom / 2.0,
+            ));
+            writer.page(&ops);
+        }
+        let pdf = writer.finish(&document.bookmarks, &title)?;
+        Ok((pdf, missing_glyph_warning(&self.fonts.take_missing_chars()).into_iter().collect()))
+    }
+
+    /// 导出为 PDF 文件，返回警告
+    pub fn export_file(&self, markdown: &str, source: Option<&Path>, target: &Path) -> Result<Vec<String>> {
+        let (pdf, warnings) = self.render(markdown, source)?;
+        fs::write(target, pdf).with_context(|| format!("无法写入文件: {}", target.display()))?;
+        Ok(warnings)
+    }
+
+    /// 页眉或页脚，`y` 为基线在内容区坐标中的位置
+    fn margin_text(&self, template: &str, page: usize, pages: usize, title: &str, y: f32) -> Vec<Op> {
+        if template.trim().is_empty() {
+            return Vec::new();
+        }
+        let size = self.options.font_size * MARGIN_TEXT_SCALE;
+        let width = self.options.page_width - self.options.margins[1] - self.options.margins[3];
+        let mut ops = Vec::new();
+        for (index, text) in expand_template(template, page, pages, title).iter().enumerate() {
+            if text.is_empty() {
+                continue;
+            }
+            let (_, text_width) = text_line(&self.fonts, text, size, MARGIN_TEXT_COLOR, 0.0, y);
+            let x = match index {
+                0 => 0.0,
+                1 => (width - text_width) / 2.0,
+                _ => width - text_width,
+            };
+            ops.extend(text_line(&self.fonts, text, size, MARGIN_TEXT_COLOR, x, y).0);
+        }
+        ops
+    }
+}
+
+/// 所有字体中都没有字形的字符的警告，这些字符在 PDF 中显示为方框
+fn missing_glyph_warning(missing: &[char]) -> Option<String> {
+    if missing.is_empty() {
+        return None;
+    }
+    let sample: String = missing.iter().take(MISSING_GLYPH_SAMPLE).collect();
+    let ellipsis = if missing.len() > MISSING_GLYPH_SAMPLE { "…" } else { "" };
+    Some(format!(
+        "{} 个字符在已加载的字体中没有字形，将显示为方框：{}{}。\
+         请在设置中用 pdf.font_files 指定包含这些字符的字体文件，或用 pdf.cjk_font_family 指定已安装的中文字体",
+        missing.len(),
+        sample,
+        ellipsis
+    ))
+}
+
+/// 生成 PDF 对象
+struct Writer<'a> {
+    fonts: &'a FontSet,
+    options: &'a PdfExportOptions,
+    pdf: Pdf,
+    next_ref: i32,
+    catalog: Ref,
+    page_tree: Ref,
+    pages: Vec<Ref>,
+    /// 每个字体用到的字形：字形编号 -> （字符，宽度）
+    used_glyphs: BTreeMap<FontId, BTreeMap<u16, (char, f32)>>,
+    /// 每个字体的引用
+    font_refs: HashMap<FontId, Ref>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(fonts: &'a FontSet, options: &'a PdfExportOptions) -> Self {
+        let mut writer = Self {
+            fonts,
+            options,
+            pdf: Pdf::new(),
+            next_ref: 1,
+            catalog: Ref::new(1),
+            page_tree: Ref::new(1),
+            pages: Vec::new(),
+            used_glyphs: BTreeMap::new(),
+            font_refs: HashMap::new(),
+        };
+        writer.catalog = writer.alloc();
+        writer.page_tree = writer.alloc();
+        writer
+    }
+
+    fn alloc(&mut self) -> Ref {
+        let id = Ref::new(self.next_ref);
+        self.next_ref += 1;
+        id
+    }
+
+    fn font_ref(&mut self, font: FontId) -> Ref {
+        if let Some(id) = self.font_refs.get(&font) {
+            return *id;
+        }
+        let id = self.alloc();
+        self.font_refs.insert(font, id);
+        id
+    }
+
+    /// 把内容区坐标转换为 PDF 坐标（原点在左下角）
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        let [top, _, _, left] = self.options.margins;
+        (left + x, self.options.page_height - top - y)
+    }
+
+    /// 写入一页
+    fn page(&mut self, ops: &[Op]) {
+        let page_id = self.alloc();
+        let content_id = self.alloc();
+        let mut content = Content::new();
+        let mut links = Vec::new();
+
+        for op in ops {
+            match op {
+                Op::Text { x, y, font, size, color, glyphs } => {
+                    let (x, y) = self.point(*x, *y);
+                    let font_id = *font;
+                    self.font_ref(font_id);
+                    let used = self.used_glyphs.entry(font_id).or_default();
+                    let mut encoded = Vec::with_capacity(glyphs.len() * 2);
+                    for glyph in glyphs {
+                        used.insert(glyph.id, (glyph.c, glyph.advance));
+                        encoded.extend_from_slice(&glyph.id.to_be_bytes());
+                    }
+                    content
+                        .begin_text()
+                        .set_font(font_name(font_id).as_name(), *size)
+                        .set_fill_rgb(color.0, color.1, color.2)
+                        .set_text_matrix([1.0, 0.0, 0.0, 1.0, x, y])
+                        .show(Str(&encoded))
+                        .end_text();
+                }
+                Op::Rect { x, y, width, height, color } => {
+                    let (x, y) = self.point(*x, *y + *height);
+                    content
+                        .set_fill_rgb(color.0, color.1, color.2)
+                        .rect(x, y, *width, *height)
+                        .fill_nonzero();
+                }
+                Op::Line { x1, y1, x2, y2, width, color } => {
+                    let (x1, y1) = self.point(*x1, *y1);
+                    let (x2, y2) = self.point(*x2, *y2);
+                    content
+                        .set_stroke_rgb(color.0, color.1, color.2)
+                        .set_line_width(*width)
+                        .move_to(x1, y1)
+                        .line_to(x2, y2)
+                        .stroke();
+                }
+                Op::Link { x, y, width, height, url } => {
+                    let (x, y) = self.point(*x, *y + *height);
+                    links.push((Rect::new(x, y, x + width, y + height), url.clone()));
+                }
+            }
+        }
+
+        let mut page = self.pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, self.options.page_width, self.options.page_height))
+            .parent(self.page_tree)
+            .contents(content_id);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        for (font, id) in &self.font_refs {
+            fonts.pair(font_name(*font).as_name(), *id);
+        }
+        fonts.finish();
+        resources.finish();
+        if !links.is_empty() {
+            let mut annotations = page.annotations();
+            for (rect, url) in &links {
+                let mut annotation = annotations.push();
+                annotation.subtype(AnnotationType::Link).rect(*rect).border(0.0, 0.0, 0.0, None);
+                annotation.action().action_type(ActionType::Uri).uri(Str(url.as_bytes()));
+            }
+        }
+        page.finish();
+
+        let content = compress(&content.finish());
+        self.pdf.stream(content_id, &content).filter(Filter::FlateDecode);
+        self.pages.push(page_id);
+    }
+
+    /// 写入字体、书签和文档信息，返回 PDF 文件内容
+    fn finish(mut self, bookmarks: &[Bookmark], title: &str) -> Result<Vec<u8>> {
+        let font_refs: Vec<(FontId, Ref)> = self.font_refs.iter().map(|(font, id)| (*font, *id)).collect();
+        for (font, id) in font_refs {
+            self.write_font(font, id)?;
+        }
+
+        let outline = (!bookmarks.is_empty()).then(|| self.write_outline(bookmarks));
+
+        let info_id = self.alloc();
+        self.pdf.document_info(info_id).title(TextStr(title)).creator(TextStr("ReadRS"));
+
+        let page_count = self.pages.len() as i32;
+        self.pdf.pages(self.page_tree).kids(self.pages.iter().copied()).count(page_count);
+        let mut catalog = self.pdf.catalog(self.catalog);
+        catalog.pages(self.page_tree);
+        if let Some(outline) = outline {
+            catalog.outlines(outline).page_mode(PageMode::UseOutlines);
+        }
+        catalog.finish();
+
+        Ok(self.pdf.finish())
+    }
+
+    /// 嵌入字体子集：Type0 字体，字形编号直接作为字符编码
+    fn write_font(&mut self, font: FontId, type0_id: Ref) -> Result<()> {
+        let face = self.fonts.face(font);
+        let glyphs = self.used_glyphs.remove(&font).unwrap_or_default();
+        let cid_id = self.alloc();
+        let descriptor_id = self.alloc();
+        let cmap_id = self.alloc();
+        let file_id = self.alloc();
+
+        let glyph_ids: Vec<u16> = std::iter::once(0).chain(glyphs.keys().copied()).collect();
+        let subset = subsetter::subset(&face.data, face.index, subsetter::Profile::pdf(&glyph_ids))
+            .map_err(|e| anyhow!("无法嵌入字体 {}: {}", face.name, e))?;
+        let base_name = format!("{}+{}", subset_tag(&glyph_ids), face.name);
+        let to_em = |units: f32| units / face.units_per_em * 1000.0;
+
+        self.pdf
+            .type0_font(type0_id)
+            .base_font(Name(base_name.as_bytes()))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_id)
+            .to_unicode(cmap_id);
+
+        let system_info = SystemInfo { registry: Str(b"Adobe"), ordering: Str(b"Identity"), supplement: 0 };
+        let mut cid = self.pdf.cid_font(cid_id);
+        cid.subtype(if face.cff { CidFontType::Type0 } else { CidFontType::Type2 })
+            .base_font(Name(base_name.as_bytes()))
+            .system_info(system_info)
+            .font_descriptor(descriptor_id)
+            .default_width(to_em(face.units_per_em / 2.0));
+        if !face.cff {
+            cid.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
+        let mut widths = cid.widths();
+        for (id, (_, advance)) in &glyphs {
+            widths.consecutive(*id, [to_em(*advance)]);
+        }
+        widths.finish();
+        cid.finish();
+
+        let mut flags = FontFlags::SYMBOLIC;
+        flags.set(FontFlags::FIXED_PITCH, face.monospaced);
+        flags.set(FontFlags::SERIF, face.serif);
+        flags.set(FontFlags::ITALIC, face.italic);
+        let [x_min, y_min, x_max, y_max] = face.bbox;
+        let mut descriptor = self.pdf.font_descriptor(descriptor_id);
+        descriptor
+            .name(Name(base_name.as_bytes()))
+            .flags(flags)
+            .bbox(Rect::new(to_em(x_min), to_em(y_min), to_em(x_max), to_em(y_max)))
+            .italic_angle(face.italic_angle)
+            .ascent(to_em(face.ascender))
+            .descent(to_em(face.descender))
+            .cap_height(to_em(face.cap_height))
+            .stem_v(80.0);
+        if face.cff {
+            descriptor.font_file3(file_id);
+        } else {
+            descriptor.font_file2(file_id);
+        }
+        descriptor.finish();
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+        for (id, (c, _)) in &glyphs {
+            cmap.pair(*id, *c);
+        }
+        self.pdf.cmap(cmap_id, &cmap.finish());
+
+        let data = compress(&subset);
+        let mut stream = self.pdf.stream(file_id, &data);
+        stream.filter(Filter::FlateDecode);
+        if face.cff {
+            stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+        }
+        stream.finish();
+        Ok(())
+    }
+
+    /// 写入书签树，返回书签根节点
+    fn write_outline(&mut self, bookmarks: &[Bookmark]) -> Ref {
+        let outline_id = self.alloc();
+        let ids: Vec<Ref> = bookmarks.iter().map(|_| self.alloc()).collect();
+
+        // 每个书签的上级：前面第一个级别更高的标题
+        let mut parents: Vec<Option<usize>> = Vec::with_capacity(bookmarks.len());
+        let mut stack: Vec<usize> = Vec::new();
+        for (index, bookmark) in bookmarks.iter().enumerate() {
+            while stack.last().is_some_and(|&last| bookmarks[last].level >= bookmark.level) {
+                stack.pop();
+            }
+            parents.push(stack.last().copied());
+            stack.push(index);
+        }
+        let children = |parent: Option<usize>| -> Vec<usize> {
+            (0..bookmarks.len()).filter(|&index| parents[index] == parent).collect()
+        };
+        let descendants = |index: usize| -> i32 {
+            let mut count = 0;
+            let mut current = index + 1;
+            while current < bookmarks.len() && bookmarks[current].level > bookmarks[index].level {
+                count += 1;
+                current += 1;
+            }
+            count
+        };
+
+        let top_level = children(None);
+        let mut outline = self.pdf.outline(outline_id);
+        if let (Some(first), Some(last)) = (top_level.first(), top_level.last()) {
+            outline.first(ids[*first]).last(ids[*last]);
+        }
+        outline.count(bookmarks.len() as i32);
+        outline.finish();
+
+        for (index, bookmark) in bookmarks.iter().enumerate() {
+            let siblings = children(parents[index]);
+            let position = siblings.iter().position(|&sibling| sibling == index).unwrap_or(0);
+            let own_children = children(Some(index));
+            let (_, top) = self.point(0.0, bookmark.y);
+            let left = self.options.margins[3];
+
+            let mut item = self.pdf.outline_item(ids[index]);
+            item.title(TextStr(&bookmark.title))
+                .parent(parents[index].map_or(outline_id, |parent| ids[parent]));
+            if position > 0 {
+                item.prev(ids[siblings[position - 1]]);
+            }
+            if let Some(next) = siblings.get(position + 1) {
+                item.next(ids[*next]);
+            }
+            if let (Some(first), Some(last)) = (own_children.first(), own_children.last()) {
+                item.first(ids[*first]).last(ids[*last]).count(descendants(index));
+            }
+            item.dest().page(self.pages[bookmark.page]).xyz(left, top, None);
+        }
+
+        outline_id
+    }
+}
+
+/// 字体在页面资源中的名称
+fn font_name(font: FontId) -> FontName {
+    FontName(format!("F{}", font.0))
+}
+
+/// 资源名称
+struct FontName(String);
+
+impl FontName {
+    fn as_name(&self) -> Name<'_> {
+        Name(self.0.as_bytes())
+    }
+}
+
+/// 字体子集的名称前缀：由用到的字形决定的六个大写字母
+fn subset_tag(glyphs: &[u16]) -> String {
+    let mut hash: u32 = 2166136261;
+    for glyph in glyphs {
+        for byte in glyph.to_be_bytes() {
+            hash = (hash ^ byte as u32).wrapping_mul(16777619);
+        }
+    }
+    (0..6)
+        .map(|_| {
+            let c = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            c
+        })
+        .collect()
+}
+
+/// 使用 zlib 压缩流
+fn compress(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_from_settings() -> Result<()> {
+        let settings = PdfSettings {
+            paper: "letter".to_string(),
+            margins_mm: vec![25.4, 12.7],
+            ..Default::default()
+        };
+        let options = PdfExportOptions::from_settings(&settings)?;
+        assert!((options.page_width - 612.0).abs() < 0.1);
+        assert!((options.page_height - 792.0).abs() < 0.1);
+        assert!((options.margins[0] - 72.0).abs() < 0.1);
+        assert!((options.margins[3] - 36.0).abs() < 0.1);
+
+        let settings = PdfSettings { paper: "b9".to_string(), ..Default::default() };
+        assert!(PdfExportOptions::from_settings(&settings).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_template() {
+        assert_eq!(expand_template("{page} / {pages}", 2, 5, "文档"), ["", "2 / 5", ""]);
+        assert_eq!(expand_template("{title} | | 第 {page} 页", 1, 1, "文档"), ["文档", "", "第 1 页"]);
+        assert_eq!(expand_template("a|b", 1, 1, ""), ["a", "b", ""]);
+    }
+
+    #[test]
+    fn test_render_pdf() -> Result<()> {
+        // 没有可用字体的环境中跳过
+        let Ok(exporter) = PdfExporter::new(PdfExportOptions::default()) else {
+            return Ok(());
+        };
+
+        let mut markdown = String::from("# 标题\n\n正文 text with [link](https://example.com).\n\n## 第一节\n\n");
+        for i in 0..80 {
+            markdown.push_str(&format!("- item {}\n", i));
+        }
+        markdown.push_str("\n## 第二节\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n");
+
+        let (pdf, _) = exporter.render(&markdown, None)?;
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF"));
+        assert!(text.contains("/Type /Outlines"));
+        assert!(text.contains("/Identity-H"));
+        assert!(text.contains("/URI (https://example.com)"));
+        // 80 个列表项需要多页
+        assert!(text.contains("/Count 2") || text.contains("/Count 3"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_glyph_warning() -> Result<()> {
+        assert!(missing_glyph_warning(&[]).is_none());
+        let warning = missing_glyph_warning(&['中', '文']).unwrap();
+        assert!(warning.contains("中文"));
+        assert!(warning.contains("pdf.font_files") && warning.contains("pdf.cjk_font_family"));
+
+        let Ok(exporter) = PdfExporter::new(PdfExportOptions::default()) else {
+            return Ok(());
+        };
+        // 私用区的字符不会出现在任何字体中；每次导出只报告这次缺少的字符
+        let (_, warnings) = exporter.render("text \u{10FFFD}", None)?;
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains('\u{10FFFD}'));
+        let (_, warnings) = exporter.render("text", None)?;
+        assert!(warnings.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,964 @@
+//! PDF 排版模块
+//!
+//! 把 Markdown 排版为分页的绘制指令：
+//! - 段落按宽度折行，中日韩文字可以在任意两个字符之间换行
+//! - 标题与后面的内容放在同一页
+//! - 代码块逐行排版，可以跨页；长行按字符折行
+//! - 表格逐行排版，跨页时在新的一页重复表头
+//! - 记录标题所在的页和位置，用于生成书签
+//!
+//! 排版结果中的坐标以内容区左上角为原点，y 轴向下，单位为磅
+
+use std::rc::Rc;
+use pulldown_cmark::{Alignment, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use super::fonts::{FontId, FontSet, FontStyle, Glyph};
+
+/// RGB 颜色（0-1）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Color(pub f32, pub f32, pub f32);
+
+const TEXT_COLOR: Color = Color(0.13, 0.13, 0.13);
+const MUTED_COLOR: Color = Color(0.4, 0.4, 0.4);
+const LINK_COLOR: Color = Color(0.0, 0.36, 0.8);
+const CODE_BACKGROUND: Color = Color(0.96, 0.96, 0.96);
+const HEADER_BACKGROUND: Color = Color(0.94, 0.94, 0.94);
+const BORDER_COLOR: Color = Color(0.8, 0.8, 0.8);
+
+/// 各级标题的字号（正文字号的倍数）
+const HEADING_SCALES: [f32; 6] = [2.0, 1.6, 1.3, 1.15, 1.0, 0.9];
+/// 段落之间的距离（字号的倍数）
+const PARAGRAPH_SPACING: f32 = 0.6;
+/// 紧凑列表项之间的距离（字号的倍数）
+const ITEM_SPACING: f32 = 0.2;
+/// 每层列表的缩进
+const LIST_INDENT: f32 = 20.0;
+/// 每层引用的缩进
+const QUOTE_INDENT: f32 = 14.0;
+/// 代码块的内边距
+const CODE_PADDING: f32 = 6.0;
+/// 表格单元格的内边距
+const CELL_PADDING: f32 = 4.0;
+/// 代码字号（正文字号的倍数）
+const CODE_SCALE: f32 = 0.9;
+/// 代码行高（代码字号的倍数）
+const CODE_LINE_HEIGHT: f32 = 1.35;
+
+/// 绘制指令
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Op {
+    /// 文字，`y` 为基线位置
+    Text { x: f32, y: f32, font: FontId, size: f32, color: Color, glyphs: Vec<Glyph> },
+    /// 填充矩形，`y` 为上边缘
+    Rect { x: f32, y: f32, width: f32, height: f32, color: Color },
+    /// 直线
+    Line { x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: Color },
+    /// 链接区域
+    Link { x: f32, y: f32, width: f32, height: f32, url: String },
+}
+
+impl Op {
+    /// 向下移动
+    fn offset(mut self, dy: f32) -> Self {
+        match &mut self {
+            Op::Text { y, .. } | Op::Rect { y, .. } | Op::Link { y, .. } => *y += dy,
+            Op::Line { y1, y2, .. } => {
+                *y1 += dy;
+                *y2 += dy;
+            }
+        }
+        self
+    }
+}
+
+/// 排版的最小单位：分页时不会被拆开
+#[derive(Debug, Clone, Default)]
+pub(super) struct Row {
+    pub height: f32,
+    /// 相对于行顶部的绘制指令
+    pub ops: Vec<Op>,
+    /// 与下一行放在同一页（标题）
+    pub keep_with_next: bool,
+    /// 段落间距，位于页首时省略
+    pub collapsible: bool,
+    /// 跨页时先在新的一页绘制的行（表头）
+    pub header: Option<Rc<Row>>,
+    /// 书签（标题级别和文字）
+    pub bookmark: Option<(usize, String)>,
+}
+
+impl Row {
+    /// 间距行
+    fn space(height: f32, ops: Vec<Op>, keep_with_next: bool) -> Self {
+        Self { height, ops, keep_with_next, collapsible: true, ..Default::default() }
+    }
+}
+
+/// 书签
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Bookmark {
+    pub level: usize,
+    pub title: String,
+    /// 页码（从 0 开始）
+    pub page: usize,
+    /// 在页面内容区中的位置
+    pub y: f32,
+}
+
+/// 排版结果
+#[derive(Debug, Default)]
+pub(super) struct Document {
+    /// 每页的绘制指令
+    pub pages: Vec<Vec<Op>>,
+    pub bookmarks: Vec<Bookmark>,
+    /// 第一个一级标题
+    pub title: Option<String>,
+}
+
+/// 排版参数
+#[derive(Debug, Clone, Copy)]
+pub(super) struct LayoutMetrics {
+    /// 内容区宽度
+    pub width: f32,
+    /// 内容区高度
+    pub height: f32,
+    /// 正文字号
+    pub font_size: f32,
+    /// 行高（字号的倍数）
+    pub line_height: f32,
+}
+
+/// 排版 Markdown 文档
+pub(super) fn layout(fonts: &FontSet, markdown: &str, metrics: LayoutMetrics) -> Document {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    options.insert(Options::ENABLE_MATH);
+
+    let mut builder = Builder::new(fonts, metrics);
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event);
+    }
+    builder.flush(ITEM_SPACING);
+
+    let (pages, bookmarks) = paginate(builder.rows, metrics.height);
+    Document { pages, bookmarks, title: builder.title }
+}
+
+/// 排版单行文字（页眉、页脚），返回绘制指令和宽度
+pub(super) fn text_line(fonts: &FontSet, text: &str, size: f32, color: Color, x: f32, baseline: f32) -> (Vec<Op>, f32) {
+    let style = FontStyle::default();
+    let mut ops = Vec::new();
+    let width = push_glyph_runs(fonts, &mut ops, text, style, size, color, x, baseline);
+    (ops, width)
+}
+
+/// 把行分配到各页
+///
+/// - 页首的段落间距被省略
+/// - 标题与后面的第一行放不下时换页
+/// - 带表头的行换页后先重复表头
+pub(super) fn paginate(rows: Vec<Row>, page_height: f32) -> (Vec<Vec<Op>>, Vec<Bookmark>) {
+    let mut pages: Vec<Vec<Op>> = vec![Vec::new()];
+    let mut bookmarks = Vec::new();
+    let mut y = 0.0;
+
+    for (index, row) in rows.iter().enumerate() {
+        if row.collapsible && y == 0.0 {
+            continue;
+        }
+
+        let needed = if row.keep_with_next {
+            let chain: f32 = rows[index..]
+                .iter()
+                .take_while(|row| row.keep_with_next)
+                .map(|row| row.height)
+                .sum::<f32>()
+                + rows[index..].iter().find(|row| !row.keep_with_next).map_or(0.0, |row| row.height);
+            // 连在一起的行超过一页时只要求当前行放得下
+            if chain > page_height { row.height } else { chain }
+        } else {
+            row.height
+        };
+
+        if y > 0.0 && y + needed > page_height {
+            pages.push(Vec::new());
+            y = 0.0;
+            if row.collapsible {
+                continue;
+            }
+            if let Some(header) = &row.header {
+                let page = pages.last_mut().expect("至少有一页");
+                page.extend(header.ops.iter().cloned().map(|op| op.offset(y)));
+                y += header.height;
+            }
+        }
+
+        if let Some((level, title)) = &row.bookmark {
+            bookmarks.push(Bookmark { level: *level, title: title.clone(), page: pages.len() - 1, y });
+        }
+        let page = pages.last_mut().expect("至少有一页");
+        page.extend(row.ops.iter().cloned().map(|op| op.offset(y)));
+        y += row.height;
+    }
+
+    (pages, bookmarks)
+}
+
+/// 行内文字
+#[derive(Debug, Clone)]
+struct Span {
+    text: String,
+    style: FontStyle,
+    color: Color,
+    /// 行内代码（绘制背景）
+    code: bool,
+    strike: bool,
+    link: Option<String>,
+}
+
+/// 折行的最小单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PieceKind {
+    Word,
+    Space,
+    Break,
+}
+
+#[derive(Debug, Clone)]
+struct Piece {
+    /// 所属的行内文字
+    span: usize,
+    text: String,
+    width: f32,
+    kind: PieceKind,
+}
+
+/// 判断是否为中日韩文字（可以在任意两个字符之间换行）
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}'
+        | '\u{20000}'..='\u{2FFFF}')
+}
+
+/// 把文字拆分为单词、空白和换行，中日韩文字每个字符单独成为一个单词
+fn split_text(text: &str) -> Vec<(String, PieceKind)> {
+    let mut pieces: Vec<(String, PieceKind)> = Vec::new();
+    for c in text.chars() {
+        let kind = if c == '\n' {
+            PieceKind::Break
+        } else if c.is_whitespace() {
+            PieceKind::Space
+        } else {
+            PieceKind::Word
+        };
+        let joins = kind != PieceKind::Break
+            && !is_cjk(c)
+            && pieces.last().is_some_and(|(last, last_kind)| {
+                *last_kind == kind && !last.chars().next_back().is_some_and(is_cjk)
+            });
+        match pieces.last_mut() {
+            Some((last, _)) if joins => last.push(c),
+            _ => pieces.push((c.to_string(), kind)),
+        }
+    }
+    pieces
+}
+
+/// 按宽度折行：行首的空白被省略，行尾的空白不计入宽度
+fn break_lines(pieces: Vec<Piece>, width: f32) -> Vec<Vec<Piece>> {
+    fn trim(line: &mut Vec<Piece>) {
+        while line.last().is_some_and(|piece| piece.kind == PieceKind::Space) {
+            line.pop();
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut line: Vec<Piece> = Vec::new();
+    let mut line_width = 0.0;
+
+    for piece in pieces {
+        match piece.kind {
+            PieceKind::Break => {
+                trim(&mut line);
+                lines.push(std::mem::take(&mut line));
+                line_width = 0.0;
+            }
+            PieceKind::Space => {
+                if !line.is_empty() {
+                    line_width += piece.width;
+                    line.push(piece);
+                }
+            }
+            PieceKind::Word => {
+                if !line.is_empty() && line_width + piece.width > width {
+                    trim(&mut line);
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                line_width += piece.width;
+                line.push(piece);
+            }
+        }
+    }
+
+    trim(&mut line);
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 按字体拆分文字并添加绘制指令，返回宽度
+#[allow(clippy::too_many_arguments)]
+fn push_glyph_runs(
+    fonts: &FontSet,
+    ops: &mut Vec<Op>,
+    text: &str,
+    style: FontStyle,
+    size: f32,
+    color: Color,
+    x: f32,
+    baseline: f32,
+) -> f32 {
+    let mut cursor = x;
+    let mut run: Option<(FontId, f32, Vec<Glyph>)> = None;
+
+    for c in text.chars() {
+        let (font, glyph) = fonts.glyph(c, style);
+        let advance = fonts.face(font).scale(glyph.advance, size);
+        match &mut run {
+            Some((run_font, _, glyphs)) if *run_font == font => glyphs.push(glyph),
+            _ => {
+                if let Some((font, start, glyphs)) = run.take() {
+                    ops.push(Op::Text { x: start, y: baseline, font, size, color, glyphs });
+                }
+                run = Some((font, cursor, vec![glyph]));
+            }
+        }
+        cursor += advance;
+    }
+    if let Some((font, start, glyphs)) = run {
+        ops.push(Op::Text { x: start, y: baseline, font, size, color, glyphs });
+    }
+
+    cursor - x
+}
+
+/// 文字的水平对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl From<Alignment> for Align {
+    fn from(alignment: Alignment) -> Self {
+        match alignment {
+            Alignment::Center => Align::Center,
+            Alignment::Right => Align::Right,
+            Alignment::None | Alignment::Left => Align::Left,
+        }
+    }
+}
+
+/// 引用和列表的嵌套
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Quote,
+    List,
+}
+
+/// 正在收集的表格
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<Vec<Span>>>,
+    in_head: bool,
+    header_rows: usize,
+}
+
+/// 根据 Markdown 事件生成行
+struct Builder<'a> {
+    fonts: &'a FontSet,
+    metrics: LayoutMetrics,
+    rows: Vec<Row>,
+    spans: Vec<Span>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    image: usize,
+    links: Vec<String>,
+    containers: Vec<Container>,
+    /// 每层列表的下一个编号（无序列表为 `None`）
+    lists: Vec<Option<u64>>,
+    /// 等待绘制的列表标记
+    marker: Option<String>,
+    heading: Option<HeadingLevel>,
+    code: Option<String>,
+    table: Option<Table>,
+    title: Option<String>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(fonts: &'a FontSet, metrics: LayoutMetrics) -> Self {
+        Self {
+            fonts,
+            metrics,
+            rows: Vec::new(),
+            spans: Vec::new(),
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            image: 0,
+            links: Vec::new(),
+            containers: Vec::new(),
+            lists: Vec::new(),
+            marker: None,
+            heading: None,
+            code: None,
+            table: None,
+            title: None,
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code {
+                Some(code) => code.push_str(&text),
+                None => self.push_text(&text, false),
+            },
+            Event::Code(text) => self.push_text(&text, true),
+            Event::InlineMath(formula) | Event::DisplayMath(formula) => {
+                self.italic += 1;
+                self.push_text(&formula, false);
+                self.italic -= 1;
+            }
+            Event::SoftBreak => self.push_text(" ", false),
+            Event::HardBreak => self.push_text("\n", false),
+            Event::Rule => {
+                self.flush(ITEM_SPACING);
+                let (left, mut ops) = self.indent(self.metrics.font_size);
+                let height = self.metrics.font_size;
+                ops.push(Op::Line {
+                    x1: left,
+                    y1: height / 2.0,
+                    x2: self.metrics.width,
+                    y2: height / 2.0,
+                    width: 1.0,
+                    color: BORDER_COLOR,
+                });
+                self.rows.push(Row { height, ops, ..Default::default() });
+            }
+            Event::TaskListMarker(checked) => {
+                let style = FontStyle::default();
+                let (box_char, fallback) = if checked { ('☑', "[x]") } else { ('☐', "[ ]") };
+                self.marker = Some(if self.fonts.has_char(box_char, style) {
+                    box_char.to_string()
+                } else {
+                    fallback.to_string()
+                });
+            }
+            Event::FootnoteReference(label) => self.push_text(&format!("[{}]", label), false),
+            Event::Html(_) | Event::InlineHtml(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.flush(ITEM_SPACING),
+            Tag::Heading { level, .. } => {
+                self.flush(ITEM_SPACING);
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush(ITEM_SPACING);
+                self.containers.push(Container::Quote);
+            }
+            Tag::CodeBlock(_) => {
+                self.flush(ITEM_SPACING);
+                self.code = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush(ITEM_SPACING);
+                self.lists.push(start);
+                self.containers.push(Container::List);
+            }
+            Tag::Item => {
+                self.flush(ITEM_SPACING);
+                let style = FontStyle::default();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ if self.fonts.has_char('•', style) => "•".to_string(),
+                    _ => "-".to_string(),
+                });
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.flush(ITEM_SPACING);
+                self.containers.push(Container::List);
+                self.marker = Some(format!("[{}]", label));
+            }
+            Tag::Table(alignments) => {
+                self.flush(ITEM_SPACING);
+                self.table = Some(Table { alignments, rows: Vec::new(), in_head: false, header_rows: 0 });
+            }
+            Tag::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.in_head = true;
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => self.spans.clear(),
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link { dest_url, .. } => self.links.push(dest_url.to_string()),
+            Tag::Image { .. } => {
+                self.image += 1;
+                self.push_text("[图片: ", false);
+            }
+            Tag::HtmlBlock | Tag::MetadataBlock(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush(PARAGRAPH_SPACING),
+            TagEnd::Heading(level) => {
+                let spans = std::mem::take(&mut self.spans);
+                self.heading = None;
+                self.heading_rows(level, spans);
+            }
+            TagEnd::BlockQuote => {
+                self.flush(ITEM_SPACING);
+                self.containers.pop();
+            }
+            TagEnd::CodeBlock => {
+                let code = self.code.take().unwrap_or_default();
+                self.code_rows(&code);
+            }
+            TagEnd::List(_) => {
+                self.flush(ITEM_SPACING);
+                self.lists.pop();
+                self.containers.pop();
+                if self.lists.is_empty() {
+                    let (_, ops) = self.indent(0.0);
+                    self.rows.push(Row::space(self.metrics.font_size * PARAGRAPH_SPACING, ops, false));
+                }
+            }
+            TagEnd::Item => self.flush(ITEM_SPACING),
+            TagEnd::FootnoteDefinition => {
+                self.flush(PARAGRAPH_SPACING);
+                self.containers.pop();
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.in_head = false;
+                    table.header_rows = table.rows.len();
+                }
+            }
+            TagEnd::TableCell => {
+                let spans = std::mem::take(&mut self.spans);
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push(spans);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.table_rows(table);
+                }
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            TagEnd::Link => {
+                self.links.pop();
+            }
+            TagEnd::Image => {
+                self.push_text("]", false);
+                self.image = self.image.saturating_sub(1);
+            }
+            TagEnd::TableRow | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_) => {}
+        }
+    }
+
+    /// 按当前样式添加行内文字
+    fn push_text(&mut self, text: &str, code: bool) {
+        let in_head = self.table.as_ref().is_some_and(|table| table.in_head);
+        let style = FontStyle {
+            bold: self.bold > 0 || self.heading.is_some() || in_head,
+            italic: self.italic > 0 || self.image > 0,
+            mono: code,
+        };
+        let link = self.links.last().cloned();
+        let color = if link.is_some() {
+            LINK_COLOR
+        } else if self.image > 0 || self.containers.contains(&Container::Quote) {
+            MUTED_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        self.spans.push(Span { text: text.to_string(), style, color, code, strike: self.strike > 0, link });
+    }
+
+    /// 当前缩进和引用竖线的绘制指令
+    fn indent(&self, height: f32) -> (f32, Vec<Op>) {
+        let mut left = 0.0;
+        let mut ops = Vec::new();
+        for container in &self.containers {
+            match container {
+                Container::Quote => {
+                    ops.push(Op::Rect { x: left, y: 0.0, width: 3.0, height, color: BORDER_COLOR });
+                    left += QUOTE_INDENT;
+                }
+                Container::List => left += LIST_INDENT,
+            }
+        }
+        (left, ops)
+    }
+
+    /// 把收集的行内文字排版为段落
+    fn flush(&mut self, spacing: f32) {
+        let spans = std::mem::take(&mut self.spans);
+        if spans.iter().all(|span| span.text.trim().is_empty()) {
+            return;
+        }
+        let size = self.metrics.font_size;
+        self.text_rows(&spans, size, false, None);
+        let (_, ops) = self.indent(size * spacing);
+        self.rows.push(Row::space(size * spacing, ops, false));
+    }
+
+    /// 标题：前后留出间距，与下一行放在同一页，并记录书签
+    fn heading_rows(&mut self, level: HeadingLevel, spans: Vec<Span>) {
+        let level = level as usize;
+        let size = self.metrics.font_size * HEADING_SCALES[level - 1];
+        let title: String = spans.iter().map(|span| span.text.as_str()).collect::<String>().trim().to_string();
+        if level == 1 && self.title.is_none() && !title.is_empty() {
+            self.title = Some(title.clone());
+        }
+
+        let (left, ops) = self.indent(size * 0.6);
+        self.rows.push(Row::space(size * 0.6, ops, true));
+        self.text_rows(&spans, size, true, Some((level, title)));
+
+        let spacing = size * 0.4;
+        let (_, mut ops) = self.indent(spacing);
+        if level <= 2 {
+            ops.push(Op::Line { x1: left, y1: 2.0, x2: self.metrics.width, y2: 2.0, width: 0.5, color: BORDER_COLOR });
+        }
+        self.rows.push(Row::space(spacing, ops, true));
+    }
+
+    /// 测量并拆分行内文字，超过宽度的单词按字符拆开
+    fn pieces(&self, spans: &[Span], size: f32, width: f32) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        for (index, span) in spans.iter().enumerate() {
+            for (text, kind) in split_text(&span.text) {
+                let measured = self.fonts.measure(&text, span.style, size);
+                if kind == PieceKind::Word && measured > width {
+                    for c in text.chars() {
+                        let text = c.to_string();
+                        let width = self.fonts.measure(&text, span.style, size);
+                        pieces.push(Piece { span: index, text, width, kind });
+                    }
+                } else {
+                    pieces.push(Piece { span: index, text, width: measured, kind });
+                }
+            }
+        }
+        pieces
+    }
+
+    /// 绘制一行文字，返回绘制指令
+    fn line_ops(&self, spans: &[Span], line: &[Piece], size: f32, x: f32, baseline: f32) -> Vec<Op> {
+        let mut backgrounds = Vec::new();
+        let mut ops = Vec::new();
+        let mut cursor = x;
+
+        for piece in line {
+            let span = &spans[piece.span];
+            if span.code {
+                backgrounds.push(Op::Rect {
+                    x: cursor,
+                    y: baseline - size * 0.95,
+                    width: piece.width,
+                    height: size * 1.25,
+                    color: CODE_BACKGROUND,
+                });
+            }
+            if piece.kind == PieceKind::Word {
+                push_glyph_runs(self.fonts, &mut ops, &piece.text, span.style, size, span.color, cursor, baseline);
+            }
+            if span.strike {
+                let y = baseline - size * 0.3;
+                ops.push(Op::Line { x1: cursor, y1: y, x2: cursor + piece.width, y2: y, width: size / 16.0, color: span.color });
+            }
+            if let Some(url) = &span.link {
+                ops.push(Op::Link { x: cursor, y: baseline - size, width: piece.width, height: size * 1.25, url: url.clone() });
+            }
+            cursor += piece.width;
+        }
+
+        backgrounds.extend(ops);
+        backgrounds
+    }
+
+    /// 基线到行顶部的距离
+    fn baseline(&self, size: f32, line_height: f32) -> f32 {
+        let face = self.fonts.face(self.fonts.font(FontStyle::default()));
+        let ascent = face.ascender / face.units_per_em;
+        let descent = -face.descender / face.units_per_em;
+        (line_height - (ascent + descent) * size) / 2.0 + ascent * size
+    }
+
+    /// 把段落排版为行，第一行绘制等待中的列表标记
+    fn text_rows(&mut self, spans: &[Span], size: f32, keep_with_next: bool, mut bookmark: Option<(usize, String)>) {
+        let line_height = size * self.metrics.line_height;
+        let (left, _) = self.indent(0.0);
+        let width = (self.metrics.width - left).max(size);
+        let baseline = self.baseline(size, line_height);
+
+        for line in break_lines(self.pieces(spans, size, width), width) {
+            let (_, mut ops) = self.indent(line_height);
+            if let Some(marker) = self.marker.take() {
+                let marker_size = self.metrics.font_size;
+                let marker_width = self.fonts.measure(&marker, FontStyle::default(), marker_size);
+                let x = left - marker_width - 5.0;
+                push_glyph_runs(self.fonts, &mut ops, &marker, FontStyle::default(), marker_size, TEXT_COLOR, x, baseline);
+            }
+            ops.extend(self.line_ops(spans, &line, size, left, baseline));
+            self.rows.push(Row { height: line_height, ops, keep_with_next, bookmark: bookmark.take(), ..Default::default() });
+        }
+    }
+
+    /// 代码块：每行一个可以跨页的行，背景逐行绘制
+    fn code_rows(&mut self, code: &str) {
+        self.marker = None;
+        let size = self.metrics.font_size * CODE_SCALE;
+        let line_height = size * CODE_LINE_HEIGHT;
+        let (left, _) = self.indent(0.0);
+        let width = self.metrics.width - left;
+        let baseline = self.baseline(size, line_height);
+        let style = FontStyle { mono: true, ..Default::default() };
+        let background = |height: f32| Op::Rect { x: left, y: 0.0, width, height, color: CODE_BACKGROUND };
+
+        let (_, mut ops) = self.indent(CODE_PADDING);
+        ops.push(background(CODE_PADDING));
+        self.rows.push(Row { height: CODE_PADDING, ops, ..Default::default() });
+
+        let code = code.strip_suffix('\n').unwrap_or(code).replace('\t', "    ");
+        for source_line in code.split('\n') {
+            // 保留行首空白，长行在任意字符处折行
+            let pieces = source_line
+                .chars()
+                .map(|c| {
+                    let text = c.to_string();
+                    let width = self.fonts.measure(&text, style, size);
+                    Piece { span: 0, text, width, kind: PieceKind::Word }
+                })
+                .collect();
+            for line in break_lines(pieces, width - CODE_PADDING * 2.0) {
+                let (_, mut ops) = self.indent(line_height);
+                ops.push(background(line_height));
+                let text: String = line.iter().map(|piece| piece.text.as_str()).collect();
+                push_glyph_runs(self.fonts, &mut ops, &text, style, size, TEXT_COLOR, left + CODE_PADDING, baseline);
+                self.rows.push(Row { height: line_height, ops, ..Default::default() });
+            }
+        }
+
+        let (_, mut ops) = self.indent(CODE_PADDING);
+        ops.push(background(CODE_PADDING));
+        self.rows.push(Row { height: CODE_PADDING, ops, ..Default::default() });
+
+        let spacing = self.metrics.font_size * PARAGRAPH_SPACING;
+        let (_, ops) = self.indent(spacing);
+        self.rows.push(Row::space(spacing, ops, false));
+    }
+
+    /// 表格：列宽按内容比例分配，每行一个可以跨页的行，表头在跨页时重复
+    fn table_rows(&mut self, table: Table) {
+        self.marker = None;
+        let size = self.metrics.font_size;
+        let line_height = size * self.metrics.line_height;
+        let (left, _) = self.indent(0.0);
+        let available = self.metrics.width - left;
+        let baseline = self.baseline(size, line_height);
+
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0).max(table.alignments.len());
+        if columns == 0 {
+            return;
+        }
+        let mut natural = vec![CELL_PADDING * 2.0; columns];
+        for row in &table.rows {
+            for (column, cell) in row.iter().enumerate() {
+                let width: f32 = cell.iter().map(|span| self.fonts.measure(&span.text, span.style, size)).sum();
+                natural[column] = natural[column].max(width + CELL_PADDING * 2.0);
+            }
+        }
+        let total: f32 = natural.iter().sum();
+        let widths: Vec<f32> = if total <= available {
+            natural
+        } else {
+            natural.iter().map(|width| width / total * available).collect()
+        };
+        let table_width: f32 = widths.iter().sum();
+
+        let mut header: Option<Rc<Row>> = None;
+        for (index, cells) in table.rows.iter().enumerate() {
+            let is_header = index < table.header_rows;
+            let laid_out: Vec<Vec<Vec<Piece>>> = (0..columns)
+                .map(|column| {
+                    let spans = cells.get(column).map(Vec::as_slice).unwrap_or_default();
+                    let width = widths[column] - CELL_PADDING * 2.0;
+                    break_lines(self.pieces(spans, size, width), width)
+                })
+                .collect();
+            let line_count = laid_out.iter().map(Vec::len).max().unwrap_or(1);
+            let height = line_count as f32 * line_height + CELL_PADDING * 2.0;
+
+            let (_, mut ops) = self.indent(height);
+            if is_header {
+                ops.push(Op::Rect { x: left, y: 0.0, width: table_width, height, color: HEADER_BACKGROUND });
+            }
+            let mut x = left;
+            for (column, lines) in laid_out.iter().enumerate() {
+                let spans = cells.get(column).map(Vec::as_slice).unwrap_or_default();
+                let align = table.alignments.get(column).copied().map(Align::from).unwrap_or(Align::Left);
+                for (line_index, line) in lines.iter().enumerate() {
+                    let line_width: f32 = line.iter().map(|piece| piece.width).sum();
+                    let free = widths[column] - CELL_PADDING * 2.0 - line_width;
+                    let offset = match align {
+                        Align::Left => 0.0,
+                        Align::Center => free / 2.0,
+                        Align::Right => free,
+                    };
+                    let y = CELL_PADDING + line_index as f32 * line_height + baseline;
+                    ops.extend(self.line_ops(spans, line, size, x + CELL_PADDING + offset.max(0.0), y));
+                }
+                ops.push(Op::Line { x1: x, y1: 0.0, x2: x, y2: height, width: 0.5, color: BORDER_COLOR });
+                x += widths[column];
+            }
+            ops.push(Op::Line { x1: x, y1: 0.0, x2: x, y2: height, width: 0.5, color: BORDER_COLOR });
+            ops.push(Op::Line { x1: left, y1: 0.0, x2: x, y2: 0.0, width: 0.5, color: BORDER_COLOR });
+            ops.push(Op::Line { x1: left, y1: height, x2: x, y2: height, width: 0.5, color: BORDER_COLOR });
+
+            let row = Row {
+                height,
+                ops,
+                keep_with_next: is_header,
+                header: if is_header { None } else { header.clone() },
+                ..Default::default()
+            };
+            if is_header {
+                header = Some(Rc::new(row.clone()));
+            }
+            self.rows.push(row);
+        }
+
+        let spacing = size * PARAGRAPH_SPACING;
+        let (_, ops) = self.indent(spacing);
+        self.rows.push(Row::space(spacing, ops, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(height: f32) -> Row {
+        Row {
+            height,
+            ops: vec![Op::Rect { x: 0.0, y: 0.0, width: 1.0, height, color: TEXT_COLOR }],
+            ..Default::default()
+        }
+    }
+
+    fn piece(text: &str, width: f32, kind: PieceKind) -> Piece {
+        Piece { span: 0, text: text.to_string(), width, kind }
+    }
+
+    fn texts(lines: &[Vec<Piece>]) -> Vec<String> {
+        lines.iter().map(|line| line.iter().map(|piece| piece.text.as_str()).collect()).collect()
+    }
+
+    #[test]
+    fn test_split_text() {
+        let pieces = split_text("hello  world\n中文ab");
+        let texts: Vec<&str> = pieces.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(texts, ["hello", "  ", "world", "\n", "中", "文", "ab"]);
+        assert_eq!(pieces[1].1, PieceKind::Space);
+        assert_eq!(pieces[3].1, PieceKind::Break);
+    }
+
+    #[test]
+    fn test_break_lines() {
+        let pieces = vec![
+            piece("aaa", 30.0, PieceKind::Word),
+            piece(" ", 5.0, PieceKind::Space),
+            piece("bbb", 30.0, PieceKind::Word),
+            piece(" ", 5.0, PieceKind::Space),
+            piece("ccc", 30.0, PieceKind::Word),
+            piece("\n", 0.0, PieceKind::Break),
+            piece("d", 10.0, PieceKind::Word),
+        ];
+        assert_eq!(texts(&break_lines(pieces, 70.0)), ["aaa bbb", "ccc", "d"]);
+        assert_eq!(texts(&break_lines(Vec::new(), 70.0)), [""]);
+    }
+
+    #[test]
+    fn test_paginate_keeps_heading_with_content() {
+        let mut heading = row(20.0);
+        heading.keep_with_next = true;
+        heading.bookmark = Some((1, "标题".to_string()));
+        let rows = vec![row(50.0), Row::space(10.0, Vec::new(), false), heading, row(40.0)];
+
+        let (pages, bookmarks) = paginate(rows, 100.0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(bookmarks, [Bookmark { level: 1, title: "标题".to_string(), page: 1, y: 0.0 }]);
+    }
+
+    #[test]
+    fn test_paginate_repeats_table_header() {
+        let mut header = row(10.0);
+        header.keep_with_next = true;
+        let header_rc = Rc::new(header.clone());
+        let mut rows = vec![header];
+        for _ in 0..5 {
+            let mut body = row(30.0);
+            body.header = Some(header_rc.clone());
+            rows.push(body);
+        }
+
+        let (pages, _) = paginate(rows, 100.0);
+        assert_eq!(pages.len(), 2);
+        // 第二页以表头开始，后面是剩下的两行
+        assert_eq!(pages[1].len(), 3);
+        assert_eq!(pages[1][0], Op::Rect { x: 0.0, y: 0.0, width: 1.0, height: 10.0, color: TEXT_COLOR });
+        assert_eq!(pages[1][1], Op::Rect { x: 0.0, y: 10.0, width: 1.0, height: 30.0, color: TEXT_COLOR });
+    }
+
+    #[test]
+    fn test_paginate_drops_space_at_page_top() {
+        let rows = vec![row(90.0), Row::space(20.0, Vec::new(), false), row(10.0)];
+        let (pages, _) = paginate(rows, 100.0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1], [Op::Rect { x: 0.0, y: 0.0, width: 1.0, height: 10.0, color: TEXT_COLOR }]);
+    }
+}
@@ -5,12 +5,15 @@
 //! - 打开文件
 //! - 保存文件
 //! - 另存为
+//! - 最近打开的文件
 
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
 
+use super::relocate;
+
 /// 文件操作管理器
 pub struct FileManager {
     /// 当前打开的文件路径
@@ -19,6 +22,10 @@ pub struct FileManager {
     content: String,
     /// 是否已修改
     is_modified: bool,
+    /// 最近打开的文件（最近的在前）
+    recent_files: Vec<PathBuf>,
+    /// 最近文件列表的最大数量
+    max_recent: usize,
 }
 
 impl FileManager {
@@ -28,6 +35,8 @@ impl FileManager {
             current_file: None,
             content: String::new(),
             is_modified: false,
+            recent_files: Vec::new(),
+            max_recent: 20,
         }
     }
 
@@ -79,6 +88,7 @@ impl FileManager {
         self.current_file = Some(path.to_path_buf());
         self.content = content;
         self.is_modified = false;
+        self.add_to_recent(path.to_path_buf());
 
         Ok(())
     }
@@ -100,6 +110,7 @@ impl FileManager {
         self.save_to_file(path)?;
         self.current_file = Some(path.to_path_buf());
         self.is_modified = false;
+        self.add_to_recent(path.to_path_buf());
         Ok(())
     }
 
@@ -121,6 +132,44 @@ impl FileManager {
         Ok(())
     }
 
+    /// 文件或文件夹被移动后更新当前文件路径
+    ///
+    /// 当前文件就是 `from` 或位于 `from` 目录下时，返回 `true`
+    pub fn relocate(&mut self, from: &Path, to: &Path) -> bool {
+        // 最近打开列表中的路径同样需要更新
+        for path in &mut self.recent_files {
+            if path.starts_with(from) {
+                *path = relocate(path, from, to);
+            }
+        }
+
+        match &self.current_file {
+            Some(current) if current.starts_with(from) => {
+                self.current_file = Some(relocate(current, from, to));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 添加到最近打开列表
+    fn add_to_recent(&mut self, path: PathBuf) {
+        self.recent_files.retain(|p| p != &path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(self.max_recent);
+    }
+
+    /// 获取最近打开的文件
+    pub fn recent_files(&self) -> &[PathBuf] {
+        &self.recent_files
+    }
+
+    /// 恢复保存的最近打开列表（最近的在前）
+    pub fn set_recent_files(&mut self, files: Vec<PathBuf>) {
+        self.recent_files = files;
+        self.recent_files.truncate(self.max_recent);
+    }
+
     /// 检查是否需要保存（文件已修改且有路径）
     pub fn needs_save(&self) -> bool {
         self.is_modified && self.current_file.is_some()
@@ -133,9 +182,69 @@ impl Default for FileManager {
     }
 }
 
+/// 原子地写入多个文件
+///
+/// 所有内容先写入与目标同目录的临时文件，全部成功后再逐个重命名覆盖目标；
+/// 任何一步失败时恢复已覆盖的文件、删除新建的文件并清理临时文件，目标文件保持不变
+pub fn write_files_atomic(files: &[(PathBuf, String)]) -> Result<()> {
+    let mut staged = Vec::new();
+
+    for (path, content) in files {
+        let temp = temp_path_for(path);
+        if let Err(e) = fs::write(&temp, content) {
+            remove_staged(&staged);
+            return Err(anyhow::anyhow!("无法写入临时文件 {}: {}", temp.display(), e));
+        }
+        staged.push((temp, path));
+    }
+
+    // 用临时文件替换目标文件，替换前保存原内容（`None` 表示原来没有该文件）
+    let mut originals: Vec<Option<Vec<u8>>> = Vec::new();
+    for (i, (temp, path)) in staged.iter().enumerate() {
+        let result = match fs::read(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+        .and_then(|original| fs::rename(temp, path).map(|_| original));
+
+        match result {
+            Ok(original) => originals.push(original),
+            Err(e) => {
+                for ((_, path), original) in staged.iter().zip(&originals) {
+                    let _ = match original {
+                        Some(content) => fs::write(path, content),
+                        None => fs::remove_file(path),
+                    };
+                }
+                remove_staged(&staged[i..]);
+                return Err(anyhow::anyhow!("无法写入文件 {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 删除尚未使用的临时文件
+fn remove_staged(staged: &[(PathBuf, &PathBuf)]) {
+    for (temp, _) in staged {
+        let _ = fs::remove_file(temp);
+    }
+}
+
+/// 生成与目标文件同目录的临时文件路径
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_manager::FileItem;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -199,6 +308,66 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_relocate() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let file_path = temp_dir.path().join("docs").join("a.md");
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        fs::write(&file_path, "# A")?;
+
+        let mut manager = FileManager::new();
+        manager.open_file(&file_path)?;
+
+        assert!(!manager.relocate(&temp_dir.path().join("other"), &temp_dir.path().join("x")));
+        assert!(manager.relocate(&temp_dir.path().join("docs"), &temp_dir.path().join("book")));
+        assert_eq!(manager.current_file(), Some(temp_dir.path().join("book").join("a.md").as_path()));
+        assert_eq!(manager.recent_files(), [temp_dir.path().join("book").join("a.md")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_files_atomic() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let first = temp_dir.path().join("a.md");
+        let second = temp_dir.path().join("b.md");
+        fs::write(&first, "old a")?;
+
+        write_files_atomic(&[
+            (first.clone(), "new a".to_string()),
+            (second.clone(), "new b".to_string()),
+        ])?;
+
+        assert_eq!(fs::read_to_string(&first)?, "new a");
+        assert_eq!(fs::read_to_string(&second)?, "new b");
+        // 不应残留临时文件
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 2);
+
+        // 目录不存在时写入失败，已存在的文件保持不变
+        let missing = temp_dir.path().join("missing").join("c.md");
+        assert!(write_files_atomic(&[
+            (first.clone(), "newer a".to_string()),
+            (missing, "c".to_string()),
+        ]).is_err());
+        assert_eq!(fs::read_to_string(&first)?, "new a");
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 2);
+
+        // 替换到一半失败时，已替换的文件恢复原内容，新建的文件被删除
+        let directory = temp_dir.path().join("dir");
+        fs::create_dir(&directory)?;
+        let third = temp_dir.path().join("c.md");
+        assert!(write_files_atomic(&[
+            (first.clone(), "newer a".to_string()),
+            (third.clone(), "c".to_string()),
+            (directory, "not a file".to_string()),
+        ]).is_err());
+        assert_eq!(fs::read_to_string(&first)?, "new a");
+        assert!(!third.exists());
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 3);
+
+        Ok(())
+    }
+
     #[test]
     fn test_is_markdown_file() {
         let markdown_file = FileItem::new(
@@ -143,6 +143,35 @@ impl FileTree {
         false
     }
 
+    /// 设置展开/折叠状态，路径不存在时返回 `false`
+    pub fn set_expanded(&mut self, path: &Path, expanded: bool) -> bool {
+        match Self::find_item_mut(&mut self.root_item, path) {
+            Some(item) => {
+                item.expanded = expanded;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 获取所有展开的目录
+    pub fn expanded_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        Self::collect_expanded(&self.root_item, &mut paths);
+        paths
+    }
+
+    /// 递归收集展开的目录
+    fn collect_expanded(item: &FileItem, paths: &mut Vec<PathBuf>) {
+        if item.file_type == FileType::Directory && item.expanded {
+            paths.push(item.path.clone());
+        }
+
+        for child in &item.children {
+            Self::collect_expanded(child, paths);
+        }
+    }
+
     /// 查找文件项（可变引用）
     pub fn find_item_mut<'a>(item: &'a mut FileItem, path: &Path) -> Option<&'a mut FileItem> {
         if item.path == path {
@@ -207,6 +236,24 @@ impl FileTree {
         }
     }
 
+    /// 获取所有文件（不含目录）
+    pub fn get_all_files(&self) -> Vec<&FileItem> {
+        let mut files = Vec::new();
+        Self::collect_files(&self.root_item, &mut files);
+        files
+    }
+
+    /// 递归收集所有文件
+    fn collect_files<'a>(item: &'a FileItem, files: &mut Vec<&'a FileItem>) {
+        if item.file_type == FileType::File {
+            files.push(item);
+        }
+
+        for child in &item.children {
+            Self::collect_files(child, files);
+        }
+    }
+
     /// 获取指定路径的子项
     pub fn get_children(&self, path: &Path) -> Option<&[FileItem]> {
         Self::find_item(&self.root_item, path)
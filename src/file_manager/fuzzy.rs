@@ -0,0 +1,369 @@
+//! 模糊匹配模块
+//!
+//! 为“快速打开”提供文件路径的模糊匹配：
+//! - 子序列匹配，智能大小写（查询包含大写字母时区分大小写）
+//! - 路径分段开头、单词边界、驼峰、连续匹配、文件名加分
+//! - 返回匹配字符的位置，用于高亮
+//! - 最近打开的文件排在前面
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::Workspace;
+
+/// 每个匹配字符的基础分
+const SCORE_MATCH: i64 = 16;
+/// 路径分段开头（`/` 之后）加分
+const BONUS_SEGMENT: i64 = 12;
+/// 单词边界（空格、`_`、`-`、`.` 之后）加分
+const BONUS_BOUNDARY: i64 = 8;
+/// 驼峰边界加分
+const BONUS_CAMEL: i64 = 6;
+/// 与上一个匹配字符相邻加分
+const BONUS_CONSECUTIVE: i64 = 8;
+/// 匹配字符位于文件名中加分
+const BONUS_FILENAME: i64 = 4;
+/// 间隔开始扣分
+const PENALTY_GAP_START: i64 = 3;
+/// 间隔每延长一个字符扣分
+const PENALTY_GAP_EXTEND: i64 = 1;
+/// 最近打开的文件加分（按最近程度递减）
+const BONUS_RECENT: i64 = 40;
+
+/// 模糊匹配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// 得分，越高越好
+    pub score: i64,
+    /// 匹配字符在候选文本中的位置（按字符计算）
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// 将匹配位置转换为合并后的字节范围，用于高亮
+    pub fn highlight_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        let mut positions = self.positions.iter().peekable();
+
+        for (index, (byte, ch)) in text.char_indices().enumerate() {
+            if positions.peek() != Some(&&index) {
+                continue;
+            }
+            positions.next();
+
+            let end = byte + ch.len_utf8();
+            match ranges.last_mut() {
+                Some(last) if last.end == byte => last.end = end,
+                _ => ranges.push(byte..end),
+            }
+        }
+
+        ranges
+    }
+}
+
+/// 对候选文本进行模糊匹配
+///
+/// 查询中的所有字符必须按顺序出现在候选文本中；空查询匹配任何文本（得分为 0）
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+
+    let case_sensitive = query.iter().any(|c| c.is_uppercase());
+    let chars: Vec<char> = candidate.chars().collect();
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a == b || a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    // 快速检查是否为子序列
+    let mut rest = chars.iter();
+    if !query.iter().all(|&q| rest.any(|&c| eq(q, c))) {
+        return None;
+    }
+
+    let filename_start = chars
+        .iter()
+        .rposition(|&c| c == '/' || c == '\\')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let bonus: Vec<i64> = (0..chars.len())
+        .map(|i| position_bonus(&chars, i) + if i >= filename_start { BONUS_FILENAME } else { 0 })
+        .collect();
+
+    // scores[j][i]：查询前 j+1 个字符匹配完、第 j 个字符匹配在 i 处的最高分
+    let n = chars.len();
+    let m = query.len();
+    let mut scores = vec![vec![None::<i64>; n]; m];
+    let mut previous = vec![vec![0usize; n]; m];
+
+    for i in 0..n {
+        if eq(query[0], chars[i]) {
+            scores[0][i] = Some(SCORE_MATCH + bonus[i]);
+        }
+    }
+
+    for j in 1..m {
+        // 间隔至少为 1 个字符时的最优前驱
+        let mut gap_best: Option<(i64, usize)> = None;
+
+        for i in 1..n {
+            if i >= 2 {
+                let extended = gap_best.map(|(score, k)| (score - PENALTY_GAP_EXTEND, k));
+                let started = scores[j - 1][i - 2].map(|score| (score - PENALTY_GAP_START, i - 2));
+                gap_best = match (extended, started) {
+                    (Some(a), Some(b)) => Some(if b.0 >= a.0 { b } else { a }),
+                    (a, b) => a.or(b),
+                };
+            }
+
+            if !eq(query[j], chars[i]) {
+                continue;
+            }
+
+            let consecutive = scores[j - 1][i - 1].map(|score| (score + BONUS_CONSECUTIVE, i - 1));
+            let best = match (consecutive, gap_best) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+
+            if let Some((score, k)) = best {
+                scores[j][i] = Some(score + SCORE_MATCH + bonus[i]);
+                previous[j][i] = k;
+            }
+        }
+    }
+
+    let (mut end, score) = scores[m - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, score)| score.map(|s| (i, s)))
+        .max_by_key(|&(i, score)| (score, std::cmp::Reverse(i)))?;
+
+    let mut positions = vec![0; m];
+    for j in (0..m).rev() {
+        positions[j] = end;
+        end = previous[j][end];
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// 字符位置的边界加分
+fn position_bonus(chars: &[char], i: usize) -> i64 {
+    let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p)) else {
+        return BONUS_SEGMENT;
+    };
+    let current = chars[i];
+
+    match prev {
+        '/' | '\\' => BONUS_SEGMENT,
+        ' ' | '_' | '-' | '.' => BONUS_BOUNDARY,
+        _ if prev.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        _ if !prev.is_alphanumeric() && current.is_alphanumeric() => BONUS_BOUNDARY,
+        _ => 0,
+    }
+}
+
+/// 快速打开的候选文件
+#[derive(Debug, Clone)]
+struct QuickOpenCandidate {
+    path: PathBuf,
+    display: String,
+}
+
+/// 快速打开的匹配结果
+#[derive(Debug, Clone)]
+pub struct QuickOpenMatch {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 显示的相对路径
+    pub display: String,
+    /// 匹配信息（匹配位置对应 `display`）
+    pub fuzzy: FuzzyMatch,
+}
+
+/// 快速打开文件查找器
+#[derive(Debug, Clone, Default)]
+pub struct QuickOpen {
+    candidates: Vec<QuickOpenCandidate>,
+}
+
+impl QuickOpen {
+    /// 从工作区收集候选文件
+    ///
+    /// `all_files` 为 `false` 时只包含 Markdown 文件；多根工作区中显示路径带根文件夹名
+    pub fn from_workspace(workspace: &Workspace, all_files: bool) -> Self {
+        let multi_root = workspace.roots().len() > 1;
+        let mut candidates = Vec::new();
+
+        for tree in workspace.roots() {
+            let root = tree.root_path();
+            let prefix = if multi_root {
+                root.file_name()
+                    .map(|name| format!("{}/", name.to_string_lossy()))
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+
+            let files = if all_files {
+                tree.get_all_files()
+            } else {
+                tree.get_markdown_files()
+            };
+
+            for item in files {
+                candidates.push(QuickOpenCandidate {
+                    display: format!("{}{}", prefix, display_path(root, &item.path)),
+                    path: item.path.clone(),
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        Self { candidates }
+    }
+
+    /// 从路径列表创建
+    pub fn from_paths(root: &Path, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut candidates: Vec<_> = paths
+            .into_iter()
+            .map(|path| QuickOpenCandidate { display: display_path(root, &path), path })
+            .collect();
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        Self { candidates }
+    }
+
+    /// 候选文件数量
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// 是否没有候选文件
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// 查找文件，返回得分最高的 `limit` 个结果
+    ///
+    /// `recent` 为最近打开的文件（最近的在前），会获得额外加分；查询为空时按最近顺序排列
+    pub fn search(&self, query: &str, recent: &[PathBuf], limit: usize) -> Vec<QuickOpenMatch> {
+        let recent_bonus = |path: &Path| {
+            recent
+                .iter()
+                .position(|p| p == path)
+                .map(|rank| BONUS_RECENT * (recent.len() - rank) as i64 / recent.len() as i64 + BONUS_RECENT)
+                .unwrap_or(0)
+        };
+
+        let mut matches: Vec<QuickOpenMatch> = self
+            .candidates
+            .iter()
+            .filter_map(|candidate| {
+                let mut fuzzy = fuzzy_match(query, &candidate.display)?;
+                fuzzy.score += recent_bonus(&candidate.path);
+                Some(QuickOpenMatch {
+                    path: candidate.path.clone(),
+                    display: candidate.display.clone(),
+                    fuzzy,
+                })
+            })
+            .collect();
+
+        // 得分相同时较短的路径优先
+        matches.sort_by(|a, b| {
+            b.fuzzy.score
+                .cmp(&a.fuzzy.score)
+                .then_with(|| a.display.len().cmp(&b.display.len()))
+                .then_with(|| a.display.cmp(&b.display))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// 相对于根目录的显示路径（使用 `/` 分隔）
+fn display_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> i64 {
+        fuzzy_match(query, candidate).map(|m| m.score).unwrap_or(i64::MIN)
+    }
+
+    #[test]
+    fn test_subsequence() {
+        assert!(fuzzy_match("rdm", "README.md").is_some());
+        assert!(fuzzy_match("mdr", "README.md").is_none());
+        assert_eq!(fuzzy_match("", "anything").map(|m| m.score), Some(0));
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(fuzzy_match("readme", "README.md").is_some());
+        assert!(fuzzy_match("ReadMe", "README.md").is_none());
+        assert!(fuzzy_match("ReadMe", "docs/ReadMe.md").is_some());
+    }
+
+    #[test]
+    fn test_positions_prefer_boundaries() {
+        let matched = fuzzy_match("fb", "foo/bar.md").unwrap();
+        assert_eq!(matched.positions, vec![0, 4]);
+
+        // 优先匹配文件名开头，而不是路径中间的字符
+        let matched = fuzzy_match("nt", "notes/todo.md").unwrap();
+        assert_eq!(matched.positions, vec![0, 6]);
+    }
+
+    #[test]
+    fn test_ranking() {
+        // 分段开头、连续匹配和文件名中的匹配得分更高
+        assert!(score("todo", "notes/todo.md") > score("todo", "t/o/d/o.md"));
+        assert!(score("guide", "docs/guide.md") > score("guide", "guide/other.md"));
+        assert!(score("gm", "guide.md") > score("gm", "program.txt"));
+    }
+
+    #[test]
+    fn test_highlight_ranges() {
+        let text = "中文/note.md";
+        let matched = fuzzy_match("中no", text).unwrap();
+
+        assert_eq!(matched.highlight_ranges(text), vec![0..3, 7..9]);
+    }
+
+    #[test]
+    fn test_quick_open_recent_first() {
+        let root = Path::new("/notes");
+        let quick_open = QuickOpen::from_paths(
+            root,
+            vec![root.join("alpha.md"), root.join("beta.md"), root.join("archive/a.md")],
+        );
+        let recent = vec![root.join("beta.md")];
+
+        let results = quick_open.search("", &recent, 10);
+        assert_eq!(results[0].display, "beta.md");
+        assert_eq!(results.len(), 3);
+
+        let results = quick_open.search("a", &recent, 10);
+        assert_eq!(results[0].display, "beta.md");
+
+        let results = quick_open.search("arch", &recent, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display, "archive/a.md");
+    }
+}
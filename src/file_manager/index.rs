@@ -0,0 +1,877 @@
+//! 全文索引模块
+//!
+//! 为工作区建立保存在磁盘上的倒排索引：
+//! - 索引文件保存在工作区根目录的 `.readrs/index.json`
+//! - 中日韩文字按二元组切分并索引单字，其余文字按单词切分
+//! - BM25 相关度排序
+//! - 短语查询（`"..."`）以及 `tag:`、`path:` 过滤
+//! - 按修改时间增量更新
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::text_fold::{FoldOptions, FoldedText};
+use super::{is_markdown_path, write_files_atomic};
+
+/// 索引目录（相对于工作区根目录）
+pub const INDEX_DIR: &str = ".readrs";
+/// 索引文件名
+const INDEX_FILE: &str = "index.json";
+/// 索引格式版本，格式变化时重新建立索引
+const INDEX_VERSION: u32 = 2;
+
+/// BM25 参数
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// 索引中的文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedDoc {
+    /// 相对于根目录的路径（使用 `/` 分隔）
+    path: String,
+    /// 修改时间（毫秒）
+    modified: u64,
+    /// 文件大小
+    size: u64,
+    /// 词元数量
+    length: usize,
+    /// 标签（小写，不含 `#`）
+    tags: Vec<String>,
+    /// 文档包含的词，用于删除时清理倒排表
+    terms: Vec<String>,
+}
+
+/// 倒排表中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posting {
+    /// 文档编号
+    doc: u32,
+    /// 词在文档中的位置
+    positions: Vec<u32>,
+}
+
+/// 索引文件内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IndexData {
+    version: u32,
+    next_id: u32,
+    docs: HashMap<u32, IndexedDoc>,
+    postings: HashMap<String, Vec<Posting>>,
+}
+
+/// 索引查询结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexHit {
+    /// 文件路径
+    pub path: PathBuf,
+    /// BM25 得分（只有过滤条件时为 0）
+    pub score: f64,
+    /// 第一个包含查询词的行号（从1开始）
+    pub line_number: usize,
+    /// 该行内容
+    pub line: String,
+}
+
+/// 解析后的查询
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexQuery {
+    /// 必须包含的词（BM25 计分）
+    pub terms: Vec<String>,
+    /// 必须连续出现的词组
+    pub phrases: Vec<Vec<String>>,
+    /// 标签过滤
+    pub tags: Vec<String>,
+    /// 路径过滤（路径包含该文本）
+    pub paths: Vec<String>,
+}
+
+impl IndexQuery {
+    /// 解析查询字符串
+    ///
+    /// - `"多个 词"` 为短语查询
+    /// - `tag:名称` 只匹配带该标签的文档（也可以写作 `tag:#名称`）
+    /// - `path:docs/` 只匹配路径包含该文本的文档
+    /// - 其余为普通词；切分出多个词元的词（如中文）按短语处理
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut rest = query;
+
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+
+            let (word, next) = if let Some(quoted) = rest.strip_prefix('"') {
+                match quoted.find('"') {
+                    Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                    None => (quoted, ""),
+                }
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+            let is_phrase = rest.starts_with('"');
+            rest = next;
+
+            if !is_phrase {
+                if let Some(tag) = word.strip_prefix("tag:") {
+                    let tag = normalize_tag(tag);
+                    if !tag.is_empty() {
+                        parsed.tags.push(tag);
+                    }
+                    continue;
+                }
+                if let Some(path) = word.strip_prefix("path:") {
+                    if !path.is_empty() {
+                        parsed.paths.push(path.replace('\\', "/").to_lowercase());
+                    }
+                    continue;
+                }
+            }
+
+            let tokens: Vec<String> = tokenize(word).into_iter().map(|(token, _)| token).collect();
+            match tokens.len() {
+                0 => {}
+                1 if !is_phrase => parsed.terms.extend(tokens),
+                _ => {
+                    parsed.terms.extend(tokens.iter().cloned());
+                    parsed.phrases.push(tokens);
+                }
+            }
+        }
+
+        parsed
+    }
+
+    /// 是否为空查询
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.tags.is_empty() && self.paths.is_empty()
+    }
+}
+
+/// 工作区全文索引
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    /// 工作区根目录
+    root: PathBuf,
+    /// 索引数据
+    data: IndexData,
+    /// 相对路径到文档编号
+    paths: HashMap<String, u32>,
+    /// 是否有未保存的修改
+    dirty: bool,
+    /// 无法读取而跳过的文件及其修改时间和大小，文件变化前不再重试
+    skipped: HashMap<String, (u64, u64)>,
+}
+
+impl SearchIndex {
+    /// 打开工作区的索引
+    ///
+    /// 索引文件不存在、损坏或版本不符时返回空索引，需调用 [`SearchIndex::update`] 建立
+    pub fn open(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref().to_path_buf();
+        let data = fs::read_to_string(Self::index_path_for(&root))
+            .ok()
+            .and_then(|content| serde_json::from_str::<IndexData>(&content).ok())
+            .filter(|data| data.version == INDEX_VERSION)
+            .unwrap_or_else(|| IndexData { version: INDEX_VERSION, ..Default::default() });
+
+        let paths = data.docs.iter().map(|(id, doc)| (doc.path.clone(), *id)).collect();
+
+        Self { root, data, paths, dirty: false, skipped: HashMap::new() }
+    }
+
+    /// 打开索引、同步文件变化并保存
+    pub fn load_or_build(root: impl AsRef<Path>) -> Result<Self> {
+        let mut index = Self::open(root);
+        index.update()?;
+        index.save()?;
+        Ok(index)
+    }
+
+    /// 索引文件路径
+    fn index_path_for(root: &Path) -> PathBuf {
+        root.join(INDEX_DIR).join(INDEX_FILE)
+    }
+
+    /// 工作区根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 已索引的文档数量
+    pub fn len(&self) -> usize {
+        self.data.docs.len()
+    }
+
+    /// 索引是否为空
+    pub fn is_empty(&self) -> bool {
+        self.data.docs.is_empty()
+    }
+
+    /// 同步工作区中的所有 Markdown 文件
+    ///
+    /// 只重新索引修改时间或大小变化的文件，并删除已不存在的文件；返回变化的文件数量
+    ///
+    /// 无法读取的文件（如不是 UTF-8 编码）会被跳过，不影响其他文件
+    pub fn update(&mut self) -> Result<usize> {
+        let mut seen = BTreeSet::new();
+        let mut changed = 0;
+
+        for path in markdown_files(&self.root) {
+            let stamp = match file_stamp(&path) {
+                Ok(stamp) => stamp,
+                Err(e) => {
+                    eprintln!("警告: {:#}", e);
+                    continue;
+                }
+            };
+            let relative = relative_path(&self.root, &path);
+            seen.insert(relative.clone());
+
+            let unchanged = self.paths
+                .get(&relative)
+                .and_then(|id| self.data.docs.get(id))
+                .map(|doc| (doc.modified, doc.size) == stamp)
+                .unwrap_or(false)
+                || self.skipped.get(&relative) == Some(&stamp);
+
+            if !unchanged {
+                match self.update_file(&path) {
+                    Ok(()) => {
+                        self.skipped.remove(&relative);
+                        changed += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("警告: {:#}", e);
+                        self.skipped.insert(relative, stamp);
+                    }
+                }
+            }
+        }
+        self.skipped.retain(|relative, _| seen.contains(relative));
+
+        let removed: Vec<String> = self.paths.keys().filter(|p| !seen.contains(*p)).cloned().collect();
+        for relative in removed {
+            self.remove_relative(&relative);
+            changed += 1;
+        }
+
+        Ok(changed)
+    }
+
+    /// 重新索引单个文件（文件不存在时从索引中删除）
+    pub fn update_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let relative = self.relative(path);
+        self.remove_relative(&relative);
+
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("无法读取文件: {}", path.display()))?;
+        let (modified, size) = file_stamp(path)?;
+
+        let id = self.data.next_id;
+        self.data.next_id += 1;
+
+        let (tokens, unigrams) = tokenize_with_unigrams(&content);
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        for (token, position) in tokens.iter().chain(&unigrams) {
+            positions.entry(token.clone()).or_default().push(*position);
+        }
+        for positions in positions.values_mut() {
+            positions.sort_unstable();
+        }
+
+        let mut terms: Vec<String> = positions.keys().cloned().collect();
+        terms.sort();
+        for (term, positions) in positions {
+            self.data.postings.entry(term).or_default().push(Posting { doc: id, positions });
+        }
+
+        self.data.docs.insert(id, IndexedDoc {
+            path: relative.clone(),
+            modified,
+            size,
+            length: tokens.len(),
+            tags: extract_tags(&content),
+            terms,
+        });
+        self.paths.insert(relative, id);
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    /// 从索引中删除文件
+    pub fn remove_file(&mut self, path: impl AsRef<Path>) {
+        let relative = self.relative(path.as_ref());
+        self.remove_relative(&relative);
+    }
+
+    /// 按相对路径删除文档
+    fn remove_relative(&mut self, relative: &str) {
+        let Some(id) = self.paths.remove(relative) else {
+            return;
+        };
+        let Some(doc) = self.data.docs.remove(&id) else {
+            return;
+        };
+
+        for term in &doc.terms {
+            if let Some(postings) = self.data.postings.get_mut(term) {
+                postings.retain(|p| p.doc != id);
+                if postings.is_empty() {
+                    self.data.postings.remove(term);
+                }
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// 是否有未保存的修改
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// 取出待保存的副本并清除修改标记，没有修改时返回 `None`
+    ///
+    /// 用于在后台保存，保存失败时需调用 [`SearchIndex::mark_dirty`]
+    pub fn take_unsaved(&mut self) -> Option<Self> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        Some(Self { dirty: true, ..self.clone() })
+    }
+
+    /// 标记为有未保存的修改
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// 各文件的修改时间和大小
+    pub fn stamps(&self) -> IndexStamps {
+        let stamps = self.data.docs
+            .values()
+            .map(|doc| (doc.path.clone(), (doc.modified, doc.size)))
+            .chain(self.skipped.iter().map(|(path, stamp)| (path.clone(), *stamp)))
+            .collect();
+        IndexStamps { root: self.root.clone(), stamps }
+    }
+
+    /// 保存索引（没有修改时不写入）
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty && Self::index_path_for(&self.root).exists() {
+            return Ok(());
+        }
+
+        let dir = self.root.join(INDEX_DIR);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("无法创建目录: {}", dir.display()))?;
+
+        let content = serde_json::to_string(&self.data)
+            .context("无法序列化索引")?;
+        write_files_atomic(&[(Self::index_path_for(&self.root), content)])?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// 查询索引，按相关度从高到低返回最多 `limit` 个结果
+    pub fn search(&self, query: &str, limit: usize) -> Vec<IndexHit> {
+        let query = IndexQuery::parse(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(u32, f64)> = self
+            .candidates(&query)
+            .into_iter()
+            .filter(|id| self.matches_filters(*id, &query))
+            .filter(|id| query.phrases.iter().all(|phrase| self.contains_phrase(*id, phrase)))
+            .map(|id| (id, self.bm25(id, &query.terms)))
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| self.data.docs[&a.0].path.cmp(&self.data.docs[&b.0].path))
+        });
+        scored.truncate(limit);
+
+        scored
+            .into_iter()
+            .map(|(id, score)| {
+                let path = self.root.join(&self.data.docs[&id].path);
+                let (line_number, line) = first_matching_line(&path, &query.terms);
+                IndexHit { path, score, line_number, line }
+            })
+            .collect()
+    }
+
+    /// 包含所有查询词的文档（没有查询词时为全部文档）
+    fn candidates(&self, query: &IndexQuery) -> Vec<u32> {
+        let mut candidates: Option<BTreeSet<u32>> = None;
+
+        for term in &query.terms {
+            let docs: BTreeSet<u32> = self.data.postings
+                .get(term)
+                .map(|postings| postings.iter().map(|p| p.doc).collect())
+                .unwrap_or_default();
+
+            candidates = Some(match candidates {
+                Some(existing) => existing.intersection(&docs).copied().collect(),
+                None => docs,
+            });
+        }
+
+        match candidates {
+            Some(candidates) => candidates.into_iter().collect(),
+            None => self.data.docs.keys().copied().collect(),
+        }
+    }
+
+    /// 检查标签和路径过滤条件
+    fn matches_filters(&self, id: u32, query: &IndexQuery) -> bool {
+        let Some(doc) = self.data.docs.get(&id) else {
+            return false;
+        };
+        let path = doc.path.to_lowercase();
+
+        query.tags.iter().all(|tag| doc.tags.contains(tag))
+            && query.paths.iter().all(|filter| path.contains(filter.as_str()))
+    }
+
+    /// 词在文档中的位置
+    fn positions(&self, id: u32, term: &str) -> Option<&[u32]> {
+        self.data.postings
+            .get(term)?
+            .iter()
+            .find(|p| p.doc == id)
+            .map(|p| p.positions.as_slice())
+    }
+
+    /// 检查文档中是否包含连续出现的短语
+    fn contains_phrase(&self, id: u32, phrase: &[String]) -> bool {
+        let Some(first) = self.positions(id, &phrase[0]) else {
+            return false;
+        };
+        let rest: Vec<&[u32]> = match phrase[1..].iter().map(|t| self.positions(id, t)).collect() {
+            Some(rest) => rest,
+            None => return false,
+        };
+
+        first.iter().any(|&start| {
+            rest.iter()
+                .enumerate()
+                .all(|(i, positions)| positions.binary_search(&(start + i as u32 + 1)).is_ok())
+        })
+    }
+
+    /// 计算 BM25 得分
+    fn bm25(&self, id: u32, terms: &[String]) -> f64 {
+        let doc_count = self.data.docs.len() as f64;
+        let average_length = self.data.docs.values().map(|d| d.length).sum::<usize>() as f64 / doc_count.max(1.0);
+        let length = self.data.docs[&id].length as f64;
+
+        let unique: BTreeSet<&String> = terms.iter().collect();
+        unique
+            .into_iter()
+            .map(|term| {
+                let postings = self.data.postings.get(term).map(Vec::as_slice).unwrap_or(&[]);
+                let frequency = postings
+                    .iter()
+                    .find(|p| p.doc == id)
+                    .map(|p| p.positions.len() as f64)
+                    .unwrap_or(0.0);
+                let df = postings.len() as f64;
+                let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                idf * frequency * (BM25_K1 + 1.0)
+                    / (frequency + BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length.max(1.0)))
+            })
+            .sum()
+    }
+
+    /// 相对于根目录的路径（使用 `/` 分隔）
+    fn relative(&self, path: &Path) -> String {
+        relative_path(&self.root, path)
+    }
+}
+
+/// 索引中各文件的修改时间和大小，用于在后台检查文件是否变化
+#[derive(Debug, Clone)]
+pub struct IndexStamps {
+    /// 工作区根目录
+    root: PathBuf,
+    /// 相对路径到（修改时间，大小）
+    stamps: HashMap<String, (u64, u64)>,
+}
+
+impl IndexStamps {
+    /// 工作区根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 工作区中的 Markdown 文件是否有新增、修改或删除
+    pub fn has_changes(&self) -> bool {
+        let mut count = 0;
+        for path in markdown_files(&self.root) {
+            let Ok(stamp) = file_stamp(&path) else {
+                continue;
+            };
+            if self.stamps.get(&relative_path(&self.root, &path)) != Some(&stamp) {
+                return true;
+            }
+            count += 1;
+        }
+        count != self.stamps.len()
+    }
+}
+
+/// 工作区中的所有 Markdown 文件（跳过隐藏文件和目录）
+fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_markdown_path(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// 相对于根目录的路径（使用 `/` 分隔）
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 词元及其位置
+type Tokens = Vec<(String, u32)>;
+
+/// 切分文本为词元，返回 (词元, 位置)
+///
+/// 文本先做大小写和变音符号折叠；中日韩文字按二元组切分（单字时保留单字），
+/// 其余字母数字按单词切分
+pub fn tokenize(text: &str) -> Vec<(String, u32)> {
+    tokenize_with_unigrams(text).0
+}
+
+/// 切分文本为词元，同时返回多字的中日韩文字中每个字的单字词元
+///
+/// 单字的位置与以该字开头的二元组相同（最后一个字与最后一个二元组相同），
+/// 建立索引时一并加入，单字查询才能找到包含该字的词
+fn tokenize_with_unigrams(text: &str) -> (Tokens, Tokens) {
+    let folded = FoldedText::new(text, FoldOptions { case: true, diacritics: true }).text;
+    let mut tokens = Vec::new();
+    let mut unigrams = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    let flush_word = |word: &mut String, tokens: &mut Vec<(String, u32)>| {
+        if !word.is_empty() {
+            let position = tokens.len() as u32;
+            tokens.push((std::mem::take(word), position));
+        }
+    };
+    let flush_cjk = |run: &mut Vec<char>, tokens: &mut Tokens, unigrams: &mut Tokens| {
+        if run.len() == 1 {
+            let position = tokens.len() as u32;
+            tokens.push((run[0].to_string(), position));
+        } else if run.len() > 1 {
+            let start = tokens.len();
+            for pair in run.windows(2) {
+                let position = tokens.len() as u32;
+                tokens.push((pair.iter().collect(), position));
+            }
+            for (i, ch) in run.iter().enumerate() {
+                unigrams.push((ch.to_string(), (start + i.min(run.len() - 2)) as u32));
+            }
+        }
+        run.clear();
+    };
+
+    for ch in folded.chars() {
+        if is_cjk(ch) {
+            flush_word(&mut word, &mut tokens);
+            cjk_run.push(ch);
+        } else if ch.is_alphanumeric() || ch == '_' {
+            flush_cjk(&mut cjk_run, &mut tokens, &mut unigrams);
+            word.push(ch);
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk_run, &mut tokens, &mut unigrams);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk_run, &mut tokens, &mut unigrams);
+
+    (tokens, unigrams)
+}
+
+/// 判断是否为中日韩文字（汉字、假名、谚文）
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x3400..=0x4DBF   // 汉字扩展 A
+        | 0x4E00..=0x9FFF   // 汉字
+        | 0xAC00..=0xD7AF   // 谚文音节
+        | 0xF900..=0xFAFF   // 兼容汉字
+        | 0x20000..=0x2FA1F // 汉字扩展 B 及以后
+    )
+}
+
+/// 提取文档标签：front matter 中的 `tags` 以及正文中的 `#标签`（跳过代码块）
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags = BTreeSet::new();
+    let mut lines = content.lines().peekable();
+
+    // YAML front matter
+    if lines.peek().map(|l| l.trim_end() == "---").unwrap_or(false) {
+        lines.next();
+        let mut in_tags = false;
+        for line in lines.by_ref() {
+            let trimmed = line.trim();
+            if trimmed == "---" || trimmed == "..." {
+                break;
+            }
+            if let Some(value) = trimmed.strip_prefix("tags:") {
+                in_tags = true;
+                let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+                tags.extend(value.split(',').map(normalize_tag).filter(|t| !t.is_empty()));
+            } else if let Some(tag) = trimmed.strip_prefix("- ").filter(|_| in_tags) {
+                tags.insert(normalize_tag(tag));
+            } else {
+                in_tags = false;
+            }
+        }
+    }
+
+    // 正文中的 #标签
+    let mut in_code = false;
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let mut previous = ' ';
+        for (i, ch) in line.char_indices() {
+            if ch == '#' && previous.is_whitespace() {
+                let tag: String = line[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                // 纯数字（如 #1）不算标签
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                    tags.insert(normalize_tag(&tag));
+                }
+            }
+            previous = ch;
+        }
+    }
+
+    tags.into_iter().filter(|t| !t.is_empty()).collect()
+}
+
+/// 规范化标签：去掉引号、`#` 和空白，转换为小写
+fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .trim_start_matches('#')
+        .to_lowercase()
+}
+
+/// 文件的修改时间（毫秒）和大小
+fn file_stamp(path: &Path) -> Result<(u64, u64)> {
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("无法读取文件信息: {}", path.display()))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Ok((modified, metadata.len()))
+}
+
+/// 查找第一个包含查询词的行
+fn first_matching_line(path: &Path, terms: &[String]) -> (usize, String) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return (1, String::new());
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .find(|(_, line)| {
+            let (tokens, unigrams) = tokenize_with_unigrams(line);
+            terms.iter().any(|term| tokens.iter().chain(&unigrams).any(|(token, _)| token == term))
+        })
+        .or_else(|| content.lines().enumerate().next())
+        .map(|(i, line)| (i + 1, line.to_string()))
+        .unwrap_or((1, String::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tokens(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|(token, _)| token).collect()
+    }
+
+    fn setup_workspace() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("docs"))?;
+        fs::write(
+            root.join("rust.md"),
+            "---\ntags: [systems, lang]\n---\n# Rust\n\nRust is fast. Rust is safe. Memory safety.\n",
+        )?;
+        fs::write(root.join("docs").join("中文.md"), "# 笔记\n\n全文搜索引擎支持中文分词。 #search\n")?;
+        fs::write(root.join("docs").join("misc.md"), "Safety first, rust later.\n```\n#include\n```\n")?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokens("Héllo, World_2!"), vec!["hello", "world_2"]);
+        assert_eq!(tokens("全文搜索"), vec!["全文", "文搜", "搜索"]);
+        assert_eq!(tokens("用Rust写"), vec!["用", "rust", "写"]);
+
+        let (_, unigrams) = tokenize_with_unigrams("全文搜索");
+        let unigrams: Vec<(&str, u32)> = unigrams.iter().map(|(token, position)| (token.as_str(), *position)).collect();
+        assert_eq!(unigrams, vec![("全", 0), ("文", 1), ("搜", 2), ("索", 2)]);
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        let content = "---\ntags:\n  - Draft\n  - \"todo\"\n---\nText #idea and #1 but not#this\n```\n#code\n```\n";
+        assert_eq!(extract_tags(content), vec!["draft", "idea", "todo"]);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = IndexQuery::parse(r#"rust "memory safety" tag:#Lang path:docs/ 中文"#);
+
+        assert_eq!(query.terms, vec!["rust", "memory", "safety", "中文"]);
+        assert_eq!(query.phrases, vec![vec!["memory".to_string(), "safety".to_string()]]);
+        assert_eq!(query.tags, vec!["lang"]);
+        assert_eq!(query.paths, vec!["docs/"]);
+    }
+
+    #[test]
+    fn test_search_ranking_and_filters() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let index = SearchIndex::load_or_build(temp_dir.path())?;
+        assert_eq!(index.len(), 3);
+
+        // rust.md 中 rust 出现次数更多，排在前面
+        let hits = index.search("rust", 10);
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].path.ends_with("rust.md"));
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(hits[0].line_number, 4);
+
+        assert_eq!(index.search("\"memory safety\"", 10).len(), 1);
+        assert!(index.search("\"safety memory\"", 10).is_empty());
+        assert_eq!(index.search("rust path:docs", 10).len(), 1);
+        assert_eq!(index.search("tag:lang", 10).len(), 1);
+        assert!(index.search("tag:include", 10).is_empty());
+
+        let hits = index.search("中文分词 tag:search", 10);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].line.contains("全文搜索"));
+        assert!(index.search("分中", 10).is_empty());
+
+        // 单字查询能找到包含该字的词
+        for query in ["全", "擎", "词"] {
+            let hits = index.search(query, 10);
+            assert_eq!(hits.len(), 1, "{}", query);
+            assert_eq!(hits[0].line_number, 3);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_update_and_persistence() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let mut index = SearchIndex::load_or_build(root)?;
+
+        assert!(root.join(INDEX_DIR).join(INDEX_FILE).exists());
+        assert_eq!(index.update()?, 0);
+        assert!(!index.stamps().has_changes());
+
+        fs::remove_file(root.join("docs").join("misc.md"))?;
+        fs::write(root.join("new.md"), "brand new rust note")?;
+        assert!(index.stamps().has_changes());
+        assert_eq!(index.update()?, 2);
+        assert!(!index.stamps().has_changes());
+
+        let mut unsaved = index.take_unsaved().expect("索引应有未保存的修改");
+        assert!(!index.is_dirty());
+        assert!(index.take_unsaved().is_none());
+        unsaved.save()?;
+
+        let reopened = SearchIndex::open(root);
+        assert_eq!(reopened.len(), 3);
+        let hits = reopened.search("rust", 10);
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().any(|h| h.path.ends_with("new.md")));
+        assert!(reopened.search("later", 10).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_index_is_rebuilt() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join(INDEX_DIR))?;
+        fs::write(root.join(INDEX_DIR).join(INDEX_FILE), "not json")?;
+
+        let index = SearchIndex::load_or_build(root)?;
+        assert_eq!(index.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreadable_file_is_skipped() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        // Latin-1 编码的文件不是合法的 UTF-8
+        fs::write(root.join("latin1.md"), b"caf\xe9 rust")?;
+
+        let mut index = SearchIndex::load_or_build(root)?;
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.search("rust", 10).len(), 2);
+        assert_eq!(index.update()?, 0);
+        // 跳过的文件没有变化时不算作工作区变化
+        assert!(!index.stamps().has_changes());
+
+        Ok(())
+    }
+}
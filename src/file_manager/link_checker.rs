@@ -0,0 +1,221 @@
+//! 链接检查模块
+//!
+//! 检查工作区中 Markdown 文件的内部链接：
+//! - 相对链接指向的文件不存在
+//! - 链接指向的标题（`#锚点`）不存在
+//! - 图片文件不存在
+//! - wiki 链接（`[[笔记]]`）找不到笔记或标题
+//! - 同一文档中重复的标题锚点
+//!
+//! 网址等外部链接不检查
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::is_markdown_path;
+use super::notes::resolve_wiki_link;
+use super::paths::{normalize_path, percent_decode, resolve_link_path};
+use crate::markdown::{extract_links, find_wiki_links, heading_anchors, heading_slug, LinkKind};
+
+/// 链接问题的类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkProblemKind {
+    /// 链接指向的文件不存在
+    MissingFile,
+    /// 链接指向的标题不存在
+    MissingAnchor,
+    /// 图片文件不存在
+    MissingImage,
+    /// wiki 链接找不到笔记
+    MissingNote,
+    /// 标题锚点与前面的标题重复，内容为前面标题的行号
+    DuplicateAnchor(usize),
+}
+
+/// 一处链接问题
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkProblem {
+    /// 所在文件
+    pub file: PathBuf,
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 问题类型
+    pub kind: LinkProblemKind,
+    /// 有问题的链接目标或锚点
+    pub target: String,
+}
+
+impl LinkProblem {
+    /// 问题说明
+    pub fn message(&self) -> String {
+        match self.kind {
+            LinkProblemKind::MissingFile => format!("链接的文件不存在: {}", self.target),
+            LinkProblemKind::MissingAnchor => format!("找不到标题: {}", self.target),
+            LinkProblemKind::MissingImage => format!("图片不存在: {}", self.target),
+            LinkProblemKind::MissingNote => format!("找不到笔记: [[{}]]", self.target),
+            LinkProblemKind::DuplicateAnchor(first_line) => {
+                format!("标题锚点 #{} 与第 {} 行的标题重复", self.target, first_line)
+            }
+        }
+    }
+}
+
+/// 检查 `files` 中所有 Markdown 文件的链接，按文件和行号排列
+///
+/// wiki 链接在 `files` 中查找笔记
+pub fn check_links(files: &[PathBuf]) -> Vec<LinkProblem> {
+    let mut checker = LinkChecker { notes: files, anchors: HashMap::new() };
+    let mut problems = Vec::new();
+
+    for file in files {
+        let file = normalize_path(file);
+        match fs::read_to_string(&file) {
+            Ok(content) => problems.extend(checker.check_file(&file, &content)),
+            Err(e) => eprintln!("警告: 无法读取文件 {}: {}", file.display(), e),
+        }
+    }
+
+    problems
+}
+
+/// 检查链接时的状态
+struct LinkChecker<'a> {
+    /// wiki 链接可以指向的笔记
+    notes: &'a [PathBuf],
+    /// 已读取的文档中的标题锚点（小写）
+    anchors: HashMap<PathBuf, Vec<String>>,
+}
+
+impl LinkChecker<'_> {
+    /// 检查一个文件中的链接
+    fn check_file(&mut self, file: &Path, content: &str) -> Vec<LinkProblem> {
+        // 问题在文件中的字节位置，用于按出现的顺序排列
+        let mut problems: Vec<(usize, LinkProblem)> = Vec::new();
+        let mut problem = |offset: usize, kind: LinkProblemKind, target: &str| {
+            let line_number = content[..offset].matches('\n').count() + 1;
+            let problem = LinkProblem { file: file.to_path_buf(), line_number, kind, target: target.to_string() };
+            problems.push((offset, problem));
+        };
+
+        let headings = heading_anchors(content);
+        let line_starts: Vec<usize> =
+            std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        for heading in &headings {
+            if let Some(first_line) = heading.duplicate_of {
+                let first = headings.iter().find(|other| other.line_number == first_line).unwrap_or(heading);
+                let offset = line_starts[heading.line_number - 1];
+                problem(offset, LinkProblemKind::DuplicateAnchor(first_line), &first.anchor);
+            }
+        }
+        let anchors = headings.iter().map(|heading| heading.anchor.to_lowercase()).collect();
+        self.anchors.insert(file.to_path_buf(), anchors);
+
+        for link in extract_links(content) {
+            if link.is_external() {
+                continue;
+            }
+            let offset = link.dest_range.start;
+            let target = match link.path_part() {
+                "" => file.to_path_buf(),
+                path => match resolve_link_path(file, path) {
+                    Some(target) => target,
+                    None => continue,
+                },
+            };
+
+            if link.kind == LinkKind::Image {
+                if !target.exists() {
+                    problem(offset, LinkProblemKind::MissingImage, &link.dest);
+                }
+                continue;
+            }
+            if !target.exists() {
+                problem(offset, LinkProblemKind::MissingFile, &link.dest);
+                continue;
+            }
+            let anchor = link.fragment().map(percent_decode).filter(|anchor| !anchor.is_empty());
+            if let Some(anchor) = anchor {
+                if is_markdown_path(&target) && !self.has_anchor(&target, &anchor) {
+                    problem(offset, LinkProblemKind::MissingAnchor, &link.dest);
+                }
+            }
+        }
+
+        let from_dir = file.parent().unwrap_or_else(|| Path::new(""));
+        for link in find_wiki_links(content) {
+            let offset = link.range.start;
+            let target = if link.note.is_empty() {
+                file.to_path_buf()
+            } else {
+                match resolve_wiki_link(self.notes, from_dir, &link.note) {
+                    Some(target) => normalize_path(&target),
+                    None => {
+                        problem(offset, LinkProblemKind::MissingNote, &link.note);
+                        continue;
+                    }
+                }
+            };
+            if let Some(heading) = &link.heading {
+                if !self.has_anchor(&target, &heading_slug(heading)) {
+                    problem(offset, LinkProblemKind::MissingAnchor, &content[link.range.clone()]);
+                }
+            }
+        }
+
+        problems.sort_by_key(|(offset, _)| *offset);
+        problems.into_iter().map(|(_, problem)| problem).collect()
+    }
+
+    /// 判断文档中是否有指定的标题锚点（不区分大小写）
+    fn has_anchor(&mut self, file: &Path, anchor: &str) -> bool {
+        let anchors = self.anchors.entry(file.to_path_buf()).or_insert_with(|| {
+            let content = fs::read_to_string(file).unwrap_or_default();
+            heading_anchors(&content).into_iter().map(|heading| heading.anchor.to_lowercase()).collect()
+        });
+        anchors.contains(&anchor.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use anyhow::Result;
+
+    #[test]
+    fn test_check_links() -> Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs/img"))?;
+        fs::write(root.join("docs/img/logo.png"), b"png")?;
+        fs::write(root.join("docs/guide.md"), "# 指南\n\n## 安装\n")?;
+        fs::write(
+            root.join("index.md"),
+            "# 首页\n\n\
+             [指南](docs/guide.md#%E5%AE%89%E8%A3%85) [缺失](docs/missing.md) [标题](docs/guide.md#卸载)\n\
+             ![logo](docs/img/logo.png) ![图](docs/img/none.png) [站外](https://example.com/a.md)\n\
+             [[guide#安装]] [[guide#配置|配置]] [[草稿]] [[#首页]] [本页](#没有)\n\n\
+             ## 首页\n",
+        )?;
+        let files = vec![root.join("docs/guide.md"), root.join("index.md")];
+
+        let problems = check_links(&files);
+        let summary: Vec<(usize, String)> =
+            problems.iter().map(|problem| (problem.line_number, problem.message())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (3, "链接的文件不存在: docs/missing.md".to_string()),
+                (3, "找不到标题: docs/guide.md#卸载".to_string()),
+                (4, "图片不存在: docs/img/none.png".to_string()),
+                (5, "找不到标题: [[guide#配置|配置]]".to_string()),
+                (5, "找不到笔记: [[草稿]]".to_string()),
+                (5, "找不到标题: #没有".to_string()),
+                (7, "标题锚点 #首页 与第 1 行的标题重复".to_string()),
+            ]
+        );
+        assert!(problems.iter().all(|problem| problem.file == root.join("index.md")));
+        Ok(())
+    }
+}
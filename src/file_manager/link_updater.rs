@@ -0,0 +1,342 @@
+//! 引用更新模块
+//!
+//! 文件或文件夹被移动、重命名后，更新工作区内指向它的相对链接：
+//! - 扫描文件树中的所有 Markdown 文件
+//! - 生成可预览的修改列表
+//! - 移动文件并原子地改写受影响的文件
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+
+use super::paths::{
+    has_encoded_non_ascii, normalize_path, percent_encode_link, relative_path, resolve_link_path, to_link_path,
+};
+use super::{write_files_atomic, FileTree};
+use crate::markdown::extract_links;
+
+/// 单处链接修改
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceEdit {
+    /// 所在文件（移动前的路径）
+    pub file: PathBuf,
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 目标地址在文件中的字节范围
+    pub range: Range<usize>,
+    /// 原目标地址
+    pub old_dest: String,
+    /// 新目标地址
+    pub new_dest: String,
+}
+
+/// 移动/重命名计划
+///
+/// 先通过 [`RenamePlan::new`] 生成修改预览，确认后再调用 [`RenamePlan::apply`]
+#[derive(Debug)]
+pub struct RenamePlan {
+    /// 原路径
+    from: PathBuf,
+    /// 新路径
+    to: PathBuf,
+    /// 所有链接修改
+    edits: Vec<ReferenceEdit>,
+    /// 生成计划时受影响文件的内容，用于应用前检查文件是否被改动
+    originals: BTreeMap<PathBuf, String>,
+}
+
+impl RenamePlan {
+    /// 为把 `from` 移动到 `to` 生成修改计划
+    pub fn new(file_tree: &FileTree, from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<Self> {
+        let from = normalize_path(from.as_ref());
+        let to = normalize_path(to.as_ref());
+
+        if !from.exists() {
+            return Err(anyhow::anyhow!("路径不存在: {}", from.display()));
+        }
+        if to.exists() {
+            return Err(anyhow::anyhow!("目标已存在: {}", to.display()));
+        }
+        if to.starts_with(&from) {
+            return Err(anyhow::anyhow!("不能移动到自身内部: {}", to.display()));
+        }
+
+        let mut edits = Vec::new();
+        let mut originals = BTreeMap::new();
+
+        for item in file_tree.get_markdown_files() {
+            let file = normalize_path(&item.path);
+            let content = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("警告: 无法读取文件 {}: {}", file.display(), e);
+                    continue;
+                }
+            };
+
+            let file_edits = Self::plan_file(&file, &content, &from, &to);
+            if !file_edits.is_empty() {
+                edits.extend(file_edits);
+                originals.insert(file, content);
+            }
+        }
+
+        Ok(Self { from, to, edits, originals })
+    }
+
+    /// 计算单个文件中需要修改的链接
+    fn plan_file(file: &Path, content: &str, from: &Path, to: &Path) -> Vec<ReferenceEdit> {
+        let new_file = relocate(file, from, to);
+        let new_dir = new_file.parent().unwrap_or_else(|| Path::new(""));
+        let mut edits = Vec::new();
+
+        for link in extract_links(content) {
+            if link.is_external() || link.path_part().is_empty() {
+                continue;
+            }
+
+            let Some(target) = resolve_link_path(file, link.path_part()) else {
+                continue;
+            };
+            let new_target = relocate(&target, from, to);
+
+            // 文件本身和链接目标都没有移动时无需修改
+            if new_file == file && new_target == target {
+                continue;
+            }
+
+            let new_dest = format_dest(&relative_path(new_dir, &new_target), &link.dest, link.angle_brackets)
+                + link.suffix();
+
+            if new_dest != link.dest {
+                edits.push(ReferenceEdit {
+                    file: file.to_path_buf(),
+                    line_number: link.line_number,
+                    range: link.dest_range.clone(),
+                    old_dest: link.dest.clone(),
+                    new_dest,
+                });
+            }
+        }
+
+        edits
+    }
+
+    /// 获取原路径
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// 获取新路径
+    pub fn to(&self) -> &Path {
+        &self.to
+    }
+
+    /// 获取所有链接修改
+    pub fn edits(&self) -> &[ReferenceEdit] {
+        &self.edits
+    }
+
+    /// 受影响的文件数量
+    pub fn affected_files(&self) -> usize {
+        self.originals.len()
+    }
+
+    /// 计算路径在移动后的位置
+    pub fn relocated(&self, path: &Path) -> PathBuf {
+        relocate(&normalize_path(path), &self.from, &self.to)
+    }
+
+    /// 执行移动并改写所有受影响的文件
+    ///
+    /// 所有新内容通过 [`write_files_atomic`] 写入，写入失败时撤销移动
+    pub fn apply(self) -> Result<()> {
+        // 检查文件在预览之后是否被修改
+        let mut updated = Vec::new();
+        for (file, original) in &self.originals {
+            let current = fs::read_to_string(file)
+                .with_context(|| format!("无法读取文件: {}", file.display()))?;
+            if &current != original {
+                return Err(anyhow::anyhow!("文件已被修改，请重新预览: {}", file.display()));
+            }
+
+            let mut content = original.clone();
+            let mut file_edits: Vec<_> = self.edits.iter().filter(|e| &e.file == file).collect();
+            file_edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+            for edit in file_edits {
+                content.replace_range(edit.range.clone(), &edit.new_dest);
+            }
+
+            updated.push((self.relocated(file), content));
+        }
+
+        // 移动文件或文件夹
+        if let Some(parent) = self.to.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        fs::rename(&self.from, &self.to)
+            .with_context(|| format!("无法移动 {} 到 {}", self.from.display(), self.to.display()))?;
+
+        // 写入失败时撤销移动
+        if let Err(e) = write_files_atomic(&updated) {
+            let _ = fs::rename(&self.to, &self.from);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+}
+
+/// 计算路径在 `from` 移动到 `to` 之后的位置，不在 `from` 下的路径保持不变
+pub fn relocate(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// 按原链接的书写风格格式化新路径
+///
+/// 保留原链接的 `./` 前缀和尖括号；原链接编码了非 ASCII 字符时新路径同样编码
+fn format_dest(path: &Path, old_dest: &str, angle_brackets: bool) -> String {
+    let mut dest = to_link_path(path);
+
+    if (old_dest.starts_with("./") && !dest.starts_with("..")) || dest.is_empty() {
+        dest = format!("./{}", dest);
+    }
+
+    percent_encode_link(&dest, angle_brackets, has_encoded_non_ascii(old_dest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 创建测试工作区：
+    /// - index.md 链接 docs/guide.md 与 img/logo.png
+    /// - docs/guide.md 链接 ../index.md 与 ../img/logo.png
+    fn setup_workspace() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+
+        fs::create_dir(root.join("docs"))?;
+        fs::create_dir(root.join("img"))?;
+        fs::write(root.join("img").join("logo.png"), b"png")?;
+        fs::write(
+            root.join("index.md"),
+            "# Home\n\n[Guide](docs/guide.md#intro)\n\n![logo](img/logo.png)\n",
+        )?;
+        fs::write(
+            root.join("docs").join("guide.md"),
+            "[Home](../index.md)\n\n![logo](../img/logo.png)\n[Web](https://example.com)\n",
+        )?;
+
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_plan_rename_image() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let file_tree = FileTree::new(root)?;
+
+        let plan = RenamePlan::new(
+            &file_tree,
+            root.join("img").join("logo.png"),
+            root.join("assets").join("logo.png"),
+        )?;
+
+        assert_eq!(plan.edits().len(), 2);
+        assert_eq!(plan.affected_files(), 2);
+        assert!(plan.edits().iter().any(|e| e.new_dest == "assets/logo.png"));
+        assert!(plan.edits().iter().any(|e| e.new_dest == "../assets/logo.png"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_dest() {
+        let path = Path::new("../img/logo (1)<新>.png");
+        assert_eq!(format_dest(path, "img/logo.png", false), "../img/logo%20%281%29%3C新%3E.png");
+        assert_eq!(format_dest(path, "img/logo.png", true), "../img/logo (1)%3C新%3E.png");
+        assert_eq!(format_dest(path, "img/%E6%97%A7.png", false), "../img/logo%20%281%29%3C%E6%96%B0%3E.png");
+        assert_eq!(format_dest(Path::new("a.md"), "./b.md", false), "./a.md");
+    }
+
+    #[test]
+    fn test_apply_move_markdown_file() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let file_tree = FileTree::new(root)?;
+
+        let plan = RenamePlan::new(
+            &file_tree,
+            root.join("docs").join("guide.md"),
+            root.join("manual").join("user guide.md"),
+        )?;
+        plan.apply()?;
+
+        let index = fs::read_to_string(root.join("index.md"))?;
+        assert!(index.contains("[Guide](manual/user%20guide.md#intro)"));
+        assert!(index.contains("![logo](img/logo.png)"));
+
+        // 被移动的文件自身的相对链接也要更新
+        let guide = fs::read_to_string(root.join("manual").join("user guide.md"))?;
+        assert!(guide.contains("[Home](../index.md)"));
+        assert!(guide.contains("![logo](../img/logo.png)"));
+        assert!(guide.contains("https://example.com"));
+        assert!(!root.join("docs").join("guide.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_directory() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let file_tree = FileTree::new(root)?;
+
+        let plan = RenamePlan::new(&file_tree, root.join("docs"), root.join("book").join("docs"))?;
+        plan.apply()?;
+
+        let index = fs::read_to_string(root.join("index.md"))?;
+        assert!(index.contains("[Guide](book/docs/guide.md#intro)"));
+
+        let guide = fs::read_to_string(root.join("book").join("docs").join("guide.md"))?;
+        assert!(guide.contains("[Home](../../index.md)"));
+        assert!(guide.contains("![logo](../../img/logo.png)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_rejects_modified_file() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let file_tree = FileTree::new(root)?;
+
+        let plan = RenamePlan::new(&file_tree, root.join("img"), root.join("images"))?;
+        fs::write(root.join("index.md"), "changed")?;
+
+        assert!(plan.apply().is_err());
+        assert!(root.join("img").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_existing_target() -> Result<()> {
+        let temp_dir = setup_workspace()?;
+        let root = temp_dir.path();
+        let file_tree = FileTree::new(root)?;
+
+        assert!(RenamePlan::new(&file_tree, root.join("index.md"), root.join("docs").join("guide.md")).is_err());
+
+        Ok(())
+    }
+}
@@ -4,16 +4,44 @@
 //! - 文件新建、打开、保存
 //! - 文件夹树视图
 //! - 文档内搜索
+//! - 查找替换（当前文档与工作区）
+//! - 移动/重命名后更新引用
+//! - 工作区（多根文件夹、最近打开）
+//! - 工作区并行搜索
+//! - 持久化全文索引
+//! - 快速打开（文件路径模糊匹配）
+//! - Wiki 链接解析与反向链接
+//! - 检查失效的内部链接和图片
 
 mod file_operations;
 mod file_tree;
+mod fuzzy;
+mod index;
+mod link_checker;
+mod link_updater;
+mod notes;
+mod paths;
+mod replace;
 mod search;
+mod text_fold;
+mod workspace;
+mod workspace_search;
 
 pub use file_operations::*;
 pub use file_tree::*;
+pub use fuzzy::*;
+pub use index::*;
+pub use link_checker::*;
+pub use link_updater::*;
+pub use notes::*;
+pub use paths::*;
+pub use replace::*;
 pub use search::*;
+pub use text_fold::*;
+pub use workspace::*;
+pub use workspace_search::*;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 文件类型枚举
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -46,10 +74,13 @@ impl FileItem {
 
     /// 判断是否为 Markdown 文件
     pub fn is_markdown(&self) -> bool {
-        self.file_type == FileType::File
-            && self.path.extension()
-                .map(|ext| ext.to_string_lossy().to_lowercase())
-                .map(|ext| ext == "md" || ext == "markdown")
-                .unwrap_or(false)
+        self.file_type == FileType::File && is_markdown_path(&self.path)
     }
 }
+
+/// 判断路径的扩展名是否为 `.md` 或 `.markdown`（不区分大小写）
+pub fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| ext == "md" || ext == "markdown")
+}
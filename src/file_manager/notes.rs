@@ -0,0 +1,238 @@
+//! 笔记链接模块
+//!
+//! 把工作区当作知识库使用：
+//! - 按笔记名解析 `[[笔记]]` 链接，同名笔记优先使用同一文件夹中的
+//! - 为尚未存在的笔记确定位置并创建
+//! - 查找链接到指定文档的其他文档（反向链接）
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{bail, Context, Result};
+
+use super::paths::{normalize_path, resolve_link_path};
+use crate::markdown::{extract_links, find_wiki_links, LinkKind};
+
+/// 反向链接的上下文最多显示的字符数
+const CONTEXT_CHARS: usize = 80;
+
+/// 一处指向当前文档的链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlink {
+    /// 链接所在的文件
+    pub file: PathBuf,
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 链接所在行的内容（过长时截取链接附近的部分）
+    pub context: String,
+}
+
+/// 按笔记名在 `notes` 中查找笔记，`from_dir` 为链接所在文档的文件夹
+///
+/// 笔记名不区分大小写，可以包含文件夹（`文件夹/笔记`）和扩展名
+pub fn resolve_wiki_link(notes: &[PathBuf], from_dir: &Path, note: &str) -> Option<PathBuf> {
+    let name = strip_markdown_extension(note.trim());
+    let wanted: Vec<String> = name
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_lowercase())
+        .collect();
+    if wanted.is_empty() {
+        return None;
+    }
+
+    notes
+        .iter()
+        .filter(|path| {
+            let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase());
+            let parents = path.parent().into_iter().flat_map(|parent| parent.components().rev());
+            let names: Vec<String> = stem
+                .into_iter()
+                .chain(parents.map(|part| part.as_os_str().to_string_lossy().to_lowercase()))
+                .take(wanted.len())
+                .collect();
+            names.iter().eq(wanted.iter().rev())
+        })
+        // 同一文件夹中的笔记优先，其次是层级较浅的
+        .min_by_key(|path| (path.parent() != Some(from_dir), path.components().count(), path.as_path()))
+        .cloned()
+}
+
+/// 新笔记的路径：放在链接所在文档的文件夹中，笔记名不能跳出该文件夹
+pub fn new_note_path(from_dir: &Path, note: &str) -> Option<PathBuf> {
+    let name = note.trim();
+    let relative = Path::new(name);
+    if name.is_empty() || !relative.components().all(|part| matches!(part, Component::Normal(_))) {
+        return None;
+    }
+    let has_extension = strip_markdown_extension(name).len() < name.len();
+    let file = if has_extension { name.to_string() } else { format!("{}.md", name) };
+    Some(normalize_path(&from_dir.join(file)))
+}
+
+/// 创建笔记，内容为以笔记名为标题的一级标题；笔记已存在时返回错误
+pub fn create_note(path: &Path) -> Result<()> {
+    if path.exists() {
+        bail!("笔记已存在: {}", path.display());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("无法创建文件夹: {}", parent.display()))?;
+    }
+
+    let title = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("无法创建笔记: {}", path.display()))?;
+    write!(file, "# {}\n\n", title).with_context(|| format!("无法写入笔记: {}", path.display()))
+}
+
+/// 查找 `notes` 中链接到 `target` 的 wiki 链接和相对链接，按文件和行号排列
+pub fn find_backlinks(notes: &[PathBuf], target: &Path) -> Vec<Backlink> {
+    let target = normalize_path(target);
+    let mut backlinks = Vec::new();
+
+    for file in notes {
+        let file = normalize_path(file);
+        if file == target {
+            continue;
+        }
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("警告: 无法读取文件 {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        let from_dir = file.parent().unwrap_or_else(|| Path::new(""));
+
+        // 链接在文件中的字节位置
+        let mut offsets: Vec<usize> = find_wiki_links(&content)
+            .into_iter()
+            .filter(|link| !link.note.is_empty())
+            .filter(|link| resolve_wiki_link(notes, from_dir, &link.note).is_some_and(|path| path == target))
+            .map(|link| link.range.start)
+            .collect();
+        offsets.extend(
+            extract_links(&content)
+                .into_iter()
+                .filter(|link| link.kind != LinkKind::Image && !link.is_external())
+                .filter(|link| resolve_link_path(&file, link.path_part()).is_some_and(|path| path == target))
+                .map(|link| link.dest_range.start),
+        );
+        offsets.sort_unstable();
+
+        let mut last_line = 0;
+        for offset in offsets {
+            let line_start = content[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+            let line_number = content[..line_start].matches('\n').count() + 1;
+            // 同一行的多个链接只列出一次
+            if line_number == last_line {
+                continue;
+            }
+            last_line = line_number;
+
+            let line_end = content[offset..].find('\n').map_or(content.len(), |pos| offset + pos);
+            backlinks.push(Backlink {
+                file: file.clone(),
+                line_number,
+                context: link_context(&content[line_start..line_end], offset - line_start),
+            });
+        }
+    }
+
+    backlinks
+}
+
+/// 截取一行中链接附近的内容
+fn link_context(line: &str, offset: usize) -> String {
+    let line = line.trim_end();
+    if line.chars().count() <= CONTEXT_CHARS {
+        return line.trim_start().to_string();
+    }
+
+    let before = line[..offset.min(line.len())].chars().count();
+    let skip = before.saturating_sub(CONTEXT_CHARS / 4);
+    let mut context: String = line.chars().skip(skip).take(CONTEXT_CHARS).collect();
+    if skip + CONTEXT_CHARS < line.chars().count() {
+        context.push('…');
+    }
+    if skip > 0 {
+        context.insert(0, '…');
+    }
+    context.trim_start().to_string()
+}
+
+/// 去掉笔记名中的 `.md` 或 `.markdown` 扩展名
+fn strip_markdown_extension(name: &str) -> &str {
+    for ext in [".md", ".markdown"] {
+        if let Some(stem) = name.len().checked_sub(ext.len()).and_then(|end| name.get(..end)) {
+            if name[stem.len()..].eq_ignore_ascii_case(ext) {
+                return stem;
+            }
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_wiki_link() {
+        let notes: Vec<PathBuf> = ["/kb/计划.md", "/kb/项目/计划.md", "/kb/项目/周报.markdown", "/kb/归档/旧/周报.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let root = Path::new("/kb");
+        assert_eq!(resolve_wiki_link(&notes, root, "计划"), Some(PathBuf::from("/kb/计划.md")));
+        assert_eq!(resolve_wiki_link(&notes, Path::new("/kb/项目"), "计划"), Some(PathBuf::from("/kb/项目/计划.md")));
+        assert_eq!(resolve_wiki_link(&notes, root, "项目/计划.md"), Some(PathBuf::from("/kb/项目/计划.md")));
+        assert_eq!(resolve_wiki_link(&notes, root, "周报"), Some(PathBuf::from("/kb/项目/周报.markdown")));
+        assert_eq!(resolve_wiki_link(&notes, root, "旧/周报"), Some(PathBuf::from("/kb/归档/旧/周报.md")));
+        assert_eq!(resolve_wiki_link(&notes, root, "不存在"), None);
+        assert_eq!(resolve_wiki_link(&notes, root, " "), None);
+    }
+
+    #[test]
+    fn test_create_note() -> Result<()> {
+        let dir = TempDir::new()?;
+        let path = new_note_path(dir.path(), "想法/新笔记").unwrap();
+        assert_eq!(path, dir.path().join("想法/新笔记.md"));
+        assert_eq!(new_note_path(dir.path(), "a.MD"), Some(dir.path().join("a.MD")));
+        assert_eq!(new_note_path(dir.path(), "../外面"), None);
+        assert_eq!(new_note_path(dir.path(), "/tmp/a"), None);
+
+        create_note(&path)?;
+        assert_eq!(fs::read_to_string(&path)?, "# 新笔记\n\n");
+        assert!(create_note(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_backlinks() -> Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir(root.join("docs"))?;
+        let target = root.join("docs/计划.md");
+        fs::write(&target, "# 计划\n\n见 [[计划#目标]]\n")?;
+        fs::write(root.join("a.md"), "# A\n\n参考 [[计划|项目计划]] 和 [计划](docs/计划.md#目标)\n\n`[[计划]]`\n")?;
+        fs::write(root.join("b.md"), format!("{}[[docs/计划]] 结尾\n![图](docs/计划.md)", "很长的前文".repeat(20)))?;
+        fs::write(root.join("c.md"), "[[其他]]\n")?;
+        let notes = vec![root.join("a.md"), root.join("b.md"), root.join("c.md"), target.clone()];
+
+        let backlinks = find_backlinks(&notes, &target);
+        assert_eq!(backlinks.len(), 2);
+        assert_eq!(backlinks[0].file, root.join("a.md"));
+        assert_eq!(backlinks[0].line_number, 3);
+        assert_eq!(backlinks[0].context, "参考 [[计划|项目计划]] 和 [计划](docs/计划.md#目标)");
+        assert_eq!(backlinks[1].line_number, 1);
+        assert!(backlinks[1].context.starts_with('…'));
+        assert!(backlinks[1].context.ends_with("[[docs/计划]] 结尾"));
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! 路径工具模块
+//!
+//! 提供与文件系统无关的路径计算：
+//! - 路径规范化（处理 `.` 和 `..`）
+//! - 计算相对路径
+//! - 解析 Markdown 链接中的相对地址
+//! - 链接地址的百分号编码与解码
+
+use std::path::{Component, Path, PathBuf};
+
+/// 规范化路径（仅做词法处理，不访问文件系统）
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // 已经到达根部时保留 `..`
+                let can_pop = matches!(
+                    result.components().next_back(),
+                    Some(Component::Normal(_))
+                );
+                if can_pop {
+                    result.pop();
+                } else if !result.has_root() {
+                    result.push("..");
+                }
+            }
+            other => result.push(other.as_os_str()),
+        }
+    }
+
+    result
+}
+
+/// 计算从目录 `from_dir` 到 `to` 的相对路径
+pub fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let from = normalize_path(from_dir);
+    let to = normalize_path(to);
+
+    let from_components: Vec<_> = from.components().collect();
+    let to_components: Vec<_> = to.components().collect();
+
+    let common = from_components
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..from_components.len() {
+        result.push("..");
+    }
+    for component in &to_components[common..] {
+        result.push(component.as_os_str());
+    }
+
+    result
+}
+
+/// 将路径转换为 Markdown 链接中使用的 `/` 分隔字符串
+pub fn to_link_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 对链接地址中的路径做百分号编码
+///
+/// `%`、`#`、`?`、`\`、尖括号、控制字符和空白总是编码；写在 `<...>` 中时空格和括号保持原样，
+/// 否则也编码；`non_ascii` 时非 ASCII 字符按 UTF-8 字节编码
+pub fn percent_encode_link(path: &str, angle_brackets: bool, non_ascii: bool) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for ch in path.chars() {
+        let escape = match ch {
+            '%' | '#' | '?' | '\\' | '<' | '>' => true,
+            ' ' | '(' | ')' => !angle_brackets,
+            c if c.is_control() || c.is_whitespace() => true,
+            c => non_ascii && !c.is_ascii(),
+        };
+        if escape {
+            let mut buffer = [0; 4];
+            for byte in ch.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(ch);
+        }
+    }
+    encoded
+}
+
+/// 链接地址是否用百分号编码了非 ASCII 字符（如 `%E4%B8%AD`）
+pub fn has_encoded_non_ascii(dest: &str) -> bool {
+    dest.as_bytes().windows(3).any(|window| {
+        window[0] == b'%'
+            && std::str::from_utf8(&window[1..])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .is_some_and(|value| value >= 0x80)
+    })
+}
+
+/// 解码链接中的百分号编码（如 `%20`）
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(value) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(value);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// 解析 Markdown 文件中的相对链接，返回目标的规范化路径
+///
+/// `dest_path` 应为链接中去掉锚点后的路径部分
+pub fn resolve_link_path(markdown_file: &Path, dest_path: &str) -> Option<PathBuf> {
+    if dest_path.is_empty() {
+        return None;
+    }
+
+    let base_dir = markdown_file.parent().unwrap_or_else(|| Path::new(""));
+    Some(normalize_path(&base_dir.join(percent_decode(dest_path))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path(Path::new("/a/b/../c/./d")), PathBuf::from("/a/c/d"));
+        assert_eq!(normalize_path(Path::new("../x")), PathBuf::from("../x"));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/root/docs/guide"), Path::new("/root/img/x.png")),
+            PathBuf::from("../../img/x.png")
+        );
+        assert_eq!(
+            relative_path(Path::new("/root/docs"), Path::new("/root/docs/a.md")),
+            PathBuf::from("a.md")
+        );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("my%20file.md"), "my file.md");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%E4%B8%AD.md"), "中.md");
+    }
+
+    #[test]
+    fn test_percent_encode_link() {
+        assert_eq!(percent_encode_link("a (1)/<b>#2.md", false, false), "a%20%281%29/%3Cb%3E%232.md");
+        assert_eq!(percent_encode_link("a (1)/<b>.md", true, false), "a (1)/%3Cb%3E.md");
+        assert_eq!(percent_encode_link("中 100%.md", false, true), "%E4%B8%AD%20100%25.md");
+        assert_eq!(percent_encode_link("中.md", false, false), "中.md");
+        assert_eq!(percent_decode(&percent_encode_link("a (1)<%>.md", false, true)), "a (1)<%>.md");
+
+        assert!(has_encoded_non_ascii("docs/%E4%B8%AD.md"));
+        assert!(!has_encoded_non_ascii("my%20file.md"));
+        assert!(!has_encoded_non_ascii("100%"));
+    }
+
+    #[test]
+    fn test_resolve_link_path() {
+        let resolved = resolve_link_path(Path::new("/root/docs/a.md"), "../img/x.png");
+        assert_eq!(resolved, Some(PathBuf::from("/root/img/x.png")));
+        assert_eq!(resolve_link_path(Path::new("/root/a.md"), ""), None);
+    }
+}
@@ -0,0 +1,503 @@
+//! 查找替换模块
+//!
+//! 提供当前文档和整个工作区的替换功能：
+//! - 替换下一个、全部替换
+//! - 正则替换，支持 `$1`、`${1}` 等捕获组引用
+//! - 工作区替换：可逐项勾选的预览，原子写入，整体撤销
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use anyhow::{Result, Context};
+
+use super::{write_files_atomic, SearchManager, SearchResult};
+
+/// 文档中的一个匹配（整篇文档中的字节范围）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentMatch {
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 匹配在文档中的字节范围
+    pub range: Range<usize>,
+    /// 捕获组内容
+    pub captures: Vec<Option<String>>,
+}
+
+/// 将按行的搜索结果转换为文档中的匹配
+pub fn document_matches(text: &str, results: &[SearchResult]) -> Vec<DocumentMatch> {
+    // `str::lines` 会去掉 `\n` 和 `\r\n`，行首偏移需要按原始换行计算
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let mut matches = Vec::new();
+    for result in results {
+        let Some(&line_start) = line_starts.get(result.line_number - 1) else {
+            continue;
+        };
+
+        for (i, &(start, end)) in result.match_positions.iter().enumerate() {
+            matches.push(DocumentMatch {
+                line_number: result.line_number,
+                range: line_start + start..line_start + end,
+                captures: result.captures.get(i).cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    matches
+}
+
+/// 展开替换文本中的捕获组引用
+///
+/// 支持 `$0`（整个匹配）、`$1`、`${1}` 和 `$$`（字面量 `$`）；
+/// 不存在的捕获组替换为空字符串
+pub fn expand_replacement(replacement: &str, whole: &str, captures: &[Option<String>]) -> String {
+    let group = |index: usize| -> &str {
+        if index == 0 {
+            whole
+        } else {
+            captures.get(index - 1).and_then(|c| c.as_deref()).unwrap_or("")
+        }
+    };
+
+    let mut result = String::with_capacity(replacement.len());
+    let mut rest = replacement;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(after) = after.strip_prefix('$') {
+            result.push('$');
+            rest = after;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}').and_then(|end| braced[..end].parse::<usize>().ok().map(|n| (end, n))) {
+                Some((end, index)) => {
+                    result.push_str(group(index));
+                    rest = &braced[end + 1..];
+                }
+                None => {
+                    result.push('$');
+                    rest = after;
+                }
+            }
+        } else {
+            let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                result.push('$');
+            } else if let Ok(index) = after[..digits].parse::<usize>() {
+                result.push_str(group(index));
+            }
+            rest = &after[digits..];
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+impl SearchManager {
+    /// 计算单个匹配的替换文本（正则模式下展开捕获组引用）
+    pub fn replacement_text(&self, text: &str, doc_match: &DocumentMatch, replacement: &str) -> String {
+        if self.options().use_regex {
+            expand_replacement(replacement, &text[doc_match.range.clone()], &doc_match.captures)
+        } else {
+            replacement.to_string()
+        }
+    }
+
+    /// 替换 `from` 之后的第一个匹配
+    ///
+    /// 返回替换后的文本和替换内容在新文本中的范围，没有匹配时返回 `None`
+    pub fn replace_next(
+        &mut self,
+        query: &str,
+        replacement: &str,
+        text: &str,
+        from: usize,
+    ) -> Result<Option<(String, Range<usize>)>> {
+        let results = self.try_search(query, text)?;
+        let matches = document_matches(text, &results);
+
+        // 到达末尾后从头开始
+        let Some(doc_match) = matches
+            .iter()
+            .find(|m| m.range.start >= from)
+            .or_else(|| matches.first())
+        else {
+            return Ok(None);
+        };
+
+        let new_text = self.replacement_text(text, doc_match, replacement);
+        let mut result = text.to_string();
+        result.replace_range(doc_match.range.clone(), &new_text);
+
+        let start = doc_match.range.start;
+        Ok(Some((result, start..start + new_text.len())))
+    }
+
+    /// 替换所有匹配，返回替换后的文本和替换次数
+    pub fn replace_all(&mut self, query: &str, replacement: &str, text: &str) -> Result<(String, usize)> {
+        let results = self.try_search(query, text)?;
+        let matches = document_matches(text, &results);
+
+        let mut result = String::with_capacity(text.len());
+        let mut last_end = 0;
+        for doc_match in &matches {
+            result.push_str(&text[last_end..doc_match.range.start]);
+            result.push_str(&self.replacement_text(text, doc_match, replacement));
+            last_end = doc_match.range.end;
+        }
+        result.push_str(&text[last_end..]);
+
+        Ok((result, matches.len()))
+    }
+}
+
+/// 工作区替换中的单个匹配
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaceItem {
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 匹配在文件中的字节范围
+    pub range: Range<usize>,
+    /// 替换前的行
+    pub old_line: String,
+    /// 替换后的行（只替换这一处）
+    pub new_line: String,
+    /// 替换文本
+    pub replacement: String,
+    /// 是否选中
+    pub selected: bool,
+}
+
+/// 工作区替换中的单个文件
+#[derive(Debug, Clone)]
+pub struct FileReplace {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 生成计划时的文件内容
+    original: String,
+    /// 文件中的所有匹配
+    pub items: Vec<ReplaceItem>,
+}
+
+impl FileReplace {
+    /// 按选中的匹配生成新内容
+    fn replaced_content(&self) -> String {
+        let mut content = String::with_capacity(self.original.len());
+        let mut last_end = 0;
+        for item in self.items.iter().filter(|item| item.selected) {
+            content.push_str(&self.original[last_end..item.range.start]);
+            content.push_str(&item.replacement);
+            last_end = item.range.end;
+        }
+        content.push_str(&self.original[last_end..]);
+        content
+    }
+}
+
+/// 工作区替换计划
+///
+/// 由 `search_in_files` 的结果生成，可以逐项勾选后一次性应用
+#[derive(Debug, Clone)]
+pub struct ReplacePlan {
+    /// 搜索关键词
+    pub query: String,
+    /// 替换文本
+    pub replacement: String,
+    /// 受影响的文件
+    pub files: Vec<FileReplace>,
+}
+
+impl ReplacePlan {
+    /// 根据多文件搜索结果生成替换计划
+    ///
+    /// 搜索结果只用于确定文件，匹配位置按读取到的当前内容重新查找，文件在搜索之后被修改也不会出错
+    pub fn new(
+        manager: &SearchManager,
+        query: &str,
+        replacement: &str,
+        results: &HashMap<String, Vec<SearchResult>>,
+    ) -> Result<Self> {
+        let mut paths: Vec<&String> = results.keys().collect();
+        paths.sort();
+
+        let options = manager.options();
+        let regex = SearchManager::build_regex(query, &options)?;
+        let mut files = Vec::new();
+        for path in paths {
+            let original = fs::read_to_string(path)
+                .with_context(|| format!("无法读取文件: {}", path))?;
+            let lines: Vec<&str> = original.lines().collect();
+            let current_results = SearchManager::search_with_regex(&regex, &options, &original);
+
+            let items = document_matches(&original, &current_results)
+                .into_iter()
+                .map(|doc_match| {
+                    let replacement = manager.replacement_text(&original, &doc_match, replacement);
+                    let old_line = lines.get(doc_match.line_number - 1).copied().unwrap_or("").to_string();
+
+                    let line_start = original[..doc_match.range.start]
+                        .rfind('\n')
+                        .map(|i| i + 1)
+                        .unwrap_or(0);
+                    let mut new_line = old_line.clone();
+                    let local = doc_match.range.start - line_start..doc_match.range.end - line_start;
+                    if local.end <= new_line.len() {
+                        new_line.replace_range(local, &replacement);
+                    }
+
+                    ReplaceItem {
+                        line_number: doc_match.line_number,
+                        range: doc_match.range,
+                        old_line,
+                        new_line,
+                        replacement,
+                        selected: true,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if !items.is_empty() {
+                files.push(FileReplace { path: PathBuf::from(path), original, items });
+            }
+        }
+
+        Ok(Self {
+            query: query.to_string(),
+            replacement: replacement.to_string(),
+            files,
+        })
+    }
+
+    /// 切换单个匹配的选中状态
+    pub fn toggle(&mut self, file_index: usize, item_index: usize) {
+        if let Some(item) = self.files.get_mut(file_index).and_then(|f| f.items.get_mut(item_index)) {
+            item.selected = !item.selected;
+        }
+    }
+
+    /// 全选或全不选
+    pub fn select_all(&mut self, selected: bool) {
+        for file in &mut self.files {
+            for item in &mut file.items {
+                item.selected = selected;
+            }
+        }
+    }
+
+    /// 选中的匹配数量
+    pub fn selected_count(&self) -> usize {
+        self.files.iter().flat_map(|f| &f.items).filter(|item| item.selected).count()
+    }
+
+    /// 应用替换
+    ///
+    /// 所有文件原子写入；返回的 [`ReplaceTransaction`] 可用于撤销整个操作
+    pub fn apply(&self) -> Result<ReplaceTransaction> {
+        let mut changes = Vec::new();
+
+        for file in &self.files {
+            if !file.items.iter().any(|item| item.selected) {
+                continue;
+            }
+
+            let current = fs::read_to_string(&file.path)
+                .with_context(|| format!("无法读取文件: {}", file.path.display()))?;
+            if current != file.original {
+                return Err(anyhow::anyhow!("文件已被修改，请重新搜索: {}", file.path.display()));
+            }
+
+            changes.push(FileChange {
+                path: file.path.clone(),
+                before: file.original.clone(),
+                after: file.replaced_content(),
+            });
+        }
+
+        let writes: Vec<_> = changes.iter().map(|c| (c.path.clone(), c.after.clone())).collect();
+        write_files_atomic(&writes)?;
+
+        Ok(ReplaceTransaction { changes })
+    }
+}
+
+/// 单个文件的修改记录
+#[derive(Debug, Clone)]
+struct FileChange {
+    path: PathBuf,
+    before: String,
+    after: String,
+}
+
+/// 已应用的工作区替换，可整体撤销
+#[derive(Debug, Clone)]
+pub struct ReplaceTransaction {
+    changes: Vec<FileChange>,
+}
+
+impl ReplaceTransaction {
+    /// 修改的文件路径
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.changes.iter().map(|c| &c.path)
+    }
+
+    /// 修改的文件数量
+    pub fn file_count(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// 撤销替换，恢复所有文件的原始内容
+    ///
+    /// 替换之后文件又被修改过时拒绝撤销
+    pub fn undo(self) -> Result<()> {
+        for change in &self.changes {
+            let current = fs::read_to_string(&change.path)
+                .with_context(|| format!("无法读取文件: {}", change.path.display()))?;
+            if current != change.after {
+                return Err(anyhow::anyhow!("文件在替换后已被修改，无法撤销: {}", change.path.display()));
+            }
+        }
+
+        let writes: Vec<_> = self.changes.iter().map(|c| (c.path.clone(), c.before.clone())).collect();
+        write_files_atomic(&writes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_manager::SearchOptions;
+    use tempfile::TempDir;
+
+    fn search_manager_with(options: SearchOptions) -> SearchManager {
+        let mut manager = SearchManager::new();
+        manager.set_options(options);
+        manager
+    }
+
+    #[test]
+    fn test_expand_replacement() {
+        let captures = vec![Some("alice".to_string()), None];
+
+        assert_eq!(expand_replacement("$1!", "alice@x", &captures), "alice!");
+        assert_eq!(expand_replacement("${1}_x $0", "alice@x", &captures), "alice_x alice@x");
+        assert_eq!(expand_replacement("$2|$9|$$|$", "m", &captures), "||$|$");
+        assert_eq!(expand_replacement("cost: $a", "m", &captures), "cost: $a");
+    }
+
+    #[test]
+    fn test_replace_all_literal() -> Result<()> {
+        let mut manager = SearchManager::new();
+        let (text, count) = manager.replace_all("cat", "dog", "Cat cat\r\ncatalog")?;
+
+        assert_eq!(count, 3);
+        assert_eq!(text, "dog dog\r\ndogalog");
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_all_regex_captures() -> Result<()> {
+        let mut manager = search_manager_with(SearchOptions { use_regex: true, ..Default::default() });
+        let (text, count) = manager.replace_all(r"(\w+)@(\w+)", "$2:$1", "a@b\n中文 c@d")?;
+
+        assert_eq!(count, 2);
+        assert_eq!(text, "b:a\n中文 d:c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_literal_keeps_dollar() -> Result<()> {
+        let mut manager = SearchManager::new();
+        let (text, _) = manager.replace_all("price", "$1", "price")?;
+
+        assert_eq!(text, "$1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_next_wraps() -> Result<()> {
+        let mut manager = SearchManager::new();
+        let text = "a x a x";
+
+        let (text, range) = manager.replace_next("a", "bb", text, 1)?.unwrap();
+        assert_eq!(text, "a x bb x");
+        assert_eq!(range, 4..6);
+
+        // 之后没有匹配时从头开始
+        let (text, range) = manager.replace_next("a", "bb", &text, range.end)?.unwrap();
+        assert_eq!(text, "bb x bb x");
+        assert_eq!(range, 0..2);
+
+        assert!(manager.replace_next("zzz", "y", &text, 0)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_replace_and_undo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let first = temp_dir.path().join("a.md");
+        let second = temp_dir.path().join("b.md");
+        fs::write(&first, "foo bar\nfoo")?;
+        fs::write(&second, "no foo here")?;
+
+        let mut manager = SearchManager::new();
+        let results = manager.search_in_files("foo", &[first.clone(), second.clone()]);
+        let mut plan = ReplacePlan::new(&manager, "foo", "baz", &results)?;
+
+        assert_eq!(plan.files.len(), 2);
+        assert_eq!(plan.selected_count(), 3);
+        assert_eq!(plan.files[0].items[0].new_line, "baz bar");
+
+        // 取消第一个文件的第二处匹配
+        plan.toggle(0, 1);
+        assert_eq!(plan.selected_count(), 2);
+
+        let transaction = plan.apply()?;
+        assert_eq!(transaction.file_count(), 2);
+        assert_eq!(fs::read_to_string(&first)?, "baz bar\nfoo");
+        assert_eq!(fs::read_to_string(&second)?, "no baz here");
+
+        transaction.undo()?;
+        assert_eq!(fs::read_to_string(&first)?, "foo bar\nfoo");
+        assert_eq!(fs::read_to_string(&second)?, "no foo here");
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_replace_plan_uses_current_content() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        fs::write(&file, "一些文字 foo\n更多 foo")?;
+
+        let mut manager = SearchManager::new();
+        let results = manager.search_in_files("foo", std::slice::from_ref(&file));
+        // 搜索之后文件变短，原来的匹配位置已经越界
+        fs::write(&file, "中 foo")?;
+        let plan = ReplacePlan::new(&manager, "foo", "bar", &results)?;
+
+        assert_eq!(plan.selected_count(), 1);
+        assert_eq!(plan.files[0].items[0].range, 4..7);
+        assert_eq!(plan.files[0].items[0].new_line, "中 bar");
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_replace_rejects_stale_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        fs::write(&file, "foo")?;
+
+        let mut manager = SearchManager::new();
+        let results = manager.search_in_files("foo", std::slice::from_ref(&file));
+        let plan = ReplacePlan::new(&manager, "foo", "bar", &results)?;
+
+        fs::write(&file, "foo changed")?;
+        assert!(plan.apply().is_err());
+        assert_eq!(fs::read_to_string(&file)?, "foo changed");
+        Ok(())
+    }
+}
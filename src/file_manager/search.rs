@@ -2,10 +2,54 @@
 //!
 //! 提供文档内搜索功能：
 //! - 关键词搜索
+//! - 区分大小写、全词匹配、正则表达式（含捕获组）
+//! - 忽略变音符号与全角字符
 //! - 搜索结果高亮
 //! - 搜索历史
 
 use std::collections::HashMap;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::text_fold::{ceil_char_boundary, floor_char_boundary, FoldOptions, FoldedText};
+
+/// 预览中匹配前后保留的字形簇数量
+const PREVIEW_CONTEXT_GRAPHEMES: usize = 20;
+
+/// 搜索选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// 区分大小写
+    pub case_sensitive: bool,
+    /// 全词匹配
+    pub whole_word: bool,
+    /// 使用正则表达式
+    pub use_regex: bool,
+    /// 区分变音符号和全角字符（关闭时 `é` 匹配 `e`，`Ａ` 匹配 `A`）
+    pub match_diacritics: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            whole_word: false,
+            use_regex: false,
+            match_diacritics: true,
+        }
+    }
+}
+
+/// 搜索历史条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    /// 搜索关键词
+    pub query: String,
+    /// 搜索时使用的选项
+    pub options: SearchOptions,
+}
 
 /// 搜索结果
 #[derive(Debug, Clone)]
@@ -14,8 +58,10 @@ pub struct SearchResult {
     pub line_number: usize,
     /// 行内容
     pub line_content: String,
-    /// 匹配位置（字符索引）
+    /// 匹配位置（字节范围）
     pub match_positions: Vec<(usize, usize)>,
+    /// 每个匹配的捕获组内容（正则模式下有效，不含整体匹配）
+    pub captures: Vec<Vec<Option<String>>>,
     /// 预览文本（包含上下文）
     pub preview: String,
 }
@@ -29,11 +75,27 @@ impl SearchResult {
             line_number,
             line_content,
             match_positions,
+            captures: Vec::new(),
             preview,
         }
     }
 
+    /// 设置捕获组内容
+    pub fn with_captures(mut self, captures: Vec<Vec<Option<String>>>) -> Self {
+        self.captures = captures;
+        self
+    }
+
+    /// 第一个匹配所在的列（从1开始，按 UTF-16 编码单元计算，与编辑器的光标位置一致）
+    pub fn column(&self) -> usize {
+        let start = self.match_positions.first().map(|&(start, _)| start).unwrap_or(0);
+        let start = floor_char_boundary(&self.line_content, start);
+        self.line_content[..start].encode_utf16().count() + 1
+    }
+
     /// 生成预览文本（包含上下文）
+    ///
+    /// 上下文按字形簇计算，不会截断多字节字符或组合字符
     fn generate_preview(line_content: &str, match_positions: &[(usize, usize)]) -> String {
         if match_positions.is_empty() {
             return String::new();
@@ -41,10 +103,22 @@ impl SearchResult {
 
         let (start, end) = match_positions[0];
         let line_len = line_content.len();
-
-        // 添加上下文（前后20个字符）
-        let context_start = start.saturating_sub(20);
-        let context_end = (end + 20).min(line_len);
+        let start = floor_char_boundary(line_content, start);
+        let end = ceil_char_boundary(line_content, end.max(start));
+
+        // 添加上下文（前后20个字形簇）
+        let context_start = line_content[..start]
+            .grapheme_indices(true)
+            .rev()
+            .take(PREVIEW_CONTEXT_GRAPHEMES)
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(start);
+        let context_end = line_content[end..]
+            .grapheme_indices(true)
+            .nth(PREVIEW_CONTEXT_GRAPHEMES)
+            .map(|(i, _)| end + i)
+            .unwrap_or(line_len);
 
         let mut preview = String::new();
 
@@ -73,6 +147,13 @@ impl SearchResult {
         let mut last_end = 0;
 
         for &(start, end) in &self.match_positions {
+            // 位置调整到字符边界，并跳过与前一个匹配重叠的部分
+            let start = floor_char_boundary(&self.line_content, start).max(last_end);
+            let end = ceil_char_boundary(&self.line_content, end);
+            if end <= start {
+                continue;
+            }
+
             // 添加匹配前的文本
             if start > last_end {
                 result.push_str(&html_escape(&self.line_content[last_end..start]));
@@ -98,9 +179,11 @@ impl SearchResult {
 /// 搜索管理器
 pub struct SearchManager {
     /// 搜索历史
-    history: Vec<String>,
+    history: Vec<SearchHistoryEntry>,
     /// 历史记录最大数量
     max_history_size: usize,
+    /// 当前搜索选项
+    options: SearchOptions,
 }
 
 impl SearchManager {
@@ -109,46 +192,119 @@ impl SearchManager {
         Self {
             history: Vec::new(),
             max_history_size: 50,
+            options: SearchOptions::default(),
+        }
+    }
+
+    /// 获取当前搜索选项
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    /// 设置搜索选项
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+    }
+
+    /// 根据关键词和选项构建正则表达式
+    pub fn build_regex(query: &str, options: &SearchOptions) -> anyhow::Result<Regex> {
+        let mut pattern = if options.use_regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+
+        // 关键词与文本使用同样的方式折叠，正则表达式只折叠其中的字面字符
+        let fold = Self::fold_options(options);
+        if !fold.is_identity() {
+            pattern = if options.use_regex {
+                fold_regex_literals(query, fold)
+            } else {
+                regex::escape(&FoldedText::new(query, fold).text)
+            };
+        }
+
+        if options.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
         }
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| anyhow::anyhow!("无效的正则表达式: {}", e))
     }
 
-    /// 在文本中搜索关键词
+    /// 搜索选项对应的文本折叠方式
+    ///
+    /// 正则模式下大小写由正则引擎处理（保证 `\p{Lu}` 等写法仍然有效），
+    /// 普通关键词则使用完整大小写折叠（`ß` 匹配 `ss`）
+    fn fold_options(options: &SearchOptions) -> FoldOptions {
+        FoldOptions {
+            case: !options.case_sensitive && !options.use_regex,
+            diacritics: !options.match_diacritics,
+        }
+    }
+
+    /// 在文本中搜索关键词（使用当前搜索选项）并记录到搜索历史
+    ///
+    /// 正则表达式无效时返回空结果，需要错误信息时使用 [`SearchManager::try_search`]
     pub fn search(&mut self, query: &str, text: &str) -> Vec<SearchResult> {
+        match self.try_search(query, text) {
+            Ok(results) => {
+                self.add_to_history(query);
+                results
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// 在文本中搜索关键词，正则表达式无效时返回错误
+    ///
+    /// 不修改搜索历史，内容变化后重新搜索时使用；用户发起的搜索需调用 [`SearchManager::add_to_history`]
+    pub fn try_search(&self, query: &str, text: &str) -> anyhow::Result<Vec<SearchResult>> {
         if query.is_empty() {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
-        // 添加到搜索历史
-        self.add_to_history(query.to_string());
+        let regex = Self::build_regex(query, &self.options)?;
+        Ok(Self::search_with_regex(&regex, &self.options, text))
+    }
 
+    /// 使用已构建的正则表达式逐行搜索文本
+    ///
+    /// 不修改搜索历史，可以在后台线程中调用
+    pub fn search_with_regex(regex: &Regex, options: &SearchOptions, text: &str) -> Vec<SearchResult> {
+        let fold = Self::fold_options(options);
         let mut results = Vec::new();
-        let query_lower = query.to_lowercase();
 
         for (line_idx, line) in text.lines().enumerate() {
-            let line_lower = line.to_lowercase();
-
-            // 查找所有匹配位置
-            let mut match_positions = Vec::new();
-            let mut search_start = 0;
-
-            while let Some(pos) = line_lower[search_start..].find(&query_lower) {
-                let actual_pos = search_start + pos;
-                match_positions.push((actual_pos, actual_pos + query.len()));
-                search_start = actual_pos + query.len();
-
-                // 防止无限循环
-                if search_start >= line_lower.len() {
-                    break;
-                }
-            }
+            let (match_positions, captures) = if fold.is_identity() {
+                find_matches(regex, line, None)
+            } else {
+                let folded = FoldedText::new(line, fold);
+                find_matches(regex, &folded.text, Some(&folded))
+            };
 
             // 如果有匹配，添加到结果
             if !match_positions.is_empty() {
-                results.push(SearchResult::new(
-                    line_idx + 1, // 行号从1开始
-                    line.to_string(),
-                    match_positions,
-                ));
+                let captures = captures
+                    .into_iter()
+                    .map(|groups| {
+                        groups
+                            .into_iter()
+                            .map(|group| group.map(|(start, end)| line[start..end].to_string()))
+                            .collect()
+                    })
+                    .collect();
+
+                results.push(
+                    SearchResult::new(
+                        line_idx + 1, // 行号从1开始
+                        line.to_string(),
+                        match_positions,
+                    )
+                    .with_captures(captures),
+                );
             }
         }
 
@@ -160,7 +316,7 @@ impl SearchManager {
         let content = std::fs::read_to_string(file_path)
             .map_err(|e| anyhow::anyhow!("无法读取文件 {}: {}", file_path.display(), e))?;
 
-        Ok(self.search(query, &content))
+        self.try_search(query, &content)
     }
 
     /// 在多个文件中搜索关键词
@@ -171,6 +327,12 @@ impl SearchManager {
     ) -> HashMap<String, Vec<SearchResult>> {
         let mut all_results = HashMap::new();
 
+        // 正则表达式无效时无需逐个读取文件
+        if let Err(e) = Self::build_regex(query, &self.options) {
+            eprintln!("搜索失败: {}", e);
+            return all_results;
+        }
+
         for file_path in file_paths {
             match self.search_in_file(query, file_path) {
                 Ok(results) => {
@@ -190,14 +352,20 @@ impl SearchManager {
         all_results
     }
 
-    /// 添加搜索到历史记录
-    fn add_to_history(&mut self, query: String) {
-        // 避免重复
-        if let Some(pos) = self.history.iter().position(|q| q == &query) {
+    /// 使用当前搜索选项把关键词添加到历史记录（空关键词不记录）
+    pub fn add_to_history(&mut self, query: &str) {
+        if query.is_empty() {
+            return;
+        }
+        let query = query.to_string();
+        let options = self.options;
+
+        // 避免重复（同一关键词只保留最近一次使用的选项）
+        if let Some(pos) = self.history.iter().position(|entry| entry.query == query) {
             self.history.remove(pos);
         }
 
-        self.history.insert(0, query);
+        self.history.insert(0, SearchHistoryEntry { query, options });
 
         // 限制历史记录数量
         if self.history.len() > self.max_history_size {
@@ -206,10 +374,16 @@ impl SearchManager {
     }
 
     /// 获取搜索历史
-    pub fn history(&self) -> &[String] {
+    pub fn history(&self) -> &[SearchHistoryEntry] {
         &self.history
     }
 
+    /// 恢复保存的搜索历史（最近的在前）
+    pub fn set_history(&mut self, history: Vec<SearchHistoryEntry>) {
+        self.history = history;
+        self.history.truncate(self.max_history_size);
+    }
+
     /// 清空搜索历史
     pub fn clear_history(&mut self) {
         self.history.clear();
@@ -224,12 +398,90 @@ impl SearchManager {
     }
 }
 
+/// 字节范围列表（捕获组可能未参与匹配）
+type GroupRanges = Vec<Option<(usize, usize)>>;
+
+/// 在一行中查找所有匹配，返回匹配范围和捕获组范围（均为原始行中的字节范围）
+fn find_matches(regex: &Regex, haystack: &str, folded: Option<&FoldedText>) -> (Vec<(usize, usize)>, Vec<GroupRanges>) {
+    let map = |start: usize, end: usize| match folded {
+        Some(folded) => folded.original_range(start, end),
+        None => (start, end),
+    };
+
+    let mut positions = Vec::new();
+    let mut captures = Vec::new();
+
+    for caps in regex.captures_iter(haystack) {
+        let whole = caps.get(0).expect("捕获组 0 总是存在");
+        // 跳过空匹配（如 `a*`），避免产生无意义的结果
+        if whole.start() == whole.end() {
+            continue;
+        }
+
+        // 折叠后的一个原始字符可能对应多个字节（如 `ß` → `ss`），映射回原文后可能与前一个匹配重叠
+        let (start, end) = map(whole.start(), whole.end());
+        if positions.last().is_some_and(|&(_, last_end)| start < last_end) {
+            continue;
+        }
+
+        positions.push((start, end));
+        captures.push(
+            caps.iter()
+                .skip(1)
+                .map(|group| group.map(|m| map(m.start(), m.end())))
+                .collect(),
+        );
+    }
+
+    (positions, captures)
+}
+
 impl Default for SearchManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// 折叠正则表达式中的字面字符，使其与折叠后的文本匹配
+///
+/// 转义序列（如 `\p{L}`）保持不变；折叠为多个字符或被去掉的字符在字符类中保持原样，
+/// 其余位置折叠为多个字符时用分组包住
+fn fold_regex_literals(pattern: &str, fold: FoldOptions) -> String {
+    let mut folded = String::with_capacity(pattern.len());
+    let mut escaped = false;
+    let mut class_depth = 0usize;
+
+    for ch in pattern.chars() {
+        if escaped {
+            escaped = false;
+            folded.push(ch);
+            continue;
+        }
+        match ch {
+            '\\' => escaped = true,
+            '[' => class_depth += 1,
+            ']' => class_depth = class_depth.saturating_sub(1),
+            _ => {}
+        }
+        if ch.is_ascii() {
+            folded.push(ch);
+            continue;
+        }
+
+        // 单独的组合字符在文本中被去掉，字符类之外同样去掉
+        let text = FoldedText::new(&ch.to_string(), fold).text;
+        if text.chars().count() == 1 {
+            folded.push_str(&regex::escape(&text));
+        } else if class_depth > 0 {
+            folded.push(ch);
+        } else if !text.is_empty() {
+            folded.push_str(&format!("(?:{})", regex::escape(&text)));
+        }
+    }
+
+    folded
+}
+
 /// HTML 转义函数
 fn html_escape(text: &str) -> String {
     text.replace('&', "&amp;")
@@ -323,8 +575,8 @@ mod tests {
         let history = manager.history();
 
         assert_eq!(history.len(), 2);
-        assert_eq!(history[0], "test1"); // 重复的被移到前面
-        assert_eq!(history[1], "test2");
+        assert_eq!(history[0].query, "test1"); // 重复的被移到前面
+        assert_eq!(history[1].query, "test2");
     }
 
     #[test]
@@ -359,6 +611,15 @@ mod tests {
         assert!(preview.len() < "This is a long line with search term in the middle".len());
     }
 
+    #[test]
+    fn test_column_counts_utf16_units() {
+        let mut manager = SearchManager::new();
+        let results = manager.search("目标", "😀 中文 目标");
+        assert_eq!(results.len(), 1);
+        // 表情占两个 UTF-16 编码单元
+        assert_eq!(results[0].column(), 7);
+    }
+
     #[test]
     fn test_clear_history() {
         let mut manager = SearchManager::new();
@@ -385,8 +646,240 @@ mod tests {
         manager.search("test5", "content");
 
         assert_eq!(manager.history().len(), 3);
-        assert_eq!(manager.history()[0], "test5");
-        assert_eq!(manager.history()[1], "test4");
-        assert_eq!(manager.history()[2], "test3");
+        assert_eq!(manager.history()[0].query, "test5");
+        assert_eq!(manager.history()[1].query, "test4");
+        assert_eq!(manager.history()[2].query, "test3");
+    }
+
+    #[test]
+    fn test_search_case_sensitive() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { case_sensitive: true, ..Default::default() });
+
+        let results = manager.search("Hello", "Hello world\nhello World\nHELLO");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_search_whole_word() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { whole_word: true, ..Default::default() });
+
+        let results = manager.search("test", "test testing attest\ncontest");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_positions, vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_search_regex_captures() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { use_regex: true, ..Default::default() });
+
+        let results = manager.search(r"(\w+)@(\w+)\.com", "mail: alice@example.com, bob@test.com");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_positions.len(), 2);
+        assert_eq!(results[0].captures[0], vec![Some("alice".to_string()), Some("example".to_string())]);
+        assert_eq!(results[0].captures[1][0].as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn test_search_invalid_regex() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { use_regex: true, ..Default::default() });
+
+        assert!(manager.try_search("(unclosed", "text").is_err());
+        assert!(manager.search("(unclosed", "text").is_empty());
+        assert!(manager.history().is_empty());
+    }
+
+    #[test]
+    fn test_try_search_keeps_history() {
+        let mut manager = SearchManager::new();
+
+        assert_eq!(manager.try_search("foo", "foo bar").unwrap().len(), 1);
+        assert!(manager.history().is_empty());
+
+        manager.set_options(SearchOptions { case_sensitive: true, ..Default::default() });
+        manager.add_to_history("foo");
+        manager.add_to_history("");
+        assert_eq!(manager.history().len(), 1);
+        assert_eq!(manager.history()[0].query, "foo");
+        assert!(manager.history()[0].options.case_sensitive);
+    }
+
+    #[test]
+    fn test_search_ignore_diacritics_and_full_width() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { match_diacritics: false, ..Default::default() });
+
+        let line = "Café au lait，ＡＢＣ";
+        let results = manager.search("cafe", line);
+        assert_eq!(results.len(), 1);
+        let (start, end) = results[0].match_positions[0];
+        assert_eq!(&line[start..end], "Café");
+
+        let results = manager.search("abc", line);
+        let (start, end) = results[0].match_positions[0];
+        assert_eq!(&line[start..end], "ＡＢＣ");
+
+        // 默认区分变音符号
+        manager.set_options(SearchOptions::default());
+        assert!(manager.search("cafe", line).is_empty());
+    }
+
+    #[test]
+    fn test_regex_ignore_diacritics() {
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { use_regex: true, match_diacritics: false, ..Default::default() });
+
+        let line = "Café au lait，ＡＢＣ (x)";
+        for (query, expected) in [("Caf[é]", "Café"), (r"cafés?\b", "Café"), ("ＡＢ.", "ＡＢＣ"), (r"（x）", "(x)")] {
+            let results = manager.search(query, line);
+            assert_eq!(results.len(), 1, "{}", query);
+            let (start, end) = results[0].match_positions[0];
+            assert_eq!(&line[start..end], expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_history_remembers_options() {
+        let mut manager = SearchManager::new();
+        let options = SearchOptions { case_sensitive: true, whole_word: true, ..Default::default() };
+        manager.set_options(options);
+
+        manager.search("word", "a word");
+
+        assert_eq!(manager.history()[0].options, options);
+    }
+
+    #[test]
+    fn test_search_in_file_with_options() -> anyhow::Result<()> {
+        let mut temp_file = NamedTempFile::new()?;
+        writeln!(temp_file, "fn main() {{}}")?;
+        writeln!(temp_file, "fn helper() {{}}")?;
+
+        let mut manager = SearchManager::new();
+        manager.set_options(SearchOptions { use_regex: true, ..Default::default() });
+        let results = manager.search_in_file(r"fn (\w+)\(", temp_file.path())?;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].captures[0][0].as_deref(), Some("helper"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_case_folding_changes_length() {
+        let mut manager = SearchManager::new();
+
+        // `İ` 小写后字节长度改变，`ẞ` 折叠为 `ss`
+        let line = "İİİ Straẞe ist hier";
+        let results = manager.search("hier", line);
+        let (start, end) = results[0].match_positions[0];
+        assert_eq!(&line[start..end], "hier");
+
+        let results = manager.search("strasse", line);
+        let (start, end) = results[0].match_positions[0];
+        assert_eq!(&line[start..end], "Straẞe");
+    }
+
+    #[test]
+    fn test_preview_cjk() {
+        let mut manager = SearchManager::new();
+        let line = "这是一个很长的中文句子，用来测试搜索结果预览是否会在多字节字符中间截断，关键字在这里出现，后面还有很多很多很多的文字内容";
+
+        let results = manager.search("关键字", line);
+
+        assert_eq!(results.len(), 1);
+        let preview = &results[0].preview;
+        assert!(preview.contains("关键字"));
+        assert!(preview.starts_with("..."));
+        assert!(preview.ends_with("..."));
+        // 上下文为前后各 20 个字形簇
+        assert_eq!(preview.trim_matches('.').chars().count(), 20 + 3 + 20);
+    }
+
+    #[test]
+    fn test_preview_keeps_graphemes_intact() {
+        // 组合字符和表情序列不能被截断
+        let line = format!("{}target{}", "e\u{301}".repeat(30), "👨‍👩‍👧".repeat(30));
+        let start = line.find("target").unwrap();
+        let result = SearchResult::new(1, line.clone(), vec![(start, start + 6)]);
+
+        let body = result.preview.trim_start_matches("...").trim_end_matches("...");
+        assert_eq!(body, format!("{}target{}", "e\u{301}".repeat(20), "👨‍👩‍👧".repeat(20)));
+    }
+
+    #[test]
+    fn test_invalid_positions_do_not_panic() {
+        // 不在字符边界上的位置会被调整，而不是导致 panic
+        let result = SearchResult::new(1, "中文内容".to_string(), vec![(1, 4), (2, 5)]);
+
+        assert!(result.preview.contains("中文"));
+        assert_eq!(result.highlighted_content(), "<mark>中文</mark>内容");
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn options() -> impl Strategy<Value = SearchOptions> {
+            (any::<bool>(), any::<bool>(), any::<bool>()).prop_map(|(case_sensitive, whole_word, match_diacritics)| {
+                SearchOptions { case_sensitive, whole_word, use_regex: false, match_diacritics }
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn search_positions_are_valid(text in any::<String>(), query in "\\PC{1,4}", options in options()) {
+                let mut manager = SearchManager::new();
+                manager.set_options(options);
+
+                for result in manager.search(&query, &text) {
+                    let line = &result.line_content;
+                    let mut last_end = 0;
+                    for &(start, end) in &result.match_positions {
+                        prop_assert!(start < end && end <= line.len());
+                        prop_assert!(start >= last_end);
+                        prop_assert!(line.is_char_boundary(start) && line.is_char_boundary(end));
+                        last_end = end;
+                    }
+                    let _ = result.highlighted_content();
+                }
+            }
+
+            #[test]
+            fn exact_matches_equal_query(text in any::<String>(), query in "\\PC{1,3}") {
+                let mut manager = SearchManager::new();
+                manager.set_options(SearchOptions { case_sensitive: true, ..Default::default() });
+
+                for result in manager.search(&query, &text) {
+                    for &(start, end) in &result.match_positions {
+                        prop_assert_eq!(&result.line_content[start..end], query.as_str());
+                    }
+                }
+            }
+
+            #[test]
+            fn case_insensitive_finds_exact_occurrences(prefix in any::<String>(), query in "\\PC{1,3}", suffix in any::<String>()) {
+                let line = format!("{}{}{}", prefix, query, suffix).replace(['\n', '\r'], " ");
+                let query = query.replace(['\n', '\r'], " ");
+                let mut manager = SearchManager::new();
+
+                // 关键词原样出现在文本中时，不区分大小写的搜索一定能找到
+                prop_assert!(!manager.search(&query, &line).is_empty());
+            }
+
+            #[test]
+            fn preview_never_panics(line in any::<String>(), start in 0usize..256, len in 0usize..32) {
+                let result = SearchResult::new(1, line.clone(), vec![(start, start + len)]);
+                let _ = result.highlighted_content();
+                prop_assert!(result.preview.len() <= line.len() + 6);
+            }
+        }
     }
 }
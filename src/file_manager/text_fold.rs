@@ -0,0 +1,193 @@
+//! 文本折叠模块
+//!
+//! 为不区分大小写、忽略变音符号的匹配生成折叠文本：
+//! - 完整大小写折叠（如 `ẞ`/`ß` → `ss`，`İ` → `i`）
+//! - 去掉变音符号、全角转半角
+//! - 记录折叠文本到原始文本的字节映射，匹配位置可以还原到原始行
+
+use unicode_normalization::UnicodeNormalization;
+
+/// 折叠选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FoldOptions {
+    /// 折叠大小写
+    pub case: bool,
+    /// 去掉变音符号并转换全角字符
+    pub diacritics: bool,
+}
+
+impl FoldOptions {
+    /// 是否不做任何折叠
+    pub fn is_identity(&self) -> bool {
+        !self.case && !self.diacritics
+    }
+}
+
+/// 折叠后的文本及其到原始文本的位置映射
+#[derive(Debug, Clone)]
+pub struct FoldedText {
+    /// 折叠后的文本
+    pub text: String,
+    /// 折叠文本中每个字节对应的原始字符范围
+    origins: Vec<(usize, usize)>,
+}
+
+impl FoldedText {
+    /// 折叠文本
+    pub fn new(text: &str, options: FoldOptions) -> Self {
+        let mut folded = Self {
+            text: String::with_capacity(text.len()),
+            origins: Vec::with_capacity(text.len()),
+        };
+
+        for (start, ch) in text.char_indices() {
+            let end = start + ch.len_utf8();
+            let piece = fold_char(ch, options);
+
+            for _ in 0..piece.len() {
+                folded.origins.push((start, end));
+            }
+            folded.text.push_str(&piece);
+        }
+
+        folded
+    }
+
+    /// 将折叠文本中的字节范围映射回原始文本
+    ///
+    /// 返回的范围总是落在原始文本的字符边界上；
+    /// 折叠文本中的半个字符（如 `ss` 中的一个 `s`）会扩展为完整的原始字符
+    pub fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let original_start = match self.origins.get(start) {
+            Some(origin) => origin.0,
+            // 起点位于文本末尾
+            None => self.origins.last().map(|o| o.1).unwrap_or(0),
+        };
+
+        let original_end = if end <= start {
+            original_start
+        } else {
+            self.origins
+                .get(end - 1)
+                .map(|o| o.1)
+                .unwrap_or(original_start)
+        };
+
+        (original_start, original_end)
+    }
+}
+
+/// 折叠单个字符
+fn fold_char(ch: char, options: FoldOptions) -> String {
+    let mut result = String::new();
+
+    let ch = if options.diacritics { to_half_width(ch) } else { ch };
+
+    if options.case {
+        match ch {
+            // 完整大小写折叠中的特殊情况
+            'ß' | 'ẞ' => result.push_str("ss"),
+            'İ' => result.push('i'),
+            'ς' => result.push('σ'),
+            _ => result.extend(ch.to_lowercase()),
+        }
+    } else {
+        result.push(ch);
+    }
+
+    if options.diacritics {
+        // 分解后去掉组合用变音符号，再重新组合（保持韩文音节等不变）
+        result = result
+            .nfd()
+            .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+            .nfc()
+            .collect();
+    }
+
+    result
+}
+
+/// 将全角 ASCII 字符和全角空格转换为半角
+fn to_half_width(ch: char) -> char {
+    match ch {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+/// 将字节位置向前调整到最近的字符边界
+pub fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    let mut index = index;
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// 将字节位置向后调整到最近的字符边界
+pub fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    let mut index = index;
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CASE: FoldOptions = FoldOptions { case: true, diacritics: false };
+    const ALL: FoldOptions = FoldOptions { case: true, diacritics: true };
+
+    #[test]
+    fn test_fold_case_changes_length() {
+        let folded = FoldedText::new("Straẞe İstanbul", CASE);
+
+        assert_eq!(folded.text, "strasse istanbul");
+
+        // `ss` 映射回原文中的 `ẞ`（3 个字节）
+        let pos = folded.text.find("sse").unwrap();
+        let (start, end) = folded.original_range(pos, pos + 3);
+        assert_eq!(&"Straẞe İstanbul"[start..end], "ẞe");
+
+        let pos = folded.text.find("istanbul").unwrap();
+        let (start, end) = folded.original_range(pos, pos + "istanbul".len());
+        assert_eq!(&"Straẞe İstanbul"[start..end], "İstanbul");
+    }
+
+    #[test]
+    fn test_fold_diacritics_and_width() {
+        let original = "Ｃａｆé 한국어";
+        let folded = FoldedText::new(original, ALL);
+
+        assert_eq!(folded.text, "cafe 한국어");
+        let (start, end) = folded.original_range(0, 4);
+        assert_eq!(&original[start..end], "Ｃａｆé");
+    }
+
+    #[test]
+    fn test_partial_character_expands() {
+        let original = "ß";
+        let folded = FoldedText::new(original, CASE);
+
+        // 只匹配到 `ss` 的第一个 `s` 时仍返回完整字符
+        assert_eq!(folded.original_range(0, 1), (0, 2));
+        assert_eq!(folded.original_range(2, 2), (2, 2));
+    }
+
+    #[test]
+    fn test_char_boundaries() {
+        let text = "中文";
+        assert_eq!(floor_char_boundary(text, 1), 0);
+        assert_eq!(ceil_char_boundary(text, 1), 3);
+        assert_eq!(ceil_char_boundary(text, 10), 6);
+    }
+}
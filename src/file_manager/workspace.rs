@@ -0,0 +1,258 @@
+//! 工作区模块
+//!
+//! 管理当前打开的文件夹：
+//! - 打开文件夹作为工作区
+//! - 多根工作区（同时显示多个文件树）
+//! - 最近打开的工作区列表
+
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+
+use super::paths::normalize_path;
+use super::{FileItem, FileTree};
+
+/// 工作区
+pub struct Workspace {
+    /// 工作区中的文件树（每个根文件夹一个）
+    roots: Vec<FileTree>,
+    /// 最近打开的工作区
+    recent: Vec<PathBuf>,
+    /// 最近列表的最大数量
+    max_recent: usize,
+}
+
+impl Workspace {
+    /// 创建空的工作区
+    pub fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            recent: Vec::new(),
+            max_recent: 10,
+        }
+    }
+
+    /// 打开文件夹，替换当前所有根文件夹
+    pub fn open(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let file_tree = Self::load_tree(path.as_ref())?;
+        self.add_to_recent(file_tree.root_path().to_path_buf());
+        self.roots = vec![file_tree];
+        Ok(())
+    }
+
+    /// 添加根文件夹（多根工作区）
+    ///
+    /// 已经存在的根文件夹不会重复添加
+    pub fn add_root(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let file_tree = Self::load_tree(path.as_ref())?;
+        let root_path = file_tree.root_path().to_path_buf();
+
+        if !self.roots.iter().any(|tree| tree.root_path() == root_path) {
+            self.roots.push(file_tree);
+        }
+        self.add_to_recent(root_path);
+
+        Ok(())
+    }
+
+    /// 移除根文件夹
+    pub fn remove_root(&mut self, path: &Path) {
+        self.roots.retain(|tree| tree.root_path() != path);
+    }
+
+    /// 加载文件树，使用绝对路径作为根
+    fn load_tree(path: &Path) -> Result<FileTree> {
+        if !path.is_dir() {
+            return Err(anyhow::anyhow!("不是文件夹: {}", path.display()));
+        }
+
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(path)
+        };
+
+        FileTree::new(normalize_path(&absolute))
+    }
+
+    /// 判断工作区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// 获取所有文件树
+    pub fn roots(&self) -> &[FileTree] {
+        &self.roots
+    }
+
+    /// 查找包含指定路径的文件树
+    pub fn root_for(&self, path: &Path) -> Option<&FileTree> {
+        self.roots.iter().find(|tree| path.starts_with(tree.root_path()))
+    }
+
+    /// 查找包含指定路径的文件树（可变引用）
+    pub fn root_for_mut(&mut self, path: &Path) -> Option<&mut FileTree> {
+        self.roots.iter_mut().find(|tree| path.starts_with(tree.root_path()))
+    }
+
+    /// 切换目录的展开/折叠状态
+    pub fn toggle_expand(&mut self, path: &Path) -> bool {
+        self.root_for_mut(path)
+            .map(|tree| tree.toggle_expand(path))
+            .unwrap_or(false)
+    }
+
+    /// 展开或折叠目录
+    pub fn set_expanded(&mut self, path: &Path, expanded: bool) -> bool {
+        self.root_for_mut(path)
+            .map(|tree| tree.set_expanded(path, expanded))
+            .unwrap_or(false)
+    }
+
+    /// 所有文件树中展开的目录
+    pub fn expanded_dirs(&self) -> Vec<PathBuf> {
+        self.roots
+            .iter()
+            .flat_map(|tree| tree.expanded_paths())
+            .collect()
+    }
+
+    /// 刷新所有文件树
+    pub fn refresh(&mut self) -> Result<()> {
+        for tree in &mut self.roots {
+            tree.refresh()?;
+        }
+        Ok(())
+    }
+
+    /// 获取所有根文件夹中的 Markdown 文件
+    pub fn markdown_files(&self) -> Vec<&FileItem> {
+        self.roots
+            .iter()
+            .flat_map(|tree| tree.get_markdown_files())
+            .collect()
+    }
+
+    /// 添加到最近打开列表
+    fn add_to_recent(&mut self, path: PathBuf) {
+        if let Some(pos) = self.recent.iter().position(|p| p == &path) {
+            self.recent.remove(pos);
+        }
+
+        self.recent.insert(0, path);
+
+        if self.recent.len() > self.max_recent {
+            self.recent.truncate(self.max_recent);
+        }
+    }
+
+    /// 获取最近打开的工作区
+    pub fn recent_workspaces(&self) -> &[PathBuf] {
+        &self.recent
+    }
+
+    /// 恢复保存的最近工作区列表（最近的在前）
+    pub fn set_recent_workspaces(&mut self, recent: Vec<PathBuf>) {
+        self.recent = recent;
+        self.recent.truncate(self.max_recent);
+    }
+
+    /// 清空最近打开列表
+    pub fn clear_recent(&mut self) {
+        self.recent.clear();
+    }
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_empty_workspace() {
+        let workspace = Workspace::new();
+
+        assert!(workspace.is_empty());
+        assert!(workspace.markdown_files().is_empty());
+        assert!(workspace.root_for(Path::new("/tmp/a.md")).is_none());
+    }
+
+    #[test]
+    fn test_open_replaces_roots() -> Result<()> {
+        let first = TempDir::new()?;
+        let second = TempDir::new()?;
+
+        let mut workspace = Workspace::new();
+        workspace.open(first.path())?;
+        workspace.open(second.path())?;
+
+        assert_eq!(workspace.roots().len(), 1);
+        assert_eq!(workspace.roots()[0].root_path(), second.path());
+        assert_eq!(workspace.recent_workspaces()[0], second.path());
+        assert_eq!(workspace.recent_workspaces()[1], first.path());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_root() -> Result<()> {
+        let first = TempDir::new()?;
+        let second = TempDir::new()?;
+        File::create(first.path().join("a.md"))?;
+        fs::create_dir(second.path().join("sub"))?;
+        File::create(second.path().join("sub").join("b.md"))?;
+
+        let mut workspace = Workspace::new();
+        workspace.add_root(first.path())?;
+        workspace.add_root(second.path())?;
+        workspace.add_root(first.path())?; // 重复
+
+        assert_eq!(workspace.roots().len(), 2);
+        assert_eq!(workspace.markdown_files().len(), 2);
+
+        let file = second.path().join("sub").join("b.md");
+        assert_eq!(workspace.root_for(&file).map(|t| t.root_path()), Some(second.path()));
+
+        workspace.remove_root(first.path());
+        assert_eq!(workspace.roots().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expanded_dirs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let sub = temp_dir.path().join("sub");
+        fs::create_dir(&sub)?;
+
+        let mut workspace = Workspace::new();
+        workspace.open(temp_dir.path())?;
+
+        assert!(workspace.set_expanded(&sub, false));
+        assert!(!workspace.expanded_dirs().contains(&sub));
+        assert!(workspace.set_expanded(&sub, true));
+        assert!(workspace.expanded_dirs().contains(&sub));
+        assert!(!workspace.set_expanded(&temp_dir.path().join("missing"), true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_file_is_rejected() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        File::create(&file)?;
+
+        let mut workspace = Workspace::new();
+        assert!(workspace.open(&file).is_err());
+        assert!(workspace.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! 工作区搜索模块
+//!
+//! 在工作区的所有文件中并行搜索：
+//! - 使用线程池并行读取和匹配文件
+//! - 每个文件的结果一产生就通过通道发送，界面可以边搜边显示
+//! - 可以随时取消
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use anyhow::Result;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use rayon::prelude::*;
+use walkdir::WalkDir;
+
+use super::{SearchManager, SearchOptions, SearchResult};
+
+/// 超过该大小的文件不参与搜索
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 单个文件的搜索结果
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 文件中的匹配行
+    pub results: Vec<SearchResult>,
+}
+
+/// 工作区搜索事件
+#[derive(Debug, Clone)]
+pub enum SearchEvent {
+    /// 一个文件的搜索结果
+    Matches(FileMatches),
+    /// 搜索结束
+    Finished {
+        /// 已搜索的文件数量
+        files_searched: usize,
+        /// 是否被取消
+        cancelled: bool,
+    },
+}
+
+/// 正在进行的工作区搜索
+///
+/// 结果通过 [`WorkspaceSearch::start`] 返回的通道接收；
+/// 丢弃句柄或调用 [`WorkspaceSearch::cancel`] 都会停止搜索
+#[derive(Debug)]
+pub struct WorkspaceSearch {
+    /// 搜索关键词
+    query: String,
+    /// 取消标记
+    cancelled: Arc<AtomicBool>,
+}
+
+impl WorkspaceSearch {
+    /// 在后台开始搜索 `roots` 下的所有文件
+    ///
+    /// 正则表达式无效时立即返回错误
+    pub fn start(
+        query: &str,
+        options: SearchOptions,
+        roots: Vec<PathBuf>,
+    ) -> Result<(Self, UnboundedReceiver<SearchEvent>)> {
+        let regex = SearchManager::build_regex(query, &options)?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = unbounded();
+
+        let flag = cancelled.clone();
+        std::thread::spawn(move || {
+            let files_searched = AtomicUsize::new(0);
+
+            roots
+                .iter()
+                .flat_map(|root| searchable_files(root))
+                .par_bridge()
+                .for_each_with(sender.clone(), |sender, path| {
+                    if flag.load(Ordering::Relaxed) {
+                        return;
+                    }
+
+                    // 无法按 UTF-8 读取的文件（如图片）直接跳过
+                    let Ok(content) = fs::read_to_string(&path) else {
+                        return;
+                    };
+                    files_searched.fetch_add(1, Ordering::Relaxed);
+
+                    let results = SearchManager::search_with_regex(&regex, &options, &content);
+                    if !results.is_empty() && !flag.load(Ordering::Relaxed) {
+                        send(sender, &flag, SearchEvent::Matches(FileMatches { path, results }));
+                    }
+                });
+
+            let _ = sender.unbounded_send(SearchEvent::Finished {
+                files_searched: files_searched.into_inner(),
+                cancelled: flag.load(Ordering::Relaxed),
+            });
+        });
+
+        Ok((Self { query: query.to_string(), cancelled }, receiver))
+    }
+
+    /// 搜索关键词
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// 取消搜索
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for WorkspaceSearch {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// 发送事件；接收端已关闭时视为取消
+fn send(sender: &UnboundedSender<SearchEvent>, cancelled: &AtomicBool, event: SearchEvent) {
+    if sender.unbounded_send(event).is_err() {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 列出根目录下需要搜索的文件（跳过隐藏文件和过大的文件）
+fn searchable_files(root: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.metadata().map(|m| m.len() <= MAX_FILE_SIZE).unwrap_or(false))
+        .map(|entry| entry.into_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on_stream;
+    use tempfile::TempDir;
+
+    fn collect(receiver: UnboundedReceiver<SearchEvent>) -> (Vec<FileMatches>, usize, bool) {
+        let mut matches = Vec::new();
+        for event in block_on_stream(receiver) {
+            match event {
+                SearchEvent::Matches(file) => matches.push(file),
+                SearchEvent::Finished { files_searched, cancelled } => {
+                    return (matches, files_searched, cancelled);
+                }
+            }
+        }
+        panic!("搜索没有结束事件");
+    }
+
+    #[test]
+    fn test_search_workspace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("docs"))?;
+        fs::create_dir(root.join(".git"))?;
+        fs::write(root.join("a.md"), "hello world\nbye")?;
+        fs::write(root.join("docs").join("b.txt"), "say Hello")?;
+        fs::write(root.join(".git").join("config"), "hello")?;
+        fs::write(root.join("image.png"), [0xff, 0xfe, 0x00])?;
+
+        let (_search, receiver) = WorkspaceSearch::start("hello", SearchOptions::default(), vec![root.to_path_buf()])?;
+        let (mut matches, files_searched, cancelled) = collect(receiver);
+        matches.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert!(!cancelled);
+        assert_eq!(files_searched, 2);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].path, root.join("a.md"));
+        assert_eq!(matches[1].results[0].column(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelled_search_finishes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        for i in 0..50 {
+            fs::write(temp_dir.path().join(format!("{}.md", i)), "match")?;
+        }
+
+        let (search, receiver) = WorkspaceSearch::start("match", SearchOptions::default(), vec![temp_dir.path().to_path_buf()])?;
+        search.cancel();
+
+        // 取消后通道仍会收到结束事件（搜索可能在取消前就已完成）
+        let (matches, files_searched, _) = collect(receiver);
+        assert!(search.is_cancelled());
+        assert!(matches.len() <= files_searched);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let options = SearchOptions { use_regex: true, ..Default::default() };
+        assert!(WorkspaceSearch::start("(", options, Vec::new()).is_err());
+    }
+}
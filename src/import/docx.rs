@@ -0,0 +1,1002 @@
+//! Word 导入
+//!
+//! 读取 .docx 文档包中的正文、样式、编号、关系和脚注，转换为文档结构：
+//! - 按样式识别标题（`Heading 1`、`Title` 或大纲级别）、引用和代码
+//! - 按编号定义还原有序列表、无序列表和嵌套层级，编号被打断时保持连续
+//! - 粗体、斜体、删除线，字符样式和等宽字体识别为行内代码
+//! - 超链接（包括域代码形式的链接）和指向书签的链接
+//! - 文档中的图片保存到 `assets/` 文件夹
+//! - 脚注和尾注按引用顺序编号
+//! - 忽略目录、修订中删除的内容和文本框
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use anyhow::{anyhow, bail, Context, Result};
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+use crate::markdown::{build_lists, flatten, Align, Assets, Block, Document, Inline, ListEntry};
+
+/// 被识别为代码的字体
+const MONOSPACE_FONTS: &[&str] = &["courier", "consolas", "menlo", "monaco", "mono", "code", "fixedsys", "lucida console"];
+
+/// 把 Word 文档转换为文档
+pub(super) fn convert(source: &Path, assets: &mut Assets) -> Result<Document> {
+    let file = File::open(source).with_context(|| format!("无法打开文件: {}", source.display()))?;
+    let mut package = Package {
+        archive: ZipArchive::new(file).with_context(|| format!("不是有效的 Word 文档: {}", source.display()))?,
+    };
+
+    let document = package
+        .xml("word/document.xml")?
+        .ok_or_else(|| anyhow!("Word 文档中没有正文: {}", source.display()))?;
+    let body = document.child("body").ok_or_else(|| anyhow!("Word 文档中没有正文: {}", source.display()))?;
+    let styles = package.xml("word/styles.xml")?.map(|styles| Styles::parse(&styles)).unwrap_or_default();
+    let numbering = package.xml("word/numbering.xml")?.map(|numbering| Numbering::parse(&numbering)).unwrap_or_default();
+    let footnotes = package.xml("word/footnotes.xml")?;
+    let endnotes = package.xml("word/endnotes.xml")?;
+
+    let mut notes = HashMap::new();
+    for (root, kind) in [(&footnotes, "footnote"), (&endnotes, "endnote")] {
+        let Some(root) = root else {
+            continue;
+        };
+        for note in root.elements().filter(|e| e.name == kind) {
+            if let Some(id) = note.attr("id") {
+                notes.insert(format!("{}:{}", kind, id), note);
+            }
+        }
+    }
+
+    let document_rels = package.relationships("word/_rels/document.xml.rels")?;
+    let mut converter = Converter {
+        package,
+        assets,
+        styles,
+        numbering,
+        rels: document_rels,
+        notes,
+        labels: HashMap::new(),
+        order: Vec::new(),
+        counters: HashMap::new(),
+        heading_ids: Vec::new(),
+        in_note: false,
+    };
+    let blocks = converter.blocks(body)?;
+
+    // 按引用顺序转换脚注和尾注，其中的引用追加到末尾
+    converter.in_note = true;
+    let mut footnotes = Vec::new();
+    let mut index = 0;
+    while index < converter.order.len() {
+        let key = converter.order[index].clone();
+        converter.rels = if key.starts_with("endnote:") {
+            converter.package.relationships("word/_rels/endnotes.xml.rels")?
+        } else {
+            converter.package.relationships("word/_rels/footnotes.xml.rels")?
+        };
+        if let Some(note) = converter.notes.get(&key).copied() {
+            let content = converter.blocks(note)?;
+            footnotes.push((converter.labels[&key].clone(), content));
+        }
+        index += 1;
+    }
+
+    let heading_ids = std::mem::take(&mut converter.heading_ids);
+    let mut document = Document { blocks, footnotes };
+    document.link_headings(&heading_ids);
+    Ok(document)
+}
+
+/// 文档包
+struct Package {
+    archive: ZipArchive<File>,
+}
+
+impl Package {
+    /// 读取并解析文档包中的 XML 文件，文件不存在时返回 `None`
+    fn xml(&mut self, name: &str) -> Result<Option<XmlElement>> {
+        let Some(data) = self.read(name)? else {
+            return Ok(None);
+        };
+        let text = String::from_utf8(data).with_context(|| format!("{} 不是 UTF-8 编码", name))?;
+        parse_xml(&text).with_context(|| format!("无法解析 Word 文档中的 {}", name)).map(Some)
+    }
+
+    fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>> {
+        let mut entry = match self.archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("无法读取 Word 文档中的 {}", name)),
+        };
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).with_context(|| format!("无法读取 Word 文档中的 {}", name))?;
+        Ok(Some(data))
+    }
+
+    /// 读取关系文件：关系编号对应的目标和是否为外部地址
+    fn relationships(&mut self, name: &str) -> Result<HashMap<String, (String, bool)>> {
+        let mut rels = HashMap::new();
+        if let Some(root) = self.xml(name)? {
+            for rel in root.elements().filter(|e| e.name == "Relationship") {
+                if let (Some(id), Some(target)) = (rel.attr("Id"), rel.attr("Target")) {
+                    let external = rel.attr("TargetMode") == Some("External");
+                    rels.insert(id.to_string(), (target.to_string(), external));
+                }
+            }
+        }
+        Ok(rels)
+    }
+}
+
+/// 段落样式的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StyleKind {
+    Heading(u8),
+    Quote,
+    Code,
+    /// 目录，导入时忽略
+    Toc,
+}
+
+/// 样式中与导入有关的属性
+#[derive(Debug, Clone, Default)]
+struct Style {
+    name: String,
+    based_on: Option<String>,
+    outline_level: Option<u8>,
+    /// 样式中的编号：编号和层级
+    numbering: Option<(String, usize)>,
+    format: RunFormat,
+}
+
+#[derive(Debug, Default)]
+struct Styles {
+    styles: HashMap<String, Style>,
+}
+
+impl Styles {
+    fn parse(root: &XmlElement) -> Self {
+        let mut styles = HashMap::new();
+        for element in root.elements().filter(|e| e.name == "style") {
+            let Some(id) = element.attr("styleId") else {
+                continue;
+            };
+            let properties = element.child("pPr");
+            let style = Style {
+                name: element.child("name").and_then(|e| e.attr("val")).unwrap_or(id).to_string(),
+                based_on: element.child("basedOn").and_then(|e| e.attr("val")).map(str::to_string),
+                outline_level: properties
+                    .and_then(|p| p.child("outlineLvl"))
+                    .and_then(|e| e.attr("val"))
+                    .and_then(|v| v.parse().ok()),
+                numbering: properties.and_then(|p| p.child("numPr")).and_then(numbering_reference),
+                format: element.child("rPr").map(RunFormat::parse).unwrap_or_default(),
+            };
+            styles.insert(id.to_string(), style);
+        }
+        Self { styles }
+    }
+
+    /// 按样式及其基础样式查找属性
+    fn find<T>(&self, id: &str, get: impl Fn(&str, &Style) -> Option<T>) -> Option<T> {
+        let mut current = Some(id);
+        for _ in 0..16 {
+            let id = current?;
+            let style = self.styles.get(id);
+            if let Some(value) = get(id, style.unwrap_or(&Style::default())) {
+                return Some(value);
+            }
+            current = style?.based_on.as_deref();
+        }
+        None
+    }
+
+    /// 段落样式的用途
+    fn kind(&self, id: &str) -> Option<StyleKind> {
+        self.find(id, |id, style| {
+            let name = style.name.to_lowercase();
+            let id = id.to_lowercase();
+            for candidate in [name.as_str(), id.as_str()] {
+                let level = candidate.strip_prefix("heading").map(str::trim).and_then(|n| n.parse::<u8>().ok());
+                if let Some(level) = level.filter(|level| (1..=9).contains(level)) {
+                    return Some(StyleKind::Heading(level.min(6)));
+                }
+                if candidate.starts_with("toc") {
+                    return Some(StyleKind::Toc);
+                }
+            }
+            if name == "title" {
+                return Some(StyleKind::Heading(1));
+            }
+            if let Some(level) = style.outline_level.filter(|level| *level < 6) {
+                return Some(StyleKind::Heading(level + 1));
+            }
+            if name.contains("quote") || name == "block text" {
+                return Some(StyleKind::Quote);
+            }
+            if ["code", "source", "verbatim", "preformatted"].iter().any(|word| name.contains(word)) {
+                return Some(StyleKind::Code);
+            }
+            None
+        })
+    }
+
+    /// 字符样式的格式
+    fn run_format(&self, id: &str) -> RunFormat {
+        let mut format = self.find(id, |_, style| Some(style.format)).unwrap_or_default();
+        let name = self.styles.get(id).map(|style| style.name.to_lowercase()).unwrap_or_default();
+        match name.as_str() {
+            "strong" => format.bold = true,
+            "emphasis" => format.italic = true,
+            name if ["code", "verbatim", "typewriter"].iter().any(|word| name.contains(word)) => format.code = true,
+            _ => {}
+        }
+        format
+    }
+}
+
+/// 编号定义：编号和层级对应的起始编号，无序列表为 `None`
+#[derive(Debug, Default)]
+struct Numbering {
+    levels: HashMap<(String, usize), Option<u64>>,
+}
+
+impl Numbering {
+    fn parse(root: &XmlElement) -> Self {
+        let mut abstract_levels: HashMap<&str, HashMap<usize, Option<u64>>> = HashMap::new();
+        for abstract_num in root.elements().filter(|e| e.name == "abstractNum") {
+            let Some(id) = abstract_num.attr("abstractNumId") else {
+                continue;
+            };
+            let levels = abstract_num.elements().filter(|e| e.name == "lvl").filter_map(|level| {
+                let index = level.attr("ilvl")?.parse().ok()?;
+                let format = level.child("numFmt").and_then(|e| e.attr("val")).unwrap_or("decimal");
+                let start = level.child("start").and_then(|e| e.attr("val")).and_then(|v| v.parse().ok()).unwrap_or(1);
+                Some((index, (!matches!(format, "bullet" | "none")).then_some(start)))
+            });
+            abstract_levels.insert(id, levels.collect());
+        }
+
+        let mut levels = HashMap::new();
+        for num in root.elements().filter(|e| e.name == "num") {
+            let Some(id) = num.attr("numId") else {
+                continue;
+            };
+            let abstract_id = num.child("abstractNumId").and_then(|e| e.attr("val")).unwrap_or_default();
+            for (index, start) in abstract_levels.get(abstract_id).into_iter().flatten() {
+                levels.insert((id.to_string(), *index), *start);
+            }
+            for level in num.elements().filter(|e| e.name == "lvlOverride") {
+                let Some(index) = level.attr("ilvl").and_then(|v| v.parse().ok()) else {
+                    continue;
+                };
+                let start = level.child("startOverride").and_then(|e| e.attr("val")).and_then(|v| v.parse().ok());
+                if let (Some(start), Some(Some(existing))) = (start, levels.get_mut(&(id.to_string(), index))) {
+                    *existing = start;
+                }
+            }
+        }
+        Self { levels }
+    }
+}
+
+/// 文字的格式
+#[derive(Debug, Clone, Copy, Default)]
+struct RunFormat {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+}
+
+impl RunFormat {
+    fn parse(properties: &XmlElement) -> Self {
+        let enabled = |name: &str| {
+            properties.child(name).is_some_and(|e| !matches!(e.attr("val"), Some("0" | "false" | "off" | "none")))
+        };
+        let font = properties.child("rFonts").and_then(|e| e.attr("ascii")).unwrap_or_default().to_lowercase();
+        Self {
+            bold: enabled("b"),
+            italic: enabled("i"),
+            strike: enabled("strike") || enabled("dstrike"),
+            code: MONOSPACE_FONTS.iter().any(|monospace| font.contains(monospace)),
+        }
+    }
+}
+
+/// 段落中的域代码
+struct Field {
+    instruction: String,
+    /// 已读完域代码，之后是域结果
+    separated: bool,
+    /// 超链接域的地址和显示内容
+    link: Option<(String, Vec<Inline>)>,
+}
+
+/// 等待合并的连续段落
+enum Pending {
+    None,
+    List(Vec<ListEntry>),
+    Quote(Vec<Block>),
+    Code(Vec<String>),
+}
+
+struct Converter<'a> {
+    package: Package,
+    assets: &'a mut Assets,
+    styles: Styles,
+    numbering: Numbering,
+    /// 当前部分的关系
+    rels: HashMap<String, (String, bool)>,
+    /// 脚注和尾注的内容，键为 `footnote:编号` 或 `endnote:编号`
+    notes: HashMap<String, &'a XmlElement>,
+    labels: HashMap<String, String>,
+    order: Vec<String>,
+    /// 有序列表每个层级当前的编号
+    counters: HashMap<(String, usize), u64>,
+    heading_ids: Vec<Vec<String>>,
+    in_note: bool,
+}
+
+impl<'a> Converter<'a> {
+    /// 转换正文、表格单元格或脚注中的段落和表格
+    fn blocks(&mut self, container: &XmlElement) -> Result<Vec<Block>> {
+        let mut blocks = Vec::new();
+        let mut pending = Pending::None;
+        self.container(container, &mut blocks, &mut pending)?;
+        flush(&mut blocks, &mut pending);
+        Ok(blocks)
+    }
+
+    fn container(&mut self, container: &XmlElement, blocks: &mut Vec<Block>, pending: &mut Pending) -> Result<()> {
+        for element in container.elements() {
+            match element.name.as_str() {
+                "p" => self.paragraph(element, blocks, pending)?,
+                "tbl" => {
+                    flush(blocks, pending);
+                    if let Some(table) = self.table(element)? {
+                        blocks.push(table);
+                    }
+                }
+                "sdt" => {
+                    if let Some(content) = element.child("sdtContent") {
+                        self.container(content, blocks, pending)?;
+                    }
+                }
+                "customXml" | "ins" | "moveTo" => self.container(element, blocks, pending)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn paragraph(&mut self, paragraph: &XmlElement, blocks: &mut Vec<Block>, pending: &mut Pending) -> Result<()> {
+        let properties = paragraph.child("pPr");
+        let style = properties.and_then(|p| p.child("pStyle")).and_then(|e| e.attr("val"));
+        let kind = style.and_then(|style| self.styles.kind(style)).or_else(|| {
+            let level: u8 = properties?.child("outlineLvl")?.attr("val")?.parse().ok()?;
+            (level < 6).then_some(StyleKind::Heading(level + 1))
+        });
+        if kind == Some(StyleKind::Toc) {
+            return Ok(());
+        }
+
+        if kind == Some(StyleKind::Code) {
+            let line = self.plain_text(paragraph);
+            match pending {
+                Pending::Code(lines) => lines.push(line),
+                _ => {
+                    flush(blocks, pending);
+                    *pending = Pending::Code(vec![line]);
+                }
+            }
+            return Ok(());
+        }
+
+        let mut bookmarks = Vec::new();
+        let content = self.inlines(paragraph, &mut bookmarks)?;
+
+        if let Some(StyleKind::Heading(level)) = kind {
+            flush(blocks, pending);
+            if !self.in_note {
+                self.heading_ids.push(bookmarks);
+            }
+            blocks.push(Block::Heading(level, content));
+            return Ok(());
+        }
+
+        let numbering = properties
+            .and_then(|p| p.child("numPr"))
+            .and_then(numbering_reference)
+            .or_else(|| style.and_then(|style| self.styles.find(style, |_, s| s.numbering.clone())))
+            .filter(|(id, _)| id != "0");
+        if let Some((id, level)) = numbering {
+            let start = self.list_number(&id, level);
+            let (checked, content) = task_marker(content);
+            let entry = ListEntry { list: id, level, start, checked, content };
+            match pending {
+                Pending::List(entries) => entries.push(entry),
+                _ => {
+                    flush(blocks, pending);
+                    *pending = Pending::List(vec![entry]);
+                }
+            }
+            return Ok(());
+        }
+
+        if is_empty(&content) {
+            return Ok(());
+        }
+        if kind == Some(StyleKind::Quote) {
+            match pending {
+                Pending::Quote(quote) => quote.push(Block::Paragraph(content)),
+                _ => {
+                    flush(blocks, pending);
+                    *pending = Pending::Quote(vec![Block::Paragraph(content)]);
+                }
+            }
+            return Ok(());
+        }
+
+        flush(blocks, pending);
+        blocks.push(Block::Paragraph(content));
+        Ok(())
+    }
+
+    /// 有序列表项的编号，无序列表返回 `None`；上级列表项出现时重新开始下级编号
+    fn list_number(&mut self, id: &str, level: usize) -> Option<u64> {
+        self.counters.retain(|(other, other_level), _| other != id || *other_level <= level);
+        let start = self.numbering.levels.get(&(id.to_string(), level)).copied().unwrap_or(None)?;
+        let counter = self.counters.entry((id.to_string(), level)).or_insert(start.saturating_sub(1));
+        *counter += 1;
+        Some(*counter)
+    }
+
+    fn table(&mut self, table: &XmlElement) -> Result<Option<Block>> {
+        let mut rows = Vec::new();
+        let mut alignments = Vec::new();
+        for row in table.elements().filter(|e| e.name == "tr") {
+            let mut cells = Vec::new();
+            for cell in row.elements().filter(|e| e.name == "tc") {
+                let properties = cell.child("tcPr");
+                let merged = properties
+                    .and_then(|p| p.child("vMerge"))
+                    .is_some_and(|e| e.attr("val").is_none_or(|v| v == "continue"));
+                let content = if merged { Vec::new() } else { flatten(self.blocks(cell)?) };
+
+                let align = cell
+                    .elements()
+                    .find(|e| e.name == "p")
+                    .and_then(|p| p.child("pPr"))
+                    .and_then(|p| p.child("jc"))
+                    .and_then(|e| e.attr("val"));
+                let align = match align {
+                    Some("center") => Align::Center,
+                    Some("right" | "end") => Align::Right,
+                    Some("left" | "start") => Align::Left,
+                    _ => Align::None,
+                };
+                if alignments.len() <= cells.len() {
+                    alignments.push(align);
+                }
+
+                cells.push(content);
+                let span: usize = properties
+                    .and_then(|p| p.child("gridSpan"))
+                    .and_then(|e| e.attr("val"))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                cells.extend(std::iter::repeat_n(Vec::new(), span.clamp(1, 100) - 1));
+            }
+            if !cells.is_empty() {
+                rows.push(cells);
+            }
+        }
+        Ok((!rows.is_empty()).then_some(Block::Table { alignments, rows }))
+    }
+
+    /// 转换段落中的文字、链接、图片和脚注引用，同时收集书签名称
+    fn inlines(&mut self, parent: &XmlElement, bookmarks: &mut Vec<String>) -> Result<Vec<Inline>> {
+        let mut inlines = Vec::new();
+        let mut fields: Vec<Field> = Vec::new();
+        self.collect_inlines(parent, bookmarks, &mut fields, &mut inlines)?;
+        // 没有结束的超链接域
+        while let Some(field) = fields.pop() {
+            if let Some((_, content)) = field.link {
+                inlines.extend(content);
+            }
+        }
+        Ok(inlines)
+    }
+
+    fn collect_inlines(
+        &mut self,
+        parent: &XmlElement,
+        bookmarks: &mut Vec<String>,
+        fields: &mut Vec<Field>,
+        inlines: &mut Vec<Inline>,
+    ) -> Result<()> {
+        for element in parent.elements() {
+            match element.name.as_str() {
+                "r" => {
+                    for child in element.elements() {
+                        if child.name == "fldChar" {
+                            match child.attr("fldCharType") {
+                                Some("begin") => {
+                                    fields.push(Field { instruction: String::new(), separated: false, link: None })
+                                }
+                                Some("separate") => {
+                                    if let Some(field) = fields.last_mut() {
+                                        field.separated = true;
+                                        field.link = hyperlink_field(&field.instruction).map(|url| (url, Vec::new()));
+                                    }
+                                }
+                                Some("end") => {
+                                    if let Some(Field { link: Some((url, content)), .. }) = fields.pop() {
+                                        output(fields, inlines).push(link(url, content));
+                                    }
+                                }
+                                _ => {}
+                            }
+                        } else if child.name == "instrText" {
+                            if let Some(field) = fields.last_mut().filter(|field| !field.separated) {
+                                field.instruction.push_str(&child.text());
+                            }
+                        }
+                    }
+                    if fields.last().is_some_and(|field| !field.separated) {
+                        continue;
+                    }
+                    let run = self.run(element)?;
+                    output(fields, inlines).extend(run);
+                }
+                "hyperlink" => {
+                    let mut content = Vec::new();
+                    self.collect_inlines(element, bookmarks, &mut Vec::new(), &mut content)?;
+                    let url = match (element.attr("id").and_then(|id| self.rels.get(id)), element.attr("anchor")) {
+                        (Some((target, _)), Some(anchor)) => Some(format!("{}#{}", target, anchor)),
+                        (Some((target, _)), None) => Some(target.clone()),
+                        (None, Some(anchor)) => Some(format!("#{}", anchor)),
+                        (None, None) => None,
+                    };
+                    match url {
+                        Some(url) => output(fields, inlines).push(link(url, content)),
+                        None => output(fields, inlines).extend(content),
+                    }
+                }
+                "fldSimple" => {
+                    let mut content = Vec::new();
+                    self.collect_inlines(element, bookmarks, &mut Vec::new(), &mut content)?;
+                    match element.attr("instr").and_then(hyperlink_field) {
+                        Some(url) => output(fields, inlines).push(link(url, content)),
+                        None => output(fields, inlines).extend(content),
+                    }
+                }
+                "bookmarkStart" => {
+                    if let Some(name) = element.attr("name").filter(|name| *name != "_GoBack") {
+                        bookmarks.push(name.to_string());
+                    }
+                }
+                "ins" | "moveTo" | "smartTag" | "customXml" | "sdtContent" | "sdt" => {
+                    self.collect_inlines(element, bookmarks, fields, inlines)?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换一段文字
+    fn run(&mut self, run: &XmlElement) -> Result<Vec<Inline>> {
+        let properties = run.child("rPr");
+        let mut format = properties
+            .and_then(|p| p.child("rStyle"))
+            .and_then(|e| e.attr("val"))
+            .map(|style| self.styles.run_format(style))
+            .unwrap_or_default();
+        if let Some(properties) = properties {
+            let direct = RunFormat::parse(properties);
+            let explicit = |name: &str| properties.child(name).is_some();
+            format = RunFormat {
+                bold: if explicit("b") { direct.bold } else { format.bold },
+                italic: if explicit("i") { direct.italic } else { format.italic },
+                strike: if explicit("strike") || explicit("dstrike") { direct.strike } else { format.strike },
+                code: format.code || direct.code,
+            };
+        }
+
+        let mut content = Vec::new();
+        for child in run.elements() {
+            self.run_content(child, format.code, &mut content)?;
+        }
+
+        // 等宽字体中的图片或脚注引用按普通内容处理
+        if format.code && content.iter().all(|inline| matches!(inline, Inline::Text(_) | Inline::LineBreak)) {
+            let code = content
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => text.as_str(),
+                    _ => " ",
+                })
+                .collect();
+            content = vec![Inline::Code(code)];
+        }
+        if format.strike {
+            content = vec![Inline::Strikethrough(content)];
+        }
+        if format.italic {
+            content = vec![Inline::Emphasis(content)];
+        }
+        if format.bold {
+            content = vec![Inline::Strong(content)];
+        }
+        Ok(content)
+    }
+
+    fn run_content(&mut self, element: &XmlElement, code: bool, content: &mut Vec<Inline>) -> Result<()> {
+        match element.name.as_str() {
+            "t" => content.push(Inline::Text(element.text())),
+            "tab" => content.push(Inline::Text(if code { "\t" } else { " " }.to_string())),
+            "noBreakHyphen" => content.push(Inline::Text("-".to_string())),
+            "br" if element.attr("type").is_none_or(|t| t == "textWrapping") => content.push(Inline::LineBreak),
+            "cr" => content.push(Inline::LineBreak),
+            "footnoteReference" | "endnoteReference" => {
+                if let Some(id) = element.attr("id") {
+                    let key = format!("{}:{}", element.name.trim_end_matches("Reference"), id);
+                    if self.notes.contains_key(&key) {
+                        content.push(Inline::FootnoteReference(self.label(&key)));
+                    }
+                }
+            }
+            "drawing" | "pict" | "object" => {
+                if let Some(image) = self.image(element)? {
+                    content.push(image);
+                }
+            }
+            "AlternateContent" => {
+                // 只使用第一种表示方式，避免同一张图片出现两次
+                if let Some(choice) = element.elements().next() {
+                    for child in choice.elements() {
+                        self.run_content(child, code, content)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 保存图片，返回图片链接
+    fn image(&mut self, drawing: &XmlElement) -> Result<Option<Inline>> {
+        let Some(id) = drawing
+            .descendants()
+            .find_map(|e| match e.name.as_str() {
+                "blip" => e.attr("embed").or_else(|| e.attr("link")),
+                "imagedata" => e.attr("id"),
+                _ => None,
+            })
+        else {
+            return Ok(None);
+        };
+        let Some((target, external)) = self.rels.get(id).cloned() else {
+            return Ok(None);
+        };
+        let properties = drawing.descendants().find(|e| e.name == "docPr");
+        let alt = properties
+            .and_then(|e| e.attr("descr").filter(|d| !d.is_empty()).or_else(|| e.attr("title")))
+            .unwrap_or_default()
+            .to_string();
+
+        let url = if external {
+            target
+        } else {
+            let name = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("word/{}", target),
+            };
+            let data = self.package.read(&name)?.ok_or_else(|| anyhow!("Word 文档中缺少图片: {}", name))?;
+            self.assets.save(&name, &data)?
+        };
+        Ok(Some(Inline::Image { url, alt, title: String::new() }))
+    }
+
+    /// 段落中的纯文字，用于代码块
+    fn plain_text(&self, paragraph: &XmlElement) -> String {
+        let mut text = String::new();
+        for element in paragraph.descendants() {
+            match element.name.as_str() {
+                "t" if !element.text().is_empty() => text.push_str(&element.text()),
+                "tab" => text.push('\t'),
+                "br" | "cr" => text.push('\n'),
+                _ => {}
+            }
+        }
+        text
+    }
+
+    /// 脚注的标签，第一次引用时按顺序编号
+    fn label(&mut self, key: &str) -> String {
+        if let Some(label) = self.labels.get(key) {
+            return label.clone();
+        }
+        self.order.push(key.to_string());
+        let label = self.order.len().to_string();
+        self.labels.insert(key.to_string(), label.clone());
+        label
+    }
+}
+
+/// 当前内容的输出位置：在超链接域结果中时放入链接内容
+fn output<'v>(fields: &'v mut [Field], inlines: &'v mut Vec<Inline>) -> &'v mut Vec<Inline> {
+    match fields.iter_mut().rev().find_map(|field| field.link.as_mut()) {
+        Some((_, content)) => content,
+        None => inlines,
+    }
+}
+
+fn link(url: String, content: Vec<Inline>) -> Inline {
+    Inline::Link { url, title: String::new(), content }
+}
+
+/// 合并等待中的段落
+fn flush(blocks: &mut Vec<Block>, pending: &mut Pending) {
+    match std::mem::replace(pending, Pending::None) {
+        Pending::None => {}
+        Pending::List(entries) => blocks.extend(build_lists(entries)),
+        Pending::Quote(content) => blocks.push(Block::Quote(content)),
+        Pending::Code(lines) => blocks.push(Block::Code { language: String::new(), code: lines.join("\n") }),
+    }
+}
+
+/// 段落的编号属性：编号和层级
+fn numbering_reference(properties: &XmlElement) -> Option<(String, usize)> {
+    let id = properties.child("numId")?.attr("val")?.to_string();
+    let level = properties.child("ilvl").and_then(|e| e.attr("val")).and_then(|v| v.parse().ok()).unwrap_or(0);
+    Some((id, level.min(8)))
+}
+
+/// 超链接域代码中的地址，如 `HYPERLINK "https://example.com"` 或 `HYPERLINK \l "书签"`
+fn hyperlink_field(instruction: &str) -> Option<String> {
+    let rest = instruction.trim().strip_prefix("HYPERLINK")?;
+    let mut anchor = false;
+    let mut url = None;
+    let mut parts = rest.split('"');
+    let mut outside = parts.next().unwrap_or_default();
+    loop {
+        if outside.split_whitespace().any(|switch| switch == "\\l") {
+            anchor = true;
+        }
+        let Some(quoted) = parts.next() else {
+            break;
+        };
+        if url.is_none() || anchor {
+            url = Some((quoted.to_string(), anchor));
+        }
+        outside = parts.next().unwrap_or_default();
+    }
+    let (mut target, mut anchor_target) = (String::new(), None);
+    if let Some((value, is_anchor)) = url {
+        if is_anchor {
+            anchor_target = Some(value);
+        } else {
+            target = value;
+        }
+    }
+    if target.is_empty() {
+        target = rest.split_whitespace().next().filter(|part| !part.starts_with('\\')).unwrap_or_default().to_string();
+    }
+    match anchor_target {
+        Some(anchor) => Some(format!("{}#{}", target, anchor)),
+        None if !target.is_empty() => Some(target),
+        None => None,
+    }
+}
+
+/// 本编辑器导出任务列表时使用的复选框符号
+fn task_marker(mut content: Vec<Inline>) -> (Option<bool>, Vec<Inline>) {
+    let Some(Inline::Text(text)) = content.first_mut() else {
+        return (None, content);
+    };
+    for (marker, checked) in [("☐", false), ("☒", true), ("☑", true)] {
+        if let Some(rest) = text.strip_prefix(marker) {
+            *text = rest.trim_start().to_string();
+            return (Some(checked), content);
+        }
+    }
+    (None, content)
+}
+
+fn is_empty(content: &[Inline]) -> bool {
+    content.iter().all(|inline| match inline {
+        Inline::Text(text) => text.trim().is_empty(),
+        Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children) => is_empty(children),
+        Inline::LineBreak => true,
+        _ => false,
+    })
+}
+
+/// 简化的 XML 元素，名称和属性不含命名空间前缀
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+#[derive(Debug)]
+enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+impl XmlElement {
+    fn new(start: &BytesStart) -> Result<Self> {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            let name = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+            attributes.push((name, attribute.unescape_value()?.into_owned()));
+        }
+        Ok(Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            attributes,
+            children: Vec::new(),
+        })
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    /// 所有后代元素，按文档顺序
+    fn descendants(&self) -> Box<dyn Iterator<Item = &XmlElement> + '_> {
+        Box::new(self.elements().flat_map(|element| std::iter::once(element).chain(element.descendants())))
+    }
+
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                XmlNode::Text(text) => Some(text.as_str()),
+                XmlNode::Element(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// 解析 XML，返回根元素
+fn parse_xml(xml: &str) -> Result<XmlElement> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![XmlElement::default()];
+    loop {
+        match reader.read_event()? {
+            XmlEvent::Start(start) => stack.push(XmlElement::new(&start)?),
+            XmlEvent::Empty(start) => {
+                let element = XmlElement::new(&start)?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Element(element));
+                }
+            }
+            XmlEvent::End(_) => {
+                let element = stack.pop().filter(|_| !stack.is_empty()).ok_or_else(|| anyhow!("多余的结束标签"))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Element(element));
+                }
+            }
+            XmlEvent::Text(text) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Text(text.unescape()?.into_owned()));
+                }
+            }
+            XmlEvent::CData(data) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Text(String::from_utf8_lossy(&data).into_owned()));
+                }
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+    if stack.len() != 1 {
+        bail!("缺少结束标签");
+    }
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().find_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        }))
+        .ok_or_else(|| anyhow!("没有根元素"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{DocxExportOptions, DocxExporter};
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// 用 Word 导出生成文档，再导入为 Markdown
+    fn round_trip(markdown: &str, dir: &Path) -> String {
+        let source = dir.join("source.md");
+        let docx = dir.join("document.docx");
+        fs::write(&source, markdown).unwrap();
+        let exporter = DocxExporter::new(DocxExportOptions::default()).unwrap();
+        exporter.export_file(markdown, Some(&source), &docx).unwrap();
+
+        let mut assets = Assets::new(&dir.join("imported.md"));
+        convert(&docx, &mut assets).unwrap().to_markdown()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let markdown = "# 标题\n\n\
+            正文有 **粗体**、*斜体*、~~删除线~~、`code` 和[链接](https://example.com)，跳到[小节](#小节)。\n\n\
+            ## 小节\n\n\
+            - 第一项\n  - 子项\n- [x] 完成\n\n\
+            3. 三\n4. 四\n\n\
+            > 引用\n\n\
+            ```\nfn main() {\n\tprintln!();\n}\n```\n\n\
+            | 名称 | 数量 |\n| :--- | ---: |\n| 苹果 | 3 |\n\n\
+            脚注引用[^a]。\n\n[^a]: 脚注内容\n";
+        assert_eq!(
+            round_trip(markdown, dir.path()),
+            "# 标题\n\n\
+             正文有 **粗体**、*斜体*、~~删除线~~、`code` 和[链接](https://example.com)，跳到[小节](#小节)。\n\n\
+             ## 小节\n\n\
+             - 第一项\n  - 子项\n- [x] 完成\n\n\
+             3. 三\n4. 四\n\n\
+             > 引用\n\n\
+             ```\nfn main() {\n\tprintln!();\n}\n```\n\n\
+             | 名称 | 数量 |\n| --- | ---: |\n| 苹果 | 3 |\n\n\
+             脚注引用[^1]。\n\n[^1]: 脚注内容\n"
+        );
+    }
+
+    #[test]
+    fn test_import_images() {
+        let dir = TempDir::new().unwrap();
+        // 1x1 的 PNG 图片
+        let png = [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4, 0x89,
+        ];
+        fs::write(dir.path().join("pixel.png"), png).unwrap();
+
+        let markdown = round_trip("![像素](pixel.png)\n", dir.path());
+        assert_eq!(markdown, "![像素](assets/image1.png)\n");
+        assert_eq!(fs::read(dir.path().join("assets/image1.png")).unwrap(), png);
+    }
+
+    #[test]
+    fn test_hyperlink_field() {
+        assert_eq!(hyperlink_field(" HYPERLINK \"https://example.com\" "), Some("https://example.com".to_string()));
+        assert_eq!(hyperlink_field("HYPERLINK \\l \"_Toc1\""), Some("#_Toc1".to_string()));
+        assert_eq!(hyperlink_field("PAGEREF _Toc1 \\h"), None);
+    }
+
+    #[test]
+    fn test_invalid_document() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("broken.docx");
+        fs::write(&path, "not a zip").unwrap();
+        let mut assets = Assets::new(&dir.path().join("broken.md"));
+        assert!(convert(&path, &mut assets).is_err());
+    }
+}
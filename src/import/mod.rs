@@ -0,0 +1,79 @@
+//! 导入模块
+//!
+//! 把其他格式的文档转换为 Markdown：
+//! - Word：标题、强调、列表、表格、链接、脚注和图片
+//! - HTML：同上，并识别常见工具生成的脚注和代码块语言
+//!
+//! 文档中的图片保存在新文档旁边的 `assets/` 文件夹中
+
+mod docx;
+
+use std::fs;
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+
+use crate::markdown::{html_to_document, Assets, HtmlConvertOptions};
+
+/// 导入选项
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// 把指向其他 HTML 页面的相对链接改为指向对应的 Markdown 文件，用于迁移整个网站或知识库
+    pub rewrite_links: bool,
+}
+
+/// 判断文件是否可以导入
+pub fn is_importable(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| matches!(ext.as_str(), "docx" | "html" | "htm"))
+}
+
+/// 把文档转换为 Markdown，图片保存在 `target` 旁边的 `assets/` 文件夹中
+pub fn convert_file(source: &Path, target: &Path, options: &ImportOptions) -> Result<String> {
+    let extension = source.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let mut assets = Assets::new(target);
+    let document = match extension.as_str() {
+        "docx" => docx::convert(source, &mut assets)?,
+        "html" | "htm" => {
+            let data = fs::read(source).with_context(|| format!("无法读取文件: {}", source.display()))?;
+            let html_options = HtmlConvertOptions {
+                source: Some(source),
+                assets: Some(&mut assets),
+                rewrite_links: options.rewrite_links,
+            };
+            html_to_document(&String::from_utf8_lossy(&data), html_options)?
+        }
+        _ => bail!("不支持导入该格式的文件: {}", source.display()),
+    };
+    Ok(document.to_markdown())
+}
+
+/// 把文档转换为 Markdown 并保存到 `target`
+pub fn import_file(source: &Path, target: &Path, options: &ImportOptions) -> Result<()> {
+    let markdown = convert_file(source, target, options)?;
+    fs::write(target, markdown).with_context(|| format!("无法写入文件: {}", target.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_file() -> Result<()> {
+        let dir = TempDir::new()?;
+        let source = dir.path().join("wiki/page.html");
+        fs::create_dir_all(source.parent().unwrap())?;
+        fs::write(&source, "<h1>页面</h1><p>见<a href=\"other.html\">其他页面</a></p>")?;
+
+        let target = dir.path().join("notes/page.md");
+        fs::create_dir_all(target.parent().unwrap())?;
+        import_file(&source, &target, &ImportOptions { rewrite_links: true })?;
+        assert_eq!(fs::read_to_string(&target)?, "# 页面\n\n见[其他页面](other.md)\n");
+
+        assert!(is_importable(Path::new("a.DOCX")));
+        assert!(!is_importable(Path::new("a.md")));
+        assert!(convert_file(Path::new("a.txt"), &target, &ImportOptions::default()).is_err());
+        Ok(())
+    }
+}
@@ -9,18 +9,46 @@
 
 use gpui::*;
 use gpui_component::*;
-use rfd::FileDialog;
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult};
 
 mod editor;
 mod markdown;
 mod preview;
 mod file_manager;
+mod config;
+mod actions;
+mod export;
+mod import;
+mod cli;
 
-use editor::TextEditor;
-use markdown::MarkdownParser;
-use preview::MarkdownPreview;
-use file_manager::{FileManager, FileTree, SearchManager, FileItem, FileType};
+use editor::{TextEdit, TextEditor, TextEditorEvent};
+use markdown::{heading_anchors, heading_slug, resolve_link_target, LinkTarget, MarkdownParser, WikiLink};
+use preview::{MarkdownPreview, PreviewEvent, PreviewHighlight};
+use file_manager::{
+    FileManager, SearchManager, SearchOptions, FileItem, FileType, RenamePlan, ReplacePlan, ReplaceTransaction,
+    Workspace, WorkspaceSearch, SearchEvent, FileMatches, SearchIndex, IndexHit, QuickOpen, QuickOpenMatch,
+    SearchResult, DocumentMatch, document_matches, Backlink, create_note, find_backlinks, new_note_path,
+    resolve_wiki_link, LinkProblem, check_links,
+};
+use export::{DocxExporter, DocxExportOptions, HtmlExporter, HtmlExportOptions, PdfExporter, PdfExportOptions};
+use import::ImportOptions;
+use config::{
+    AppState, CommandMatch, FileState, Keymap, KeymapFile, PreviewStyle, Settings, SettingsFile, StateStore, UserCss,
+    UserCssFile, WindowGeometry, THEMES, format_keystrokes, search_commands,
+};
+use futures::StreamExt;
+use gpui_component::input::{Input, InputState};
 use gpui_component::button::Button;
+use std::time::Duration;
+
+/// 检查设置文件是否变化的间隔
+const SETTINGS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 检查工作区文件是否变化（以更新全文索引）的间隔
+const INDEX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// 定期保存应用状态的间隔（异常退出时也能恢复大部分状态）
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// 索引修改后延迟保存的时间，期间的多次修改只写入一次
+const INDEX_SAVE_DELAY: Duration = Duration::from_secs(2);
 
 /// 主窗口视图
 /// 
@@ -34,69 +62,780 @@ pub struct MainWindow {
     markdown_content: SharedString,
     /// 文件管理器
     file_manager: Entity<FileManager>,
-    /// 文件树
-    file_tree: Entity<FileTree>,
+    /// 工作区（一个或多个文件树）
+    workspace: Entity<Workspace>,
     /// 搜索管理器
     search_manager: Entity<SearchManager>,
+    /// 搜索输入框
+    search_input: Entity<InputState>,
     /// 搜索查询
     search_query: SharedString,
-    /// 搜索结果
-    search_results: Vec<String>,
+    /// 替换输入框
+    replace_input: Entity<InputState>,
+    /// 待确认的工作区替换计划
+    replace_plan: Option<ReplacePlan>,
+    /// 最近一次工作区替换，用于撤销
+    last_replace: Option<ReplaceTransaction>,
+    /// 搜索提示（错误、替换结果等）
+    search_messages: Vec<String>,
+    /// 当前文档的搜索结果
+    document_results: Vec<SearchResult>,
+    /// 当前文档中的所有匹配（按位置排序）
+    document_matches: Vec<DocumentMatch>,
+    /// 当前选中的匹配
+    current_match: Option<usize>,
+    /// 正在进行的工作区搜索
+    workspace_search: Option<WorkspaceSearch>,
+    /// 接收工作区搜索结果的任务
+    search_task: Option<Task<()>>,
+    /// 工作区搜索结果（按文件分组）
+    workspace_results: Vec<FileMatches>,
+    /// 工作区搜索状态
+    search_status: Option<String>,
+    /// 使用全文索引搜索
+    use_index: bool,
+    /// 工作区各根文件夹的全文索引
+    search_indexes: Vec<SearchIndex>,
+    /// 正在后台建立或更新全文索引的任务
+    index_task: Option<Task<()>>,
+    /// 后台更新索引期间重新索引的文件，更新完成后在新索引上重新应用
+    pending_index_files: Vec<std::path::PathBuf>,
+    /// 等待延迟保存索引的任务
+    index_save_task: Option<Task<()>>,
+    /// 正在后台写入索引文件的任务（同一时间只有一个）
+    index_write_task: Option<Task<()>>,
+    /// 写入索引文件时持有的锁
+    index_write_lock: std::sync::Arc<std::sync::Mutex<()>>,
+    /// 定期检查工作区文件变化的任务
+    index_watch_task: Option<Task<()>>,
+    /// 索引搜索结果
+    index_results: Vec<IndexHit>,
+    /// 待确认的移动/重命名计划
+    rename_plan: Option<RenamePlan>,
+    /// 快速打开的候选文件（打开面板时为 `Some`）
+    quick_open: Option<QuickOpen>,
+    /// 快速打开输入框
+    quick_open_input: Entity<InputState>,
+    /// 快速打开是否包含所有文件（否则只有 Markdown 文件）
+    quick_open_all_files: bool,
+    /// 快速打开的匹配结果
+    quick_open_results: Vec<QuickOpenMatch>,
+    /// 快速打开中选中的结果
+    quick_open_selected: usize,
+    /// 状态文件（无法确定状态目录时为 `None`）
+    state_store: Option<StateStore>,
+    /// 定期保存应用状态的任务
+    state_task: Option<Task<()>>,
+    /// 跨会话保存的应用状态
+    app_state: AppState,
+    /// 设置文件（无法确定配置目录时为 `None`）
+    settings_file: Option<SettingsFile>,
+    /// 当前生效的用户设置
+    settings: Settings,
+    /// 设置文件的错误（格式错误或取值无效），显示在工具栏下方
+    settings_error: Option<String>,
+    /// 当前使用的主题
+    theme: config::Theme,
+    /// 用户 CSS 文件（无法确定配置目录时为 `None`）
+    user_css_file: Option<UserCssFile>,
+    /// 用户 CSS，覆盖预览和导出的样式
+    user_css: UserCss,
+    /// 用户 CSS 中无法识别的内容，与设置错误一起显示
+    css_warnings: Vec<String>,
+    /// 快捷键文件（无法确定配置目录时为 `None`）
+    keymap_file: Option<KeymapFile>,
+    /// 启动时生效的快捷键
+    keymap: Keymap,
+    /// 快捷键文件中的问题，与设置错误一起显示
+    keymap_warnings: Vec<String>,
+    /// 命令面板是否打开
+    command_palette: bool,
+    /// 命令面板输入框
+    command_palette_input: Entity<InputState>,
+    /// 命令面板的匹配结果
+    command_palette_results: Vec<CommandMatch>,
+    /// 命令面板中选中的结果
+    command_palette_selected: usize,
+    /// 监视设置文件、用户 CSS 和快捷键文件变化的任务
+    settings_task: Option<Task<()>>,
+    /// 等待执行的自动保存
+    autosave_task: Option<Task<()>>,
+    /// 最近一次导出、导入或打开链接的结果
+    export_message: Option<String>,
+    /// 正在后台进行的 PDF 导出
+    export_task: Option<Task<()>>,
+    /// 链接到当前文档的其他文档
+    backlinks: Vec<Backlink>,
+    /// 正在后台查找反向链接的任务
+    backlinks_task: Option<Task<()>>,
+    /// 链接检查发现的问题（检查过链接后为 `Some`，显示在搜索结果区域）
+    link_problems: Option<Vec<LinkProblem>>,
+    /// 正在后台进行的链接检查
+    link_check_task: Option<Task<()>>,
 }
 
 impl MainWindow {
     /// 创建新的主窗口
-    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+    ///
+    /// `paths` 为命令行传入的路径：文件夹作为工作区打开，文件直接在编辑器中打开；
+    /// 没有传入路径时恢复上次会话的工作区和文件
+    pub fn new(
+        paths: Vec<std::path::PathBuf>,
+        state_store: Option<StateStore>,
+        app_state: AppState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // 读取用户设置
+        let mut settings_file = SettingsFile::open_default();
+        let (settings, settings_error) = load_settings(settings_file.as_mut());
+        let mut user_css_file = UserCssFile::open_default();
+        let (user_css, css_warnings) = load_user_css(user_css_file.as_mut());
+
+        // 读取快捷键并注册按键绑定
+        let mut keymap_file = KeymapFile::open_default();
+        let (keymap, mut keymap_warnings) = load_keymap(keymap_file.as_mut());
+        let (bindings, errors) = actions::key_bindings(&keymap);
+        keymap_warnings.extend(errors);
+        cx.bind_keys(bindings);
+
         // 创建编辑器
-        let editor = cx.new(|cx| TextEditor::new(window, cx));
+        let editor = cx.new(|cx| TextEditor::new(settings.editor.clone(), window, cx));
 
-        // 创建预览器
+        // 创建预览器（样式在应用主题时设置）
         let preview = cx.new(|_cx| MarkdownPreview::new());
 
         // 创建文件管理器
         let file_manager = cx.new(|_cx| FileManager::new());
 
-        // 创建文件树（使用当前目录作为根）
-        let current_dir = std::env::current_dir().unwrap_or_default();
-        let file_tree = cx.new(|_cx| {
-            FileTree::new(&current_dir).unwrap_or_else(|_| FileTree::new(".").unwrap_or_else(|_| {
-                // 如果都失败了，创建一个空的文件树
-                use file_manager::{FileItem, FileType};
-                
-                // 创建失败的备用方案
-                let mut item = FileItem::new(
-                    "root".to_string(),
-                    std::path::PathBuf::from("."),
-                    FileType::Directory,
-                );
-                item.expanded = true;
-                FileTree::new(".").unwrap_or_else(|_| {
-                    // 如果还是失败，panic
-                    panic!("无法创建文件树")
-                })
-            }))
-        });
+        // 创建工作区（初始为空，由命令行参数或“打开文件夹”填充）
+        let workspace = cx.new(|_cx| Workspace::new());
 
         // 创建搜索管理器
         let search_manager = cx.new(|_cx| SearchManager::new());
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("搜索..."));
+        let replace_input = cx.new(|cx| InputState::new(window, cx).placeholder("替换为..."));
+        let quick_open_input = cx.new(|cx| InputState::new(window, cx).placeholder("输入文件名快速打开..."));
+        let command_palette_input = cx.new(|cx| InputState::new(window, cx).placeholder("输入命令..."));
 
         let mut main_window = Self {
             editor: editor.clone(),
             preview: preview.clone(),
             markdown_content: SharedString::default(),
             file_manager: file_manager.clone(),
-            file_tree: file_tree.clone(),
+            workspace: workspace.clone(),
             search_manager: search_manager.clone(),
+            search_input: search_input.clone(),
             search_query: SharedString::default(),
-            search_results: Vec::new(),
+            replace_input,
+            replace_plan: None,
+            last_replace: None,
+            search_messages: Vec::new(),
+            document_results: Vec::new(),
+            document_matches: Vec::new(),
+            current_match: None,
+            workspace_search: None,
+            search_task: None,
+            workspace_results: Vec::new(),
+            search_status: None,
+            use_index: false,
+            search_indexes: Vec::new(),
+            index_task: None,
+            pending_index_files: Vec::new(),
+            index_save_task: None,
+            index_write_task: None,
+            index_write_lock: Default::default(),
+            index_watch_task: None,
+            index_results: Vec::new(),
+            rename_plan: None,
+            quick_open: None,
+            quick_open_input,
+            quick_open_all_files: false,
+            quick_open_results: Vec::new(),
+            quick_open_selected: 0,
+            state_store,
+            state_task: None,
+            app_state,
+            settings_file,
+            settings,
+            settings_error,
+            theme: config::Theme::light(),
+            user_css_file,
+            user_css,
+            css_warnings,
+            keymap_file,
+            keymap,
+            keymap_warnings,
+            command_palette: false,
+            command_palette_input,
+            command_palette_results: Vec::new(),
+            command_palette_selected: 0,
+            settings_task: None,
+            autosave_task: None,
+            export_message: None,
+            export_task: None,
+            backlinks: Vec::new(),
+            backlinks_task: None,
+            link_problems: None,
+            link_check_task: None,
         };
 
         // 订阅编辑器内容变化，实时更新预览
         main_window.setup_realtime_preview(window, cx);
+        main_window.sync_preview_base_dir(cx);
+        main_window.setup_link_handlers(window, cx);
+        main_window.setup_search_input(window, cx);
+        main_window.setup_quick_open_input(window, cx);
+        main_window.setup_command_palette_input(window, cx);
+        main_window.setup_state_persistence(window, cx);
+        main_window.setup_settings_watcher(window, cx);
+        main_window.setup_index_watcher(cx);
+        main_window.setup_theme_observer(window, cx);
+        main_window.apply_theme(window, cx);
+
+        main_window.restore_state(paths, window, cx);
 
         main_window
     }
 
+    /// 定期检查设置文件和用户 CSS，文件变化后重新加载
+    fn setup_settings_watcher(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.settings_file.is_none() && self.user_css_file.is_none() && self.keymap_file.is_none() {
+            return;
+        }
+
+        self.settings_task = Some(cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor().timer(SETTINGS_POLL_INTERVAL).await;
+
+                let result = this.update_in(cx, |this, window, cx| {
+                    if this.settings_file.as_ref().is_some_and(|file| file.has_changed()) {
+                        this.reload_settings(window, cx);
+                    }
+                    if this.user_css_file.as_ref().is_some_and(|file| file.has_changed()) {
+                        this.reload_user_css(window, cx);
+                    }
+                    if this.keymap_file.as_ref().is_some_and(|file| file.has_changed()) {
+                        this.reload_keymap(cx);
+                    }
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// 重新读取设置文件并应用；设置无效时保留当前设置并显示错误
+    fn reload_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(file) = &mut self.settings_file else {
+            return;
+        };
+
+        match file.load() {
+            Ok(settings) => {
+                self.settings_error = None;
+                self.apply_settings(settings, window, cx);
+            }
+            Err(e) => self.settings_error = Some(format!("{:#}", e)),
+        }
+        cx.notify();
+    }
+
+    /// 重新读取用户 CSS 并应用到预览
+    fn reload_user_css(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (user_css, css_warnings) = load_user_css(self.user_css_file.as_mut());
+        self.user_css = user_css;
+        self.css_warnings = css_warnings;
+        self.apply_theme(window, cx);
+    }
+
+    /// 重新检查快捷键文件
+    ///
+    /// GPUI 无法单独移除已注册的按键绑定，新的快捷键在重启后生效
+    fn reload_keymap(&mut self, cx: &mut Context<Self>) {
+        let (_, mut warnings) = load_keymap(self.keymap_file.as_mut());
+        warnings.push("快捷键设置已修改，重启后生效".to_string());
+        self.keymap_warnings = warnings;
+        cx.notify();
+    }
+
+    /// 把设置应用到编辑器、预览和界面
+    fn apply_settings(&mut self, settings: Settings, window: &mut Window, cx: &mut Context<Self>) {
+        let rebuilt = self.editor.update(cx, |editor, cx| {
+            editor.apply_settings(&settings.editor, window, cx)
+        });
+        if rebuilt {
+            // 输入状态已重建，需要重新订阅内容变化
+            self.setup_realtime_preview(window, cx);
+        }
+
+        if !settings.autosave.enabled {
+            self.autosave_task = None;
+        }
+
+        self.settings = settings;
+        self.apply_theme(window, cx);
+    }
+
+    /// 系统切换明暗外观时，如果主题设置为跟随系统则重新应用主题
+    fn setup_theme_observer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        cx.observe_window_appearance(window, |this, window, cx| {
+            if this.settings.appearance.theme == config::SYSTEM_THEME {
+                this.apply_theme(window, cx);
+            }
+        })
+        .detach();
+    }
+
+    /// 按设置确定主题，应用到界面组件和预览
+    fn apply_theme(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let system_dark = matches!(window.appearance(), WindowAppearance::Dark | WindowAppearance::VibrantDark);
+        self.theme = config::Theme::for_setting(&self.settings.appearance.theme, system_dark);
+
+        let mode = if self.theme.dark { ThemeMode::Dark } else { ThemeMode::Light };
+        Theme::change(mode, Some(window), cx);
+
+        let style = PreviewStyle::new(&self.theme, &self.settings.preview, &self.user_css);
+        let code_theme = self.settings.appearance.code_theme.clone();
+        self.preview.update(cx, |preview, cx| {
+            preview.set_style(style, &code_theme);
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    /// 切换到下一个主题
+    ///
+    /// 只在本次运行中生效，设置文件修改后以设置文件为准
+    fn cycle_theme(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let current = THEMES
+            .iter()
+            .position(|name| *name == self.settings.appearance.theme)
+            .unwrap_or(0);
+        self.settings.appearance.theme = THEMES[(current + 1) % THEMES.len()].to_string();
+        self.apply_theme(window, cx);
+    }
+
+    /// 打开设置文件（不存在时先写入默认设置），保存后自动生效
+    fn open_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(file) = &self.settings_file else {
+            self.settings_error = Some("无法确定配置目录".to_string());
+            cx.notify();
+            return;
+        };
+
+        let path = file.path().to_path_buf();
+        match file.create_if_missing() {
+            Ok(()) => self.open_file(path, window, cx),
+            Err(e) => {
+                self.settings_error = Some(format!("{:#}", e));
+                cx.notify();
+            }
+        }
+    }
+
+    /// 打开快捷键文件（不存在时先写入列出所有命令的模板）
+    fn open_keymap(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(file) = &self.keymap_file else {
+            self.settings_error = Some("无法确定配置目录".to_string());
+            cx.notify();
+            return;
+        };
+
+        let path = file.path().to_path_buf();
+        match file.create_if_missing() {
+            Ok(()) => self.open_file(path, window, cx),
+            Err(e) => {
+                self.settings_error = Some(format!("{:#}", e));
+                cx.notify();
+            }
+        }
+    }
+
+    /// 停止输入一段时间后自动保存（需要在设置中开启）
+    fn schedule_autosave(&mut self, cx: &mut Context<Self>) {
+        if !self.settings.autosave.enabled || self.file_manager.read(cx).current_file().is_none() {
+            return;
+        }
+
+        let delay = Duration::from_millis(self.settings.autosave.delay_ms);
+        // 替换旧任务会取消上一次尚未执行的保存
+        self.autosave_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = this.update(cx, |this, cx| {
+                this.autosave_task = None;
+                this.autosave(cx);
+            });
+        }));
+    }
+
+    /// 内容与已保存的不同时保存当前文件
+    fn autosave(&mut self, cx: &mut Context<Self>) {
+        let content = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        if content != self.file_manager.read(cx).content() {
+            self.save_file(cx);
+        }
+    }
+
+    /// 恢复上次会话的状态
+    ///
+    /// 命令行传入了路径时打开这些路径，否则重新打开上次的工作区和文件
+    fn restore_state(&mut self, paths: Vec<std::path::PathBuf>, window: &mut Window, cx: &mut Context<Self>) {
+        let state = self.app_state.clone();
+
+        self.search_manager.update(cx, |manager, _cx| {
+            manager.set_history(state.search_history);
+        });
+        self.file_manager.update(cx, |manager, _cx| {
+            manager.set_recent_files(state.recent_files);
+        });
+        self.workspace.update(cx, |workspace, _cx| {
+            workspace.set_recent_workspaces(state.recent_workspaces);
+        });
+
+        if !paths.is_empty() {
+            self.open_paths(paths, window, cx);
+            return;
+        }
+
+        for root in state.workspace_roots {
+            self.add_workspace_folder(root, cx);
+        }
+        self.workspace.update(cx, |workspace, _cx| {
+            for dir in &state.expanded_dirs {
+                workspace.set_expanded(dir, true);
+            }
+        });
+        if let Some(file) = state.current_file {
+            self.open_file(file, window, cx);
+        }
+    }
+
+    /// 跟踪窗口位置，定期保存状态，并在关闭窗口或退出应用时保存
+    fn setup_state_persistence(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        cx.observe_window_bounds(window, |this, window, _cx| {
+            this.app_state.window = Some(window_geometry(window.window_bounds()));
+        })
+        .detach();
+
+        let this = cx.entity().downgrade();
+        window.on_window_should_close(cx, move |_window, cx| {
+            let _ = this.update(cx, |this, cx| {
+                this.save_state(cx);
+                this.flush_indexes();
+            });
+            true
+        });
+
+        cx.on_app_quit(|this, cx| {
+            this.save_state(cx);
+            this.flush_indexes();
+            async {}
+        })
+        .detach();
+
+        self.state_task = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(STATE_SAVE_INTERVAL).await;
+                if this.update(cx, |this, cx| this.save_state(cx)).is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// 保存应用状态，下次启动时恢复
+    fn save_state(&mut self, cx: &mut Context<Self>) {
+        self.remember_file_state(cx);
+
+        let workspace = self.workspace.read(cx);
+        self.app_state.workspace_roots = workspace
+            .roots()
+            .iter()
+            .map(|tree| tree.root_path().to_path_buf())
+            .collect();
+        self.app_state.expanded_dirs = workspace.expanded_dirs();
+        self.app_state.recent_workspaces = workspace.recent_workspaces().to_vec();
+
+        let file_manager = self.file_manager.read(cx);
+        self.app_state.recent_files = file_manager.recent_files().to_vec();
+        self.app_state.current_file = file_manager.current_file().map(|path| path.to_path_buf());
+
+        self.app_state.search_history = self.search_manager.read(cx).history().to_vec();
+
+        if let Some(store) = &self.state_store {
+            if let Err(e) = store.save(&self.app_state) {
+                eprintln!("保存应用状态失败: {}", e);
+            }
+        }
+    }
+
+    /// 记住当前文件的光标和预览滚动位置
+    fn remember_file_state(&mut self, cx: &mut Context<Self>) {
+        let Some(path) = self.file_manager.read(cx).current_file().map(|path| path.to_path_buf()) else {
+            return;
+        };
+
+        let editor = self.editor.read(cx);
+        let (cursor_line, cursor_column) = editor.cursor_position(cx);
+        let editor_line = editor.first_visible_line(cx);
+        let scroll_offset = self.preview.read(cx).scroll_offset();
+        self.app_state.remember_file(FileState { path, cursor_line, cursor_column, scroll_offset, editor_line });
+    }
+
+    /// 切换文件前保存应用状态，记录当前文件的编辑位置
+    fn save_file_switch_state(&mut self, cx: &mut Context<Self>) {
+        // 启动时还没有打开文件，工作区也尚未恢复，此时不能覆盖保存的状态
+        if self.file_manager.read(cx).current_file().is_some() {
+            self.save_state(cx);
+        }
+    }
+
+    /// 恢复文件上次的光标、编辑区和预览的滚动位置
+    fn restore_file_state(&mut self, path: &std::path::Path, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(state) = self.app_state.file_state(path).cloned() else {
+            return;
+        };
+
+        self.editor.update(cx, |editor, cx| {
+            if state.editor_line > 0 {
+                editor.scroll_to_line(state.editor_line, window, cx);
+            }
+            editor.go_to(state.cursor_line, state.cursor_column, window, cx);
+        });
+        self.preview.update(cx, |preview, cx| {
+            preview.set_scroll_offset(state.scroll_offset);
+            cx.notify();
+        });
+    }
+
+    /// 打开命令行传入的路径
+    fn open_paths(&mut self, paths: Vec<std::path::PathBuf>, window: &mut Window, cx: &mut Context<Self>) {
+        for path in paths {
+            if path.is_dir() {
+                self.add_workspace_folder(path, cx);
+            } else if path.is_file() {
+                // 单独打开文件时，用其所在文件夹作为工作区
+                if self.workspace.read(cx).is_empty() {
+                    if let Some(parent) = path.parent() {
+                        let parent = if parent.as_os_str().is_empty() {
+                            std::path::Path::new(".")
+                        } else {
+                            parent
+                        };
+                        self.add_workspace_folder(parent.to_path_buf(), cx);
+                    }
+                }
+                self.open_file(path, window, cx);
+            } else {
+                eprintln!("路径不存在: {}", path.display());
+            }
+        }
+    }
+
+    /// 打开文件夹作为工作区（替换当前工作区）
+    fn open_workspace(&mut self, path: std::path::PathBuf, cx: &mut Context<Self>) {
+        self.workspace.update(cx, |workspace, _cx| {
+            if let Err(e) = workspace.open(&path) {
+                eprintln!("打开文件夹失败: {}", e);
+            }
+        });
+        self.rename_plan = None;
+        self.replace_plan = None;
+        self.last_replace = None;
+        self.link_problems = None;
+        self.link_check_task = None;
+        self.cancel_workspace_search();
+        self.workspace_results.clear();
+        self.index_results.clear();
+        self.search_status = None;
+        self.sync_indexes(cx);
+        self.sync_notes(cx);
+        cx.notify();
+    }
+
+    /// 向工作区添加文件夹
+    fn add_workspace_folder(&mut self, path: std::path::PathBuf, cx: &mut Context<Self>) {
+        self.workspace.update(cx, |workspace, _cx| {
+            if let Err(e) = workspace.add_root(&path) {
+                eprintln!("添加文件夹失败: {}", e);
+            }
+        });
+        self.sync_indexes(cx);
+        self.sync_notes(cx);
+        cx.notify();
+    }
+
+    /// 在后台使全文索引与工作区的根文件夹保持一致并同步文件变化，完成后替换当前的索引
+    ///
+    /// 更新期间仍使用原来的索引搜索；期间重新索引的文件会在新索引上重新应用，
+    /// 修改由 [`MainWindow::schedule_index_save`] 统一保存
+    fn sync_indexes(&mut self, cx: &mut Context<Self>) {
+        let roots: Vec<std::path::PathBuf> = self.workspace
+            .read(cx)
+            .roots()
+            .iter()
+            .map(|tree| tree.root_path().to_path_buf())
+            .collect();
+
+        let mut existing = self.search_indexes.clone();
+        self.index_task = Some(cx.spawn(async move |this, cx| {
+            let indexes = cx.background_spawn(async move {
+                let mut indexes = Vec::new();
+                for root in roots {
+                    let mut index = match existing.iter().position(|index| index.root() == root) {
+                        Some(pos) => existing.swap_remove(pos),
+                        None => SearchIndex::open(&root),
+                    };
+                    if let Err(e) = index.update() {
+                        eprintln!("更新索引失败 {}: {}", root.display(), e);
+                    }
+                    indexes.push(index);
+                }
+                indexes
+            }).await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.search_indexes = indexes;
+                this.index_task = None;
+                for path in std::mem::take(&mut this.pending_index_files) {
+                    this.index_file(&path, cx);
+                }
+                this.schedule_index_save(cx);
+                cx.notify();
+            });
+        }));
+    }
+
+    /// 重新索引单个文件并延迟保存
+    fn index_file(&mut self, path: &std::path::Path, cx: &mut Context<Self>) {
+        if self.index_task.is_some() {
+            self.pending_index_files.push(path.to_path_buf());
+        }
+        if let Some(index) = self.search_indexes.iter_mut().find(|index| path.starts_with(index.root())) {
+            if let Err(e) = index.update_file(path) {
+                eprintln!("更新索引失败: {}", e);
+            }
+            self.schedule_index_save(cx);
+        }
+    }
+
+    /// 定期在后台检查工作区文件是否变化（包括在其他程序中的修改），有变化时更新索引
+    fn setup_index_watcher(&mut self, cx: &mut Context<Self>) {
+        self.index_watch_task = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(INDEX_POLL_INTERVAL).await;
+
+                let Ok(stamps) = this.update(cx, |this, _cx| {
+                    if this.index_task.is_some() {
+                        Vec::new()
+                    } else {
+                        this.search_indexes.iter().map(|index| index.stamps()).collect::<Vec<_>>()
+                    }
+                }) else {
+                    break;
+                };
+                if stamps.is_empty() {
+                    continue;
+                }
+
+                let changed = cx.background_spawn(async move {
+                    stamps.iter().any(|stamps| stamps.has_changes())
+                }).await;
+                if changed {
+                    let _ = this.update(cx, |this, cx| {
+                        if this.index_task.is_none() {
+                            this.sync_indexes(cx);
+                        }
+                    });
+                }
+            }
+        }));
+    }
+
+    /// 延迟保存索引，替换旧任务会推迟上一次尚未执行的保存
+    fn schedule_index_save(&mut self, cx: &mut Context<Self>) {
+        self.index_save_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(INDEX_SAVE_DELAY).await;
+            let _ = this.update(cx, |this, cx| {
+                this.index_save_task = None;
+                this.save_indexes(cx);
+            });
+        }));
+    }
+
+    /// 在后台保存有修改的索引
+    ///
+    /// 上一次写入尚未完成时推迟保存，保证同一个索引文件不会被同时写入
+    fn save_indexes(&mut self, cx: &mut Context<Self>) {
+        if self.index_write_task.is_some() {
+            self.schedule_index_save(cx);
+            return;
+        }
+
+        let unsaved: Vec<SearchIndex> = self.search_indexes
+            .iter_mut()
+            .filter_map(|index| index.take_unsaved())
+            .collect();
+        if unsaved.is_empty() {
+            return;
+        }
+
+        let write_lock = self.index_write_lock.clone();
+        self.index_write_task = Some(cx.spawn(async move |this, cx| {
+            let failed = cx.background_spawn(async move {
+                let _guard = write_lock.lock().unwrap_or_else(|e| e.into_inner());
+                let mut failed = Vec::new();
+                for mut index in unsaved {
+                    if let Err(e) = index.save() {
+                        eprintln!("保存索引失败 {}: {}", index.root().display(), e);
+                        failed.push(index.root().to_path_buf());
+                    }
+                }
+                failed
+            }).await;
+
+            let _ = this.update(cx, |this, _cx| {
+                this.index_write_task = None;
+                // 保存失败的索引保留修改标记，下次修改时重试
+                for index in &mut this.search_indexes {
+                    if failed.iter().any(|root| root == index.root()) {
+                        index.mark_dirty();
+                    }
+                }
+            });
+        }));
+    }
+
+    /// 退出前立即保存有修改的索引
+    fn flush_indexes(&mut self) {
+        // 后台写入可能尚未开始，修改标记已清除的索引也重新保存
+        if self.index_write_task.take().is_some() {
+            for index in &mut self.search_indexes {
+                index.mark_dirty();
+            }
+        }
+        self.index_save_task = None;
+        // 等待正在进行的后台写入完成，避免同时写入同一个文件
+        let _guard = self.index_write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        for index in &mut self.search_indexes {
+            if let Err(e) = index.save() {
+                eprintln!("保存索引失败 {}: {}", index.root().display(), e);
+            }
+        }
+    }
+
+    /// 当前工作区的首个根路径（用于文件对话框的初始目录）
+    fn default_directory(&self, cx: &mut Context<Self>) -> std::path::PathBuf {
+        self.workspace
+            .read(cx)
+            .roots()
+            .first()
+            .map(|tree| tree.root_path().to_path_buf())
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default())
+    }
+
     /// 设置实时预览功能
     /// 
     /// 当编辑器内容变化时，自动更新预览
@@ -105,104 +844,1911 @@ impl MainWindow {
         let input_state = self.editor.read(cx).input_state();
         
         // 订阅输入状态的变化事件
-        cx.subscribe_in(&input_state, window, move |_view, state, event, _window, cx| {
+        cx.subscribe_in(&input_state, window, move |view, state, event, _window, cx| {
             use gpui_component::input::InputEvent as ComponentInputEvent;
             if let ComponentInputEvent::Change = event {
                 let content = state.read(cx).value();
+                // 选中搜索匹配等操作也会发出变化事件，内容没有变化时忽略
+                if content == view.markdown_content {
+                    return;
+                }
+                view.markdown_content = content.clone();
                 // 直接传递 Markdown 内容到预览器进行渲染
                 preview.update(cx, |preview, _cx| {
                     preview.update_html(content.to_string());
                 });
+                // 内容变化后匹配位置也会变化
+                if !view.document_results.is_empty() {
+                    view.refresh_document_results(cx);
+                }
+                view.schedule_autosave(cx);
                 cx.notify();
             }
-        })
-        .detach();
+        })
+        .detach();
+    }
+
+    /// 设置搜索输入框：按回车执行搜索，查询未变化时跳转到下一个匹配
+    fn setup_search_input(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let search_input = self.search_input.clone();
+
+        cx.subscribe_in(&search_input, window, |this, state, event, window, cx| {
+            use gpui_component::input::InputEvent as ComponentInputEvent;
+            if let ComponentInputEvent::PressEnter { .. } = event {
+                let query = state.read(cx).value().to_string();
+                if !this.document_matches.is_empty() && this.search_query.as_ref() == query {
+                    this.navigate_match(true, window, cx);
+                } else {
+                    this.start_search(query, cx);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// 设置快速打开输入框：输入时更新结果，按回车打开选中的文件
+    fn setup_quick_open_input(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let quick_open_input = self.quick_open_input.clone();
+
+        cx.subscribe_in(&quick_open_input, window, |this, _state, event, window, cx| {
+            use gpui_component::input::InputEvent as ComponentInputEvent;
+            match event {
+                ComponentInputEvent::Change => {
+                    this.quick_open_selected = 0;
+                    this.update_quick_open_results(cx);
+                }
+                ComponentInputEvent::PressEnter { .. } => {
+                    if let Some(result) = this.quick_open_results.get(this.quick_open_selected) {
+                        let path = result.path.clone();
+                        this.close_quick_open(cx);
+                        this.open_file(path, window, cx);
+                    }
+                }
+                _ => {}
+            }
+        })
+        .detach();
+    }
+
+    /// 打开或关闭快速打开面板
+    fn toggle_quick_open(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.quick_open.is_some() {
+            self.close_quick_open(cx);
+            return;
+        }
+        self.close_command_palette(cx);
+
+        self.quick_open = Some(QuickOpen::from_workspace(self.workspace.read(cx), self.quick_open_all_files));
+        self.quick_open_selected = 0;
+        self.quick_open_input.update(cx, |state, cx| {
+            state.set_value("", window, cx);
+        });
+        let focus_handle = self.quick_open_input.read(cx).focus_handle(cx);
+        window.focus(&focus_handle);
+
+        self.update_quick_open_results(cx);
+    }
+
+    /// 设置命令面板输入框：输入时更新结果，按回车执行选中的命令
+    fn setup_command_palette_input(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let command_palette_input = self.command_palette_input.clone();
+
+        cx.subscribe_in(&command_palette_input, window, |this, _state, event, window, cx| {
+            use gpui_component::input::InputEvent as ComponentInputEvent;
+            match event {
+                ComponentInputEvent::Change => {
+                    this.command_palette_selected = 0;
+                    this.update_command_palette_results(cx);
+                }
+                ComponentInputEvent::PressEnter { .. } => {
+                    this.run_palette_command(this.command_palette_selected, window, cx);
+                }
+                _ => {}
+            }
+        })
+        .detach();
+    }
+
+    /// 打开或关闭命令面板
+    fn toggle_command_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.command_palette {
+            self.close_command_palette(cx);
+            return;
+        }
+        self.close_quick_open(cx);
+
+        self.command_palette = true;
+        self.command_palette_selected = 0;
+        self.command_palette_input.update(cx, |state, cx| {
+            state.set_value("", window, cx);
+        });
+        let focus_handle = self.command_palette_input.read(cx).focus_handle(cx);
+        window.focus(&focus_handle);
+
+        self.update_command_palette_results(cx);
+    }
+
+    /// 关闭命令面板
+    fn close_command_palette(&mut self, cx: &mut Context<Self>) {
+        self.command_palette = false;
+        self.command_palette_results.clear();
+        cx.notify();
+    }
+
+    /// 根据输入更新命令面板的结果
+    fn update_command_palette_results(&mut self, cx: &mut Context<Self>) {
+        let query = self.command_palette_input.read(cx).value().to_string();
+        self.command_palette_results = search_commands(&query);
+        cx.notify();
+    }
+
+    /// 执行命令面板中的命令
+    ///
+    /// 先把焦点交还编辑器，再通过动作分发执行，与按快捷键的效果相同
+    fn run_palette_command(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(result) = self.command_palette_results.get(index) else {
+            return;
+        };
+        let Some(action) = actions::command_action(result.command.id) else {
+            return;
+        };
+
+        self.close_command_palette(cx);
+        let focus_handle = self.editor.read(cx).input_state().read(cx).focus_handle(cx);
+        window.focus(&focus_handle);
+        window.dispatch_action(action, cx);
+    }
+
+    /// 关闭快速打开面板
+    fn close_quick_open(&mut self, cx: &mut Context<Self>) {
+        self.quick_open = None;
+        self.quick_open_results.clear();
+        cx.notify();
+    }
+
+    /// 根据输入更新快速打开的结果
+    fn update_quick_open_results(&mut self, cx: &mut Context<Self>) {
+        let Some(quick_open) = &self.quick_open else {
+            return;
+        };
+
+        let query = self.quick_open_input.read(cx).value().to_string();
+        let recent = self.file_manager.read(cx).recent_files().to_vec();
+        self.quick_open_results = quick_open.search(&query, &recent, 50);
+        cx.notify();
+    }
+
+    /// 处理命令面板和快速打开中的按键：Esc 关闭，上下键选择
+    fn handle_key_down(&mut self, event: &KeyDownEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        let key = event.keystroke.key.as_str();
+
+        if self.command_palette {
+            match key {
+                "escape" => self.close_command_palette(cx),
+                "down" if self.command_palette_selected + 1 < self.command_palette_results.len() => {
+                    self.command_palette_selected += 1;
+                    cx.notify();
+                }
+                "up" => {
+                    self.command_palette_selected = self.command_palette_selected.saturating_sub(1);
+                    cx.notify();
+                }
+                _ => return,
+            }
+            cx.stop_propagation();
+            return;
+        }
+
+        if self.quick_open.is_none() {
+            return;
+        }
+
+        match key {
+            "escape" => self.close_quick_open(cx),
+            "down" if self.quick_open_selected + 1 < self.quick_open_results.len() => {
+                self.quick_open_selected += 1;
+                cx.notify();
+            }
+            "up" => {
+                self.quick_open_selected = self.quick_open_selected.saturating_sub(1);
+                cx.notify();
+            }
+            _ => return,
+        }
+        cx.stop_propagation();
+    }
+
+    /// 渲染快速打开面板
+    fn render_quick_open(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let highlight = HighlightStyle {
+            color: Some(rgb(ui.accent).into()),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        };
+
+        let mut list = div().flex().flex_col();
+        for (i, result) in self.quick_open_results.iter().enumerate() {
+            let path = result.path.clone();
+            let ranges = result.fuzzy.highlight_ranges(&result.display);
+
+            list = list.child(
+                div()
+                    .id(("quick_open_item", i))
+                    .px_2()
+                    .py_1()
+                    .cursor_pointer()
+                    .when(i == self.quick_open_selected, |element| element.bg(rgb(ui.selection)))
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(
+                        StyledText::new(result.display.clone())
+                            .with_highlights(ranges.into_iter().map(|range| (range, highlight)))
+                    )
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.close_quick_open(cx);
+                        this.open_file(path.clone(), window, cx);
+                    }))
+            );
+        }
+
+        div()
+            .absolute()
+            .top(px(48.0))
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                div()
+                    .w(px(600.0))
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .p_2()
+                    .bg(rgb(ui.overlay))
+                    .border_1()
+                    .border_color(rgb(ui.sidebar_border))
+                    .text_sm()
+                    .text_color(rgb(ui.sidebar_text))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_1()
+                            .child(div().flex_1().child(Input::new(&self.quick_open_input)))
+                            .child(
+                                Button::new("quick_open_all_files")
+                                    .child("全部文件")
+                                    .selected(self.quick_open_all_files)
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.quick_open_all_files = !this.quick_open_all_files;
+                                        this.quick_open = Some(QuickOpen::from_workspace(
+                                            this.workspace.read(cx),
+                                            this.quick_open_all_files,
+                                        ));
+                                        this.quick_open_selected = 0;
+                                        this.update_quick_open_results(cx);
+                                    }))
+                            )
+                    )
+                    .child(if self.quick_open_results.is_empty() {
+                        div().p_2().child("没有匹配的文件")
+                    } else {
+                        list
+                    })
+            )
+    }
+
+    /// 渲染命令面板：命令名称和当前快捷键
+    fn render_command_palette(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let highlight = HighlightStyle {
+            color: Some(rgb(ui.accent).into()),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        };
+
+        let mut list = div().flex().flex_col();
+        for (i, result) in self.command_palette_results.iter().enumerate() {
+            let bindings: Vec<String> = self.keymap
+                .bindings(result.command.id)
+                .iter()
+                .map(|keys| format_keystrokes(keys))
+                .collect();
+
+            list = list.child(
+                div()
+                    .id(("command_palette_item", i))
+                    .flex()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .cursor_pointer()
+                    .when(i == self.command_palette_selected, |element| element.bg(rgb(ui.selection)))
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(
+                        StyledText::new(result.command.title)
+                            .with_highlights(result.title_ranges.iter().cloned().map(|range| (range, highlight)))
+                    )
+                    .child(div().text_color(rgb(ui.muted_text)).child(bindings.join(", ")))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.run_palette_command(i, window, cx);
+                    }))
+            );
+        }
+
+        div()
+            .absolute()
+            .top(px(48.0))
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                div()
+                    .w(px(600.0))
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .p_2()
+                    .bg(rgb(ui.overlay))
+                    .border_1()
+                    .border_color(rgb(ui.sidebar_border))
+                    .text_sm()
+                    .text_color(rgb(ui.sidebar_text))
+                    .child(Input::new(&self.command_palette_input))
+                    .child(if self.command_palette_results.is_empty() {
+                        div().p_2().child("没有匹配的命令")
+                    } else {
+                        list
+                    })
+            )
+    }
+
+    /// 切换搜索选项并重新搜索
+    fn toggle_search_option(&mut self, toggle: impl FnOnce(&mut SearchOptions), cx: &mut Context<Self>) {
+        self.search_manager.update(cx, |manager, _cx| {
+            let mut options = manager.options();
+            toggle(&mut options);
+            manager.set_options(options);
+        });
+
+        let query = self.search_query.to_string();
+        self.start_search(query, cx);
+    }
+
+    /// 渲染搜索选项开关
+    fn render_search_options(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let options = self.search_manager.read(cx).options();
+
+        div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .child(
+                Button::new("search_case")
+                    .child("Aa")
+                    .selected(options.case_sensitive)
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.toggle_search_option(|o| o.case_sensitive = !o.case_sensitive, cx);
+                    }))
+            )
+            .child(
+                Button::new("search_word")
+                    .child("ab")
+                    .selected(options.whole_word)
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.toggle_search_option(|o| o.whole_word = !o.whole_word, cx);
+                    }))
+            )
+            .child(
+                Button::new("search_regex")
+                    .child(".*")
+                    .selected(options.use_regex)
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.toggle_search_option(|o| o.use_regex = !o.use_regex, cx);
+                    }))
+            )
+            .child(
+                Button::new("search_index")
+                    .child("索引")
+                    .selected(self.use_index)
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.use_index = !this.use_index;
+                        let query = this.search_query.to_string();
+                        this.start_search(query, cx);
+                    }))
+            )
+            .child(
+                Button::new("search_diacritics")
+                    .child("é")
+                    .selected(options.match_diacritics)
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.toggle_search_option(|o| o.match_diacritics = !o.match_diacritics, cx);
+                    }))
+            )
+    }
+
+    /// 更新预览内容
+    fn update_preview(&mut self, markdown: &str, cx: &mut Context<Self>) {
+        let html = MarkdownParser::parse_with_styles(markdown);
+        self.preview.update(cx, |preview, _cx| {
+            preview.update_html(html);
+        });
+    }
+
+    /// 选择并打开文件
+    fn open_file_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("Markdown", &["md", "markdown"])
+            .add_filter("Text", &["txt"])
+            .add_filter("Word / HTML", &["docx", "html", "htm"])
+            .add_filter("All Files", &["*"])
+            .pick_file()
+        {
+            if import::is_importable(&path) {
+                self.import_file_dialog(path, window, cx);
+            } else {
+                self.open_file(path, window, cx);
+            }
+        }
+    }
+
+    /// 选择保存位置，把 Word 或 HTML 文档转换为 Markdown 后打开
+    fn import_file_dialog(&mut self, source: std::path::PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        let file_name = source
+            .file_stem()
+            .map(|stem| format!("{}.md", stem.to_string_lossy()))
+            .unwrap_or_else(|| "未命名.md".to_string());
+        let mut dialog = FileDialog::new()
+            .add_filter("Markdown", &["md", "markdown"])
+            .set_file_name(file_name);
+        if let Some(directory) = source.parent() {
+            dialog = dialog.set_directory(directory);
+        }
+        let Some(target) = dialog.save_file() else {
+            return;
+        };
+
+        match import::import_file(&source, &target, &ImportOptions::default()) {
+            Ok(()) => {
+                self.export_message = Some(format!("已导入: {}", target.display()));
+                self.open_file(target, window, cx);
+            }
+            Err(e) => {
+                self.export_message = Some(format!("导入失败: {:#}", e));
+                cx.notify();
+            }
+        }
+    }
+
+    /// 选择保存位置并另存为
+    fn save_as_dialog(&mut self, cx: &mut Context<Self>) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("Markdown", &["md", "markdown"])
+            .add_filter("Text", &["txt"])
+            .add_filter("All Files", &["*"])
+            .save_file()
+        {
+            self.save_as(path, cx);
+        }
+    }
+
+    /// 选择文件夹作为工作区打开
+    fn open_folder_dialog(&mut self, cx: &mut Context<Self>) {
+        if let Some(path) = FileDialog::new()
+            .set_directory(self.default_directory(cx))
+            .pick_folder()
+        {
+            self.open_workspace(path, cx);
+        }
+    }
+
+    /// 选择文件夹添加到工作区
+    fn add_folder_dialog(&mut self, cx: &mut Context<Self>) {
+        if let Some(path) = FileDialog::new()
+            .set_directory(self.default_directory(cx))
+            .pick_folder()
+        {
+            self.add_workspace_folder(path, cx);
+        }
+    }
+
+    /// 先选择要移动的文件，再选择新位置，然后预览需要更新的链接
+    fn move_file_dialog(&mut self, cx: &mut Context<Self>) {
+        let root = self.default_directory(cx);
+        let Some(from) = FileDialog::new()
+            .set_directory(&root)
+            .pick_file()
+        else {
+            return;
+        };
+        let file_name = from.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if let Some(to) = FileDialog::new()
+            .set_directory(from.parent().unwrap_or(&root))
+            .set_file_name(file_name)
+            .save_file()
+        {
+            self.prepare_move(from, to, cx);
+        }
+    }
+
+    /// 按当前主题、用户 CSS 和代码主题创建 HTML 导出器
+    fn html_exporter(&self) -> HtmlExporter {
+        let style = PreviewStyle::new(&self.theme, &self.settings.preview, &self.user_css);
+        HtmlExporter::new(HtmlExportOptions {
+            style: style.html_style(&self.user_css),
+            code_theme: self.settings.appearance.code_theme.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// 选择保存位置，把当前文档导出为自包含的 HTML 文件
+    fn export_html_dialog(&mut self, cx: &mut Context<Self>) {
+        let source = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        let file_name = source
+            .as_deref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| format!("{}.html", stem.to_string_lossy()))
+            .unwrap_or_else(|| "未命名.html".to_string());
+        let directory = source
+            .as_deref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| self.default_directory(cx));
+        let Some(target) = FileDialog::new()
+            .add_filter("HTML", &["html", "htm"])
+            .set_directory(directory)
+            .set_file_name(file_name)
+            .save_file()
+        else {
+            return;
+        };
+
+        let markdown = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        self.export_message = Some(match self.html_exporter().export_file(&markdown, source.as_deref(), &target) {
+            Ok(()) => format!("已导出: {}", target.display()),
+            Err(e) => format!("导出失败: {:#}", e),
+        });
+        cx.notify();
+    }
+
+    /// 选择文件夹，把工作区中的所有 Markdown 文件导出为互相链接的 HTML 页面
+    ///
+    /// 工作区有多个根文件夹时，每个根文件夹导出到目标文件夹中的同名子文件夹
+    fn export_workspace_dialog(&mut self, cx: &mut Context<Self>) {
+        let roots: Vec<std::path::PathBuf> = self.workspace
+            .read(cx)
+            .roots()
+            .iter()
+            .map(|tree| tree.root_path().to_path_buf())
+            .collect();
+        if roots.is_empty() {
+            self.export_message = Some("请先打开文件夹".to_string());
+            cx.notify();
+            return;
+        }
+        let Some(target_dir) = FileDialog::new()
+            .set_directory(self.default_directory(cx))
+            .pick_folder()
+        else {
+            return;
+        };
+
+        let exporter = self.html_exporter();
+        let mut pages = 0;
+        let mut errors = Vec::new();
+        for root in &roots {
+            let target = match root.file_name() {
+                Some(name) if roots.len() > 1 => target_dir.join(name),
+                _ => target_dir.clone(),
+            };
+            match exporter.export_workspace(root, &target) {
+                Ok(result) => {
+                    pages += result.pages.len();
+                    errors.extend(result.errors);
+                }
+                Err(e) => errors.push(format!("{:#}", e)),
+            }
+        }
+
+        let mut message = format!("已导出 {} 个页面到 {}", pages, target_dir.display());
+        if !errors.is_empty() {
+            message.push_str(&format!("，{} 个错误: {}", errors.len(), errors.join("; ")));
+        }
+        self.export_message = Some(message);
+        cx.notify();
+    }
+
+    /// 选择保存位置，在后台把当前文档导出为 PDF
+    ///
+    /// 读取字体和排版可能较慢，导出期间可以继续编辑
+    fn export_pdf_dialog(&mut self, cx: &mut Context<Self>) {
+        let source = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        let file_name = source
+            .as_deref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| format!("{}.pdf", stem.to_string_lossy()))
+            .unwrap_or_else(|| "未命名.pdf".to_string());
+        let directory = source
+            .as_deref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| self.default_directory(cx));
+        let Some(target) = FileDialog::new()
+            .add_filter("PDF", &["pdf"])
+            .set_directory(directory)
+            .set_file_name(file_name)
+            .save_file()
+        else {
+            return;
+        };
+
+        let markdown = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        let settings = self.settings.pdf.clone();
+        self.export_message = Some(format!("正在导出: {}", target.display()));
+        cx.notify();
+
+        // 字体数据不能跨线程共享，导出器在后台线程中创建
+        self.export_task = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn({
+                    let target = target.clone();
+                    async move {
+                        let exporter = PdfExporter::new(PdfExportOptions::from_settings(&settings)?)?;
+                        exporter.export_file(&markdown, source.as_deref(), &target)
+                    }
+                })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.export_task = None;
+                this.export_message = Some(match result {
+                    Ok(warnings) if warnings.is_empty() => format!("已导出: {}", target.display()),
+                    Ok(warnings) => format!("已导出: {}（警告: {}）", target.display(), warnings.join("；")),
+                    Err(e) => format!("导出失败: {:#}", e),
+                });
+                cx.notify();
+            });
+        }));
+    }
+
+    /// 选择保存位置，把当前文档导出为 Word 文档
+    fn export_docx_dialog(&mut self, cx: &mut Context<Self>) {
+        let source = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        let file_name = source
+            .as_deref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| format!("{}.docx", stem.to_string_lossy()))
+            .unwrap_or_else(|| "未命名.docx".to_string());
+        let directory = source
+            .as_deref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| self.default_directory(cx));
+        let Some(target) = FileDialog::new()
+            .add_filter("Word", &["docx"])
+            .set_directory(directory)
+            .set_file_name(file_name)
+            .save_file()
+        else {
+            return;
+        };
+
+        let markdown = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        let result = DocxExporter::new(DocxExportOptions::from_settings(&self.settings.docx))
+            .and_then(|exporter| exporter.export_file(&markdown, source.as_deref(), &target));
+        self.export_message = Some(match result {
+            Ok(()) => format!("已导出: {}", target.display()),
+            Err(e) => format!("导出失败: {:#}", e),
+        });
+        cx.notify();
+    }
+
+    /// 聚焦搜索框
+    fn focus_search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let focus_handle = self.search_input.read(cx).focus_handle(cx);
+        window.focus(&focus_handle);
+    }
+
+    /// 为命令表中的每个命令注册动作处理
+    fn bind_actions(&self, element: Stateful<Div>, cx: &mut Context<Self>) -> Stateful<Div> {
+        element
+            .on_action(cx.listener(|this, _: &actions::NewFile, window, cx| this.new_file(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::OpenFile, window, cx| this.open_file_dialog(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::SaveFile, _window, cx| this.save_file(cx)))
+            .on_action(cx.listener(|this, _: &actions::SaveAs, _window, cx| this.save_as_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::OpenFolder, _window, cx| this.open_folder_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::AddFolder, _window, cx| this.add_folder_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::MoveFile, _window, cx| this.move_file_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::QuickOpen, window, cx| this.toggle_quick_open(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::CommandPalette, window, cx| {
+                this.toggle_command_palette(window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::Find, window, cx| this.focus_search(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::NextMatch, window, cx| this.navigate_match(true, window, cx)))
+            .on_action(cx.listener(|this, _: &actions::PreviousMatch, window, cx| {
+                this.navigate_match(false, window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::ReplaceNext, window, cx| this.replace_next(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::ReplaceAll, window, cx| this.replace_all(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::ReplaceInWorkspace, _window, cx| {
+                this.prepare_workspace_replace(cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::UndoReplace, window, cx| {
+                this.undo_workspace_replace(window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::CycleTheme, window, cx| this.cycle_theme(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::OpenSettings, window, cx| this.open_settings(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::OpenKeymap, window, cx| this.open_keymap(window, cx)))
+            .on_action(cx.listener(|this, _: &actions::ExportHtml, _window, cx| this.export_html_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::ExportPdf, _window, cx| this.export_pdf_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::ExportDocx, _window, cx| this.export_docx_dialog(cx)))
+            .on_action(cx.listener(|this, _: &actions::ExportWorkspaceHtml, _window, cx| {
+                this.export_workspace_dialog(cx)
+            }))
+            .on_action(cx.listener(|this, _: &actions::CheckLinks, _window, cx| this.check_workspace_links(cx)))
+    }
+
+    /// 新建文件
+    fn new_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.save_file_switch_state(cx);
+        self.file_manager.update(cx, |manager, _cx| {
+            manager.new_file();
+        });
+        self.editor.update(cx, |editor, cx| {
+            editor.set_content("", window, cx);
+            editor.set_document_path(None);
+        });
+        self.sync_preview_base_dir(cx);
+        self.refresh_backlinks(cx);
+        self.search_messages.clear();
+        self.clear_document_results(cx);
+        cx.notify();
+    }
+
+    /// 打开文件
+    fn open_file(&mut self, path: std::path::PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        self.save_file_switch_state(cx);
+
+        // 先尝试打开文件并获取内容
+        let open_result = self.file_manager.update(cx, |manager, _cx| {
+            manager.open_file(&path).map(|_| manager.content().to_string())
+        });
+        
+        // 如果成功，更新编辑器内容
+        if let Ok(content) = open_result {
+            self.editor.update(cx, |editor, cx| {
+                editor.set_content(content, window, cx);
+                editor.set_document_path(Some(&path));
+            });
+            self.sync_preview_base_dir(cx);
+            self.restore_file_state(&path, window, cx);
+            self.refresh_backlinks(cx);
+        }
+        cx.notify();
+    }
+
+    /// 保存文件
+    fn save_file(&mut self, cx: &mut Context<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            let content = editor.content(cx).to_string();
+            self.file_manager.update(cx, |manager, _cx| {
+                manager.set_content(content);
+                if let Err(e) = manager.save_file() {
+                    eprintln!("保存文件失败: {}", e);
+                }
+            });
+        });
+
+        let current_file = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        if let Some(path) = current_file {
+            self.index_file(&path, cx);
+        }
+        cx.notify();
+    }
+
+    /// 另存为
+    fn save_as(&mut self, path: std::path::PathBuf, cx: &mut Context<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            let content = editor.content(cx).to_string();
+            let saved = self.file_manager.update(cx, |manager, _cx| {
+                manager.set_content(content);
+                let result = manager.save_as(&path);
+                if let Err(e) = &result {
+                    eprintln!("另存为失败: {}", e);
+                }
+                result.is_ok()
+            });
+            // 新文档中粘贴的图片暂存在临时文件夹，第一次保存时移到文档旁边
+            if saved {
+                if let Err(e) = editor.save_document_as(&path) {
+                    eprintln!("移动图片失败: {:#}", e);
+                }
+            }
+        });
+        self.sync_preview_base_dir(cx);
+        self.refresh_backlinks(cx);
+        cx.notify();
+    }
+
+    /// 预览中图片的相对路径相对于当前文档所在的文件夹
+    fn sync_preview_base_dir(&mut self, cx: &mut Context<Self>) {
+        let base_dir = self.editor.read(cx).base_dir().to_path_buf();
+        self.preview.update(cx, |preview, cx| {
+            preview.set_base_dir(base_dir);
+            cx.notify();
+        });
+    }
+
+    /// 执行搜索：有工作区时搜索工作区中的所有文件，否则只搜索当前文档
+    fn start_search(&mut self, query: String, cx: &mut Context<Self>) {
+        if self.workspace.read(cx).is_empty() {
+            self.perform_search(query.clone(), cx);
+        } else if self.use_index {
+            self.search_index(query.clone(), cx);
+        } else {
+            self.start_workspace_search(query.clone(), cx);
+        }
+
+        // 每次用户发起的搜索只记录一次历史，查询无效（有错误信息）时不记录
+        if self.search_messages.is_empty() {
+            self.search_manager.update(cx, |manager, _cx| manager.add_to_history(&query));
+        }
+    }
+
+    /// 使用全文索引搜索工作区（支持短语、`tag:` 和 `path:`）
+    fn search_index(&mut self, query: String, cx: &mut Context<Self>) {
+        self.search_query = query.clone().into();
+        self.cancel_workspace_search();
+        self.workspace_results.clear();
+        self.search_messages.clear();
+        // 同时搜索当前文档，以便在编辑器中跳转和高亮匹配
+        self.current_match = None;
+        self.refresh_document_results(cx);
+
+        let mut hits: Vec<IndexHit> = self.search_indexes
+            .iter()
+            .flat_map(|index| index.search(&query, 100))
+            .collect();
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        hits.truncate(100);
+
+        self.search_status = Some(format!("索引中找到 {} 个文件", hits.len()));
+        self.index_results = hits;
+        cx.notify();
+    }
+
+    /// 在当前文档中搜索
+    fn perform_search(&mut self, query: String, cx: &mut Context<Self>) {
+        self.search_query = query.clone().into();
+        self.cancel_workspace_search();
+        self.workspace_results.clear();
+        self.index_results.clear();
+        self.search_status = None;
+
+        self.search_messages.clear();
+        self.current_match = None;
+        self.refresh_document_results(cx);
+    }
+
+    /// 按当前查询重新搜索当前文档（内容变化后调用），尽量保留选中的匹配
+    ///
+    /// 工作区或索引搜索时同样搜索当前文档，用于跳转和高亮
+    fn refresh_document_results(&mut self, cx: &mut Context<Self>) {
+        let query = self.search_query.to_string();
+        if query.is_empty() {
+            self.clear_document_results(cx);
+            return;
+        }
+
+        let content = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        let results = self.search_manager.update(cx, |manager, _cx| manager.try_search(&query, &content));
+
+        match results {
+            Ok(results) => {
+                self.document_matches = document_matches(&content, &results);
+                self.document_results = results;
+            }
+            Err(e) => {
+                self.search_messages = vec![e.to_string()];
+                self.document_matches.clear();
+                self.document_results.clear();
+            }
+        }
+
+        if self.current_match.is_some_and(|i| i >= self.document_matches.len()) {
+            self.current_match = None;
+        }
+        self.update_preview_highlights(cx);
+        cx.notify();
+    }
+
+    /// 清空当前文档的搜索结果和高亮
+    fn clear_document_results(&mut self, cx: &mut Context<Self>) {
+        self.document_results.clear();
+        self.document_matches.clear();
+        self.current_match = None;
+        self.update_preview_highlights(cx);
+    }
+
+    /// 把当前文档的匹配同步到预览高亮
+    fn update_preview_highlights(&mut self, cx: &mut Context<Self>) {
+        // `document_matches` 与各行的匹配位置按相同顺序一一对应
+        let highlights = self.document_results
+            .iter()
+            .flat_map(|result| {
+                result.match_positions.iter().map(|&(start, end)| (result.line_number, start..end))
+            })
+            .enumerate()
+            .map(|(i, (line_number, range))| PreviewHighlight {
+                line_number,
+                range,
+                current: self.current_match == Some(i),
+            })
+            .collect();
+
+        self.preview.update(cx, |preview, cx| {
+            preview.set_highlights(highlights);
+            cx.notify();
+        });
+    }
+
+    /// 选中指定的匹配：在编辑器中选中匹配的文本，并在预览和编辑区的匹配标记中标出当前匹配
+    ///
+    /// 不修改文档，跳转不会触发自动保存
+    fn select_match(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(doc_match) = self.document_matches.get(index).cloned() else {
+            return;
+        };
+
+        self.current_match = Some(index);
+        self.editor.update(cx, |editor, cx| {
+            editor.select_range(doc_match.range, window, cx);
+        });
+        self.update_preview_highlights(cx);
+        cx.notify();
+    }
+
+    /// 渲染编辑区右侧的匹配标记：按所在行的位置标出当前文档中的所有匹配，点击跳转
+    fn render_match_markers(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let line_count = self.markdown_content.lines().count().max(1);
+        let mut element = div().absolute().top_0().right_0().w(px(6.0)).h_full();
+
+        for (i, doc_match) in self.document_matches.iter().enumerate() {
+            let current = self.current_match == Some(i);
+            let position = (doc_match.line_number - 1) as f32 / line_count as f32;
+            element = element.child(
+                div()
+                    .id(("match_marker", i))
+                    .absolute()
+                    .top(relative(position))
+                    .w_full()
+                    .h(px(3.0))
+                    .cursor_pointer()
+                    .bg(rgb(if current { ui.accent } else { ui.muted_text }))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.select_match(i, window, cx);
+                    }))
+            );
+        }
+
+        element
+    }
+
+    /// 跳转到下一个（`forward`）或上一个匹配，到达末尾后循环
+    fn navigate_match(&mut self, forward: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let count = self.document_matches.len();
+        if count == 0 {
+            return;
+        }
+
+        let index = match (self.current_match, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        self.select_match(index, window, cx);
+    }
+
+    /// 渲染当前文档的搜索结果，点击跳转到对应位置
+    fn render_document_results(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let mut element = div().flex().flex_col().gap_1();
+
+        for message in &self.search_messages {
+            element = element.child(div().child(message.clone()));
+        }
+
+        if !self.document_matches.is_empty() {
+            element = element.child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(match self.current_match {
+                        Some(i) => format!("第 {}/{} 处", i + 1, self.document_matches.len()),
+                        None => format!("共 {} 处", self.document_matches.len()),
+                    })
+                    .child(
+                        Button::new("previous_match")
+                            .child("↑")
+                            .on_click(cx.listener(|this, _event, window, cx| {
+                                this.navigate_match(false, window, cx);
+                            }))
+                    )
+                    .child(
+                        Button::new("next_match")
+                            .child("↓")
+                            .on_click(cx.listener(|this, _event, window, cx| {
+                                this.navigate_match(true, window, cx);
+                            }))
+                    )
+            );
+        }
+
+        let current_line = self.current_match
+            .and_then(|i| self.document_matches.get(i))
+            .map(|m| m.line_number);
+
+        for (i, result) in self.document_results.iter().enumerate() {
+            // 点击一行时选中该行的第一个匹配
+            let first_match = self.document_matches
+                .iter()
+                .position(|m| m.line_number == result.line_number);
+
+            element = element.child(
+                div()
+                    .id(("document_result", i))
+                    .cursor_pointer()
+                    .when(current_line == Some(result.line_number), |element| element.bg(rgb(ui.selection)))
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(format!("Line {}: {}", result.line_number, result.preview))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        if let Some(index) = first_match {
+                            this.select_match(index, window, cx);
+                        }
+                    }))
+            );
+        }
+
+        element
+    }
+
+    /// 在后台并行搜索工作区中的所有文件，结果逐个文件显示
+    fn start_workspace_search(&mut self, query: String, cx: &mut Context<Self>) {
+        self.search_query = query.clone().into();
+        self.cancel_workspace_search();
+        self.workspace_results.clear();
+        self.index_results.clear();
+        self.search_messages.clear();
+        self.search_status = None;
+        // 同时搜索当前文档，以便在编辑器中跳转和高亮匹配
+        self.current_match = None;
+        self.refresh_document_results(cx);
+
+        if query.is_empty() {
+            cx.notify();
+            return;
+        }
+
+        let roots: Vec<std::path::PathBuf> = self.workspace
+            .read(cx)
+            .roots()
+            .iter()
+            .map(|tree| tree.root_path().to_path_buf())
+            .collect();
+        let options = self.search_manager.read(cx).options();
+
+        match WorkspaceSearch::start(&query, options, roots) {
+            Ok((search, mut receiver)) => {
+                self.workspace_search = Some(search);
+                self.search_status = Some("搜索中…".to_string());
+                self.search_task = Some(cx.spawn(async move |this, cx| {
+                    while let Some(event) = receiver.next().await {
+                        if this.update(cx, |this, cx| this.handle_search_event(event, cx)).is_err() {
+                            break;
+                        }
+                    }
+                }));
+            }
+            Err(e) => self.search_messages = vec![e.to_string()],
+        }
+        cx.notify();
+    }
+
+    /// 处理工作区搜索事件
+    fn handle_search_event(&mut self, event: SearchEvent, cx: &mut Context<Self>) {
+        match event {
+            SearchEvent::Matches(file) => {
+                // 按路径排序插入，避免结果随完成顺序跳动
+                let index = self.workspace_results.partition_point(|f| f.path < file.path);
+                self.workspace_results.insert(index, file);
+            }
+            SearchEvent::Finished { files_searched, cancelled } => {
+                let matches: usize = self.workspace_results
+                    .iter()
+                    .flat_map(|f| &f.results)
+                    .map(|r| r.match_positions.len())
+                    .sum();
+                self.search_status = Some(if cancelled {
+                    format!("已取消：{} 个文件中找到 {} 处", self.workspace_results.len(), matches)
+                } else {
+                    format!(
+                        "{} 个文件中找到 {} 处（共搜索 {} 个文件）",
+                        self.workspace_results.len(),
+                        matches,
+                        files_searched
+                    )
+                });
+                self.workspace_search = None;
+                self.search_task = None;
+            }
+        }
+        cx.notify();
+    }
+
+    /// 取消正在进行的工作区搜索
+    fn cancel_workspace_search(&mut self) {
+        if let Some(search) = self.workspace_search.take() {
+            search.cancel();
+        }
+        self.search_task = None;
+    }
+
+    /// 订阅预览中点击链接和编辑区中 Ctrl+点击链接的事件
+    fn setup_link_handlers(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        cx.subscribe_in(&self.preview, window, |this, _preview, event: &PreviewEvent, window, cx| match event {
+            PreviewEvent::OpenLink(dest) => this.open_link(dest, window, cx),
+            PreviewEvent::OpenWikiLink(link) => this.open_wiki_link(link, window, cx),
+        })
+        .detach();
+        cx.subscribe_in(&self.editor, window, |this, _editor, event: &TextEditorEvent, window, cx| match event {
+            TextEditorEvent::OpenLink(dest) => this.open_link(dest, window, cx),
+            TextEditorEvent::OpenWikiLink(link) => this.open_wiki_link(link, window, cx),
+        })
+        .detach();
+    }
+
+    /// 打开链接：网址确认后在浏览器中打开，`#标题` 跳转到当前文档中的标题，
+    /// Markdown 文档在编辑器中打开（可以指定标题），其他文件确认后用系统默认程序打开
+    fn open_link(&mut self, dest: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let base_dir = self.editor.read(cx).base_dir().to_path_buf();
+        match resolve_link_target(&base_dir, dest) {
+            Some(LinkTarget::External(url)) => {
+                let answer = MessageDialog::new()
+                    .set_title("打开链接")
+                    .set_description(format!("在浏览器中打开以下地址？\n{}", url))
+                    .set_buttons(MessageButtons::OkCancel)
+                    .show();
+                if answer == MessageDialogResult::Ok {
+                    cx.open_url(&url);
+                }
+            }
+            Some(LinkTarget::Anchor(anchor)) => self.go_to_anchor(&anchor, window, cx),
+            Some(LinkTarget::Document { path, anchor }) => {
+                if !path.is_file() {
+                    self.export_message = Some(format!("链接的文档不存在: {}", path.display()));
+                    cx.notify();
+                    return;
+                }
+                if self.file_manager.read(cx).current_file() != Some(path.as_path()) {
+                    self.open_file(path, window, cx);
+                }
+                if let Some(anchor) = anchor {
+                    self.go_to_anchor(&anchor, window, cx);
+                }
+            }
+            Some(LinkTarget::File(path)) => {
+                if !path.exists() {
+                    self.export_message = Some(format!("链接的文件不存在: {}", path.display()));
+                    cx.notify();
+                    return;
+                }
+                // 文件可能是脚本或程序，打开前需要确认
+                let answer = MessageDialog::new()
+                    .set_title("打开文件")
+                    .set_description(format!("用系统默认程序打开以下文件？\n{}", path.display()))
+                    .set_buttons(MessageButtons::OkCancel)
+                    .show();
+                if answer == MessageDialogResult::Ok {
+                    cx.open_with_system(&path);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// 打开 wiki 链接：按笔记名在工作区中查找笔记，笔记不存在时询问是否创建
+    fn open_wiki_link(&mut self, link: &WikiLink, window: &mut Window, cx: &mut Context<Self>) {
+        let anchor = link.heading.as_deref().map(heading_slug);
+        if link.note.is_empty() {
+            if let Some(anchor) = anchor {
+                self.go_to_anchor(&anchor, window, cx);
+            }
+            return;
+        }
+
+        let base_dir = self.editor.read(cx).base_dir().to_path_buf();
+        let notes = self.workspace_notes(cx);
+        let Some(path) = resolve_wiki_link(&notes, &base_dir, &link.note) else {
+            if let Some(path) = self.create_linked_note(&link.note, cx) {
+                self.open_file(path, window, cx);
+            }
+            return;
+        };
+        if self.file_manager.read(cx).current_file() != Some(path.as_path()) {
+            self.open_file(path, window, cx);
+        }
+        if let Some(anchor) = anchor {
+            self.go_to_anchor(&anchor, window, cx);
+        }
+    }
+
+    /// 询问是否创建 wiki 链接指向的笔记，新笔记放在当前文档所在的文件夹中
+    fn create_linked_note(&mut self, note: &str, cx: &mut Context<Self>) -> Option<std::path::PathBuf> {
+        let current_file = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        let directory = current_file
+            .as_deref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| self.default_directory(cx));
+        let Some(path) = new_note_path(&directory, note) else {
+            self.export_message = Some(format!("无法创建笔记: {}", note));
+            cx.notify();
+            return None;
+        };
+
+        let answer = MessageDialog::new()
+            .set_title("创建笔记")
+            .set_description(format!("笔记“{}”不存在，是否创建？\n{}", note, path.display()))
+            .set_buttons(MessageButtons::OkCancel)
+            .show();
+        if answer != MessageDialogResult::Ok {
+            return None;
+        }
+        if let Err(e) = create_note(&path) {
+            self.export_message = Some(format!("创建笔记失败: {:#}", e));
+            cx.notify();
+            return None;
+        }
+
+        self.workspace.update(cx, |workspace, _cx| {
+            if let Err(e) = workspace.refresh() {
+                eprintln!("刷新文件树失败: {}", e);
+            }
+        });
+        self.sync_indexes(cx);
+        self.sync_notes(cx);
+        Some(path)
+    }
+
+    /// 工作区中所有 Markdown 文件的路径
+    fn workspace_notes(&self, cx: &mut Context<Self>) -> Vec<std::path::PathBuf> {
+        self.workspace.read(cx).markdown_files().into_iter().map(|item| item.path.clone()).collect()
+    }
+
+    /// 把工作区中的笔记同步到预览（用于显示 wiki 链接的笔记是否存在），并重新查找反向链接
+    fn sync_notes(&mut self, cx: &mut Context<Self>) {
+        let notes = self.workspace_notes(cx);
+        self.preview.update(cx, |preview, cx| {
+            preview.set_notes(notes);
+            cx.notify();
+        });
+        self.refresh_backlinks(cx);
+    }
+
+    /// 在后台查找工作区中链接到当前文档的其他文档
+    fn refresh_backlinks(&mut self, cx: &mut Context<Self>) {
+        let current_file = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        let Some(target) = current_file else {
+            self.backlinks.clear();
+            self.backlinks_task = None;
+            return;
+        };
+
+        let notes = self.workspace_notes(cx);
+        self.backlinks_task = Some(cx.spawn(async move |this, cx| {
+            let backlinks = cx.background_spawn(async move { find_backlinks(&notes, &target) }).await;
+            let _ = this.update(cx, |this, cx| {
+                this.backlinks = backlinks;
+                this.backlinks_task = None;
+                cx.notify();
+            });
+        }));
+    }
+
+    /// 渲染反向链接：链接到当前文档的文件、行号和所在行的内容，点击后跳转到链接所在的位置
+    fn render_backlinks(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let root = self.default_directory(cx);
+        let mut element = div()
+            .id("backlinks")
+            .max_h(px(180.0))
+            .overflow_y_scroll()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .border_t(px(1.0))
+            .border_color(rgb(ui.divider))
+            .text_xs()
+            .text_color(rgb(ui.muted_text))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(rgb(ui.panel_header_text))
+                    .child(format!("反向链接 ({})", self.backlinks.len()))
+            );
+        if self.backlinks.is_empty() {
+            return element.child("没有其他文档链接到这里");
+        }
+
+        for (i, backlink) in self.backlinks.iter().enumerate() {
+            let path = backlink.file.clone();
+            let line = backlink.line_number;
+            element = element.child(
+                div()
+                    .id(("backlink", i))
+                    .cursor_pointer()
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(
+                        div()
+                            .text_color(rgb(ui.sidebar_text))
+                            .child(format!("{}:{}", path.strip_prefix(&root).unwrap_or(&path).display(), line))
+                    )
+                    .child(div().pl_2().child(backlink.context.clone()))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.open_search_hit(path.clone(), line, 1, window, cx);
+                    }))
+            );
+        }
+
+        element
+    }
+
+    /// 在后台检查工作区中所有 Markdown 文件的链接，没有工作区时只检查当前文件
+    ///
+    /// 检查的是已保存的内容，结果显示在搜索结果区域
+    fn check_workspace_links(&mut self, cx: &mut Context<Self>) {
+        let mut files = self.workspace_notes(cx);
+        if files.is_empty() {
+            match self.file_manager.read(cx).current_file() {
+                Some(path) => files.push(path.to_path_buf()),
+                None => {
+                    self.export_message = Some("请先打开文件夹或保存文档".to_string());
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+
+        self.link_problems = Some(Vec::new());
+        self.link_check_task = Some(cx.spawn(async move |this, cx| {
+            let problems = cx.background_spawn(async move { check_links(&files) }).await;
+            let _ = this.update(cx, |this, cx| {
+                this.link_check_task = None;
+                this.link_problems = Some(problems);
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    /// 渲染链接检查的结果（文件、行号和问题），点击后跳转到问题所在的位置
+    fn render_link_problems(&self, problems: &[LinkProblem], cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let root = self.default_directory(cx);
+        let status = if self.link_check_task.is_some() {
+            "正在检查链接…".to_string()
+        } else if problems.is_empty() {
+            "没有发现问题".to_string()
+        } else {
+            format!("发现 {} 个问题", problems.len())
+        };
+
+        let mut element = div().flex().flex_col().gap_1().child(
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(status)
+                .child(
+                    Button::new("close_link_problems")
+                        .child("关闭")
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.link_problems = None;
+                            this.link_check_task = None;
+                            cx.notify();
+                        }))
+                )
+        );
+
+        for (i, problem) in problems.iter().enumerate() {
+            let path = problem.file.clone();
+            let line = problem.line_number;
+            element = element.child(
+                div()
+                    .id(("link_problem", i))
+                    .pt_1()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(
+                        div()
+                            .text_color(rgb(ui.sidebar_text))
+                            .child(format!("{}:{}", path.strip_prefix(&root).unwrap_or(&path).display(), line))
+                    )
+                    .child(div().pl_2().child(problem.message()))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.open_search_hit(path.clone(), line, 1, window, cx);
+                    }))
+            );
+        }
+
+        element
+    }
+
+    /// 跳转到当前文档中锚点对应的标题：编辑区的光标移到标题所在行，预览滚动到标题
+    fn go_to_anchor(&mut self, anchor: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let content = self.editor.update(cx, |editor, cx| editor.content(cx));
+        let lower = anchor.to_lowercase();
+        let headings = heading_anchors(&content);
+        let Some(heading) = headings.into_iter().find(|heading| heading.anchor.to_lowercase() == lower) else {
+            self.export_message = Some(format!("找不到标题: #{}", anchor));
+            cx.notify();
+            return;
+        };
+
+        self.editor.update(cx, |editor, cx| {
+            editor.go_to(heading.line_number, 1, window, cx);
+        });
+        self.preview.update(cx, |preview, cx| {
+            preview.scroll_to_line(heading.line_number);
+            cx.notify();
+        });
+    }
+
+    /// 打开搜索结果所在的文件并跳转到匹配位置
+    fn open_search_hit(
+        &mut self,
+        path: std::path::PathBuf,
+        line: usize,
+        column: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.file_manager.read(cx).current_file() != Some(path.as_path()) {
+            self.open_file(path, window, cx);
+        }
+        self.editor.update(cx, |editor, cx| {
+            editor.go_to(line, column, window, cx);
+        });
+    }
+
+    /// 渲染工作区搜索结果（按文件分组）
+    fn render_workspace_results(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let root = self.default_directory(cx);
+        let mut element = div().flex().flex_col().gap_1();
+
+        if let Some(status) = &self.search_status {
+            element = element.child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(status.clone())
+                    .when(self.workspace_search.is_some(), |element| {
+                        element.child(
+                            Button::new("cancel_search")
+                                .child("停止")
+                                .on_click(cx.listener(|this, _event, _window, cx| {
+                                    this.cancel_workspace_search();
+                                    this.search_status = Some("已取消".to_string());
+                                    cx.notify();
+                                }))
+                        )
+                    })
+            );
+        }
+
+        for (i, hit) in self.index_results.iter().enumerate() {
+            let path = hit.path.clone();
+            let line = hit.line_number;
+
+            element = element.child(
+                div()
+                    .id(("index_hit", i))
+                    .pt_1()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(rgb(ui.hover)))
+                    .child(
+                        div()
+                            .text_color(rgb(ui.sidebar_text))
+                            .child(format!(
+                                "{} ({:.2})",
+                                hit.path.strip_prefix(&root).unwrap_or(&hit.path).display(),
+                                hit.score
+                            ))
+                    )
+                    .child(div().pl_2().child(format!("{}  {}", line, hit.line.trim())))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.open_search_hit(path.clone(), line, 1, window, cx);
+                    }))
+            );
+        }
+
+        let mut index = 0;
+        for file in &self.workspace_results {
+            element = element.child(
+                div()
+                    .pt_1()
+                    .text_color(rgb(ui.sidebar_text))
+                    .child(format!(
+                        "{} ({})",
+                        file.path.strip_prefix(&root).unwrap_or(&file.path).display(),
+                        file.results.len()
+                    ))
+            );
+
+            for result in &file.results {
+                let path = file.path.clone();
+                let line = result.line_number;
+                let column = result.column();
+
+                element = element.child(
+                    div()
+                        .id(("search_hit", index))
+                        .pl_2()
+                        .cursor_pointer()
+                        .hover(|style| style.bg(rgb(ui.hover)))
+                        .child(format!("{}:{}  {}", line, column, result.preview))
+                        .on_click(cx.listener(move |this, _event, window, cx| {
+                            this.open_search_hit(path.clone(), line, column, window, cx);
+                        }))
+                );
+                index += 1;
+            }
+        }
+
+        element
     }
 
-    /// 更新预览内容
-    fn update_preview(&mut self, markdown: &str, cx: &mut Context<Self>) {
-        let html = MarkdownParser::parse_with_styles(markdown);
-        self.preview.update(cx, |preview, _cx| {
-            preview.update_html(html);
+    /// 替换当前文档中光标之后的下一个匹配，替换后光标移到替换的文字之后
+    ///
+    /// 替换作为一次编辑应用，撤销时作为一步
+    fn replace_next(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.search_input.read(cx).value().to_string();
+        let replacement = self.replace_input.read(cx).value().to_string();
+        if query.is_empty() {
+            return;
+        }
+
+        let content = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        let offset = self.editor.update(cx, |editor, cx| editor.selection(window, cx).start);
+        let result = self.search_manager.update(cx, |manager, _cx| {
+            manager.replace_next(&query, &replacement, &content, offset)
         });
+
+        match result {
+            Ok(Some((new_content, range))) => {
+                if let Some(mut edit) = TextEdit::between(&content, &new_content, offset) {
+                    edit.selection = range.end..range.end;
+                    self.editor.update(cx, |editor, cx| editor.apply_edit(&edit, window, cx));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => self.search_messages = vec![e.to_string()],
+        }
+
+        self.perform_search(query, cx);
     }
 
-    /// 新建文件
-    fn new_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        self.file_manager.update(cx, |manager, _cx| {
-            manager.new_file();
-        });
-        self.editor.update(cx, |editor, cx| {
-            editor.set_content("", window, cx);
+    /// 替换当前文档中的所有匹配
+    ///
+    /// 所有替换作为一次编辑应用，撤销时作为一步，光标留在原来的文字旁
+    fn replace_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.search_input.read(cx).value().to_string();
+        let replacement = self.replace_input.read(cx).value().to_string();
+        if query.is_empty() {
+            return;
+        }
+
+        let content = self.editor.update(cx, |editor, cx| editor.content(cx).to_string());
+        let cursor = self.editor.update(cx, |editor, cx| editor.selection(window, cx).start);
+        let result = self.search_manager.update(cx, |manager, _cx| {
+            manager.replace_all(&query, &replacement, &content)
         });
-        self.search_results.clear();
+
+        match result {
+            Ok((new_content, count)) => {
+                if let Some(edit) = TextEdit::between(&content, &new_content, cursor) {
+                    self.editor.update(cx, |editor, cx| editor.apply_edit(&edit, window, cx));
+                }
+                self.perform_search(query, cx);
+                self.search_messages.insert(0, format!("已替换 {} 处", count));
+            }
+            Err(e) => self.search_messages = vec![e.to_string()],
+        }
         cx.notify();
     }
 
-    /// 打开文件
-    fn open_file(&mut self, path: std::path::PathBuf, window: &mut Window, cx: &mut Context<Self>) {
-        // 先尝试打开文件并获取内容
-        let open_result = self.file_manager.update(cx, |manager, _cx| {
-            manager.open_file(&path).map(|_| manager.content().to_string())
+    /// 在工作区所有 Markdown 文件中搜索，生成替换预览
+    fn prepare_workspace_replace(&mut self, cx: &mut Context<Self>) {
+        let query = self.search_input.read(cx).value().to_string();
+        let replacement = self.replace_input.read(cx).value().to_string();
+        if query.is_empty() {
+            return;
+        }
+
+        // 先保存当前文件，替换基于磁盘上的内容
+        if self.file_manager.read(cx).current_file().is_some() {
+            self.save_file(cx);
+        }
+
+        let files: Vec<std::path::PathBuf> = self.workspace
+            .read(cx)
+            .markdown_files()
+            .into_iter()
+            .map(|item| item.path.clone())
+            .collect();
+
+        let plan = self.search_manager.update(cx, |manager, _cx| {
+            let results = manager.search_in_files(&query, &files);
+            ReplacePlan::new(manager, &query, &replacement, &results)
         });
-        
-        // 如果成功，更新编辑器内容
-        if let Ok(content) = open_result {
-            self.editor.update(cx, |editor, cx| {
-                editor.set_content(content, window, cx);
-            });
+
+        match plan {
+            Ok(plan) if plan.files.is_empty() => self.search_messages = vec!["工作区中没有匹配".to_string()],
+            Ok(plan) => self.replace_plan = Some(plan),
+            Err(e) => self.search_messages = vec![e.to_string()],
         }
         cx.notify();
     }
 
-    /// 保存文件
-    fn save_file(&mut self, cx: &mut Context<Self>) {
-        self.editor.update(cx, |editor, cx| {
-            let content = editor.content(cx).to_string();
-            self.file_manager.update(cx, |manager, _cx| {
-                manager.set_content(content);
-                if let Err(e) = manager.save_file() {
-                    eprintln!("保存文件失败: {}", e);
+    /// 应用工作区替换计划
+    fn apply_workspace_replace(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(plan) = self.replace_plan.take() else {
+            return;
+        };
+
+        match plan.apply() {
+            Ok(transaction) => {
+                self.search_messages = vec![format!("已修改 {} 个文件", transaction.file_count())];
+                let paths: Vec<_> = transaction.paths().cloned().collect();
+                self.last_replace = Some(transaction);
+                self.reload_if_current(&paths, window, cx);
+                for path in &paths {
+                    self.index_file(path, cx);
                 }
-            });
-        });
+            }
+            Err(e) => self.search_messages = vec![format!("替换失败: {}", e)],
+        }
         cx.notify();
     }
 
-    /// 另存为
-    fn save_as(&mut self, path: std::path::PathBuf, cx: &mut Context<Self>) {
-        self.editor.update(cx, |editor, cx| {
-            let content = editor.content(cx).to_string();
-            self.file_manager.update(cx, |manager, _cx| {
-                manager.set_content(content);
-                if let Err(e) = manager.save_as(&path) {
-                    eprintln!("另存为失败: {}", e);
+    /// 撤销最近一次工作区替换
+    fn undo_workspace_replace(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(transaction) = self.last_replace.take() else {
+            return;
+        };
+
+        let paths: Vec<_> = transaction.paths().cloned().collect();
+        match transaction.undo() {
+            Ok(()) => {
+                self.search_messages = vec!["已撤销替换".to_string()];
+                self.reload_if_current(&paths, window, cx);
+                for path in &paths {
+                    self.index_file(path, cx);
                 }
-            });
-        });
+            }
+            Err(e) => self.search_messages = vec![format!("撤销失败: {}", e)],
+        }
         cx.notify();
     }
 
-    /// 执行搜索
-    fn perform_search(&mut self, query: String, cx: &mut Context<Self>) {
-        if query.is_empty() {
-            self.search_results.clear();
+    /// 当前文件在修改列表中时重新加载
+    fn reload_if_current(&mut self, paths: &[std::path::PathBuf], window: &mut Window, cx: &mut Context<Self>) {
+        let current_file = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        if let Some(path) = current_file {
+            if paths.contains(&path) {
+                self.open_file(path, window, cx);
+            }
+        }
+    }
+
+    /// 渲染工作区替换预览
+    fn render_replace_preview(&self, plan: &ReplacePlan, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let root = self.default_directory(cx);
+
+        let mut element = div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(format!(
+                "{} → {}：已选 {} 处",
+                plan.query,
+                plan.replacement,
+                plan.selected_count()
+            ));
+
+        let mut index = 0;
+        for (file_index, file) in plan.files.iter().enumerate() {
+            element = element.child(
+                div()
+                    .text_color(rgb(ui.sidebar_text))
+                    .child(file.path.strip_prefix(&root).unwrap_or(&file.path).display().to_string())
+            );
+
+            for (item_index, item) in file.items.iter().enumerate() {
+                element = element.child(
+                    div()
+                        .flex()
+                        .items_start()
+                        .gap_1()
+                        .child(
+                            Button::new(("replace_item", index))
+                                .child(if item.selected { "☑" } else { "☐" })
+                                .on_click(cx.listener(move |this, _event, _window, cx| {
+                                    if let Some(plan) = &mut this.replace_plan {
+                                        plan.toggle(file_index, item_index);
+                                    }
+                                    cx.notify();
+                                }))
+                        )
+                        .child(
+                            div()
+                                .flex_1()
+                                .child(format!("{}: - {}", item.line_number, item.old_line.trim()))
+                                .child(
+                                    div()
+                                        .text_color(rgb(ui.sidebar_text))
+                                        .child(format!("{}: + {}", item.line_number, item.new_line.trim()))
+                                )
+                        )
+                );
+                index += 1;
+            }
+        }
+
+        element.child(
+            div()
+                .flex()
+                .gap_2()
+                .child(
+                    Button::new("apply_replace")
+                        .child("应用")
+                        .on_click(cx.listener(|this, _event, window, cx| {
+                            this.apply_workspace_replace(window, cx);
+                        }))
+                )
+                .child(
+                    Button::new("cancel_replace")
+                        .child("取消")
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.replace_plan = None;
+                            cx.notify();
+                        }))
+                )
+        )
+    }
+
+    /// 准备移动/重命名文件，生成引用更新预览
+    fn prepare_move(&mut self, from: std::path::PathBuf, to: std::path::PathBuf, cx: &mut Context<Self>) {
+        // 先保存当前文件，避免应用修改后重新加载时丢失编辑内容
+        if self.file_manager.read(cx).current_file().is_some() {
+            self.save_file(cx);
+        }
+
+        let workspace = self.workspace.read(cx);
+        let plan = match workspace.root_for(&from) {
+            Some(file_tree) => RenamePlan::new(file_tree, &from, &to),
+            None => Err(anyhow::anyhow!("文件不在工作区内: {}", from.display())),
+        };
+        match plan {
+            Ok(plan) => self.rename_plan = Some(plan),
+            Err(e) => eprintln!("无法移动文件: {}", e),
+        }
+        cx.notify();
+    }
+
+    /// 应用移动/重命名计划
+    fn apply_move(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(plan) = self.rename_plan.take() else {
+            return;
+        };
+
+        let from = plan.from().to_path_buf();
+        let to = plan.to().to_path_buf();
+
+        if let Err(e) = plan.apply() {
+            eprintln!("移动文件失败: {}", e);
             cx.notify();
             return;
         }
 
-        self.editor.update(cx, |editor, cx| {
-            let content = editor.content(cx).to_string();
-            self.search_manager.update(cx, |manager, _cx| {
-                let results = manager.search(&query, &content);
-                self.search_results = results.iter()
-                    .map(|r| format!("Line {}: {}", r.line_number, r.preview))
-                    .collect();
-            });
+        self.file_manager.update(cx, |manager, _cx| {
+            manager.relocate(&from, &to);
+        });
+
+        // 当前文件的内容或路径可能已改变，重新加载
+        let current_file = self.file_manager.read(cx).current_file().map(|p| p.to_path_buf());
+        if let Some(path) = current_file {
+            self.open_file(path, window, cx);
+        }
+
+        self.workspace.update(cx, |workspace, _cx| {
+            if let Err(e) = workspace.refresh() {
+                eprintln!("刷新文件树失败: {}", e);
+            }
         });
+        self.sync_indexes(cx);
+        self.sync_notes(cx);
         cx.notify();
     }
 
+    /// 渲染引用更新预览
+    fn render_rename_preview(&self, plan: &RenamePlan, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let ui = self.theme.ui;
+        let root = self.workspace
+            .read(cx)
+            .root_for(plan.from())
+            .map(|tree| tree.root_path().to_path_buf())
+            .unwrap_or_default();
+        let display = |path: &std::path::Path| {
+            path.strip_prefix(&root).unwrap_or(path).display().to_string()
+        };
+
+        let mut element = div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(format!("{} → {}", display(plan.from()), display(plan.to())))
+            .child(format!(
+                "将修改 {} 个文件中的 {} 处链接",
+                plan.affected_files(),
+                plan.edits().len()
+            ));
+
+        for edit in plan.edits() {
+            element = element.child(
+                div()
+                    .child(format!("{}:{}", display(&edit.file), edit.line_number))
+                    .child(
+                        div()
+                            .pl_2()
+                            .text_color(rgb(ui.sidebar_text))
+                            .child(format!("{} → {}", edit.old_dest, edit.new_dest))
+                    )
+            );
+        }
+
+        element.child(
+            div()
+                .flex()
+                .gap_2()
+                .child(
+                    Button::new("apply_move")
+                        .child("应用")
+                        .on_click(cx.listener(|this, _event, window, cx| {
+                            this.apply_move(window, cx);
+                        }))
+                )
+                .child(
+                    Button::new("cancel_move")
+                        .child("取消")
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.rename_plan = None;
+                            cx.notify();
+                        }))
+                )
+        )
+    }
+
     /// 渲染文件项
     fn render_file_item(&self, item: &FileItem, depth: usize, cx: &mut Context<MainWindow>) -> impl IntoElement {
         let is_directory = item.file_type == FileType::Directory;
@@ -227,11 +2773,11 @@ impl MainWindow {
         
         // 文本颜色
         let text_color = if is_markdown {
-            rgb(0x00ccff)  // Markdown 文件用蓝色
+            rgb(self.theme.ui.markdown_file)
         } else if is_directory {
-            rgb(0xffcc00)  // 文件夹用黄色
+            rgb(self.theme.ui.folder)
         } else {
-            rgb(0xcccccc)  // 普通文件用灰色
+            rgb(self.theme.ui.sidebar_text)
         };
         
         // 如果是文件，使用 Button 组件支持点击
@@ -246,8 +2792,8 @@ impl MainWindow {
             // 文件夹使用 Button 组件支持点击展开/折叠
             Button::new("folder")
                 .on_click(cx.listener(move |this, _event, _window, cx| {
-                    this.file_tree.update(cx, |file_tree, _cx| {
-                        file_tree.toggle_expand(&path);
+                    this.workspace.update(cx, |workspace, _cx| {
+                        workspace.toggle_expand(&path);
                     });
                     cx.notify();
                 }))
@@ -279,17 +2825,89 @@ impl MainWindow {
     }
     
     /// 渲染文件树
+    ///
+    /// 多根工作区时每个根文件夹单独显示一个标题；工作区为空时显示空状态
     fn render_file_tree(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
-        let file_tree = self.file_tree.read(cx);
-        let root_item = file_tree.root_item();
-        
-        let children = root_item.children.clone();
+        let workspace = self.workspace.read(cx);
+        if workspace.is_empty() {
+            return self.render_empty_workspace(cx).into_any_element();
+        }
+
+        let roots: Vec<FileItem> = workspace.roots().iter().map(|tree| tree.root_item().clone()).collect();
+        let show_headers = roots.len() > 1;
         let mut element = div();
-        
-        for child in children {
-            element = self.render_file_item_recursive(element, child, 0, cx);
+
+        for (index, root_item) in roots.into_iter().enumerate() {
+            if show_headers {
+                let root_path = root_item.path.clone();
+                element = element.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .justify_between()
+                        .mt_1()
+                        .text_xs()
+                        .text_color(rgb(self.theme.ui.muted_text))
+                        .child(root_item.name.to_uppercase())
+                        .child(
+                            Button::new(("remove_root", index))
+                                .child("×")
+                                .on_click(cx.listener(move |this, _event, _window, cx| {
+                                    this.workspace.update(cx, |workspace, _cx| {
+                                        workspace.remove_root(&root_path);
+                                    });
+                                    this.sync_indexes(cx);
+                                    this.sync_notes(cx);
+                                    cx.notify();
+                                }))
+                        )
+                );
+            }
+
+            for child in root_item.children {
+                element = self.render_file_item_recursive(element, child, 0, cx);
+            }
         }
-        
+
+        element.into_any_element()
+    }
+
+    /// 渲染空工作区：打开文件夹按钮和最近打开的工作区
+    fn render_empty_workspace(&self, cx: &mut Context<MainWindow>) -> impl IntoElement {
+        let recent = self.workspace.read(cx).recent_workspaces().to_vec();
+
+        let mut element = div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .p_2()
+            .text_sm()
+            .text_color(rgb(self.theme.ui.muted_text))
+            .child("尚未打开文件夹")
+            .child(
+                Button::new("empty_open_folder")
+                    .child("打开文件夹…")
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        if let Some(path) = FileDialog::new().pick_folder() {
+                            this.open_workspace(path, cx);
+                        }
+                    }))
+            );
+
+        if !recent.is_empty() {
+            element = element.child(div().mt_2().text_xs().child("最近打开"));
+            for (index, path) in recent.into_iter().enumerate() {
+                let label = path.display().to_string();
+                element = element.child(
+                    Button::new(("recent_workspace", index))
+                        .child(label)
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.open_workspace(path.clone(), cx);
+                        }))
+                );
+            }
+        }
+
         element
     }
 }
@@ -298,14 +2916,31 @@ impl Render for MainWindow {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         // 获取当前文件名
         let filename = self.file_manager.read(cx).current_filename();
+        // 预览区占编辑区和预览区总宽度的比例
+        let preview_width = self.settings.preview.width as f32;
+        // 打开了工作区中的文件时在预览下方显示反向链接
+        let show_backlinks =
+            self.file_manager.read(cx).current_file().is_some() && !self.workspace.read(cx).is_empty();
+        let ui = self.theme.ui;
+        // 设置错误以及用户 CSS 和快捷键的警告
+        let notices: Vec<String> = self.settings_error
+            .iter()
+            .chain(&self.css_warnings)
+            .chain(&self.keymap_warnings)
+            .cloned()
+            .collect();
 
         // 创建三栏布局：左侧文件树 + 中间编辑器 + 右侧预览
         div()
+            .id("main_window")
+            .relative()
             .h_full()
             .w_full()
             .flex()
             .flex_col()
-            .bg(rgb(0xf5f5f5))
+            .bg(rgb(ui.background))
+            .capture_key_down(cx.listener(Self::handle_key_down))
+            .map(|element| self.bind_actions(element, cx))
             .child(
                 // 顶部工具栏
                 div()
@@ -314,7 +2949,7 @@ impl Render for MainWindow {
                     .flex()
                     .items_center()
                     .px_2()
-                    .bg(rgb(0x2d2d2d))
+                    .bg(rgb(ui.toolbar))
                     .child(
                         // 文件操作按钮
                         div()
@@ -332,15 +2967,7 @@ impl Render for MainWindow {
                                 Button::new("open")
                                     .child("打开")
                                     .on_click(cx.listener(|this, _event, window, cx| {
-                                        // 打开文件对话框
-                                        if let Some(path) = FileDialog::new()
-                                            .add_filter("Markdown", &["md", "markdown"])
-                                            .add_filter("Text", &["txt"])
-                                            .add_filter("All Files", &["*"])
-                                            .pick_file()
-                                        {
-                                            this.open_file(path, window, cx);
-                                        }
+                                        this.open_file_dialog(window, cx);
                                     }))
                             )
                             .child(
@@ -353,16 +2980,71 @@ impl Render for MainWindow {
                             .child(
                                 Button::new("save_as")
                                     .child("另存为")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.save_as_dialog(cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("settings")
+                                    .child("设置")
                                     .on_click(cx.listener(|this, _event, window, cx| {
-                                        // 打开保存对话框
-                                        if let Some(path) = FileDialog::new()
-                                            .add_filter("Markdown", &["md", "markdown"])
-                                            .add_filter("Text", &["txt"])
-                                            .add_filter("All Files", &["*"])
-                                            .save_file()
-                                        {
-                                            this.save_as(path, cx);
-                                        }
+                                        this.open_settings(window, cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("theme")
+                                    .child(format!("主题: {}", self.settings.appearance.theme))
+                                    .on_click(cx.listener(|this, _event, window, cx| {
+                                        this.cycle_theme(window, cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("command_palette")
+                                    .child("命令")
+                                    .on_click(cx.listener(|this, _event, window, cx| {
+                                        this.toggle_command_palette(window, cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("quick_open")
+                                    .child("快速打开")
+                                    .on_click(cx.listener(|this, _event, window, cx| {
+                                        this.toggle_quick_open(window, cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("open_folder")
+                                    .child("打开文件夹")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.open_folder_dialog(cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("add_folder")
+                                    .child("添加文件夹")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.add_folder_dialog(cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("move")
+                                    .child("移动/重命名")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.move_file_dialog(cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("export_html")
+                                    .child("导出")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.export_html_dialog(cx);
+                                    }))
+                            )
+                            .child(
+                                Button::new("export_pdf")
+                                    .child("导出 PDF")
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.export_pdf_dialog(cx);
                                     }))
                             )
                     )
@@ -371,7 +3053,7 @@ impl Render for MainWindow {
                         div()
                             .flex_1()
                             .px_4()
-                            .text_color(rgb(0xffffff))
+                            .text_color(rgb(ui.toolbar_text))
                             .text_sm()
                             .child(filename)
                     )
@@ -389,38 +3071,108 @@ impl Render for MainWindow {
                                     .child(
                                         div()
                                             .w(px(150.0))
-                                            .px_2()
-                                            .py_1()
-                                            .bg(rgb(0x4a4a4a))
-                                            .text_color(rgb(0xffffff))
-                                            .text_sm()
-                                            .rounded(px(2.0))
-                                            .child("搜索...")
+                                            .child(Input::new(&self.search_input))
                                     )
+                                    .child(self.render_search_options(cx))
                                     .child(
                                         Button::new("search")
                                             .child("搜索")
                                             .on_click(cx.listener(|this, _event, _window, cx| {
-                                                this.perform_search("test".to_string(), cx);
+                                                let query = this.search_input.read(cx).value().to_string();
+                                                this.start_search(query, cx);
+                                            }))
+                                    )
+                            )
+                            .child(
+                                // 替换框和替换按钮
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .gap_1()
+                                    .child(
+                                        div()
+                                            .w(px(120.0))
+                                            .child(Input::new(&self.replace_input))
+                                    )
+                                    .child(
+                                        Button::new("replace_next")
+                                            .child("替换")
+                                            .on_click(cx.listener(|this, _event, window, cx| {
+                                                this.replace_next(window, cx);
+                                            }))
+                                    )
+                                    .child(
+                                        Button::new("replace_all")
+                                            .child("全部替换")
+                                            .on_click(cx.listener(|this, _event, window, cx| {
+                                                this.replace_all(window, cx);
+                                            }))
+                                    )
+                                    .child(
+                                        Button::new("replace_workspace")
+                                            .child("工作区替换")
+                                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                                this.prepare_workspace_replace(cx);
                                             }))
                                     )
+                                    .when(self.last_replace.is_some(), |element| {
+                                        element.child(
+                                            Button::new("undo_replace")
+                                                .child("撤销替换")
+                                                .on_click(cx.listener(|this, _event, window, cx| {
+                                                    this.undo_workspace_replace(window, cx);
+                                                }))
+                                        )
+                                    })
                             )
                     )
             )
+            .when(!notices.is_empty(), |element| {
+                // 设置文件或用户 CSS 有误时提示用户，修正后自动消失
+                element.child(
+                    div()
+                        .w_full()
+                        .px_4()
+                        .py_1()
+                        .bg(rgb(ui.error_background))
+                        .text_color(rgb(ui.error_text))
+                        .text_sm()
+                        .children(notices.into_iter().map(|notice| div().child(notice)))
+                )
+            })
+            .when_some(self.export_message.clone(), |element, message| {
+                // 导出、导入或打开链接的结果，点击后关闭
+                element.child(
+                    div()
+                        .id("export_message")
+                        .w_full()
+                        .px_4()
+                        .py_1()
+                        .bg(rgb(ui.toolbar))
+                        .text_color(rgb(ui.toolbar_text))
+                        .text_sm()
+                        .cursor_pointer()
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.export_message = None;
+                            cx.notify();
+                        }))
+                        .child(message)
+                )
+            })
             .child(
                 // 主内容区域
                 div()
                     .flex_1()
                     .flex()
-                    .bg(rgb(0xf5f5f5))
+                    .bg(rgb(ui.background))
                     .child(
                         // 左侧文件树和搜索结果（宽度 250px）
                         div()
                             .w(px(250.0))
                             .h_full()
-                            .bg(rgb(0x2d2d2d))
+                            .bg(rgb(ui.toolbar))
                             .border_r(px(1.0))
-                            .border_color(rgb(0x1a1a1a))
+                            .border_color(rgb(ui.sidebar_border))
                             .flex()
                             .flex_col()
                             .child(
@@ -433,7 +3185,7 @@ impl Render for MainWindow {
                                         div()
                                             .p_2()
                                             .text_sm()
-                                            .text_color(rgb(0xcccccc))
+                                            .text_color(rgb(ui.sidebar_text))
                                             .child("文件树")
                                     )
                                     .child(
@@ -450,13 +3202,21 @@ impl Render for MainWindow {
                                     .flex_col()
                                     .h_2_5()  // 40% 高度
                                     .border_t(px(1.0))
-                                    .border_color(rgb(0x1a1a1a))
+                                    .border_color(rgb(ui.sidebar_border))
                                     .child(
                                         div()
                                             .p_2()
                                             .text_sm()
-                                            .text_color(rgb(0xcccccc))
-                                            .child("搜索结果")
+                                            .text_color(rgb(ui.sidebar_text))
+                                            .child(if self.rename_plan.is_some() {
+                                                "引用更新预览"
+                                            } else if self.replace_plan.is_some() {
+                                                "替换预览"
+                                            } else if self.link_problems.is_some() {
+                                                "链接问题"
+                                            } else {
+                                                "搜索结果"
+                                            })
                                     )
                                     .child(
                                         div()
@@ -464,21 +3224,21 @@ impl Render for MainWindow {
                                             .overflow_scroll()
                                             .p_2()
                                             .text_xs()
-                                            .text_color(rgb(0x999999))
+                                            .text_color(rgb(ui.muted_text))
                                             .map(|mut element| {
-                                                // 显示搜索结果
-                                                if self.search_results.is_empty() {
+                                                // 有待确认的移动时优先显示引用更新预览
+                                                if let Some(plan) = &self.rename_plan {
+                                                    element = element.child(self.render_rename_preview(plan, cx));
+                                                } else if let Some(plan) = &self.replace_plan {
+                                                    element = element.child(self.render_replace_preview(plan, cx));
+                                                } else if let Some(problems) = &self.link_problems {
+                                                    element = element.child(self.render_link_problems(problems, cx));
+                                                } else if self.search_status.is_some() {
+                                                    element = element.child(self.render_workspace_results(cx));
+                                                } else if self.search_messages.is_empty() && self.document_results.is_empty() {
                                                     element = element.child("暂无搜索结果");
                                                 } else {
-                                                    for (i, result) in self.search_results.iter().enumerate() {
-                                                        if i > 0 {
-                                                            element = element.child(div().h(px(4.0)));
-                                                        }
-                                                        element = element.child(
-                                                            div()
-                                                                .child(result.clone())
-                                                        );
-                                                    }
+                                                    element = element.child(self.render_document_results(cx));
                                                 }
                                                 element
                                             })
@@ -493,44 +3253,46 @@ impl Render for MainWindow {
                             .child(
                                 // 左侧编辑区
                                 div()
-                                    .w_1_2()
+                                    .w(relative(1.0 - preview_width))
                                     .h_full()
                                     .border_r(px(1.0))
-                                    .border_color(rgb(0xdddddd))
-                                    .bg(rgb(0xffffff))
+                                    .border_color(rgb(ui.border))
+                                    .bg(rgb(ui.panel))
                                     .flex()
                                     .flex_col()
                                     .child(
                                         div()
                                             .p_2()
                                             .text_sm()
-                                            .text_color(rgb(0x666666))
+                                            .text_color(rgb(ui.panel_header_text))
                                             .border_b(px(1.0))
-                                            .border_color(rgb(0xeeeeee))
+                                            .border_color(rgb(ui.divider))
                                             .child("编辑器")
                                     )
                                     .child(
                                         div()
                                             .flex_1()
                                             .overflow_hidden()
+                                            .relative()
                                             .child(self.editor.clone())
+                                            .child(self.render_match_markers(cx))
                                     )
                             )
                             .child(
                                 // 右侧预览区
                                 div()
-                                    .w_1_2()
+                                    .w(relative(preview_width))
                                     .h_full()
-                                    .bg(rgb(0xffffff))
+                                    .bg(rgb(ui.panel))
                                     .flex()
                                     .flex_col()
                                     .child(
                                         div()
                                             .p_2()
                                             .text_sm()
-                                            .text_color(rgb(0x666666))
+                                            .text_color(rgb(ui.panel_header_text))
                                             .border_b(px(1.0))
-                                            .border_color(rgb(0xeeeeee))
+                                            .border_color(rgb(ui.divider))
                                             .child("预览")
                                     )
                                     .child(
@@ -539,14 +3301,120 @@ impl Render for MainWindow {
                                             .overflow_hidden()
                                             .child(self.preview.clone())
                                     )
+                                    .when(show_backlinks, |element| {
+                                        element.child(self.render_backlinks(cx))
+                                    })
                             )
                     )
             )
+            .when(self.quick_open.is_some(), |element| {
+                // 快速打开面板浮在内容之上
+                element.child(self.render_quick_open(cx))
+            })
+            .when(self.command_palette, |element| {
+                element.child(self.render_command_palette(cx))
+            })
+    }
+}
+
+/// 解析命令行参数，返回要打开的文件或文件夹
+fn parse_args() -> Vec<std::path::PathBuf> {
+    std::env::args_os()
+        .skip(1)
+        .map(std::path::PathBuf::from)
+        .collect()
+}
+
+/// 读取上次保存的应用状态，读取失败时使用默认状态
+fn load_app_state(store: Option<&StateStore>) -> AppState {
+    let Some(store) = store else {
+        return AppState::default();
+    };
+
+    store.load().unwrap_or_else(|e| {
+        eprintln!("读取应用状态失败: {:#}", e);
+        AppState::default()
+    })
+}
+
+/// 读取用户设置，失败时使用默认设置并返回错误信息
+fn load_settings(file: Option<&mut SettingsFile>) -> (Settings, Option<String>) {
+    let Some(file) = file else {
+        return (Settings::default(), None);
+    };
+
+    match file.load() {
+        Ok(settings) => (settings, None),
+        Err(e) => (Settings::default(), Some(format!("{:#}", e))),
+    }
+}
+
+/// 读取用户 CSS，返回无法识别的内容；读取失败时不使用用户 CSS
+fn load_user_css(file: Option<&mut UserCssFile>) -> (UserCss, Vec<String>) {
+    let Some(file) = file else {
+        return (UserCss::default(), Vec::new());
+    };
+
+    file.load().unwrap_or_else(|e| (UserCss::default(), vec![format!("{:#}", e)]))
+}
+
+/// 读取快捷键，返回快捷键文件中的问题；读取失败时使用默认快捷键
+fn load_keymap(file: Option<&mut KeymapFile>) -> (Keymap, Vec<String>) {
+    let Some(file) = file else {
+        return (Keymap::default(), Vec::new());
+    };
+
+    file.load().unwrap_or_else(|e| (Keymap::default(), vec![format!("{:#}", e)]))
+}
+
+/// 把窗口位置转换为可保存的形式
+fn window_geometry(bounds: WindowBounds) -> WindowGeometry {
+    let (bounds, maximized) = match bounds {
+        WindowBounds::Windowed(bounds) => (bounds, false),
+        WindowBounds::Maximized(bounds) | WindowBounds::Fullscreen(bounds) => (bounds, true),
+    };
+
+    WindowGeometry {
+        x: f32::from(bounds.origin.x),
+        y: f32::from(bounds.origin.y),
+        width: f32::from(bounds.size.width),
+        height: f32::from(bounds.size.height),
+        maximized,
+    }
+}
+
+/// 根据保存的窗口位置确定初始窗口，没有保存时使用默认大小
+fn initial_window_bounds(geometry: Option<WindowGeometry>) -> WindowBounds {
+    let Some(geometry) = geometry else {
+        return WindowBounds::Windowed(Bounds::new(
+            Point::new(px(100.0), px(100.0)),  // 初始位置
+            gpui::Size::new(px(1400.0), px(900.0))  // 初始大小：1400x900（更大的窗口以容纳分栏）
+        ));
+    };
+
+    let bounds = Bounds::new(
+        Point::new(px(geometry.x), px(geometry.y)),
+        gpui::Size::new(px(geometry.width.max(400.0)), px(geometry.height.max(300.0))),
+    );
+    if geometry.maximized {
+        WindowBounds::Maximized(bounds)
+    } else {
+        WindowBounds::Windowed(bounds)
     }
 }
 
 /// 应用程序入口点
 fn main() {
+    // 子命令在命令行中完成，不打开窗口
+    let args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    let paths = parse_args();
+    let state_store = StateStore::open_default();
+    let app_state = load_app_state(state_store.as_ref());
+
     // 创建 GPUI 应用实例
     let app = Application::new();
 
@@ -565,20 +3433,15 @@ fn main() {
                         title: Some("ReadRS - Markdown 编辑器".into()),
                         ..Default::default()
                     }),
-                    // 窗口初始大小和位置
-                    window_bounds: Some(WindowBounds::Windowed(
-                        Bounds::new(
-                            Point::new(px(100.0), px(100.0)),  // 初始位置
-                            gpui::Size::new(px(1400.0), px(900.0))  // 初始大小：1400x900（更大的窗口以容纳分栏）
-                        )
-                    )),
+                    // 窗口初始大小和位置（恢复上次关闭时的位置）
+                    window_bounds: Some(initial_window_bounds(app_state.window)),
                     // 窗口默认聚焦
                     focus: true,
                     ..Default::default()
                 },
                 |window, cx| {
                     // 创建主窗口视图
-                    let view = cx.new(|cx| MainWindow::new(window, cx));
+                    let view = cx.new(|cx| MainWindow::new(paths, state_store, app_state, window, cx));
                     
                     // 重要：窗口的第一层必须是 Root 组件
                     cx.new(|cx| Root::new(view, window, cx))
@@ -0,0 +1,219 @@
+//! 文档中的图片
+//!
+//! 导入、粘贴或拖入的图片保存在文档旁边的图片文件夹中（默认为 `assets/`）：
+//! - 文件名只保留字母、数字、`-`、`_` 和 `.`
+//! - 内容相同的图片只保存一次，文件名重复但内容不同时加上编号
+//! - 粘贴和拖入的图片在文件名后加上 UUID（如 `assets/截图-<uuid>.png`），避免与其他文档的图片冲突
+//! - 未保存的文档使用临时文件夹，第一次保存时图片随文档移动
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+/// 图片文件夹的名称
+pub const ASSETS_DIR: &str = "assets";
+
+/// 可以粘贴或拖入文档的图片格式
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "tif", "tiff"];
+
+/// 保存到文档旁边的图片
+pub struct Assets {
+    dir: PathBuf,
+    /// 图片文件夹相对于文档的路径，用于生成 Markdown 中的链接
+    folder: String,
+    /// 是否在文件名后加上 UUID
+    unique: bool,
+    /// 已保存的图片内容和对应的文件名
+    saved: HashMap<Vec<u8>, String>,
+}
+
+impl Assets {
+    /// 图片保存在文档 `target` 所在文件夹的 `assets/` 中
+    pub fn new(target: &Path) -> Self {
+        let parent = target.parent().unwrap_or(Path::new(""));
+        Self { dir: parent.join(ASSETS_DIR), folder: ASSETS_DIR.to_string(), unique: false, saved: HashMap::new() }
+    }
+
+    /// 粘贴或拖入的图片保存在 `document_dir` 中的 `folder` 文件夹（相对路径），文件名加上 UUID
+    pub fn in_folder(document_dir: &Path, folder: &str) -> Self {
+        let folder = normalize_folder(folder);
+        Self { dir: document_dir.join(&folder), folder, unique: true, saved: HashMap::new() }
+    }
+
+    /// 保存图片，返回在 Markdown 中使用的相对路径
+    pub fn save(&mut self, name: &str, data: &[u8]) -> Result<String> {
+        if let Some(saved) = self.saved.get(data) {
+            return Ok(self.link(saved));
+        }
+
+        fs::create_dir_all(&self.dir).with_context(|| format!("无法创建文件夹: {}", self.dir.display()))?;
+
+        let name = sanitize(name);
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem.to_string(), format!(".{}", extension)),
+            _ => (name.clone(), String::new()),
+        };
+        let mut file_name = match self.unique {
+            true => format!("{}-{}{}", stem, Uuid::new_v4(), extension),
+            false => name,
+        };
+        let mut number = 1;
+        loop {
+            let path = self.dir.join(&file_name);
+            match fs::read(&path) {
+                Ok(existing) if existing == data => break,
+                Ok(_) => {
+                    file_name = format!("{}-{}{}", stem, number, extension);
+                    number += 1;
+                }
+                Err(_) => {
+                    fs::write(&path, data).with_context(|| format!("无法保存图片: {}", path.display()))?;
+                    break;
+                }
+            }
+        }
+
+        let link = self.link(&file_name);
+        self.saved.insert(data.to_vec(), file_name);
+        Ok(link)
+    }
+
+    /// 图片文件在 Markdown 中使用的相对路径
+    fn link(&self, file_name: &str) -> String {
+        if self.folder.is_empty() {
+            file_name.to_string()
+        } else {
+            format!("{}/{}", self.folder, file_name)
+        }
+    }
+}
+
+/// 判断文件是否是可以插入文档的图片
+pub fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// 图片 MIME 类型对应的扩展名，如 `image/svg+xml` 对应 `svg`
+pub fn image_extension(mime: &str) -> &str {
+    match mime.strip_prefix("image/").unwrap_or(mime) {
+        "jpeg" => "jpg",
+        "svg+xml" => "svg",
+        "" => "png",
+        extension => extension,
+    }
+}
+
+/// 新的未保存文档使用的临时文件夹（只返回路径，保存图片时才创建）
+pub fn draft_dir() -> PathBuf {
+    std::env::temp_dir().join("readrs").join("drafts").join(Uuid::new_v4().to_string())
+}
+
+/// 把 `from` 中图片文件夹里的文件移到 `to` 中的同名文件夹，用于未保存的文档第一次保存
+///
+/// 目标中已有同名文件时保留原文件；移动完成后删除空的源文件夹
+pub fn move_assets(from: &Path, to: &Path, folder: &str) -> Result<()> {
+    let folder = normalize_folder(folder);
+    let source = from.join(&folder);
+    let Ok(entries) = fs::read_dir(&source) else {
+        return Ok(());
+    };
+    let target = to.join(&folder);
+    fs::create_dir_all(&target).with_context(|| format!("无法创建文件夹: {}", target.display()))?;
+
+    for entry in entries {
+        let entry = entry.with_context(|| format!("无法读取文件夹: {}", source.display()))?;
+        let destination = target.join(entry.file_name());
+        if destination.exists() {
+            continue;
+        }
+        // 临时文件夹可能在其他磁盘上，无法重命名时复制后删除
+        if fs::rename(entry.path(), &destination).is_err() {
+            fs::copy(entry.path(), &destination)
+                .with_context(|| format!("无法移动图片: {}", entry.path().display()))?;
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    let _ = fs::remove_dir(&source);
+    Ok(())
+}
+
+/// 统一图片文件夹的写法：使用 `/` 分隔，去掉开头的 `./` 和结尾的 `/`
+fn normalize_folder(folder: &str) -> String {
+    folder
+        .trim()
+        .replace('\\', "/")
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 去掉文件名中的特殊字符，空格替换为 `-`
+fn sanitize(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let name: String = name
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') => Some(c),
+            _ => None,
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() { "image".to_string() } else { name.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_assets() {
+        let dir = TempDir::new().unwrap();
+        let mut assets = Assets::new(&dir.path().join("note.md"));
+
+        assert_eq!(assets.save("图 1.png", b"a").unwrap(), "assets/图-1.png");
+        // 内容相同时使用已保存的图片
+        assert_eq!(assets.save("other.png", b"a").unwrap(), "assets/图-1.png");
+        // 文件名相同但内容不同时加上编号
+        assert_eq!(assets.save("图 1.png", b"b").unwrap(), "assets/图-1-1.png");
+        assert_eq!(assets.save("../../x/.png", b"c").unwrap(), "assets/png");
+
+        assert_eq!(fs::read(dir.path().join("assets/图-1.png")).unwrap(), b"a");
+        assert_eq!(fs::read(dir.path().join("assets/图-1-1.png")).unwrap(), b"b");
+
+        // 再次导入时使用内容相同的已有文件
+        let mut assets = Assets::new(&dir.path().join("note.md"));
+        assert_eq!(assets.save("图 1.png", b"b").unwrap(), "assets/图-1-1.png");
+    }
+
+    #[test]
+    fn test_paste_assets() {
+        let draft = TempDir::new().unwrap();
+        let mut assets = Assets::in_folder(draft.path(), "./images/notes/");
+
+        let link = assets.save("截图.png", b"a").unwrap();
+        let name = link.strip_prefix("images/notes/截图-").unwrap();
+        assert!(name.ends_with(".png") && name.len() == "00000000-0000-0000-0000-000000000000.png".len());
+        assert_eq!(assets.save("other.png", b"a").unwrap(), link);
+        assert_ne!(assets.save("截图.png", b"b").unwrap(), link);
+
+        // 第一次保存时图片随文档移动
+        let document = TempDir::new().unwrap();
+        move_assets(draft.path(), document.path(), "images/notes").unwrap();
+        assert_eq!(fs::read(document.path().join(&link)).unwrap(), b"a");
+        assert!(!draft.path().join(&link).exists());
+        move_assets(draft.path(), document.path(), "missing").unwrap();
+
+        assert!(is_image_file(Path::new("a/b.JPG")));
+        assert!(!is_image_file(Path::new("a/b.md")));
+        assert_eq!(image_extension("image/svg+xml"), "svg");
+        assert_eq!(image_extension("image/jpeg"), "jpg");
+        assert_eq!(image_extension("image/webp"), "webp");
+    }
+}
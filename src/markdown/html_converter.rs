@@ -0,0 +1,789 @@
+//! HTML 转换为 Markdown
+//!
+//! 用于导入 HTML 页面和粘贴从浏览器、Word 复制的内容：
+//! - 段落、标题、引用、列表、任务列表、代码块、表格和分隔线
+//! - 粗体、斜体、删除线和行内代码，包括用 `style` 设置的格式
+//! - Word 复制的内容中用段落表示的列表
+//! - 识别本编辑器、pandoc 和 MediaWiki 生成的脚注
+//! - 内嵌（`data:`）和本地的图片保存到 `assets/` 文件夹
+//! - 忽略脚本、样式、导航栏、目录等不属于正文的内容
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use base64::Engine;
+use scraper::{ElementRef, Html, Node};
+
+use super::{
+    build_lists, flatten, is_external_dest, plain_text, Align, Assets, Block, Document, Inline, ListEntry, ListItem,
+};
+
+/// 不属于正文、直接忽略的元素
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "nav", "button", "select", "textarea", "iframe", "object",
+    "svg", "math", "canvas", "video", "audio",
+];
+
+/// 不属于正文的元素的 `class`：目录、MediaWiki 的编辑链接和不打印的内容
+const SKIPPED_CLASSES: &[&str] = &["toc", "mw-editsection", "noprint"];
+
+/// 块级元素，其余元素按行内内容处理
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "center", "dd", "details", "dialog", "div", "dl", "dt",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hgroup",
+    "hr", "li", "main", "ol", "p", "pre", "section", "summary", "table", "ul",
+];
+
+/// 包含脚注定义的元素
+const FOOTNOTE_CONTAINERS: &[&str] = &["footnotes", "references"];
+
+/// 脚注中返回正文的链接
+const BACKLINK_CLASSES: &[&str] = &["footnote-back", "footnote-backref", "reversefootnote", "mw-cite-backlink"];
+
+/// HTML 转换选项
+#[derive(Default)]
+pub struct HtmlConvertOptions<'a> {
+    /// HTML 文件的位置，相对路径的图片相对于它读取
+    pub source: Option<&'a Path>,
+    /// 保存图片的位置；没有时忽略内嵌的图片，其他图片保留原地址
+    pub assets: Option<&'a mut Assets>,
+    /// 把指向其他 HTML 页面的相对链接改为指向对应的 Markdown 文件
+    pub rewrite_links: bool,
+}
+
+/// 把 HTML 片段（如剪贴板中的内容）转换为 Markdown，末尾不加换行
+pub fn html_to_markdown(html: &str, options: HtmlConvertOptions) -> Result<String> {
+    let markdown = html_to_document(html, options)?.to_markdown();
+    Ok(markdown.trim_end_matches('\n').to_string())
+}
+
+/// 把 HTML 转换为文档
+pub fn html_to_document(html: &str, options: HtmlConvertOptions) -> Result<Document> {
+    let html = Html::parse_document(strip_clipboard_header(html));
+    let mut converter = Converter {
+        source_dir: options.source.and_then(Path::parent),
+        assets: options.assets,
+        rewrite_links: options.rewrite_links,
+        definitions: find_footnotes(&html),
+        labels: HashMap::new(),
+        order: Vec::new(),
+        heading_ids: Vec::new(),
+        in_footnote: false,
+    };
+
+    let root = html.select(&scraper::Selector::parse("body").unwrap()).next().unwrap_or(html.root_element());
+    let blocks = converter.blocks(root)?;
+
+    // 按引用顺序转换脚注，脚注中的引用追加到末尾，最后是没有被引用的脚注
+    converter.in_footnote = true;
+    let mut footnotes = Vec::new();
+    let mut index = 0;
+    loop {
+        if index == converter.order.len() {
+            let unreferenced = converter.definitions.iter().find(|(id, _)| !converter.labels.contains_key(id.as_str()));
+            match unreferenced.map(|(id, _)| id.clone()) {
+                Some(id) => {
+                    converter.label(&id);
+                }
+                None => break,
+            }
+        }
+        let id = converter.order[index].clone();
+        let definition = converter.definitions.iter().find(|(other, _)| *other == id).map(|(_, element)| *element);
+        if let Some(definition) = definition {
+            let content = converter.blocks(definition)?;
+            footnotes.push((converter.labels[&id].clone(), content));
+        }
+        index += 1;
+    }
+
+    let heading_ids = std::mem::take(&mut converter.heading_ids);
+    let mut document = Document { blocks, footnotes };
+    document.link_headings(&heading_ids);
+    Ok(document)
+}
+
+struct Converter<'a, 'b> {
+    source_dir: Option<&'b Path>,
+    assets: Option<&'b mut Assets>,
+    rewrite_links: bool,
+    /// 脚注定义的 id 和元素
+    definitions: Vec<(String, ElementRef<'a>)>,
+    /// 已引用脚注的 id 和标签
+    labels: HashMap<String, String>,
+    /// 按引用顺序排列的脚注 id
+    order: Vec<String>,
+    /// 正文中每个标题在原文档中的锚点
+    heading_ids: Vec<Vec<String>>,
+    in_footnote: bool,
+}
+
+impl<'a> Converter<'a, '_> {
+    /// 转换元素中的块级内容，连续的行内内容组成段落
+    fn blocks(&mut self, parent: ElementRef<'a>) -> Result<Vec<Block>> {
+        let mut blocks = Vec::new();
+        let mut inlines = Vec::new();
+        // Word 中用段落表示的连续列表项
+        let mut list_entries = Vec::new();
+        for child in parent.children() {
+            if let Some(text) = child.value().as_text() {
+                if !(list_entries.is_empty() || text.trim().is_empty()) {
+                    blocks.extend(build_lists(std::mem::take(&mut list_entries)));
+                }
+                inlines.push(Inline::Text(collapse_whitespace(text)));
+                continue;
+            }
+            let Some(element) = ElementRef::wrap(child) else {
+                continue;
+            };
+            if self.is_skipped(element) {
+                continue;
+            }
+            if let Some((list, level)) = word_list_paragraph(element) {
+                push_paragraph(&mut blocks, std::mem::take(&mut inlines));
+                let start = word_list_number(element);
+                let content = self.inlines(element)?;
+                list_entries.push(ListEntry { list, level, start, checked: None, content });
+                continue;
+            }
+            blocks.extend(build_lists(std::mem::take(&mut list_entries)));
+            if BLOCK_ELEMENTS.contains(&element.value().name()) {
+                push_paragraph(&mut blocks, std::mem::take(&mut inlines));
+                self.block(element, &mut blocks)?;
+            } else {
+                inlines.extend(self.inline(element)?);
+            }
+        }
+        blocks.extend(build_lists(list_entries));
+        push_paragraph(&mut blocks, inlines);
+        Ok(blocks)
+    }
+
+    fn block(&mut self, element: ElementRef<'a>, blocks: &mut Vec<Block>) -> Result<()> {
+        match element.value().name() {
+            name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let level = name[1..].parse().unwrap_or(1);
+                let mut content = self.inlines(element)?;
+                collapse_spaces(&mut content, &mut true);
+                if plain_text(&content).trim().is_empty() {
+                    return Ok(());
+                }
+                if !self.in_footnote {
+                    let ids = std::iter::once(element)
+                        .chain(element.descendent_elements())
+                        .filter_map(|e| e.value().id().or_else(|| e.value().attr("name")))
+                        .map(str::to_string)
+                        .collect();
+                    self.heading_ids.push(ids);
+                }
+                blocks.push(Block::Heading(level, content));
+            }
+            "p" | "dt" | "summary" | "figcaption" => {
+                let content = self.inlines(element)?;
+                push_paragraph(blocks, content);
+            }
+            "blockquote" => {
+                let content = self.blocks(element)?;
+                if !content.is_empty() {
+                    blocks.push(Block::Quote(content));
+                }
+            }
+            "ul" | "ol" => {
+                let start = (element.value().name() == "ol")
+                    .then(|| element.value().attr("start").and_then(|s| s.trim().parse().ok()).unwrap_or(1));
+                let mut items = Vec::new();
+                for child in element.child_elements() {
+                    if self.is_skipped(child) {
+                        continue;
+                    }
+                    if child.value().name() == "li" {
+                        items.push(ListItem { checked: task_checkbox(child), blocks: self.blocks(child)? });
+                    } else if let Some(ListItem { blocks, .. }) = items.last_mut() {
+                        // 列表中直接嵌套的列表属于上一项
+                        self.block(child, blocks)?;
+                    }
+                }
+                if !items.is_empty() {
+                    blocks.push(Block::List { start, items });
+                }
+            }
+            "pre" => {
+                let language = std::iter::once(element)
+                    .chain(element.descendent_elements().filter(|e| e.value().name() == "code"))
+                    .find_map(code_language)
+                    .unwrap_or_default();
+                blocks.push(Block::Code { language, code: pre_text(element) });
+            }
+            "table" => self.table(element, blocks)?,
+            "hr" => blocks.push(Block::Rule),
+            "li" => {
+                // 不在列表中的列表项
+                let content = self.blocks(element)?;
+                blocks.push(Block::List { start: None, items: vec![ListItem { checked: None, blocks: content }] });
+            }
+            _ => blocks.extend(self.blocks(element)?),
+        }
+        Ok(())
+    }
+
+    fn table(&mut self, table: ElementRef<'a>, blocks: &mut Vec<Block>) -> Result<()> {
+        let mut rows = Vec::new();
+        let mut alignments = Vec::new();
+        for child in table.child_elements() {
+            match child.value().name() {
+                "caption" => push_paragraph(blocks, self.inlines(child)?),
+                "thead" | "tbody" | "tfoot" => {
+                    for row in child.child_elements().filter(|e| e.value().name() == "tr") {
+                        rows.push(self.table_row(row, &mut alignments)?);
+                    }
+                }
+                "tr" => rows.push(self.table_row(child, &mut alignments)?),
+                _ => {}
+            }
+        }
+        rows.retain(|row: &Vec<Vec<Inline>>| !row.is_empty());
+        if !rows.is_empty() {
+            blocks.push(Block::Table { alignments, rows });
+        }
+        Ok(())
+    }
+
+    /// 转换表格的一行，合并的单元格后补上空单元格
+    fn table_row(&mut self, row: ElementRef<'a>, alignments: &mut Vec<Align>) -> Result<Vec<Vec<Inline>>> {
+        let mut cells = Vec::new();
+        for cell in row.child_elements().filter(|e| matches!(e.value().name(), "td" | "th")) {
+            let align = cell_align(cell);
+            if alignments.len() <= cells.len() {
+                alignments.push(align);
+            } else if alignments[cells.len()] == Align::None {
+                alignments[cells.len()] = align;
+            }
+            cells.push(flatten(self.blocks(cell)?));
+            let span: usize = cell.value().attr("colspan").and_then(|s| s.trim().parse().ok()).unwrap_or(1);
+            cells.extend(std::iter::repeat_n(Vec::new(), span.clamp(1, 100) - 1));
+        }
+        Ok(cells)
+    }
+
+    /// 转换元素中的行内内容
+    fn inlines(&mut self, parent: ElementRef<'a>) -> Result<Vec<Inline>> {
+        let mut inlines = Vec::new();
+        for child in parent.children() {
+            if let Some(text) = child.value().as_text() {
+                inlines.push(Inline::Text(collapse_whitespace(text)));
+            } else if let Some(element) = ElementRef::wrap(child) {
+                if !self.is_skipped(element) {
+                    inlines.extend(self.inline(element)?);
+                }
+            }
+        }
+        Ok(inlines)
+    }
+
+    fn inline(&mut self, element: ElementRef<'a>) -> Result<Vec<Inline>> {
+        let value = element.value();
+        let inline = match value.name() {
+            "strong" | "b" if !style_is(value.attr("style"), "font-weight", is_normal_weight) => {
+                Inline::Strong(self.inlines(element)?)
+            }
+            "em" | "i" | "cite" | "dfn" | "var" if !style_is(value.attr("style"), "font-style", |v| v == "normal") => {
+                Inline::Emphasis(self.inlines(element)?)
+            }
+            "s" | "strike" | "del" => Inline::Strikethrough(self.inlines(element)?),
+            "code" | "kbd" | "samp" | "tt" => Inline::Code(element.text().collect()),
+            "br" => Inline::LineBreak,
+            "img" => return self.image(element),
+            "a" => return self.link(element),
+            "sup" => {
+                // 只包含脚注引用的上标直接转换为脚注引用
+                let content = self.inlines(element)?;
+                let significant: Vec<&Inline> =
+                    content.iter().filter(|i| !matches!(i, Inline::Text(text) if text.trim().is_empty())).collect();
+                match significant.as_slice() {
+                    [reference @ Inline::FootnoteReference(_)] => return Ok(vec![(*reference).clone()]),
+                    _ => return Ok(content),
+                }
+            }
+            "input" => return Ok(Vec::new()),
+            name if BLOCK_ELEMENTS.contains(&name) => {
+                // 行内内容中的块级元素前后换行
+                let mut content = vec![Inline::LineBreak];
+                content.extend(flatten(self.blocks(element)?));
+                content.push(Inline::LineBreak);
+                return Ok(content);
+            }
+            _ => {
+                let mut content = self.inlines(element)?;
+                let style = value.attr("style");
+                if style_is(style, "font-weight", |v| v == "bold" || v.parse::<u32>().is_ok_and(|w| w >= 600)) {
+                    content = vec![Inline::Strong(content)];
+                }
+                if style_is(style, "font-style", |v| v == "italic" || v == "oblique") {
+                    content = vec![Inline::Emphasis(content)];
+                }
+                if style_is(style, "text-decoration", |v| v.contains("line-through"))
+                    || style_is(style, "text-decoration-line", |v| v.contains("line-through"))
+                {
+                    content = vec![Inline::Strikethrough(content)];
+                }
+                return Ok(content);
+            }
+        };
+        Ok(vec![inline])
+    }
+
+    fn link(&mut self, element: ElementRef<'a>) -> Result<Vec<Inline>> {
+        let href = element.value().attr("href").map(str::trim).unwrap_or_default();
+        if let Some(id) = href.strip_prefix('#') {
+            if self.definitions.iter().any(|(other, _)| other == id) {
+                return Ok(vec![Inline::FootnoteReference(self.label(id))]);
+            }
+        }
+
+        // 标题旁的固定链接
+        if href.starts_with('#') && matches!(element.text().collect::<String>().trim(), "¶" | "§" | "#") {
+            return Ok(Vec::new());
+        }
+
+        let content = self.inlines(element)?;
+        if href.is_empty() || href.to_ascii_lowercase().starts_with("javascript:") {
+            return Ok(content);
+        }
+
+        let url = if self.rewrite_links { markdown_link(href) } else { href.to_string() };
+        let title = element.value().attr("title").unwrap_or_default().to_string();
+        Ok(vec![Inline::Link { url, title, content }])
+    }
+
+    fn image(&mut self, element: ElementRef<'a>) -> Result<Vec<Inline>> {
+        let Some(src) = element.value().attr("src").map(str::trim).filter(|src| !src.is_empty()) else {
+            return Ok(Vec::new());
+        };
+        let alt = element.value().attr("alt").unwrap_or_default().to_string();
+        let title = element.value().attr("title").unwrap_or_default().to_string();
+
+        let url = if let Some((mime, data)) = decode_data_uri(src) {
+            let Some(assets) = self.assets.as_deref_mut() else {
+                return Ok(Vec::new());
+            };
+            let extension = mime.strip_prefix("image/").map(|ext| ext.trim_end_matches("+xml")).unwrap_or("png");
+            assets.save(&format!("image.{}", extension), &data)?
+        } else if let Some(data) = self.local_file(src) {
+            let path = src.split(['?', '#']).next().unwrap_or(src);
+            let name = decode_percent(path);
+            match self.assets.as_deref_mut() {
+                Some(assets) => assets.save(&name, &data)?,
+                None => src.to_string(),
+            }
+        } else {
+            src.to_string()
+        };
+        Ok(vec![Inline::Image { url, alt, title }])
+    }
+
+    /// 读取相对于源文件的本地图片
+    fn local_file(&self, src: &str) -> Option<Vec<u8>> {
+        if self.assets.is_none() || is_external_dest(src) {
+            return None;
+        }
+        let path = src.split(['?', '#']).next()?;
+        fs::read(self.source_dir?.join(decode_percent(path))).ok()
+    }
+
+    /// 脚注的标签，第一次引用时按顺序编号
+    fn label(&mut self, id: &str) -> String {
+        if let Some(label) = self.labels.get(id) {
+            return label.clone();
+        }
+        self.order.push(id.to_string());
+        let label = self.order.len().to_string();
+        self.labels.insert(id.to_string(), label.clone());
+        label
+    }
+
+    /// 忽略的元素：不属于正文的元素、脚注定义（最后单独转换）和脚注中返回正文的链接
+    fn is_skipped(&self, element: ElementRef<'a>) -> bool {
+        let value = element.value();
+        if SKIPPED_ELEMENTS.contains(&value.name()) || value.attr("hidden").is_some() {
+            return true;
+        }
+        if value.attr("type").is_some_and(|t| t.eq_ignore_ascii_case("hidden")) {
+            return true;
+        }
+        // Word 复制的列表项中的编号
+        if style_is(value.attr("style"), "mso-list", |v| v == "ignore") {
+            return true;
+        }
+        if value.classes().any(|class| {
+            class == "footnote-definition-label" || BACKLINK_CLASSES.contains(&class) || SKIPPED_CLASSES.contains(&class)
+        })
+            || value.attr("role") == Some("doc-backlink")
+        {
+            return true;
+        }
+        if self.in_footnote {
+            return value.name() == "a"
+                && matches!(element.text().collect::<String>().trim(), "↩" | "↩\u{fe0f}" | "↩\u{fe0e}" | "^" | "↑");
+        }
+        is_footnote_container(element)
+            || value.id().is_some_and(|id| self.definitions.iter().any(|(other, definition)| other == id && *definition == element))
+    }
+}
+
+/// 找出文档中的脚注定义
+fn find_footnotes(html: &Html) -> Vec<(String, ElementRef<'_>)> {
+    let mut definitions = Vec::new();
+    for element in html.root_element().descendent_elements() {
+        let Some(id) = element.value().id() else {
+            continue;
+        };
+        let is_definition = element.value().has_class("footnote-definition", scraper::CaseSensitivity::AsciiCaseInsensitive)
+            || (element.value().name() == "li"
+                && element.ancestors().filter_map(ElementRef::wrap).any(is_footnote_container));
+        if is_definition {
+            definitions.push((id.to_string(), element));
+        }
+    }
+    definitions
+}
+
+fn is_footnote_container(element: ElementRef<'_>) -> bool {
+    element.value().attr("role") == Some("doc-endnotes")
+        || (matches!(element.value().name(), "section" | "div" | "aside" | "ol")
+            && element.value().classes().any(|class| FOOTNOTE_CONTAINERS.contains(&class)))
+}
+
+/// Word 复制的内容中表示列表项的段落：`style` 中的 `mso-list: l0 level2 lfo1` 表示列表和层级
+fn word_list_paragraph(element: ElementRef<'_>) -> Option<(String, usize)> {
+    if element.value().name() != "p" {
+        return None;
+    }
+    let value = style_value(element.value().attr("style"), "mso-list")?;
+    let mut parts = value.split_whitespace();
+    let list = parts.next()?.to_string();
+    let level = parts.find_map(|part| part.strip_prefix("level")?.parse::<usize>().ok()).unwrap_or(1);
+    Some((list, level.saturating_sub(1)))
+}
+
+/// Word 列表项的编号，编号是数字时为有序列表
+fn word_list_number(element: ElementRef<'_>) -> Option<u64> {
+    let marker = element
+        .descendent_elements()
+        .find(|e| style_is(e.value().attr("style"), "mso-list", |v| v == "ignore"))?
+        .text()
+        .collect::<String>();
+    let marker = marker.trim().trim_end_matches(['.', ')']);
+    marker.parse().ok()
+}
+
+/// 去掉 Windows 剪贴板中 HTML 内容前的描述信息（`Version:0.9 StartHTML:...`）
+fn strip_clipboard_header(html: &str) -> &str {
+    if html.starts_with("Version:") {
+        html.find('<').map_or(html, |start| &html[start..])
+    } else {
+        html
+    }
+}
+
+/// 任务列表项的复选框状态：列表项的第一个内容是复选框时返回是否选中
+fn task_checkbox(item: ElementRef<'_>) -> Option<bool> {
+    for node in item.descendants().skip(1) {
+        if let Some(text) = node.value().as_text() {
+            if text.trim().is_empty() {
+                continue;
+            }
+            return None;
+        }
+        let Some(element) = node.value().as_element() else {
+            continue;
+        };
+        match element.name() {
+            "input" if element.attr("type").is_some_and(|t| t.eq_ignore_ascii_case("checkbox")) => {
+                return Some(element.attr("checked").is_some());
+            }
+            "p" | "span" | "label" | "div" => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// 把段落加入块列表，只有空白的段落不加入
+fn push_paragraph(blocks: &mut Vec<Block>, mut inlines: Vec<Inline>) {
+    collapse_spaces(&mut inlines, &mut true);
+    while matches!(inlines.last(), Some(Inline::LineBreak)) {
+        inlines.pop();
+    }
+    while matches!(inlines.first(), Some(Inline::LineBreak)) {
+        inlines.remove(0);
+    }
+    if !plain_text(&inlines).trim().is_empty() || contains_image(&inlines) {
+        blocks.push(Block::Paragraph(inlines));
+    }
+}
+
+fn contains_image(inlines: &[Inline]) -> bool {
+    inlines.iter().any(|inline| match inline {
+        Inline::Image { .. } => true,
+        Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children) => contains_image(children),
+        Inline::Link { content, .. } => contains_image(content),
+        _ => false,
+    })
+}
+
+/// 去掉行首和连续的空格（HTML 中相邻的空白只显示一个空格）
+fn collapse_spaces(inlines: &mut [Inline], after_space: &mut bool) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => {
+                if *after_space {
+                    *text = text.trim_start_matches(' ').to_string();
+                }
+                if !text.is_empty() {
+                    *after_space = text.ends_with(' ');
+                }
+            }
+            Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children) => {
+                collapse_spaces(children, after_space)
+            }
+            Inline::Link { content, .. } => collapse_spaces(content, after_space),
+            Inline::LineBreak => *after_space = true,
+            _ => *after_space = false,
+        }
+    }
+}
+
+/// 把连续的空白替换为一个空格
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0c') {
+            if !in_space {
+                result.push(' ');
+            }
+            in_space = true;
+        } else {
+            result.push(c);
+            in_space = false;
+        }
+    }
+    result
+}
+
+/// 代码块的文字，`<br>` 转换为换行
+fn pre_text(element: ElementRef<'_>) -> String {
+    let mut text = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            Node::Text(content) => text.push_str(content),
+            Node::Element(e) if e.name() == "br" => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// 从 `class` 中识别代码语言，支持 `language-rust`、`lang-rust` 和 pandoc 的 `sourceCode rust`
+fn code_language(element: ElementRef<'_>) -> Option<String> {
+    let classes: Vec<&str> = element.value().classes().collect();
+    classes
+        .iter()
+        .find_map(|class| class.strip_prefix("language-").or_else(|| class.strip_prefix("lang-")))
+        .or_else(|| {
+            classes
+                .contains(&"sourceCode")
+                .then(|| classes.iter().find(|class| **class != "sourceCode").copied())
+                .flatten()
+        })
+        .or_else(|| element.value().attr("data-lang"))
+        .map(str::to_string)
+}
+
+fn cell_align(cell: ElementRef<'_>) -> Align {
+    let align = cell.value().attr("align").map(|a| a.trim().to_ascii_lowercase()).or_else(|| {
+        style_value(cell.value().attr("style"), "text-align").map(|v| v.to_ascii_lowercase())
+    });
+    match align.as_deref() {
+        Some("left") => Align::Left,
+        Some("center") => Align::Center,
+        Some("right") => Align::Right,
+        _ => Align::None,
+    }
+}
+
+/// 读取 `style` 属性中某个属性的值
+fn style_value<'s>(style: Option<&'s str>, property: &str) -> Option<&'s str> {
+    style?.split(';').find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        name.trim().eq_ignore_ascii_case(property).then(|| value.trim().trim_end_matches("!important").trim())
+    })
+}
+
+fn style_is(style: Option<&str>, property: &str, matches: impl Fn(&str) -> bool) -> bool {
+    style_value(style, property).is_some_and(|value| matches(&value.to_ascii_lowercase()))
+}
+
+fn is_normal_weight(value: &str) -> bool {
+    value == "normal" || value.parse::<u32>().is_ok_and(|weight| weight < 600)
+}
+
+/// 把指向其他 HTML 页面的相对链接改为指向对应的 Markdown 文件
+fn markdown_link(href: &str) -> String {
+    if is_external_dest(href) || href.starts_with('#') {
+        return href.to_string();
+    }
+    let (path, fragment) = match href.find(['#', '?']) {
+        Some(pos) => href.split_at(pos),
+        None => (href, ""),
+    };
+    let fragment = if fragment.starts_with('?') { fragment.find('#').map(|pos| &fragment[pos..]).unwrap_or("") } else { fragment };
+    for extension in [".html", ".htm"] {
+        if path.len() > extension.len() && path.to_ascii_lowercase().ends_with(extension) {
+            return format!("{}.md{}", &path[..path.len() - extension.len()], fragment);
+        }
+    }
+    href.to_string()
+}
+
+/// 解码 `data:` 地址中的 base64 数据
+pub fn decode_data_uri(src: &str) -> Option<(String, Vec<u8>)> {
+    let rest = src.strip_prefix("data:")?;
+    let (header, data) = rest.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+    Some((mime.to_ascii_lowercase(), data))
+}
+
+/// 解码地址中的百分号编码
+fn decode_percent(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            if let Some(byte) = path.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn import(html: &str, dir: &Path) -> String {
+        let source = dir.join("page.html");
+        let mut assets = Assets::new(&dir.join("page.md"));
+        let options = HtmlConvertOptions { source: Some(&source), assets: Some(&mut assets), rewrite_links: true };
+        html_to_document(html, options).unwrap().to_markdown()
+    }
+
+    #[test]
+    fn test_convert_blocks() {
+        let dir = TempDir::new().unwrap();
+        let html = r##"<html><head><title>x</title><style>p{}</style></head><body>
+            <nav><a href="index.html">首页</a></nav>
+            <h1 id="intro">简介 <a href="#intro">¶</a></h1>
+            <p>普通  文字，<b>粗体</b>、<em>斜体</em>、<del>删除</del>和 <code>code</code>。<br>
+               <span style="font-weight: 700">样式粗体</span>
+               <b style="font-weight:normal">不是粗体</b></p>
+            <ul><li><input type="checkbox" checked> 完成</li><li><input type="checkbox"> 未完成</li></ul>
+            <ol start="3"><li>三<ul><li>子项</li></ul></li><li><p>四</p></li></ol>
+            <blockquote><p>引用</p></blockquote>
+            <pre><code class="language-rust">fn main() {
+    println!("hi");
+}</code></pre>
+            <table><thead><tr><th>名称</th><th align="right">数量</th></tr></thead>
+            <tbody><tr><td>苹果</td><td style="text-align: right">3</td></tr></tbody></table>
+            <hr>
+            <p><a href="other.html#part">其他页面</a> <a href="https://example.com/a.html">外部</a> <a href="#intro">简介</a></p>
+            <script>alert(1)</script>
+        </body></html>"##;
+        assert_eq!(
+            import(html, dir.path()),
+            "# 简介\n\n\
+             普通 文字，**粗体**、*斜体*、~~删除~~和 `code`。\\\n**样式粗体** 不是粗体\n\n\
+             - [x] 完成\n- [ ] 未完成\n\n\
+             3. 三\n   - 子项\n4. 四\n\n\
+             > 引用\n\n\
+             ```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n\
+             | 名称 | 数量 |\n| --- | ---: |\n| 苹果 | 3 |\n\n\
+             ---\n\n\
+             [其他页面](other.md#part) [外部](https://example.com/a.html) [简介](#简介)\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_footnotes() {
+        let dir = TempDir::new().unwrap();
+
+        // 本编辑器导出的脚注
+        let html = r##"<p>正文<sup class="footnote-reference"><a href="#b">1</a></sup>和<sup class="footnote-reference"><a href="#a">2</a></sup></p>
+            <div class="footnote-definition" id="a"><sup class="footnote-definition-label">2</sup><p>脚注 A</p></div>
+            <div class="footnote-definition" id="b"><sup class="footnote-definition-label">1</sup><p>脚注 B</p></div>"##;
+        assert_eq!(import(html, dir.path()), "正文[^1]和[^2]\n\n[^1]: 脚注 B\n\n[^2]: 脚注 A\n");
+
+        // pandoc 生成的脚注
+        let html = r##"<p>正文<a href="#fn1" class="footnote-ref" id="fnref1" role="doc-noteref"><sup>1</sup></a></p>
+            <section id="footnotes" class="footnotes footnotes-end-of-document" role="doc-endnotes"><hr />
+            <ol><li id="fn1"><p>脚注<a href="#fnref1" class="footnote-back" role="doc-backlink">↩︎</a></p></li>
+            <li id="fn2"><p>未引用</p></li></ol></section>"##;
+        assert_eq!(import(html, dir.path()), "正文[^1]\n\n[^1]: 脚注\n\n[^2]: 未引用\n");
+    }
+
+    #[test]
+    fn test_convert_images() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("images")).unwrap();
+        fs::write(dir.path().join("images/logo 1.png"), b"png").unwrap();
+
+        let html = r#"<p><img src="images/logo%201.png" alt="标志">
+            <img src="data:image/gif;base64,R0lG" alt="动图">
+            <img src="https://example.com/a.png" title="远程"></p>"#;
+        assert_eq!(
+            import(html, dir.path()),
+            "![标志](assets/logo-1.png) ![动图](assets/image.gif) ![](https://example.com/a.png \"远程\")\n"
+        );
+        assert_eq!(fs::read(dir.path().join("assets/logo-1.png")).unwrap(), b"png");
+        assert_eq!(fs::read(dir.path().join("assets/image.gif")).unwrap(), b"GIF");
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        // 从浏览器复制的片段
+        let html = "<html><body><!--StartFragment--><span>普通 <b>粗体</b></span>\
+            <img src=\"data:image/png;base64,iVBO\" alt=\"内嵌\"><!--EndFragment--></body></html>";
+        assert_eq!(html_to_markdown(html, HtmlConvertOptions::default()).unwrap(), "普通 **粗体**");
+
+        // 从 Word 复制的列表，Windows 剪贴板中带有描述信息
+        let html = "Version:0.9\r\nStartHTML:0000000105\r\n<html><body>\
+            <p class=MsoListParagraphCxSpFirst style='mso-list:l0 level1 lfo1'><![if !supportLists]>\
+            <span style='mso-list:Ignore'>1.<span>&nbsp;&nbsp;</span></span><![endif]>第一项</p>\
+            <p class=MsoListParagraphCxSpLast style='mso-list:l0 level2 lfo1'><![if !supportLists]>\
+            <span style='font-family:Symbol'><span style='mso-list:Ignore'>·<span>&nbsp;</span></span></span>\
+            <![endif]>子项<o:p></o:p></p>\
+            <p class=MsoNormal><o:p>&nbsp;</o:p></p><p class=MsoNormal>段落</p></body></html>";
+        assert_eq!(
+            html_to_markdown(html, HtmlConvertOptions::default()).unwrap(),
+            "1. 第一项\n   - 子项\n\n段落"
+        );
+    }
+
+    #[test]
+    fn test_markdown_link() {
+        assert_eq!(markdown_link("guide/setup.html"), "guide/setup.md");
+        assert_eq!(markdown_link("setup.HTM?x=1#top"), "setup.md#top");
+        assert_eq!(markdown_link("https://example.com/a.html"), "https://example.com/a.html");
+        assert_eq!(markdown_link("image.png"), "image.png");
+    }
+}
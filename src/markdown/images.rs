@@ -0,0 +1,263 @@
+//! 预览中的图片
+//!
+//! 从一行 Markdown 中找出图片，并确定图片文件的位置：
+//! - Markdown 图片 `![alt](src "title")`，可以在后面用 `{width=50% height=200}` 指定大小
+//! - HTML 图片 `<img src="..." width="..." height="...">`
+//! - 相对路径相对于文档所在的文件夹，也支持绝对路径、`file://`、网址和内嵌的 `data:` 图片
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use scraper::{Html, Selector};
+
+use super::decode_data_uri;
+use crate::file_manager::{normalize_path, percent_decode};
+
+/// 图片的宽度或高度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageLength {
+    /// 像素
+    Pixels(f32),
+    /// 占预览区宽度的百分比（只用于宽度）
+    Percent(f32),
+}
+
+/// 行中的一张图片
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreviewImage {
+    pub url: String,
+    pub alt: String,
+    pub title: String,
+    pub width: Option<ImageLength>,
+    pub height: Option<ImageLength>,
+}
+
+/// 行中的文字或图片
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinePart {
+    /// 文字在行中的字节范围
+    Text(Range<usize>),
+    Image(PreviewImage),
+}
+
+/// 图片文件的位置
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageLocation {
+    /// 本地文件
+    File(PathBuf),
+    /// 网址
+    Remote(String),
+    /// 内嵌的图片（MIME 类型和内容）
+    Embedded(String, Vec<u8>),
+}
+
+/// 把一行拆分为文字和图片，没有图片时返回整行文字
+pub fn split_images(line: &str) -> Vec<LinePart> {
+    let mut images: Vec<(Range<usize>, PreviewImage)> = Vec::new();
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut events = Parser::new_ext(line, options).into_offset_iter();
+
+    while let Some((event, range)) = events.next() {
+        match event {
+            Event::Start(Tag::Image { dest_url, title, .. }) => {
+                let mut alt = String::new();
+                for (event, _) in events.by_ref() {
+                    match event {
+                        Event::End(TagEnd::Image) => break,
+                        Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                        _ => {}
+                    }
+                }
+                let mut image =
+                    PreviewImage { url: dest_url.to_string(), alt, title: title.to_string(), ..Default::default() };
+
+                // 图片后紧跟的 `{width=50%}` 属性
+                let mut end = range.end;
+                if let Some(attributes) = line[end..].strip_prefix('{').and_then(|rest| rest.split_once('}')) {
+                    for (name, value) in attributes.0.split_whitespace().filter_map(|pair| pair.split_once('=')) {
+                        set_size(&mut image, name, value.trim_matches(['"', '\'']));
+                    }
+                    end += attributes.0.len() + 2;
+                }
+                images.push((range.start..end, image));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let html = html.to_string();
+                let lower = html.to_ascii_lowercase();
+                let mut position = 0;
+                while let Some(start) = lower[position..].find("<img").map(|offset| position + offset) {
+                    let Some(end) = lower[start..].find('>').map(|offset| start + offset + 1) else {
+                        break;
+                    };
+                    if let Some(image) = html_image(&html[start..end]) {
+                        // 块级 HTML 的范围从行首开始，与事件文字对应
+                        let offset = range.start;
+                        images.push((offset + start..offset + end, image));
+                    }
+                    position = end;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut parts = Vec::new();
+    let mut position = 0;
+    for (range, image) in images {
+        if range.start < position || range.end > line.len() {
+            continue;
+        }
+        if range.start > position {
+            parts.push(LinePart::Text(position..range.start));
+        }
+        parts.push(LinePart::Image(image));
+        position = range.end;
+    }
+    if position < line.len() || parts.is_empty() {
+        parts.push(LinePart::Text(position..line.len()));
+    }
+    parts
+}
+
+/// 确定图片文件的位置，相对路径相对于 `base_dir`；无法识别的地址返回 `None`
+pub fn locate_image(base_dir: &Path, url: &str) -> Option<ImageLocation> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+    if url.starts_with("data:") {
+        let (mime, data) = decode_data_uri(url)?;
+        return Some(ImageLocation::Embedded(mime, data));
+    }
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        return Some(ImageLocation::Remote(url.to_string()));
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    let path = percent_decode(&path[..path.find(['#', '?']).unwrap_or(path.len())]);
+    let path = Path::new(&path);
+    if path.is_absolute() {
+        return Some(ImageLocation::File(normalize_path(path)));
+    }
+    // 其他协议（如 `ftp:`）不支持
+    if url.contains("://") {
+        return None;
+    }
+    Some(ImageLocation::File(normalize_path(&base_dir.join(path))))
+}
+
+/// 读取 `<img>` 标签的地址和大小
+fn html_image(tag: &str) -> Option<PreviewImage> {
+    let fragment = Html::parse_fragment(tag);
+    let selector = Selector::parse("img").ok()?;
+    let element = fragment.select(&selector).next()?.value();
+
+    let mut image = PreviewImage {
+        url: element.attr("src")?.to_string(),
+        alt: element.attr("alt").unwrap_or_default().to_string(),
+        title: element.attr("title").unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+    for name in ["width", "height"] {
+        if let Some(value) = element.attr(name) {
+            set_size(&mut image, name, value);
+        }
+    }
+    // `style="width: 50%"` 中的大小
+    for declaration in element.attr("style").unwrap_or_default().split(';') {
+        if let Some((name, value)) = declaration.split_once(':') {
+            set_size(&mut image, name.trim(), value.trim());
+        }
+    }
+    Some(image)
+}
+
+/// 设置图片的宽度或高度，如 `50%`、`200`、`200px`
+fn set_size(image: &mut PreviewImage, name: &str, value: &str) {
+    let length = if let Some(percent) = value.strip_suffix('%') {
+        percent.trim().parse().ok().filter(|percent| *percent > 0.0 && *percent <= 100.0).map(ImageLength::Percent)
+    } else {
+        let pixels = value.strip_suffix("px").unwrap_or(value);
+        pixels.trim().parse().ok().filter(|pixels| *pixels > 0.0).map(ImageLength::Pixels)
+    };
+    match (name.to_ascii_lowercase().as_str(), length) {
+        ("width", Some(length)) => image.width = Some(length),
+        // 预览区的高度不固定，高度只支持像素
+        ("height", Some(length @ ImageLength::Pixels(_))) => image.height = Some(length),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn images(line: &str) -> Vec<PreviewImage> {
+        split_images(line)
+            .into_iter()
+            .filter_map(|part| match part {
+                LinePart::Image(image) => Some(image),
+                LinePart::Text(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_images() {
+        let line = "见 ![图 *一*](a.png \"标题\"){width=50% height=120px} 和 `![代码](x.png)`";
+        let parts = split_images(line);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], LinePart::Text(0..4));
+        assert_eq!(
+            parts[1],
+            LinePart::Image(PreviewImage {
+                url: "a.png".to_string(),
+                alt: "图 一".to_string(),
+                title: "标题".to_string(),
+                width: Some(ImageLength::Percent(50.0)),
+                height: Some(ImageLength::Pixels(120.0)),
+            })
+        );
+        let LinePart::Text(rest) = &parts[2] else {
+            panic!("应为文字");
+        };
+        assert_eq!(&line[rest.clone()], " 和 `![代码](x.png)`");
+
+        // 没有图片时返回整行
+        assert_eq!(split_images("普通段落"), vec![LinePart::Text(0..12)]);
+        assert_eq!(split_images(""), vec![LinePart::Text(0..0)]);
+
+        let found = images("- ![](<assets/a b.png>) 文字 <IMG SRC='b.gif' width=\"200\" style=\"height: 50%\">");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].url, "assets/a b.png");
+        assert_eq!(found[1].url, "b.gif");
+        assert_eq!(found[1].width, Some(ImageLength::Pixels(200.0)));
+        assert_eq!(found[1].height, None);
+
+        let found = images("<img src=\"c.svg\" alt=\"图\">");
+        assert_eq!(found, vec![PreviewImage { url: "c.svg".to_string(), alt: "图".to_string(), ..Default::default() }]);
+    }
+
+    #[test]
+    fn test_locate_image() {
+        let base = Path::new("/notes/docs");
+        assert_eq!(
+            locate_image(base, "../assets/%E5%9B%BE.png?v=2"),
+            Some(ImageLocation::File(PathBuf::from("/notes/assets/图.png")))
+        );
+        assert_eq!(locate_image(base, "/tmp/a.png"), Some(ImageLocation::File(PathBuf::from("/tmp/a.png"))));
+        assert_eq!(locate_image(base, "file:///tmp/a.png"), Some(ImageLocation::File(PathBuf::from("/tmp/a.png"))));
+        assert_eq!(
+            locate_image(base, "https://example.com/a.png"),
+            Some(ImageLocation::Remote("https://example.com/a.png".to_string()))
+        );
+        assert_eq!(
+            locate_image(base, "data:image/png;base64,cG5n"),
+            Some(ImageLocation::Embedded("image/png".to_string(), b"png".to_vec()))
+        );
+        assert_eq!(locate_image(base, "ftp://example.com/a.png"), None);
+        assert_eq!(locate_image(base, " "), None);
+    }
+}
@@ -1,6 +1,22 @@
 //! LaTeX 公式渲染模块
 //!
-//! LaTeX 公式渲染器（纯 Rust 实现，无需 JS 引擎）
+//! LaTeX 公式渲染器（纯 Rust 实现，无需 JS 引擎）：
+//! - 预览和导出时把常用的公式子集排版为 SVG，其余公式显示源码
+//! - 按与导出相同的规则查找一行中的公式
+
+use std::ops::Range;
+use pulldown_cmark::{Event, Options, Parser};
+
+/// 一行中的公式
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathSpan {
+    /// 公式（包括 `$`）在行中的字节范围
+    pub range: Range<usize>,
+    /// 公式内容（不含 `$`）
+    pub formula: String,
+    /// 是否为块级公式（`$$...$$`）
+    pub display: bool,
+}
 
 /// LaTeX 公式渲染器
 pub struct LatexRenderer;
@@ -57,6 +73,313 @@ impl LatexRenderer {
     pub fn contains_latex(text: &str) -> bool {
         text.contains('$')
     }
+
+    /// 查找一行中的公式，代码和转义的 `\$` 中的 `$` 不算作公式
+    pub fn find_math(line: &str) -> Vec<MathSpan> {
+        if !line.contains('$') {
+            return Vec::new();
+        }
+        Parser::new_ext(line, Options::ENABLE_MATH)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::InlineMath(formula) => Some(MathSpan { range, formula: formula.to_string(), display: false }),
+                Event::DisplayMath(formula) => Some(MathSpan { range, formula: formula.to_string(), display: true }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 把公式（不含 `$`）渲染为导出用的 HTML：能排版时为 SVG，否则为 `<code class="math">` 中的公式源码
+    pub fn render_html(formula: &str, display: bool) -> String {
+        Self::render_svg(formula, display)
+            .unwrap_or_else(|| format!("<code class=\"math\">{}</code>", html_escape(formula.trim())))
+    }
+
+    /// 把公式（不含 `$`）排版为 SVG，公式中有不支持的命令或环境时返回 `None`
+    ///
+    /// 支持常用的子集：希腊字母和运算符号、上下标、`\frac`、`\sqrt`、`\text` 等，
+    /// 在一行中用 Unicode 字符排版（分式写作 `a/b`）。`display` 为块级公式，字号更大
+    pub fn render_svg(formula: &str, display: bool) -> Option<String> {
+        let chars: Vec<char> = formula.trim().chars().collect();
+        if !is_supported(&chars) {
+            return None;
+        }
+        let mut runs = Vec::new();
+        layout(&chars, Script::Normal, &mut runs);
+
+        let font_size = if display { 20.0 } else { 16.0 };
+        let width = runs
+            .iter()
+            .map(|(text, script)| text.chars().map(char_width).sum::<f64>() * script.scale())
+            .sum::<f64>()
+            * font_size
+            + 4.0;
+        let height = font_size * 1.6;
+        let baseline = font_size * 1.1;
+
+        let mut spans = String::new();
+        let mut offset = 0.0;
+        for (text, script) in &runs {
+            let target = script.offset() * font_size;
+            spans.push_str(&format!(
+                "<tspan dy=\"{:.1}\" font-size=\"{:.1}\">{}</tspan>",
+                target - offset,
+                font_size * script.scale(),
+                html_escape(text)
+            ));
+            offset = target;
+        }
+
+        Some(format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"math\" width=\"{w:.1}\" height=\"{h:.1}\" \
+             viewBox=\"0 0 {w:.1} {h:.1}\" style=\"vertical-align: middle\">\
+             <text x=\"2\" y=\"{baseline:.1}\" font-family=\"'Cambria Math', 'STIX Two Math', 'Times New Roman', serif\" \
+             fill=\"currentColor\">{spans}</text></svg>",
+            w = width,
+            h = height,
+        ))
+    }
+}
+
+/// 公式中一段文字的位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Script {
+    Normal,
+    Superscript,
+    Subscript,
+}
+
+impl Script {
+    /// 相对正文的字号
+    fn scale(self) -> f64 {
+        match self {
+            Self::Normal => 1.0,
+            _ => 0.7,
+        }
+    }
+
+    /// 相对基线的偏移（字号的倍数，向下为正）
+    fn offset(self) -> f64 {
+        match self {
+            Self::Normal => 0.0,
+            Self::Superscript => -0.4,
+            Self::Subscript => 0.25,
+        }
+    }
+}
+
+/// 命令对应的符号
+const SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ε"), ("varepsilon", "ε"),
+    ("zeta", "ζ"), ("eta", "η"), ("theta", "θ"), ("iota", "ι"), ("kappa", "κ"), ("lambda", "λ"),
+    ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"), ("pi", "π"), ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"),
+    ("upsilon", "υ"), ("phi", "φ"), ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"),
+    ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"), ("Pi", "Π"),
+    ("Sigma", "Σ"), ("Phi", "Φ"), ("Psi", "Ψ"), ("Omega", "Ω"),
+    ("int", "∫"), ("iint", "∬"), ("oint", "∮"), ("sum", "∑"), ("prod", "∏"), ("infty", "∞"),
+    ("partial", "∂"), ("nabla", "∇"), ("pm", "±"), ("mp", "∓"), ("times", "×"), ("div", "÷"),
+    ("cdot", "·"), ("ast", "∗"), ("leq", "≤"), ("le", "≤"), ("geq", "≥"), ("ge", "≥"), ("neq", "≠"),
+    ("ne", "≠"), ("approx", "≈"), ("equiv", "≡"), ("sim", "∼"), ("propto", "∝"), ("to", "→"),
+    ("rightarrow", "→"), ("leftarrow", "←"), ("Rightarrow", "⇒"), ("Leftarrow", "⇐"),
+    ("leftrightarrow", "↔"), ("Leftrightarrow", "⇔"), ("mapsto", "↦"), ("in", "∈"), ("notin", "∉"),
+    ("subset", "⊂"), ("subseteq", "⊆"), ("supset", "⊃"), ("cup", "∪"), ("cap", "∩"),
+    ("emptyset", "∅"), ("forall", "∀"), ("exists", "∃"), ("neg", "¬"), ("land", "∧"), ("lor", "∨"),
+    ("ldots", "…"), ("cdots", "⋯"), ("dots", "…"), ("prime", "′"), ("circ", "∘"), ("degree", "°"),
+    ("langle", "⟨"), ("rangle", "⟩"), ("quad", "\u{2003}"), ("qquad", "\u{2003}\u{2003}"),
+];
+
+/// 排版时有特殊处理的命令
+const LAYOUT_COMMANDS: &[&str] = &[
+    "frac", "dfrac", "tfrac", "sqrt", "text", "mathrm", "mathbf", "mathit", "mathsf", "mathtt", "operatorname",
+    "left", "right", "big", "Big", "bigg", "Bigg", "displaystyle", "limits",
+];
+
+/// 可以转义的单个字符（`\{`、`\%` 等）和间距命令
+const ESCAPED_CHARS: &str = "{}%$#_&,:; !";
+
+/// 按原样显示名称的函数命令
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "arg", "deg",
+];
+
+/// 检查公式是否只用到排版支持的命令
+///
+/// 不认识的命令（如 `\hat`、`\mathbb`）、环境（`\begin{matrix}`）、换行（`\\`）、
+/// 对齐符号（`&`）以及 `\sqrt[n]` 都无法排版
+fn is_supported(chars: &[char]) -> bool {
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 1;
+                let name = command_name(chars, &mut i);
+                let known = if name.chars().all(|c| c.is_ascii_alphabetic()) {
+                    LAYOUT_COMMANDS.contains(&name.as_str())
+                        || FUNCTIONS.contains(&name.as_str())
+                        || SYMBOLS.iter().any(|(command, _)| *command == name)
+                } else {
+                    ESCAPED_CHARS.contains(name.as_str())
+                };
+                if !known || (name == "sqrt" && chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'[')) {
+                    return false;
+                }
+            }
+            '&' => return false,
+            _ => i += 1,
+        }
+    }
+    true
+}
+
+/// 排版公式，把文字按上下标位置追加到 `runs`
+fn layout(chars: &[char], script: Script, runs: &mut Vec<(String, Script)>) {
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 1;
+                let name = command_name(chars, &mut i);
+                layout_command(&name, chars, &mut i, script, runs);
+            }
+            '{' => {
+                let group = argument(chars, &mut i);
+                layout(&group, script, runs);
+            }
+            '^' | '_' => {
+                let inner = if chars[i] == '^' { Script::Superscript } else { Script::Subscript };
+                i += 1;
+                let group = argument(chars, &mut i);
+                // 嵌套的上下标保持同一位置
+                layout(&group, if script == Script::Normal { inner } else { script }, runs);
+            }
+            c if c.is_whitespace() => i += 1,
+            c => {
+                push_run(runs, &c.to_string(), script);
+                i += 1;
+            }
+        }
+    }
+}
+
+/// 排版一个命令
+fn layout_command(name: &str, chars: &[char], i: &mut usize, script: Script, runs: &mut Vec<(String, Script)>) {
+    match name {
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = argument(chars, i);
+            let denominator = argument(chars, i);
+            layout_wrapped(&numerator, script, runs);
+            push_run(runs, "/", script);
+            layout_wrapped(&denominator, script, runs);
+        }
+        "sqrt" => {
+            push_run(runs, "√", script);
+            let radicand = argument(chars, i);
+            layout_wrapped(&radicand, script, runs);
+        }
+        "text" | "mathrm" | "mathbf" | "mathit" | "mathsf" | "mathtt" | "operatorname" => {
+            let text: String = argument(chars, i).into_iter().collect();
+            push_run(runs, &text, script);
+        }
+        // 定界符大小命令只影响大小，后面的定界符照常显示
+        "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "limits" => {}
+        "," | ":" | ";" | " " => push_run(runs, "\u{2009}", script),
+        "!" => {}
+        _ => {
+            if let Some((_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+                push_run(runs, symbol, script);
+            } else if FUNCTIONS.contains(&name) {
+                push_run(runs, &format!("{} ", name), script);
+            } else {
+                // `\{`、`\%` 等转义字符显示字符本身
+                push_run(runs, name, script);
+            }
+        }
+    }
+}
+
+/// 排版分子、分母等参数，多于一个字符时加上括号
+fn layout_wrapped(group: &[char], script: Script, runs: &mut Vec<(String, Script)>) {
+    let wrap = group.iter().filter(|c| !c.is_whitespace()).count() > 1;
+    if wrap {
+        push_run(runs, "(", script);
+    }
+    layout(group, script, runs);
+    if wrap {
+        push_run(runs, ")", script);
+    }
+}
+
+/// 读取 `\` 之后的命令名：连续的字母，或单个其他字符
+fn command_name(chars: &[char], i: &mut usize) -> String {
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_alphabetic() {
+        *i += 1;
+    }
+    if *i == start && *i < chars.len() {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+/// 读取命令或上下标的参数：`{...}` 中的内容、一个命令或一个字符
+fn argument(chars: &[char], i: &mut usize) -> Vec<char> {
+    while *i < chars.len() && chars[*i].is_whitespace() {
+        *i += 1;
+    }
+    let Some(&first) = chars.get(*i) else {
+        return Vec::new();
+    };
+
+    match first {
+        '{' => {
+            let start = *i + 1;
+            let mut depth = 0;
+            while *i < chars.len() {
+                match chars[*i] {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            *i += 1;
+                            return chars[start..*i - 1].to_vec();
+                        }
+                    }
+                    _ => {}
+                }
+                *i += 1;
+            }
+            // 缺少右括号时取到末尾
+            chars[start..].to_vec()
+        }
+        '\\' => {
+            let start = *i;
+            *i += 1;
+            command_name(chars, i);
+            chars[start..*i].to_vec()
+        }
+        _ => {
+            *i += 1;
+            vec![first]
+        }
+    }
+}
+
+/// 追加文字，与上一段位置相同时合并
+fn push_run(runs: &mut Vec<(String, Script)>, text: &str, script: Script) {
+    match runs.last_mut() {
+        Some((last, last_script)) if *last_script == script => last.push_str(text),
+        _ => runs.push((text.to_string(), script)),
+    }
+}
+
+/// 估算字符宽度（字号的倍数）
+fn char_width(c: char) -> f64 {
+    if c.is_ascii() {
+        0.6
+    } else {
+        1.0
+    }
 }
 
 /// HTML 转义函数
@@ -87,6 +410,51 @@ mod tests {
         assert!(result.contains("int"));
     }
 
+    #[test]
+    fn test_render_svg() {
+        let svg = LatexRenderer::render_svg(r"\alpha^2 + \frac{1}{x_i} \leq \sqrt{a+b}", false).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">α</tspan>"));
+        assert!(svg.contains(">2</tspan>"));
+        assert!(svg.contains("1/(x"));
+        assert!(svg.contains(">i</tspan>"));
+        assert!(svg.contains("≤√(a+b)"));
+
+        // 特殊字符需要转义
+        let svg = LatexRenderer::render_svg(r"a < b \text{ and } c", true).unwrap();
+        assert!(svg.contains("a&lt;b and c"));
+    }
+
+    #[test]
+    fn test_unsupported_formula_shows_source() {
+        for formula in [
+            r"\begin{matrix} a & b \\ c & d \end{matrix}",
+            r"\hat{x} + \mathbb{R}",
+            r"\sqrt[3]{x}",
+        ] {
+            assert!(LatexRenderer::render_svg(formula, true).is_none(), "{}", formula);
+        }
+
+        let html = LatexRenderer::render_html(r"\begin{matrix} a & b \end{matrix}", false);
+        assert_eq!(html, r#"<code class="math">\begin{matrix} a &amp; b \end{matrix}</code>"#);
+        assert!(LatexRenderer::render_html(r"\{x \mid x > 0\}", false).starts_with("<code"));
+        assert!(LatexRenderer::render_html(r"50\% \cdot x_{i}", false).starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_find_math() {
+        let line = r"面积 $\pi r^2$，价格 \$5 和 `$x$`，$$E = mc^2$$";
+        let spans = LatexRenderer::find_math(line);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(&line[spans[0].range.clone()], r"$\pi r^2$");
+        assert_eq!(spans[0].formula, r"\pi r^2");
+        assert!(!spans[0].display);
+        assert_eq!(spans[1].formula, "E = mc^2");
+        assert!(spans[1].display);
+
+        assert!(LatexRenderer::find_math("no math").is_empty());
+    }
+
     #[test]
     fn test_contains_latex() {
         assert!(LatexRenderer::contains_latex("$x^2$"));
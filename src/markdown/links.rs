@@ -0,0 +1,437 @@
+//! Markdown 链接提取模块
+//!
+//! 从 Markdown 源码中提取链接与图片引用：
+//! - 行内链接 `[text](dest)` 与图片 `![alt](dest)`
+//! - 引用定义 `[id]: dest`
+//! - 记录目标地址在源码中的字节范围，便于原地改写
+//! - 生成标题锚点
+//! - 查找可点击的链接，确定链接指向网页、标题还是其他文档
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+
+use crate::file_manager::{is_markdown_path, normalize_path, percent_decode};
+
+/// 链接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// 普通链接
+    Link,
+    /// 图片
+    Image,
+    /// 引用定义（`[id]: dest`）
+    Definition,
+}
+
+/// 源码中的一个链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownLink {
+    /// 链接类型
+    pub kind: LinkKind,
+    /// 链接目标（源码中的原始写法，不含尖括号）
+    pub dest: String,
+    /// 目标地址在源码中的字节范围
+    pub dest_range: Range<usize>,
+    /// 所在行号（从1开始）
+    pub line_number: usize,
+    /// 目标是否使用 `<...>` 包裹
+    pub angle_brackets: bool,
+}
+
+impl MarkdownLink {
+    /// 判断是否为外部链接（带协议或以 `/` 开头的绝对地址）
+    pub fn is_external(&self) -> bool {
+        is_external_dest(&self.dest)
+    }
+
+    /// 获取路径部分（去掉 `#锚点` 和 `?查询`）
+    pub fn path_part(&self) -> &str {
+        let end = self.dest.find(['#', '?']).unwrap_or(self.dest.len());
+        &self.dest[..end]
+    }
+
+    /// 获取路径之后的后缀（`#锚点` 或 `?查询`，包含前导符号）
+    pub fn suffix(&self) -> &str {
+        &self.dest[self.path_part().len()..]
+    }
+
+    /// 获取锚点（不含 `#`）
+    pub fn fragment(&self) -> Option<&str> {
+        self.dest.find('#').map(|pos| &self.dest[pos + 1..])
+    }
+}
+
+/// 判断链接目标是否为外部地址（带协议或以 `/` 开头的绝对地址）
+pub fn is_external_dest(dest: &str) -> bool {
+    dest.starts_with('/') || dest.starts_with("//") || has_scheme(dest)
+}
+
+/// 生成标题的锚点，规则与 GitHub 相同：
+/// 转为小写，去掉标点，空格替换为 `-`
+pub fn heading_slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 文字中的一个链接（预览和编辑区中可点击）
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineLink {
+    /// 整个链接在文字中的字节范围
+    pub range: Range<usize>,
+    /// 链接文字（包括其中的格式标记）的字节范围，没有文字时与 `range` 相同
+    pub label: Range<usize>,
+    /// 链接目标
+    pub dest: String,
+}
+
+/// 链接指向的位置
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// 网页等外部地址（在浏览器中打开）
+    External(String),
+    /// 当前文档中的标题
+    Anchor(String),
+    /// 其他 Markdown 文档，可以指定标题
+    Document { path: PathBuf, anchor: Option<String> },
+    /// 其他本地文件（用系统默认程序打开）
+    File(PathBuf),
+}
+
+/// 文档中的标题锚点
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingAnchor {
+    /// 标题所在行号（从1开始）
+    pub line_number: usize,
+    /// 锚点，重复的标题依次加上 `-1`、`-2`，与 HTML 导出相同
+    pub anchor: String,
+    /// 与前面的标题重复时为第一个标题的行号，链接只能通过不带后缀的锚点指向第一个标题
+    pub duplicate_of: Option<usize>,
+}
+
+/// 查找文字中的行内链接和自动链接（`<https://...>`），按位置排列
+///
+/// 传入整个文档时引用式链接（`[text][id]`）也会解析到对应的目标
+pub fn find_inline_links(text: &str) -> Vec<InlineLink> {
+    let mut links = Vec::new();
+    let mut events = Parser::new_ext(text, parser_options()).into_offset_iter();
+
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Link { dest_url, .. }) = event else {
+            continue;
+        };
+        // 链接文字包括其中的强调等标记
+        let mut label: Option<Range<usize>> = None;
+        for (event, inner) in events.by_ref() {
+            if let Event::End(TagEnd::Link) = event {
+                break;
+            }
+            label = Some(label.map_or(inner.clone(), |label| label.start.min(inner.start)..label.end.max(inner.end)));
+        }
+        links.push(InlineLink {
+            label: label.unwrap_or_else(|| range.clone()),
+            range,
+            dest: dest_url.to_string(),
+        });
+    }
+
+    links
+}
+
+/// 确定链接指向的位置，相对路径相对于 `base_dir`
+pub fn resolve_link_target(base_dir: &Path, dest: &str) -> Option<LinkTarget> {
+    let dest = dest.trim();
+    if let Some(anchor) = dest.strip_prefix('#') {
+        return Some(LinkTarget::Anchor(percent_decode(anchor)));
+    }
+    if dest.starts_with("//") || has_scheme(dest) {
+        return match dest.strip_prefix("file://") {
+            Some(path) => resolve_link_target(base_dir, path),
+            None => Some(LinkTarget::External(dest.to_string())),
+        };
+    }
+
+    let (path, anchor) = match dest.split_once('#') {
+        Some((path, anchor)) => (path, Some(percent_decode(anchor)).filter(|anchor| !anchor.is_empty())),
+        None => (dest, None),
+    };
+    let path = path.split('?').next().unwrap_or_default();
+    if path.is_empty() {
+        return None;
+    }
+    let path = normalize_path(&base_dir.join(percent_decode(path)));
+    Some(if is_markdown_path(&path) { LinkTarget::Document { path, anchor } } else { LinkTarget::File(path) })
+}
+
+/// 文档中所有标题的锚点，标题中指定的 `{#id}` 优先
+pub fn heading_anchors(markdown: &str) -> Vec<HeadingAnchor> {
+    let mut options = parser_options();
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+
+    let mut anchors = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    // 每个锚点第一次出现的行号
+    let mut first_lines: HashMap<String, usize> = HashMap::new();
+    let mut events = Parser::new_ext(markdown, options).into_offset_iter();
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Heading { id, .. }) = event else {
+            continue;
+        };
+        let mut text = String::new();
+        for (event, _) in events.by_ref() {
+            match event {
+                Event::End(TagEnd::Heading(_)) => break,
+                Event::Text(part) | Event::Code(part) => text.push_str(&part),
+                _ => {}
+            }
+        }
+
+        let line_number = markdown[..range.start].matches('\n').count() + 1;
+        // 标题中指定的锚点保持不变，其他标题重复时加上后缀
+        let base = id.as_ref().map_or_else(|| heading_slug(&text), |id| id.to_string());
+        let duplicate_of = first_lines.get(&base).copied();
+        let anchor = if id.is_some() {
+            base
+        } else {
+            let count = counts.entry(base.clone()).or_insert(0);
+            let anchor = if *count == 0 { base } else { format!("{}-{}", base, count) };
+            *count += 1;
+            anchor
+        };
+        first_lines.entry(anchor.clone()).or_insert(line_number);
+        anchors.push(HeadingAnchor { line_number, anchor, duplicate_of });
+    }
+
+    anchors
+}
+
+/// 解析链接时启用的扩展语法
+fn parser_options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
+
+/// 判断地址是否带协议（如 `https:`、`mailto:`）
+fn has_scheme(dest: &str) -> bool {
+    match dest.find(':') {
+        Some(pos) if pos > 1 => dest[..pos]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'),
+        _ => false,
+    }
+}
+
+/// 提取 Markdown 文本中的所有行内链接、图片和引用定义
+///
+/// 引用式链接（`[text][id]`）本身不单独返回，其目标由对应的引用定义给出
+pub fn extract_links(markdown: &str) -> Vec<MarkdownLink> {
+    let parser = Parser::new_ext(markdown, parser_options());
+    let mut links = Vec::new();
+
+    // 引用定义在构造解析器时已经收集完成
+    for (_, def) in parser.reference_definitions().iter() {
+        let source = &markdown[def.span.clone()];
+        if let Some(colon) = source.find("]:") {
+            let offset = def.span.start + colon + 2;
+            if let Some((range, angle)) = scan_destination(markdown, offset) {
+                links.push(make_link(markdown, LinkKind::Definition, range, angle));
+            }
+        }
+    }
+
+    for (event, range) in parser.into_offset_iter() {
+        let (kind, link_type) = match event {
+            Event::Start(Tag::Link { link_type, .. }) => (LinkKind::Link, link_type),
+            Event::Start(Tag::Image { link_type, .. }) => (LinkKind::Image, link_type),
+            _ => continue,
+        };
+
+        if link_type != LinkType::Inline {
+            continue;
+        }
+
+        // 行内链接的范围形如 `[text](dest "title")`，目标位于最后一个 `](` 之后
+        let source = &markdown[range.clone()];
+        if let Some(pos) = source.rfind("](") {
+            let offset = range.start + pos + 2;
+            if let Some((dest_range, angle)) = scan_destination(markdown, offset) {
+                links.push(make_link(markdown, kind, dest_range, angle));
+            }
+        }
+    }
+
+    links.sort_by_key(|link| link.dest_range.start);
+    links
+}
+
+/// 构造链接记录
+fn make_link(markdown: &str, kind: LinkKind, range: Range<usize>, angle_brackets: bool) -> MarkdownLink {
+    MarkdownLink {
+        kind,
+        dest: markdown[range.clone()].to_string(),
+        line_number: markdown[..range.start].matches('\n').count() + 1,
+        dest_range: range,
+        angle_brackets,
+    }
+}
+
+/// 从指定位置扫描链接目标，返回目标的字节范围以及是否使用尖括号
+fn scan_destination(markdown: &str, offset: usize) -> Option<(Range<usize>, bool)> {
+    let rest = &markdown[offset..];
+    let skipped = rest.len() - rest.trim_start().len();
+    let start = offset + skipped;
+    let rest = &markdown[start..];
+
+    if let Some(inner) = rest.strip_prefix('<') {
+        let end = inner.find(['>', '\n'])?;
+        return Some((start + 1..start + 1 + end, true));
+    }
+
+    // 普通目标：遇到空白或不平衡的右括号结束
+    let mut depth = 0usize;
+    let mut end = rest.len();
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                end = i;
+                break;
+            }
+            ')' => depth -= 1,
+            c if c.is_whitespace() => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if end == 0 {
+        return None;
+    }
+
+    Some((start..start + end, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_inline_links() {
+        let markdown = "See [doc](../a.md) and ![img](img/x.png \"title\").";
+        let links = extract_links(markdown);
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].kind, LinkKind::Link);
+        assert_eq!(links[0].dest, "../a.md");
+        assert_eq!(&markdown[links[0].dest_range.clone()], "../a.md");
+        assert_eq!(links[1].kind, LinkKind::Image);
+        assert_eq!(links[1].dest, "img/x.png");
+    }
+
+    #[test]
+    fn test_extract_definition_and_line_numbers() {
+        let markdown = "# 标题\n\n[ref][id]\n\n[id]: <docs/my file.md>\n";
+        let links = extract_links(markdown);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].kind, LinkKind::Definition);
+        assert_eq!(links[0].dest, "docs/my file.md");
+        assert!(links[0].angle_brackets);
+        assert_eq!(links[0].line_number, 5);
+    }
+
+    #[test]
+    fn test_link_parts() {
+        let links = extract_links("[a](b.md#intro) [b](https://example.com)");
+
+        assert_eq!(links[0].path_part(), "b.md");
+        assert_eq!(links[0].fragment(), Some("intro"));
+        assert_eq!(links[0].suffix(), "#intro");
+        assert!(!links[0].is_external());
+        assert!(links[1].is_external());
+    }
+
+    #[test]
+    fn test_heading_slug() {
+        assert_eq!(heading_slug("Hello, World!"), "hello-world");
+        assert_eq!(heading_slug(" 安装 与 使用 "), "安装-与-使用");
+        assert_eq!(heading_slug("a_b-c (v2)"), "a_b-c-v2");
+    }
+
+    #[test]
+    fn test_find_inline_links() {
+        let text = "见 [**安装** 说明](docs/install.md#步骤) 和 <https://example.com>，`[代码](x.md)`";
+        let links = find_inline_links(text);
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text[links[0].range.clone()], "[**安装** 说明](docs/install.md#步骤)");
+        assert_eq!(&text[links[0].label.clone()], "**安装** 说明");
+        assert_eq!(links[0].dest, "docs/install.md#步骤");
+        assert_eq!(&text[links[1].label.clone()], "https://example.com");
+
+        // 传入整个文档时解析引用式链接
+        let links = find_inline_links("[文档][doc]\n\n[doc]: a.md\n");
+        assert_eq!(links[0].dest, "a.md");
+    }
+
+    #[test]
+    fn test_resolve_link_target() {
+        let base = Path::new("/notes/guide");
+        assert_eq!(
+            resolve_link_target(base, "https://example.com/a"),
+            Some(LinkTarget::External("https://example.com/a".to_string()))
+        );
+        assert_eq!(resolve_link_target(base, "#%E5%AE%89%E8%A3%85"), Some(LinkTarget::Anchor("安装".to_string())));
+        assert_eq!(
+            resolve_link_target(base, "../api/my%20file.md#usage"),
+            Some(LinkTarget::Document {
+                path: PathBuf::from("/notes/api/my file.md"),
+                anchor: Some("usage".to_string())
+            })
+        );
+        assert_eq!(
+            resolve_link_target(base, "intro.markdown#"),
+            Some(LinkTarget::Document { path: PathBuf::from("/notes/guide/intro.markdown"), anchor: None })
+        );
+        assert_eq!(
+            resolve_link_target(base, "file:///tmp/report.pdf"),
+            Some(LinkTarget::File(PathBuf::from("/tmp/report.pdf")))
+        );
+        assert_eq!(resolve_link_target(base, "?query"), None);
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let markdown = "# 介绍\n\n```\n# 不是标题\n```\n\n## 介绍\n\n## `API` 用法 {#api}\n\n设置\n---\n\n# API {#api}\n";
+        let anchors = heading_anchors(markdown);
+        let anchors: Vec<(usize, &str, Option<usize>)> = anchors
+            .iter()
+            .map(|heading| (heading.line_number, heading.anchor.as_str(), heading.duplicate_of))
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![(1, "介绍", None), (7, "介绍-1", Some(1)), (9, "api", None), (11, "设置", None), (14, "api", Some(9))]
+        );
+    }
+
+    #[test]
+    fn test_links_in_code_are_ignored() {
+        let links = extract_links("`[a](b.md)`\n\n```\n[c](d.md)\n```\n");
+        assert!(links.is_empty());
+    }
+}
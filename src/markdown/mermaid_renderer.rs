@@ -1,6 +1,11 @@
 //! Mermaid 流程图渲染模块
 //!
-//! Mermaid 图表渲染器（占位实现）
+//! Mermaid 图表渲染器（纯 Rust 实现，无需 JS 引擎）：
+//! - 流程图：按连线分层排列节点，支持方向、节点形状和连线文字
+//! - 时序图：参与者、生命线和消息
+//! - 其他类型的图表以源码形式显示在 SVG 中
+
+use std::collections::HashMap;
 
 /// Mermaid 图表类型
 #[derive(Debug, Clone, Copy)]
@@ -12,17 +17,45 @@ pub enum DiagramType {
     Gantt,
 }
 
+impl DiagramType {
+    /// 根据图表定义的第一行判断图表类型
+    pub fn detect(first_line: &str) -> Self {
+        let line = first_line.trim();
+        if line.starts_with("sequenceDiagram") {
+            Self::SequenceDiagram
+        } else if line.starts_with("classDiagram") {
+            Self::ClassDiagram
+        } else if line.starts_with("stateDiagram") {
+            Self::StateDiagram
+        } else if line.starts_with("gantt") {
+            Self::Gantt
+        } else {
+            Self::Flowchart
+        }
+    }
+}
+
+/// 图表使用的字号
+const FONT_SIZE: f64 = 14.0;
+/// 线条颜色
+const STROKE: &str = "#555";
+/// 节点填充颜色
+const FILL: &str = "#f4f4f8";
+
 /// Mermaid 图表渲染器
 pub struct MermaidRenderer;
 
 impl MermaidRenderer {
-    /// 渲染 Mermaid 图表为 SVG（占位实现）
-    pub fn render(mermaid: &str, _diagram_type: DiagramType) -> String {
-        // 占位实现，实际渲染需要集成 mermaid 库
-        format!(
-            "<div style=\"background: #f5f5f5; padding: 20px; border: 1px dashed #999;\">Mermaid 图表占位符<br><pre>{}</pre></div>",
-            html_escape(mermaid)
-        )
+    /// 渲染 Mermaid 图表为 SVG
+    ///
+    /// 无法解析的图表显示为包含源码的 SVG
+    pub fn render(mermaid: &str, diagram_type: DiagramType) -> String {
+        let svg = match diagram_type {
+            DiagramType::Flowchart => Flowchart::parse(mermaid).map(|chart| chart.to_svg()),
+            DiagramType::SequenceDiagram => SequenceDiagram::parse(mermaid).map(|diagram| diagram.to_svg()),
+            _ => None,
+        };
+        svg.unwrap_or_else(|| source_svg(mermaid))
     }
 
     /// 渲染流程图
@@ -63,52 +96,542 @@ impl MermaidRenderer {
 
         while i < lines.len() {
             let line = lines[i].trim();
-            
+
             if line == "```mermaid" || line == "```graph" {
                 let mut diagram = String::new();
                 let mut diagram_type = DiagramType::Flowchart;
-                
+
                 if line == "```mermaid" {
                     i += 1;
                     // 尝试从下一行获取图表类型
                     if i < lines.len() {
                         let type_line = lines[i].trim();
-                        diagram_type = match type_line {
-                            "graph TD" | "graph LR" => DiagramType::Flowchart,
-                            "sequenceDiagram" => DiagramType::SequenceDiagram,
-                            "classDiagram" => DiagramType::ClassDiagram,
-                            "stateDiagram" => DiagramType::StateDiagram,
-                            "gantt" => DiagramType::Gantt,
-                            _ => DiagramType::Flowchart,
-                        };
+                        diagram_type = DiagramType::detect(type_line);
                         diagram.push_str(type_line);
                         diagram.push('\n');
                     }
                 } else {
-                    diagram_type = DiagramType::Flowchart;
                     diagram.push_str(&line[3..]); // 移除 ```
                     diagram.push('\n');
                 }
-                
+
                 i += 1;
-                
+
                 // 收集图表内容
                 while i < lines.len() && lines[i].trim() != "```" {
                     diagram.push_str(lines[i]);
                     diagram.push('\n');
                     i += 1;
                 }
-                
+
                 diagrams.push((diagram, diagram_type));
             }
-            
+
             i += 1;
         }
-        
+
         diagrams
     }
 }
 
+/// 流程图节点形状
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shape {
+    /// `[文字]`
+    Rect,
+    /// `(文字)`
+    Round,
+    /// `((文字))`
+    Circle,
+    /// `{文字}`
+    Diamond,
+}
+
+/// 流程图节点
+#[derive(Debug, Clone)]
+struct Node {
+    label: String,
+    shape: Shape,
+}
+
+/// 流程图连线
+#[derive(Debug, Clone)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    dashed: bool,
+    arrow: bool,
+}
+
+/// 深度优先遍历中节点的状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+/// 流程图
+#[derive(Debug, Default)]
+struct Flowchart {
+    /// 方向：`TD`/`TB`、`BT`、`LR`、`RL`
+    direction: String,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Flowchart {
+    /// 解析流程图，没有节点时返回 `None`
+    fn parse(source: &str) -> Option<Self> {
+        let mut chart = Self { direction: "TD".to_string(), ..Default::default() };
+        let mut index = HashMap::new();
+
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line_number == 0 && (line.starts_with("graph") || line.starts_with("flowchart")) {
+                if let Some(direction) = line.split_whitespace().nth(1) {
+                    chart.direction = direction.trim_end_matches(';').to_uppercase();
+                }
+                continue;
+            }
+            for statement in line.split(';') {
+                chart.parse_statement(statement.trim(), &mut index);
+            }
+        }
+
+        (!chart.nodes.is_empty()).then_some(chart)
+    }
+
+    /// 解析一条语句：节点，或用连线相连的一串节点
+    fn parse_statement(&mut self, statement: &str, index: &mut HashMap<String, usize>) {
+        const IGNORED: &[&str] = &["subgraph", "end", "style", "classDef", "class", "click", "linkStyle", "direction", "%%"];
+        if statement.is_empty() || IGNORED.iter().any(|keyword| statement.starts_with(keyword)) {
+            return;
+        }
+
+        let mut rest = statement;
+        let Some(mut previous) = self.parse_node(&mut rest, index) else {
+            return;
+        };
+        while let Some((label, dashed, arrow)) = parse_arrow(&mut rest) {
+            let Some(next) = self.parse_node(&mut rest, index) else {
+                return;
+            };
+            self.edges.push(Edge { from: previous, to: next, label, dashed, arrow });
+            previous = next;
+        }
+    }
+
+    /// 解析节点（`id`、`id[文字]` 等），返回节点序号
+    fn parse_node(&mut self, rest: &mut &str, index: &mut HashMap<String, usize>) -> Option<usize> {
+        let text = rest.trim_start();
+        let id_len = text
+            .char_indices()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+            .map_or(text.len(), |(i, _)| i);
+        if id_len == 0 {
+            return None;
+        }
+        let id = &text[..id_len];
+        let mut after = &text[id_len..];
+
+        let mut shape_label = None;
+        for (open, close, shape) in [("((", "))", Shape::Circle), ("(", ")", Shape::Round), ("[", "]", Shape::Rect), ("{", "}", Shape::Diamond), (">", "]", Shape::Rect)] {
+            if let Some(inner) = after.strip_prefix(open) {
+                if let Some(end) = inner.find(close) {
+                    let label = inner[..end].trim().trim_matches('"').to_string();
+                    shape_label = Some((shape, label));
+                    after = &inner[end + close.len()..];
+                }
+                break;
+            }
+        }
+        *rest = after;
+
+        let node = *index.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(Node { label: id.to_string(), shape: Shape::Rect });
+            self.nodes.len() - 1
+        });
+        if let Some((shape, label)) = shape_label {
+            self.nodes[node].shape = shape;
+            self.nodes[node].label = label;
+        }
+        Some(node)
+    }
+
+    /// 按连线分层：每个节点位于所有前驱节点的下一层，环路中的回边不参与分层
+    fn ranks(&self) -> Vec<usize> {
+        let mut outgoing = vec![Vec::new(); self.nodes.len()];
+        for (index, edge) in self.edges.iter().enumerate() {
+            outgoing[edge.from].push(index);
+        }
+        let mut visited = vec![Visit::New; self.nodes.len()];
+        let mut back_edges = vec![false; self.edges.len()];
+        for node in 0..self.nodes.len() {
+            self.find_back_edges(node, &outgoing, &mut visited, &mut back_edges);
+        }
+
+        let mut ranks = vec![0; self.nodes.len()];
+        for _ in 0..self.nodes.len() {
+            let mut changed = false;
+            for (edge, back) in self.edges.iter().zip(&back_edges) {
+                if !back && edge.from != edge.to && ranks[edge.to] < ranks[edge.from] + 1 {
+                    ranks[edge.to] = ranks[edge.from] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        ranks
+    }
+
+    /// 深度优先遍历，指向遍历路径上节点的连线是回边
+    fn find_back_edges(&self, node: usize, outgoing: &[Vec<usize>], visited: &mut [Visit], back_edges: &mut [bool]) {
+        if visited[node] != Visit::New {
+            return;
+        }
+        visited[node] = Visit::Active;
+        for &edge in &outgoing[node] {
+            let to = self.edges[edge].to;
+            match visited[to] {
+                Visit::Active => back_edges[edge] = true,
+                Visit::New => self.find_back_edges(to, outgoing, visited, back_edges),
+                Visit::Done => {}
+            }
+        }
+        visited[node] = Visit::Done;
+    }
+
+    /// 生成 SVG
+    fn to_svg(&self) -> String {
+        const MARGIN: f64 = 20.0;
+        const HEIGHT: f64 = 40.0;
+        const RANK_GAP: f64 = 60.0;
+        const NODE_GAP: f64 = 30.0;
+
+        let horizontal = matches!(self.direction.as_str(), "LR" | "RL");
+        let reversed = matches!(self.direction.as_str(), "BT" | "RL");
+
+        let sizes: Vec<(f64, f64)> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let width = (text_width(&node.label) + 24.0).max(60.0);
+                match node.shape {
+                    Shape::Diamond => (width * 1.4, HEIGHT * 1.4),
+                    Shape::Circle => (width.max(HEIGHT * 1.4), HEIGHT * 1.4),
+                    _ => (width, HEIGHT),
+                }
+            })
+            .collect();
+
+        let ranks = self.ranks();
+        let rank_count = ranks.iter().max().map_or(0, |max| max + 1);
+        let mut layers: Vec<Vec<usize>> = vec![Vec::new(); rank_count];
+        for (node, &rank) in ranks.iter().enumerate() {
+            let rank = if reversed { rank_count - 1 - rank } else { rank };
+            layers[rank].push(node);
+        }
+
+        // 每层沿排列方向的长度和垂直方向的厚度
+        let along = |node: usize| if horizontal { sizes[node].1 } else { sizes[node].0 };
+        let across = |node: usize| if horizontal { sizes[node].0 } else { sizes[node].1 };
+        let layer_lengths: Vec<f64> = layers
+            .iter()
+            .map(|layer| layer.iter().map(|&node| along(node)).sum::<f64>() + NODE_GAP * layer.len().saturating_sub(1) as f64)
+            .collect();
+        let layer_thickness: Vec<f64> = layers
+            .iter()
+            .map(|layer| layer.iter().map(|&node| across(node)).fold(0.0, f64::max))
+            .collect();
+        let max_length = layer_lengths.iter().cloned().fold(0.0, f64::max);
+
+        let mut centers = vec![(0.0, 0.0); self.nodes.len()];
+        let mut depth = MARGIN;
+        for (layer_index, layer) in layers.iter().enumerate() {
+            let mut position = MARGIN + (max_length - layer_lengths[layer_index]) / 2.0;
+            let middle = depth + layer_thickness[layer_index] / 2.0;
+            for &node in layer {
+                let center = position + along(node) / 2.0;
+                centers[node] = if horizontal { (middle, center) } else { (center, middle) };
+                position += along(node) + NODE_GAP;
+            }
+            depth += layer_thickness[layer_index] + RANK_GAP;
+        }
+        let total_depth = depth - RANK_GAP + MARGIN;
+        let (width, height) = if horizontal {
+            (total_depth, max_length + 2.0 * MARGIN)
+        } else {
+            (max_length + 2.0 * MARGIN, total_depth)
+        };
+
+        let mut body = String::new();
+        for edge in &self.edges {
+            let from = centers[edge.from];
+            let to = centers[edge.to];
+            if edge.from == edge.to {
+                continue;
+            }
+            let start = clip(from, to, sizes[edge.from]);
+            let end = clip(to, from, sizes[edge.to]);
+            body.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"1.5\"{}{}/>",
+                start.0,
+                start.1,
+                end.0,
+                end.1,
+                STROKE,
+                if edge.dashed { " stroke-dasharray=\"5,4\"" } else { "" },
+                if edge.arrow { " marker-end=\"url(#mermaid-arrow)\"" } else { "" },
+            ));
+            if let Some(label) = &edge.label {
+                body.push_str(&text_element((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0, label, true));
+            }
+        }
+
+        for ((node, (x, y)), (w, h)) in self.nodes.iter().zip(&centers).zip(&sizes) {
+            let shape = match node.shape {
+                Shape::Rect | Shape::Round => format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{}\"",
+                    x - w / 2.0,
+                    y - h / 2.0,
+                    w,
+                    h,
+                    if node.shape == Shape::Round { 16 } else { 4 }
+                ),
+                Shape::Circle => format!("<ellipse cx=\"{:.1}\" cy=\"{:.1}\" rx=\"{:.1}\" ry=\"{:.1}\"", x, y, w / 2.0, h / 2.0),
+                Shape::Diamond => format!(
+                    "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\"",
+                    x,
+                    y - h / 2.0,
+                    x + w / 2.0,
+                    y,
+                    x,
+                    y + h / 2.0,
+                    x - w / 2.0,
+                    y
+                ),
+            };
+            body.push_str(&format!("{} fill=\"{}\" stroke=\"{}\" stroke-width=\"1.5\"/>", shape, FILL, STROKE));
+            body.push_str(&text_element(*x, *y, &node.label, false));
+        }
+
+        svg_document(width, height, &body)
+    }
+}
+
+/// 解析连线：`-->`、`---`、`-.->`、`==>`，以及 `-->|文字|` 和 `-- 文字 -->`
+///
+/// 返回连线文字、是否为虚线、是否有箭头
+fn parse_arrow(rest: &mut &str) -> Option<(Option<String>, bool, bool)> {
+    let text = rest.trim_start();
+    let len = text.find(|c| !matches!(c, '-' | '=' | '.' | '>')).unwrap_or(text.len());
+    let arrow = &text[..len];
+    if len < 2 || !(arrow.starts_with('-') || arrow.starts_with('=')) {
+        return None;
+    }
+
+    let mut after = &text[len..];
+    let mut label = None;
+    let mut full_arrow = arrow.to_string();
+
+    if let Some(inner) = after.trim_start().strip_prefix('|') {
+        if let Some(end) = inner.find('|') {
+            label = Some(inner[..end].trim().to_string());
+            after = &inner[end + 1..];
+        }
+    } else if !arrow.ends_with('>') && (arrow == "--" || arrow == "==") {
+        // `A -- 文字 --> B`
+        let end = ["-->", "==>", "---"].iter().filter_map(|close| after.find(close).map(|i| (i, *close))).min();
+        if let Some((end, close)) = end {
+            label = Some(after[..end].trim().to_string());
+            full_arrow = close.to_string();
+            after = &after[end + close.len()..];
+        }
+    }
+
+    *rest = after;
+    Some((label.filter(|l| !l.is_empty()), full_arrow.contains('.'), full_arrow.ends_with('>')))
+}
+
+/// 从节点中心沿连线方向到节点边框的交点
+fn clip(center: (f64, f64), toward: (f64, f64), size: (f64, f64)) -> (f64, f64) {
+    let dx = toward.0 - center.0;
+    let dy = toward.1 - center.1;
+    if dx == 0.0 && dy == 0.0 {
+        return center;
+    }
+    let tx = if dx == 0.0 { f64::INFINITY } else { size.0 / 2.0 / dx.abs() };
+    let ty = if dy == 0.0 { f64::INFINITY } else { size.1 / 2.0 / dy.abs() };
+    let t = tx.min(ty);
+    (center.0 + dx * t, center.1 + dy * t)
+}
+
+/// 时序图中的消息
+#[derive(Debug)]
+struct Message {
+    from: usize,
+    to: usize,
+    text: String,
+    dashed: bool,
+}
+
+/// 时序图
+#[derive(Debug, Default)]
+struct SequenceDiagram {
+    /// 参与者的标识和显示名称
+    participants: Vec<(String, String)>,
+    messages: Vec<Message>,
+}
+
+impl SequenceDiagram {
+    /// 解析时序图，没有参与者时返回 `None`
+    fn parse(source: &str) -> Option<Self> {
+        const ARROWS: &[&str] = &["-->>", "->>", "--x", "-x", "--)", "-)", "-->", "->"];
+        let mut diagram = Self::default();
+
+        for line in source.lines().skip(1) {
+            let line = line.trim();
+            if let Some(declaration) = line.strip_prefix("participant ").or_else(|| line.strip_prefix("actor ")) {
+                let (id, name) = match declaration.split_once(" as ") {
+                    Some((id, name)) => (id.trim(), name.trim()),
+                    None => (declaration.trim(), declaration.trim()),
+                };
+                diagram.participant(id, name);
+                continue;
+            }
+
+            let (head, text) = line.split_once(':').unwrap_or((line, ""));
+            let Some((position, arrow)) = ARROWS.iter().filter_map(|arrow| head.find(arrow).map(|i| (i, *arrow))).min_by_key(|(i, arrow)| (*i, usize::MAX - arrow.len())) else {
+                continue;
+            };
+            let from = head[..position].trim();
+            let to = head[position + arrow.len()..].trim().trim_start_matches(['+', '-']);
+            if from.is_empty() || to.is_empty() || from.contains(' ') {
+                continue;
+            }
+            let from = diagram.participant(from, from);
+            let to = diagram.participant(to, to);
+            diagram.messages.push(Message { from, to, text: text.trim().to_string(), dashed: arrow.starts_with("--") });
+        }
+
+        (!diagram.participants.is_empty()).then_some(diagram)
+    }
+
+    /// 查找或添加参与者，返回序号
+    fn participant(&mut self, id: &str, name: &str) -> usize {
+        match self.participants.iter().position(|(existing, _)| existing == id) {
+            Some(index) => index,
+            None => {
+                self.participants.push((id.to_string(), name.to_string()));
+                self.participants.len() - 1
+            }
+        }
+    }
+
+    /// 生成 SVG
+    fn to_svg(&self) -> String {
+        const MARGIN: f64 = 20.0;
+        const HEADER: f64 = 36.0;
+        const STEP: f64 = 40.0;
+
+        let column = self
+            .participants
+            .iter()
+            .map(|(_, name)| text_width(name) + 40.0)
+            .chain(self.messages.iter().map(|message| text_width(&message.text) + 30.0))
+            .fold(120.0, f64::max);
+        let x = |index: usize| MARGIN + column * (index as f64 + 0.5);
+        let width = column * self.participants.len() as f64 + 2.0 * MARGIN;
+        let height = MARGIN * 2.0 + HEADER + STEP * (self.messages.len() as f64 + 0.5);
+
+        let mut body = String::new();
+        for (index, (_, name)) in self.participants.iter().enumerate() {
+            let box_width = column - 20.0;
+            body.push_str(&format!(
+                "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"{STROKE}\" stroke-dasharray=\"4,4\"/>",
+                MARGIN + HEADER,
+                height - MARGIN,
+                x = x(index),
+            ));
+            body.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{MARGIN}\" width=\"{box_width:.1}\" height=\"{HEADER}\" rx=\"4\" fill=\"{FILL}\" stroke=\"{STROKE}\" stroke-width=\"1.5\"/>",
+                x(index) - box_width / 2.0,
+            ));
+            body.push_str(&text_element(x(index), MARGIN + HEADER / 2.0, name, false));
+        }
+
+        for (step, message) in self.messages.iter().enumerate() {
+            let y = MARGIN + HEADER + STEP * (step as f64 + 1.0);
+            let dash = if message.dashed { " stroke-dasharray=\"5,4\"" } else { "" };
+            if message.from == message.to {
+                let left = x(message.from);
+                body.push_str(&format!(
+                    "<path d=\"M{left:.1},{:.1} h30 v16 h-30\" fill=\"none\" stroke=\"{STROKE}\" stroke-width=\"1.5\"{dash} marker-end=\"url(#mermaid-arrow)\"/>",
+                    y - 8.0,
+                ));
+                body.push_str(&text_element(left + column / 4.0, y - 16.0, &message.text, true));
+            } else {
+                body.push_str(&format!(
+                    "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" stroke=\"{STROKE}\" stroke-width=\"1.5\"{dash} marker-end=\"url(#mermaid-arrow)\"/>",
+                    x(message.from),
+                    x(message.to),
+                ));
+                body.push_str(&text_element((x(message.from) + x(message.to)) / 2.0, y - 10.0, &message.text, true));
+            }
+        }
+
+        svg_document(width, height, &body)
+    }
+}
+
+/// 把图表源码显示在 SVG 中（不支持的图表类型）
+fn source_svg(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let width = lines.iter().map(|line| text_width(line)).fold(200.0, f64::max) + 40.0;
+    let height = lines.len() as f64 * FONT_SIZE * 1.4 + 40.0;
+
+    let mut body = format!(
+        "<rect x=\"1\" y=\"1\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{FILL}\" stroke=\"#999\" stroke-dasharray=\"4,4\"/>",
+        width - 2.0,
+        height - 2.0
+    );
+    body.push_str(&format!("<text x=\"20\" y=\"20\" font-family=\"monospace\" font-size=\"{FONT_SIZE}\" fill=\"#333\">"));
+    for line in &lines {
+        body.push_str(&format!("<tspan x=\"20\" dy=\"{:.1}\">{}</tspan>", FONT_SIZE * 1.4, html_escape(line)));
+    }
+    body.push_str("</text>");
+
+    svg_document(width, height, &body)
+}
+
+/// 居中的文字，`halo` 时加上背景色描边，避免与线条重叠难以阅读
+fn text_element(x: f64, y: f64, text: &str, halo: bool) -> String {
+    format!(
+        "<text x=\"{x:.1}\" y=\"{y:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" font-size=\"{FONT_SIZE}\" fill=\"#333\"{}>{}</text>",
+        if halo { " stroke=\"#fff\" stroke-width=\"3\" paint-order=\"stroke\"" } else { "" },
+        html_escape(text)
+    )
+}
+
+/// 包装为完整的 SVG
+fn svg_document(width: f64, height: f64, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"mermaid\" width=\"{width:.0}\" height=\"{height:.0}\" \
+         viewBox=\"0 0 {width:.0} {height:.0}\" font-family=\"-apple-system, 'Segoe UI', 'Helvetica Neue', Arial, sans-serif\">\
+         <defs><marker id=\"mermaid-arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" \
+         orient=\"auto-start-reverse\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"{STROKE}\"/></marker></defs>{body}</svg>"
+    )
+}
+
+/// 估算文字宽度（像素）
+fn text_width(text: &str) -> f64 {
+    text.chars().map(|c| if c.is_ascii() { 0.6 } else { 1.0 }).sum::<f64>() * FONT_SIZE
+}
+
 /// HTML 转义函数
 fn html_escape(text: &str) -> String {
     text.replace('&', "&amp;")
@@ -131,54 +654,43 @@ graph TD
     B -->|No| D[Not OK]
 "#;
         let result = MermaidRenderer::render_flowchart(mermaid);
-        assert!(result.contains("Mermaid 图表占位符"));
+        assert!(result.contains("<svg"));
     }
 
     #[test]
-    fn test_contains_mermaid() {
-        assert!(MermaidRenderer::contains_mermaid("```mermaid\ngraph TD\n```"));
-        assert!(MermaidRenderer::contains_mermaid("```graph\nA-->B\n```"));
-        assert!(!MermaidRenderer::contains_mermaid("no mermaid here"));
-    }
+    fn test_parse_flowchart() {
+        let chart = Flowchart::parse("graph LR\n  A[开始] --> B{判断} -- 是 --> C((结束))\n  B -.-> A; D").unwrap();
+        assert_eq!(chart.direction, "LR");
+        let labels: Vec<&str> = chart.nodes.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(labels, ["开始", "判断", "结束", "D"]);
+        assert_eq!(chart.nodes[1].shape, Shape::Diamond);
+        assert_eq!(chart.nodes[2].shape, Shape::Circle);
 
-    #[test]
-    fn test_extract_mermaid() {
-        let text = r#"
-# Document
+        assert_eq!(chart.edges.len(), 3);
+        assert_eq!(chart.edges[1].label.as_deref(), Some("是"));
+        assert!(chart.edges[2].dashed);
+        assert_eq!(chart.ranks(), [0, 1, 2, 0]);
 
-```mermaid
-graph TD
-    A --> B
-```
-
-Some text
-
-```mermaid
-sequenceDiagram
-    A->>B: Message
-```
-"#;
-        let diagrams = MermaidRenderer::extract_mermaid(text);
-        assert_eq!(diagrams.len(), 2);
-        assert!(matches!(diagrams[0].1, DiagramType::Flowchart));
-        assert!(matches!(diagrams[1].1, DiagramType::SequenceDiagram));
+        let svg = chart.to_svg();
+        assert!(svg.contains(">判断</text>"));
+        assert!(svg.contains("<polygon"));
+        assert!(svg.contains("<ellipse"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_render_sequence() {
+        let svg = MermaidRenderer::render_sequence("sequenceDiagram\n  participant A as Alice\n  A->>B: Hello <b>\n  B-->>A: Hi");
+        assert!(svg.contains(">Alice</text>"));
+        assert!(svg.contains(">B</text>"));
+        assert!(svg.contains("Hello &lt;b&gt;"));
+        assert!(svg.contains("stroke-dasharray=\"5,4\""));
+    }
 
     #[test]
-    fn test_render_flowchart() {
-        let mermaid = r#"
-graph TD
-    A[Start] --> B{Is it?}
-    B -->|Yes| C[OK]
-    B -->|No| D[Not OK]
-"#;
-        let result = MermaidRenderer::render_flowchart(mermaid);
-        assert!(result.contains("<svg"));
+    fn test_unsupported_diagram_shows_source() {
+        let svg = MermaidRenderer::render_gantt("gantt\n  title A & B");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("title A &amp; B"));
     }
 
     #[test]
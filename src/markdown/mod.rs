@@ -5,12 +5,28 @@
 //! - 标题、列表、引用、代码块等基础语法
 //! - LaTeX 公式渲染
 //! - Mermaid 流程图渲染
+//! - 链接与图片引用提取
+//! - HTML 转换为 Markdown（导入和粘贴）
+//! - 预览中的图片
+//! - Wiki 链接（`[[笔记]]`）
 
 mod parser;
 mod latex_renderer;
 mod mermaid_renderer;
+mod links;
+mod writer;
+mod html_converter;
+mod assets;
+mod images;
+mod wiki_links;
 
 pub use parser::*;
 pub use latex_renderer::*;
 pub use mermaid_renderer::*;
+pub use links::*;
+pub use writer::*;
+pub use html_converter::*;
+pub use assets::*;
+pub use images::*;
+pub use wiki_links::*;
 
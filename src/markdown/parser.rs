@@ -4,6 +4,62 @@
 
 use pulldown_cmark::{Parser, Options, html};
 
+/// 生成 HTML 时使用的样式
+///
+/// 颜色和字体均为 CSS 取值
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlStyle {
+    /// 正文字体
+    pub font_family: String,
+    /// 字号（像素）
+    pub font_size: f64,
+    /// 行高（字号的倍数）
+    pub line_height: f64,
+    /// 正文颜色
+    pub text_color: String,
+    /// 背景颜色
+    pub background_color: String,
+    /// 标题颜色
+    pub heading_color: String,
+    /// 标题字体（`None` 时与正文相同）
+    pub heading_font_family: Option<String>,
+    /// 链接颜色
+    pub link_color: String,
+    /// 代码背景颜色
+    pub code_background: String,
+    /// 代码字体
+    pub code_font_family: String,
+    /// 引用竖线颜色
+    pub quote_border_color: String,
+    /// 引用文字颜色
+    pub quote_color: String,
+    /// 表格和分隔线颜色
+    pub border_color: String,
+    /// 附加在内置样式之后的 CSS（如用户 CSS）
+    pub extra_css: String,
+}
+
+impl Default for HtmlStyle {
+    fn default() -> Self {
+        Self {
+            font_family: "-apple-system, BlinkMacSystemFont, 'Segoe UI', 'Roboto', 'Helvetica Neue', Arial, sans-serif".to_string(),
+            font_size: 16.0,
+            line_height: 1.6,
+            text_color: "#333".to_string(),
+            background_color: "transparent".to_string(),
+            heading_color: "inherit".to_string(),
+            heading_font_family: None,
+            link_color: "#0066cc".to_string(),
+            code_background: "#f5f5f5".to_string(),
+            code_font_family: "'Courier New', monospace".to_string(),
+            quote_border_color: "#ddd".to_string(),
+            quote_color: "#666".to_string(),
+            border_color: "#ddd".to_string(),
+            extra_css: String::new(),
+        }
+    }
+}
+
 /// Markdown 解析器
 pub struct MarkdownParser;
 
@@ -38,19 +94,31 @@ impl MarkdownParser {
     /// 
     /// 这个方法会添加基本的 HTML 结构，包括样式
     pub fn parse_with_styles(markdown: &str) -> String {
-        let html_content = Self::parse_to_html(markdown);
-        
+        Self::parse_with_style(markdown, &HtmlStyle::default())
+    }
+
+    /// 使用指定的排版样式解析 Markdown 文本，返回带样式的 HTML 片段
+    pub fn parse_with_style(markdown: &str, style: &HtmlStyle) -> String {
+        Self::wrap_with_style(&Self::parse_to_html(markdown), style)
+    }
+
+    /// 为已生成的 HTML 加上排版样式
+    pub fn wrap_with_style(html_content: &str, style: &HtmlStyle) -> String {
         // 添加基本的样式和结构
         format!(
             r#"
             <div class="markdown-preview" style="
-                font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', 'Roboto', 'Helvetica Neue', Arial, sans-serif;
-                line-height: 1.6;
-                color: #333;
+                font-family: {};
+                font-size: {}px;
+                line-height: {};
+                color: {};
+                background-color: {};
                 padding: 20px;
                 max-width: 100%;
             ">
                 <style>
+                    .markdown-preview h1, .markdown-preview h2, .markdown-preview h3,
+                    .markdown-preview h4, .markdown-preview h5, .markdown-preview h6 {{ color: {}; font-family: {}; }}
                     .markdown-preview h1 {{ font-size: 2em; font-weight: bold; margin-top: 0.67em; margin-bottom: 0.67em; }}
                     .markdown-preview h2 {{ font-size: 1.5em; font-weight: bold; margin-top: 0.83em; margin-bottom: 0.83em; }}
                     .markdown-preview h3 {{ font-size: 1.17em; font-weight: bold; margin-top: 1em; margin-bottom: 1em; }}
@@ -61,22 +129,22 @@ impl MarkdownParser {
                     .markdown-preview ul, .markdown-preview ol {{ margin-top: 1em; margin-bottom: 1em; padding-left: 2em; }}
                     .markdown-preview li {{ margin-top: 0.5em; margin-bottom: 0.5em; }}
                     .markdown-preview blockquote {{ 
-                        border-left: 4px solid #ddd; 
+                        border-left: 4px solid {}; 
                         padding-left: 1em; 
                         margin-left: 0; 
-                        color: #666; 
+                        color: {}; 
                         margin-top: 1em; 
                         margin-bottom: 1em;
                     }}
                     .markdown-preview code {{
-                        background-color: #f5f5f5;
+                        background-color: {};
                         padding: 2px 4px;
                         border-radius: 3px;
-                        font-family: 'Courier New', monospace;
+                        font-family: {};
                         font-size: 0.9em;
                     }}
                     .markdown-preview pre {{
-                        background-color: #f5f5f5;
+                        background-color: {};
                         padding: 1em;
                         border-radius: 4px;
                         overflow-x: auto;
@@ -89,17 +157,36 @@ impl MarkdownParser {
                     }}
                     .markdown-preview strong {{ font-weight: bold; }}
                     .markdown-preview em {{ font-style: italic; }}
-                    .markdown-preview a {{ color: #0066cc; text-decoration: none; }}
+                    .markdown-preview a {{ color: {}; text-decoration: none; }}
                     .markdown-preview a:hover {{ text-decoration: underline; }}
                     .markdown-preview hr {{
                         border: none;
-                        border-top: 1px solid #ddd;
+                        border-top: 1px solid {};
                         margin: 2em 0;
                     }}
+                    .markdown-preview table {{ border-collapse: collapse; }}
+                    .markdown-preview th, .markdown-preview td {{ border: 1px solid {}; padding: 6px 12px; }}
+                    {}
                 </style>
                 {}
             </div>
             "#,
+            style.font_family,
+            style.font_size,
+            style.line_height,
+            style.text_color,
+            style.background_color,
+            style.heading_color,
+            style.heading_font_family.as_deref().unwrap_or("inherit"),
+            style.quote_border_color,
+            style.quote_color,
+            style.code_background,
+            style.code_font_family,
+            style.code_background,
+            style.link_color,
+            style.border_color,
+            style.border_color,
+            style.extra_css,
             html_content
         )
     }
@@ -133,5 +220,24 @@ mod tests {
         assert!(html.contains("item 1"));
         assert!(html.contains("item 2"));
     }
+
+    #[test]
+    fn test_parse_with_style() {
+        let style = HtmlStyle {
+            font_family: "Georgia".to_string(),
+            font_size: 18.0,
+            line_height: 1.8,
+            link_color: "#ff0000".to_string(),
+            extra_css: ".markdown-preview h1 { color: red; }".to_string(),
+            ..Default::default()
+        };
+        let html = MarkdownParser::parse_with_style("# Title", &style);
+        assert!(html.contains("font-family: Georgia;"));
+        assert!(html.contains("font-size: 18px;"));
+        assert!(html.contains("line-height: 1.8;"));
+        assert!(html.contains(".markdown-preview a { color: #ff0000;"));
+        assert!(html.contains(".markdown-preview h1 { color: red; }"));
+        assert!(html.contains("<h1>Title</h1>"));
+    }
 }
 
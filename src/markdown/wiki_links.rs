@@ -0,0 +1,135 @@
+//! Wiki 链接模块
+//!
+//! 查找 `[[笔记]]` 形式的链接：
+//! - `[[笔记名]]` 指向工作区中同名的笔记，也可以写成 `[[文件夹/笔记名]]`
+//! - `[[笔记名#标题]]` 指向笔记中的标题，`[[#标题]]` 指向当前文档中的标题
+//! - `[[笔记名|显示文字]]` 指定显示的文字
+//! - 忽略代码中的链接和嵌入（`![[...]]`）
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+/// 文字中的一个 wiki 链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    /// 整个链接（包括 `[[` 和 `]]`）在文字中的字节范围
+    pub range: Range<usize>,
+    /// 显示文字的字节范围：有 `|显示文字` 时为显示文字，否则为括号中的全部内容
+    pub label: Range<usize>,
+    /// 笔记名，只有标题时为空
+    pub note: String,
+    /// 标题（标题文字，不是锚点）
+    pub heading: Option<String>,
+}
+
+/// 查找文字中的 wiki 链接，按位置排列
+pub fn find_wiki_links(text: &str) -> Vec<WikiLink> {
+    if !text.contains("[[") {
+        return Vec::new();
+    }
+
+    let code = code_ranges(text);
+    let mut links = Vec::new();
+    let mut position = 0;
+    while let Some(start) = text[position..].find("[[").map(|offset| position + offset) {
+        position = start + 2;
+        if text[..start].ends_with('!') || code.iter().any(|range| range.contains(&start)) {
+            continue;
+        }
+        let Some(length) = text[position..].find("]]") else {
+            break;
+        };
+        let inner = position..position + length;
+        if text[inner.clone()].trim().is_empty() || text[inner.clone()].contains(['[', ']', '\n']) {
+            continue;
+        }
+
+        let (target, label) = match text[inner.clone()].find('|') {
+            Some(pipe) => (inner.start..inner.start + pipe, trim_range(text, inner.start + pipe + 1..inner.end)),
+            None => (inner.clone(), trim_range(text, inner.clone())),
+        };
+        let target = &text[target];
+        let (note, heading) = match target.split_once('#') {
+            Some((note, heading)) => (note, Some(heading.trim().to_string()).filter(|heading| !heading.is_empty())),
+            None => (target, None),
+        };
+        if note.trim().is_empty() && heading.is_none() {
+            continue;
+        }
+
+        position = inner.end + 2;
+        links.push(WikiLink {
+            range: start..position,
+            label: if label.is_empty() { trim_range(text, inner) } else { label },
+            note: note.trim().to_string(),
+            heading,
+        });
+    }
+
+    links
+}
+
+/// 代码（行内代码、代码块）和 HTML 在文字中的字节范围
+fn code_ranges(text: &str) -> Vec<Range<usize>> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_TASKLISTS;
+    let mut ranges = Vec::new();
+    let mut events = Parser::new_ext(text, options).into_offset_iter();
+    while let Some((event, range)) = events.next() {
+        match event {
+            // 开始事件的范围包括整个代码块
+            Event::Start(Tag::CodeBlock(_)) => {
+                ranges.push(range);
+                for (event, _) in events.by_ref() {
+                    if let Event::End(TagEnd::CodeBlock) = event {
+                        break;
+                    }
+                }
+            }
+            Event::Code(_) | Event::Html(_) | Event::InlineHtml(_) => ranges.push(range),
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// 去掉范围两端的空白
+fn trim_range(text: &str, range: Range<usize>) -> Range<usize> {
+    let part = &text[range.clone()];
+    let start = range.start + (part.len() - part.trim_start().len());
+    let end = range.end - (part.len() - part.trim_end().len());
+    start..end.max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_wiki_links() {
+        let text = "见 [[项目计划]]、[[笔记/会议 #议程]] 和 [[ 周报 | 本周 ]]，[[#结论]]";
+        let links = find_wiki_links(text);
+        let summary: Vec<(&str, &str, &str, Option<&str>)> = links
+            .iter()
+            .map(|link| {
+                (&text[link.range.clone()], &text[link.label.clone()], link.note.as_str(), link.heading.as_deref())
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("[[项目计划]]", "项目计划", "项目计划", None),
+                ("[[笔记/会议 #议程]]", "笔记/会议 #议程", "笔记/会议", Some("议程")),
+                ("[[ 周报 | 本周 ]]", "本周", "周报", None),
+                ("[[#结论]]", "#结论", "", Some("结论")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignored_wiki_links() {
+        let text = "`[[代码]]` ![[图片.png]] [[]] [[a\nb]] [[未闭合\n\n```\n[[代码块]]\n```\n";
+        assert!(find_wiki_links(text).is_empty());
+        assert!(find_wiki_links("普通文字").is_empty());
+    }
+}
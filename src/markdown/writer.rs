@@ -0,0 +1,707 @@
+//! Markdown 生成
+//!
+//! HTML 和 Word 文档先转换为这里的文档结构，再统一生成 Markdown：
+//! - 合并相邻的相同格式，去掉没有内容的格式
+//! - 转义文字中在 Markdown 里有特殊含义的字符
+//! - 列表、引用和脚注中的内容按层级缩进
+
+use std::collections::HashMap;
+
+use super::heading_slug;
+
+/// 行内内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Strong(Vec<Inline>),
+    Emphasis(Vec<Inline>),
+    Strikethrough(Vec<Inline>),
+    Code(String),
+    Link { url: String, title: String, content: Vec<Inline> },
+    Image { url: String, alt: String, title: String },
+    LineBreak,
+    /// 脚注引用，内容为脚注标签
+    FootnoteReference(String),
+}
+
+impl Inline {
+    /// 生成单个行内内容的 Markdown，如插入图片时的 `![alt](assets/a.png)`
+    pub fn to_markdown(&self) -> String {
+        write_inlines(std::slice::from_ref(self))
+    }
+}
+
+/// 表格列的对齐方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// 列表项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListItem {
+    /// 任务列表项是否完成，不是任务列表项时为 `None`
+    pub checked: Option<bool>,
+    pub blocks: Vec<Block>,
+}
+
+/// 块级内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    Quote(Vec<Block>),
+    /// 列表，有序列表带起始编号
+    List { start: Option<u64>, items: Vec<ListItem> },
+    Code { language: String, code: String },
+    /// 表格，第一行为表头
+    Table { alignments: Vec<Align>, rows: Vec<Vec<Vec<Inline>>> },
+    Rule,
+}
+
+/// 导入的文档
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+    /// 脚注标签和内容
+    pub footnotes: Vec<(String, Vec<Block>)>,
+}
+
+/// 按层级排列的列表段落，用于还原嵌套列表
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListEntry {
+    /// 所属列表的编号，编号不同的列表项属于不同的列表
+    pub list: String,
+    pub level: usize,
+    /// 有序列表的起始编号，无序列表为 `None`
+    pub start: Option<u64>,
+    pub checked: Option<bool>,
+    pub content: Vec<Inline>,
+}
+
+impl Document {
+    /// 生成 Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = write_blocks(&self.blocks, false);
+        for (label, blocks) in &self.footnotes {
+            let content = write_blocks(blocks, false);
+            if !markdown.is_empty() {
+                markdown.push_str("\n\n");
+            }
+            markdown.push_str(&format!("[^{}]: {}", label, indent(&content, "    ", false)));
+        }
+        if !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// 把指向原文档中标题的链接改为指向 Markdown 标题的锚点
+    ///
+    /// `heading_ids` 按顺序列出正文中每个标题在原文档中的锚点
+    pub fn link_headings(&mut self, heading_ids: &[Vec<String>]) {
+        let mut anchors = HashMap::new();
+        for (ids, anchor) in heading_ids.iter().zip(self.heading_anchors()) {
+            for id in ids {
+                anchors.insert(id.clone(), anchor.clone());
+            }
+        }
+        if !anchors.is_empty() {
+            self.rewrite_anchors(&anchors);
+        }
+    }
+
+    /// 正文中每个标题的锚点，重复的锚点加上编号，与 HTML 导出相同
+    fn heading_anchors(&self) -> Vec<String> {
+        fn collect(blocks: &[Block], counts: &mut HashMap<String, usize>, anchors: &mut Vec<String>) {
+            for block in blocks {
+                match block {
+                    Block::Heading(_, content) => {
+                        let slug = heading_slug(&plain_text(content));
+                        let count = counts.entry(slug.clone()).or_insert(0);
+                        anchors.push(if *count == 0 { slug } else { format!("{}-{}", slug, count) });
+                        *count += 1;
+                    }
+                    Block::Quote(children) => collect(children, counts, anchors),
+                    Block::List { items, .. } => {
+                        for item in items {
+                            collect(&item.blocks, counts, anchors);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut anchors = Vec::new();
+        collect(&self.blocks, &mut HashMap::new(), &mut anchors);
+        anchors
+    }
+
+    /// 把指向原文档中锚点的链接改为指向 Markdown 标题的锚点
+    fn rewrite_anchors(&mut self, anchors: &HashMap<String, String>) {
+        fn inlines(nodes: &mut [Inline], anchors: &HashMap<String, String>) {
+            for node in nodes {
+                match node {
+                    Inline::Link { url, content, .. } => {
+                        if let Some(anchor) = url.strip_prefix('#').and_then(|anchor| anchors.get(anchor)) {
+                            *url = format!("#{}", anchor);
+                        }
+                        inlines(content, anchors);
+                    }
+                    Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children) => {
+                        inlines(children, anchors)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        fn blocks(nodes: &mut [Block], anchors: &HashMap<String, String>) {
+            for node in nodes {
+                match node {
+                    Block::Heading(_, content) | Block::Paragraph(content) => inlines(content, anchors),
+                    Block::Quote(children) => blocks(children, anchors),
+                    Block::List { items, .. } => {
+                        for item in items {
+                            blocks(&mut item.blocks, anchors);
+                        }
+                    }
+                    Block::Table { rows, .. } => {
+                        for cell in rows.iter_mut().flatten() {
+                            inlines(cell, anchors);
+                        }
+                    }
+                    Block::Code { .. } | Block::Rule => {}
+                }
+            }
+        }
+
+        blocks(&mut self.blocks, anchors);
+        for (_, footnote) in &mut self.footnotes {
+            blocks(footnote, anchors);
+        }
+    }
+}
+
+/// 把按层级排列的列表段落还原为嵌套列表
+///
+/// 层级更深的段落成为上一个列表项中的子列表；同一层级中不同的列表分开
+pub fn build_lists(entries: Vec<ListEntry>) -> Vec<Block> {
+    fn list(entries: &[ListEntry], index: &mut usize, level: usize) -> Block {
+        let start = entries[*index].start;
+        let id = &entries[*index].list;
+        let mut items: Vec<ListItem> = Vec::new();
+        while let Some(entry) = entries.get(*index) {
+            if entry.level < level {
+                break;
+            }
+            if entry.level > level {
+                let nested = list(entries, index, entry.level);
+                if items.is_empty() {
+                    items.push(ListItem::default());
+                }
+                if let Some(item) = items.last_mut() {
+                    item.blocks.push(nested);
+                }
+                continue;
+            }
+            if entry.start.is_some() != start.is_some() || entry.list != *id {
+                break;
+            }
+            items.push(ListItem {
+                checked: entry.checked,
+                blocks: vec![Block::Paragraph(entry.content.clone())],
+            });
+            *index += 1;
+        }
+        Block::List { start, items }
+    }
+
+    let mut blocks = Vec::new();
+    let mut index = 0;
+    while index < entries.len() {
+        let level = entries[index].level;
+        blocks.push(list(&entries, &mut index, level));
+    }
+    blocks
+}
+
+/// 行内内容中的文字，不含格式
+pub fn plain_text(nodes: &[Inline]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Inline::Text(content) | Inline::Code(content) => text.push_str(content),
+            Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children) => {
+                text.push_str(&plain_text(children))
+            }
+            Inline::Link { content, .. } => text.push_str(&plain_text(content)),
+            Inline::Image { alt, .. } => text.push_str(alt),
+            Inline::LineBreak => text.push(' '),
+            Inline::FootnoteReference(_) => {}
+        }
+    }
+    text
+}
+
+/// 把块级内容合并为行内内容（如表格单元格），段落之间换行
+pub fn flatten(blocks: Vec<Block>) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for block in blocks {
+        if !inlines.is_empty() {
+            inlines.push(Inline::LineBreak);
+        }
+        match block {
+            Block::Heading(_, content) | Block::Paragraph(content) => inlines.extend(content),
+            Block::Quote(children) => inlines.extend(flatten(children)),
+            Block::List { items, .. } => {
+                let items: Vec<Inline> = items
+                    .into_iter()
+                    .flat_map(|item| {
+                        let mut content = vec![Inline::LineBreak, Inline::Text("• ".to_string())];
+                        content.extend(flatten(item.blocks));
+                        content
+                    })
+                    .skip(1)
+                    .collect();
+                inlines.extend(items);
+            }
+            Block::Code { code, .. } => inlines.push(Inline::Code(code)),
+            Block::Table { rows, .. } => {
+                for cell in rows.into_iter().flatten() {
+                    inlines.push(Inline::Text(" ".to_string()));
+                    inlines.extend(cell);
+                }
+            }
+            Block::Rule => {}
+        }
+    }
+    inlines
+}
+
+/// 生成多个块，块之间空一行（紧凑列表项中不空行）
+fn write_blocks(blocks: &[Block], tight: bool) -> String {
+    let parts: Vec<String> = blocks.iter().map(write_block).filter(|part| !part.is_empty()).collect();
+    parts.join(if tight { "\n" } else { "\n\n" })
+}
+
+fn write_block(block: &Block) -> String {
+    match block {
+        Block::Heading(level, content) => {
+            let text = write_inlines(content).replace('\n', " ");
+            let text = text.trim();
+            if text.is_empty() {
+                String::new()
+            } else {
+                format!("{} {}", "#".repeat((*level).clamp(1, 6) as usize), text)
+            }
+        }
+        Block::Paragraph(content) => {
+            let text = write_inlines(content);
+            text.trim().lines().map(|line| escape_line_start(line.trim_start())).collect::<Vec<_>>().join("\n")
+        }
+        Block::Quote(blocks) => {
+            let content = write_blocks(blocks, false);
+            if content.is_empty() {
+                return String::new();
+            }
+            content
+                .lines()
+                .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Block::List { start, items } => {
+            let tight = items.iter().all(|item| {
+                matches!(item.blocks.as_slice(), [] | [Block::Paragraph(_)] | [Block::Paragraph(_), Block::List { .. }])
+            });
+            let items: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let marker = match start {
+                        Some(start) => format!("{}. ", start + index as u64),
+                        None => "- ".to_string(),
+                    };
+                    let task = match item.checked {
+                        Some(true) => "[x] ",
+                        Some(false) => "[ ] ",
+                        None => "",
+                    };
+                    let content = write_blocks(&item.blocks, tight);
+                    let padding = " ".repeat(marker.len());
+                    format!("{}{}{}", marker, task, indent(&content, &padding, false)).trim_end().to_string()
+                })
+                .collect();
+            items.join(if tight { "\n" } else { "\n\n" })
+        }
+        Block::Code { language, code } => {
+            let fence = "`".repeat(longest_run(code, '`').max(2) + 1);
+            format!("{}{}\n{}\n{}", fence, language, code.trim_end_matches('\n'), fence)
+        }
+        Block::Table { alignments, rows } => {
+            let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+            if columns == 0 {
+                return String::new();
+            }
+            let row = |cells: &[Vec<Inline>]| {
+                let cells: Vec<String> = (0..columns)
+                    .map(|column| {
+                        cells
+                            .get(column)
+                            .map(|cell| {
+                                write_inlines_with(cell, "<br>").trim().replace('\n', "<br>").replace('|', "\\|")
+                            })
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                format!("| {} |", cells.join(" | "))
+            };
+            let delimiter: Vec<&str> = (0..columns)
+                .map(|column| match alignments.get(column).copied().unwrap_or_default() {
+                    Align::None => "---",
+                    Align::Left => ":---",
+                    Align::Center => ":---:",
+                    Align::Right => "---:",
+                })
+                .collect();
+            // 表头本来就以粗体显示，去掉整个单元格的粗体
+            let header: Vec<Vec<Inline>> = rows[0]
+                .iter()
+                .map(|cell| match cell.as_slice() {
+                    [Inline::Strong(content)] => content.clone(),
+                    _ => cell.clone(),
+                })
+                .collect();
+            let mut lines = vec![row(&header), format!("| {} |", delimiter.join(" | "))];
+            lines.extend(rows[1..].iter().map(|cells| row(cells)));
+            lines.join("\n")
+        }
+        Block::Rule => "---".to_string(),
+    }
+}
+
+/// 生成行内内容
+fn write_inlines(nodes: &[Inline]) -> String {
+    write_inlines_with(nodes, "\\\n")
+}
+
+/// 生成行内内容，硬换行写为 `line_break`（表格单元格中不能换行，使用 `<br>`）
+fn write_inlines_with(nodes: &[Inline], line_break: &str) -> String {
+    let mut text = String::new();
+    for node in normalize(nodes.to_vec()) {
+        match node {
+            Inline::Text(content) => text.push_str(&escape(&content)),
+            Inline::Strong(children) => text.push_str(&wrap(&write_inlines_with(&children, line_break), "**")),
+            Inline::Emphasis(children) => text.push_str(&wrap(&write_inlines_with(&children, line_break), "*")),
+            Inline::Strikethrough(children) => text.push_str(&wrap(&write_inlines_with(&children, line_break), "~~")),
+            Inline::Code(code) => {
+                let code = code.replace('\n', " ");
+                let fence = "`".repeat(longest_run(&code, '`') + 1);
+                let padding = if code.starts_with('`') || code.ends_with('`') { " " } else { "" };
+                text.push_str(&format!("{}{}{}{}{}", fence, padding, code, padding, fence));
+            }
+            Inline::Link { url, title, content } => {
+                let label = write_inlines_with(&content, line_break);
+                let label = label.trim();
+                if label.is_empty() || (label == escape(&url) && url.contains("://") && title.is_empty()) {
+                    text.push_str(&format!("<{}>", url));
+                } else {
+                    text.push_str(&format!("[{}]({}{})", label, destination(&url), link_title(&title)));
+                }
+            }
+            Inline::Image { url, alt, title } => {
+                text.push_str(&format!("![{}]({}{})", escape(&alt), destination(&url), link_title(&title)));
+            }
+            Inline::LineBreak => text.push_str(line_break),
+            Inline::FootnoteReference(label) => text.push_str(&format!("[^{}]", label)),
+        }
+    }
+    text
+}
+
+/// 合并相邻的文字和相同格式，去掉没有内容的格式
+fn normalize(nodes: Vec<Inline>) -> Vec<Inline> {
+    let mut result: Vec<Inline> = Vec::new();
+    for node in nodes {
+        let node = match node {
+            Inline::Strong(children) => Inline::Strong(unwrap_nested(normalize(children), |n| match n {
+                Inline::Strong(children) => Ok(children),
+                other => Err(other),
+            })),
+            Inline::Emphasis(children) => Inline::Emphasis(unwrap_nested(normalize(children), |n| match n {
+                Inline::Emphasis(children) => Ok(children),
+                other => Err(other),
+            })),
+            Inline::Strikethrough(children) => Inline::Strikethrough(unwrap_nested(normalize(children), |n| match n {
+                Inline::Strikethrough(children) => Ok(children),
+                other => Err(other),
+            })),
+            Inline::Link { url, title, content } => Inline::Link { url, title, content: normalize(content) },
+            other => other,
+        };
+
+        // 只有空白的格式只保留文字
+        let node = match node {
+            Inline::Strong(children) | Inline::Emphasis(children) | Inline::Strikethrough(children)
+                if children.iter().all(|child| matches!(child, Inline::Text(text) if text.trim().is_empty())) =>
+            {
+                let text: String = children
+                    .into_iter()
+                    .map(|child| match child {
+                        Inline::Text(text) => text,
+                        _ => String::new(),
+                    })
+                    .collect();
+                if text.is_empty() {
+                    continue;
+                }
+                Inline::Text(text)
+            }
+            Inline::Text(text) if text.is_empty() => continue,
+            Inline::Code(code) if code.is_empty() => continue,
+            other => other,
+        };
+
+        match (result.last_mut(), node) {
+            (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+            (Some(Inline::Code(last)), Inline::Code(code)) => last.push_str(&code),
+            (Some(Inline::Strong(last)), Inline::Strong(children))
+            | (Some(Inline::Emphasis(last)), Inline::Emphasis(children))
+            | (Some(Inline::Strikethrough(last)), Inline::Strikethrough(children)) => {
+                last.extend(children);
+                let merged = normalize(std::mem::take(last));
+                *last = merged;
+            }
+            (_, node) => result.push(node),
+        }
+    }
+    result
+}
+
+/// 去掉与外层相同的嵌套格式，如粗体中的粗体
+fn unwrap_nested(children: Vec<Inline>, unwrap: impl Fn(Inline) -> Result<Vec<Inline>, Inline>) -> Vec<Inline> {
+    children
+        .into_iter()
+        .flat_map(|child| match unwrap(child) {
+            Ok(children) => children,
+            Err(child) => vec![child],
+        })
+        .collect()
+}
+
+/// 用格式标记包围文字，首尾的空白放在标记之外
+fn wrap(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{}{}{}{}{}", leading, marker, trimmed, marker, trailing)
+}
+
+/// 转义文字中的 Markdown 标记
+///
+/// 单词中间的 `_` 不会被当作强调，不需要转义
+fn escape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut escaped = String::with_capacity(text.len());
+    for (index, &c) in chars.iter().enumerate() {
+        let needs_escape = match c {
+            '\\' | '`' | '*' | '[' | ']' | '<' => true,
+            '_' => {
+                let before = index.checked_sub(1).and_then(|i| chars.get(i)).is_some_and(|c| c.is_alphanumeric());
+                let after = chars.get(index + 1).is_some_and(|c| c.is_alphanumeric());
+                !(before && after)
+            }
+            '~' => chars.get(index + 1) == Some(&'~') || (index > 0 && chars[index - 1] == '~'),
+            _ => false,
+        };
+        if needs_escape {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 转义行首会被当作标题、引用或列表的字符
+fn escape_line_start(line: &str) -> String {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some('#' | '>') => format!("\\{}", line),
+        Some('-' | '+' | '=') if line.trim_end().chars().all(|c| c == '-' || c == '=') => format!("\\{}", line),
+        Some('-' | '+') if chars.next() == Some(' ') => format!("\\{}", line),
+        Some(c) if c.is_ascii_digit() => {
+            let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+            let rest = &line[digits..];
+            if (rest.starts_with(". ") || rest.starts_with(") ")) || rest == "." || rest == ")" {
+                format!("{}\\{}", &line[..digits], rest)
+            } else {
+                line.to_string()
+            }
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// 链接地址，包含空格或括号时用尖括号包围
+fn destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+fn link_title(title: &str) -> String {
+    if title.is_empty() {
+        String::new()
+    } else {
+        format!(" \"{}\"", title.replace('"', "\\\""))
+    }
+}
+
+/// 除第一行外的每一行加上缩进，空行不加
+fn indent(text: &str, padding: &str, first: bool) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| {
+            if line.is_empty() || (index == 0 && !first) {
+                line.to_string()
+            } else {
+                format!("{}{}", padding, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 文字中连续出现某个字符的最大次数
+fn longest_run(text: &str, c: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == c {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(content: &str) -> Inline {
+        Inline::Text(content.to_string())
+    }
+
+    fn paragraph(content: &str) -> Block {
+        Block::Paragraph(vec![text(content)])
+    }
+
+    #[test]
+    fn test_inlines() {
+        let document = Document {
+            blocks: vec![Block::Paragraph(vec![
+                Inline::Strong(vec![text("粗")]),
+                Inline::Strong(vec![text("体 ")]),
+                text("和"),
+                Inline::Emphasis(vec![Inline::Emphasis(vec![text("斜体")])]),
+                Inline::Strong(vec![text("  ")]),
+                Inline::Code("a`b".to_string()),
+                text(" "),
+                Inline::Link { url: "https://a.com/x y".to_string(), title: String::new(), content: vec![text("链接")] },
+                text(" "),
+                Inline::Link { url: "https://a.com".to_string(), title: String::new(), content: vec![text("https://a.com")] },
+                Inline::LineBreak,
+                text("snake_case *星号* [括号] _下划线_"),
+                Inline::FootnoteReference("1".to_string()),
+            ])],
+            footnotes: vec![("1".to_string(), vec![paragraph("脚注"), paragraph("第二段")])],
+        };
+        assert_eq!(
+            document.to_markdown(),
+            "**粗体** 和*斜体*  ``a`b`` [链接](<https://a.com/x y>) <https://a.com>\\\n\
+             snake_case \\*星号\\* \\[括号\\] \\_下划线\\_[^1]\n\n[^1]: 脚注\n\n    第二段\n"
+        );
+
+        let image = Inline::Image { url: "assets/图 1.png".to_string(), alt: "*图*".to_string(), title: String::new() };
+        assert_eq!(image.to_markdown(), "![\\*图\\*](<assets/图 1.png>)");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let document = Document {
+            blocks: vec![
+                Block::Heading(2, vec![text("标题")]),
+                Block::Paragraph(vec![text("# 不是标题\n1. 不是列表")]),
+                Block::Quote(vec![paragraph("引用"), paragraph("第二段")]),
+                Block::Code { language: "rust".to_string(), code: "let a = \"```\";\n".to_string() },
+                Block::Table {
+                    alignments: vec![Align::Left, Align::Right],
+                    rows: vec![
+                        vec![vec![text("a")], vec![text("b")]],
+                        vec![vec![text("1|2")], vec![text("x"), Inline::LineBreak, text("y")]],
+                    ],
+                },
+                Block::Rule,
+            ],
+            footnotes: Vec::new(),
+        };
+        assert_eq!(
+            document.to_markdown(),
+            "## 标题\n\n\\# 不是标题\n1\\. 不是列表\n\n> 引用\n>\n> 第二段\n\n\
+             ````rust\nlet a = \"```\";\n````\n\n| a | b |\n| :--- | ---: |\n| 1\\|2 | x<br>y |\n\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_build_lists() {
+        let entry = |level, start: Option<u64>, content: &str| ListEntry {
+            list: format!("{}", start.is_some()),
+            level,
+            start,
+            checked: None,
+            content: vec![text(content)],
+        };
+        let blocks = build_lists(vec![
+            entry(0, Some(3), "三"),
+            entry(1, None, "子项"),
+            entry(2, None, "孙项"),
+            entry(0, Some(3), "四"),
+            entry(0, None, "无序"),
+        ]);
+        let document = Document { blocks, footnotes: Vec::new() };
+        assert_eq!(document.to_markdown(), "3. 三\n   - 子项\n     - 孙项\n4. 四\n\n- 无序\n");
+
+        let mut task = entry(0, None, "完成");
+        task.checked = Some(true);
+        let document = Document { blocks: build_lists(vec![task]), footnotes: Vec::new() };
+        assert_eq!(document.to_markdown(), "- [x] 完成\n");
+    }
+
+    #[test]
+    fn test_link_headings() {
+        let link = |url: &str| Inline::Link { url: url.to_string(), title: String::new(), content: vec![text("跳转")] };
+        let mut document = Document {
+            blocks: vec![
+                Block::Heading(1, vec![text("简介")]),
+                Block::Heading(2, vec![text("简介")]),
+                Block::Paragraph(vec![link("#_Toc1"), text(" "), link("#_Toc2"), text(" "), link("#other")]),
+            ],
+            footnotes: Vec::new(),
+        };
+        document.link_headings(&[vec!["_Toc1".to_string()], vec!["_Toc2".to_string(), "second".to_string()]]);
+        assert_eq!(document.to_markdown(), "# 简介\n\n## 简介\n\n[跳转](#简介) [跳转](#简介-1) [跳转](#other)\n");
+    }
+}
@@ -2,10 +2,39 @@
 //! 
 //! 使用自定义的 Markdown 渲染器渲染预览内容
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
 use gpui::*;
-use crate::markdown::{LatexRenderer, MermaidRenderer};
+use crate::markdown::{
+    find_inline_links, find_wiki_links, locate_image, split_images, DiagramType, ImageLength, ImageLocation,
+    LatexRenderer, LinePart, MermaidRenderer, PreviewImage, WikiLink,
+};
+use crate::file_manager::resolve_wiki_link;
+use crate::config::{css_color, PreviewColors, PreviewStyle};
 use crate::editor::SyntaxHighlighter;
 
+/// 预览中高亮的搜索匹配
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewHighlight {
+    /// 行号（从1开始）
+    pub line_number: usize,
+    /// 匹配在该行中的字节范围
+    pub range: Range<usize>,
+    /// 是否为当前选中的匹配
+    pub current: bool,
+}
+
+/// 预览发出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewEvent {
+    /// 点击了链接，内容为链接目标
+    OpenLink(String),
+    /// 点击了 wiki 链接（`[[笔记]]`）
+    OpenWikiLink(WikiLink),
+}
+
 /// Markdown 预览器
 /// 
 /// 负责渲染解析后的 Markdown 内容
@@ -14,6 +43,22 @@ pub struct MarkdownPreview {
     markdown_content: SharedString,
     /// 语法高亮器
     syntax_highlighter: SyntaxHighlighter,
+    /// 搜索匹配高亮
+    highlights: Vec<PreviewHighlight>,
+    /// 预览区的滚动状态
+    scroll_handle: ScrollHandle,
+    /// 预览样式（颜色、字体、字号、行高）
+    style: PreviewStyle,
+    /// 图片相对路径的起点（当前文档所在的文件夹）
+    base_dir: PathBuf,
+    /// 工作区中的 Markdown 文件，用于区分 wiki 链接指向的笔记是否存在
+    notes: Arc<Vec<PathBuf>>,
+    /// 点击后放大显示的图片
+    zoomed: Option<ImageSource>,
+    /// 源码每一行对应的预览元素序号，用于滚动到指定行
+    line_items: Vec<usize>,
+    /// 下次渲染后需要滚动到的行（从1开始）
+    pending_line: Option<usize>,
 }
 
 impl MarkdownPreview {
@@ -22,9 +67,56 @@ impl MarkdownPreview {
         Self {
             markdown_content: SharedString::default(),
             syntax_highlighter: SyntaxHighlighter::new(),
+            highlights: Vec::new(),
+            scroll_handle: ScrollHandle::new(),
+            style: PreviewStyle::default(),
+            base_dir: PathBuf::new(),
+            notes: Arc::default(),
+            zoomed: None,
+            line_items: Vec::new(),
+            pending_line: None,
         }
     }
 
+    /// 设置图片相对路径的起点
+    pub fn set_base_dir(&mut self, base_dir: PathBuf) {
+        self.base_dir = base_dir;
+        self.zoomed = None;
+    }
+
+    /// 设置工作区中的 Markdown 文件
+    pub fn set_notes(&mut self, notes: Vec<PathBuf>) {
+        self.notes = Arc::new(notes);
+    }
+
+    /// 设置预览样式和代码高亮主题
+    pub fn set_style(&mut self, style: PreviewStyle, code_theme: &str) {
+        self.style = style;
+        self.syntax_highlighter.set_theme(code_theme);
+    }
+
+    /// 当前向下滚动的距离（像素）
+    pub fn scroll_offset(&self) -> f32 {
+        -f32::from(self.scroll_handle.offset().y)
+    }
+
+    /// 滚动到指定位置（像素）
+    pub fn set_scroll_offset(&mut self, offset: f32) {
+        self.scroll_handle.set_offset(point(px(0.0), px(-offset.max(0.0))));
+    }
+
+    /// 滚动到源码中的指定行（从1开始），如链接指向的标题
+    ///
+    /// 预览内容可能还没有按最新的文档渲染，滚动在下次渲染时进行
+    pub fn scroll_to_line(&mut self, line_number: usize) {
+        self.pending_line = Some(line_number);
+    }
+
+    /// 设置搜索匹配高亮
+    pub fn set_highlights(&mut self, highlights: Vec<PreviewHighlight>) {
+        self.highlights = highlights;
+    }
+
     /// 更新预览内容
     /// 
     /// # 参数
@@ -34,63 +126,229 @@ impl MarkdownPreview {
     }
 }
 
+impl EventEmitter<PreviewEvent> for MarkdownPreview {}
+
 impl Render for MarkdownPreview {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        let content = self.markdown_content.to_string();
-        
-        // 检查是否包含 LaTeX 公式
-        if LatexRenderer::contains_latex(&content) {
-            let rendered = LatexRenderer::render(&content);
-            return div().text_sm().p_4().child(rendered);
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut context = RenderContext {
+            base_dir: self.base_dir.clone(),
+            notes: self.notes.clone(),
+            preview: cx.entity().downgrade(),
+            count: 0,
+            line_items: Vec::new(),
+        };
+        // 每个块都是滚动区域的直接子元素，可以按序号滚动到对应的块
+        let content = self
+            .render_content(&mut context)
+            .id("markdown_preview")
+            .size_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .bg(rgb(self.style.colors.background))
+            .text_color(rgb(self.style.colors.text))
+            .font_family(self.style.font_family.clone())
+            .text_size(px(self.style.font_size as f32))
+            .line_height(relative(self.style.line_height as f32));
+
+        self.line_items = context.line_items;
+        if let Some(line_number) = self.pending_line.take() {
+            if let Some(item) = self.line_items.get(line_number.saturating_sub(1)) {
+                self.scroll_handle.scroll_to_item(*item);
+            }
         }
-        
-        // 检查是否包含 Mermaid 图表
-        if MermaidRenderer::contains_mermaid(&content) {
-            let diagrams = MermaidRenderer::extract_mermaid(&content);
-            let mut element = div().text_sm().p_4();
-            
-            for (diagram, diagram_type) in diagrams {
-                let svg = MermaidRenderer::render(&diagram, diagram_type);
-                element = element.child(
-                    div()
-                        .mb_4()
-                        .child(svg)
-                );
+
+        div()
+            .relative()
+            .size_full()
+            .child(content)
+            .children(self.zoomed.clone().map(|source| self.render_lightbox(source, cx)))
+    }
+}
+
+impl MarkdownPreview {
+    /// 放大显示图片，点击任意位置关闭
+    fn render_lightbox(&self, source: ImageSource, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .id("image_lightbox")
+            .absolute()
+            .inset_0()
+            .p_4()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(hsla(0.0, 0.0, 0.0, 0.8))
+            .cursor_pointer()
+            .on_click(cx.listener(|this, _, _window, cx| {
+                this.zoomed = None;
+                cx.notify();
+            }))
+            .child(img(source).size_full().object_fit(ObjectFit::Contain))
+    }
+
+    /// 渲染预览内容，公式和 Mermaid 图表在文档中各自的位置渲染
+    fn render_content(&self, context: &mut RenderContext) -> Div {
+        let content = self.markdown_content.to_string();
+        render_markdown_preview(&content, &self.syntax_highlighter, &self.highlights, &self.style, context)
+    }
+}
+
+/// 渲染预览时的状态
+struct RenderContext {
+    /// 图片相对路径的起点
+    base_dir: PathBuf,
+    /// 工作区中的 Markdown 文件
+    notes: Arc<Vec<PathBuf>>,
+    /// 点击图片时放大显示，点击链接时发出事件
+    preview: WeakEntity<MarkdownPreview>,
+    /// 已渲染的可点击元素数量，用于生成元素 ID
+    count: usize,
+    /// 源码每一行对应的块序号
+    line_items: Vec<usize>,
+}
+
+impl RenderContext {
+    /// 下一个可点击元素的编号
+    fn next_id(&mut self) -> usize {
+        self.count += 1;
+        self.count
+    }
+
+    /// 渲染一张图片：加载时显示占位符，无法加载时显示图片的说明和地址
+    ///
+    /// 预览不下载网络图片，网络图片显示为带说明的链接，点击后在浏览器中打开
+    fn image(&mut self, image: &PreviewImage, style: &PreviewStyle) -> AnyElement {
+        let colors = style.colors;
+        let label = if image.alt.is_empty() { image.url.clone() } else { format!("{} ({})", image.alt, image.url) };
+        let source = match locate_image(&self.base_dir, &image.url) {
+            Some(ImageLocation::Remote(url)) => return self.remote_image(&label, url, &colors),
+            location => location.and_then(image_source),
+        };
+        let Some(source) = source else {
+            return broken_image(&label, &colors);
+        };
+
+        let muted = colors.muted;
+        let loading = format!("图片加载中… {}", image.alt);
+        let mut element = img(source.clone())
+            .max_w_full()
+            .object_fit(ObjectFit::ScaleDown)
+            .with_loading(move || div().text_color(rgb(muted)).child(loading.clone()).into_any_element())
+            .with_fallback(move || broken_image(&label, &colors));
+        let mut container = div().id(("preview_image", self.next_id())).max_w_full().cursor_pointer();
+
+        match image.width {
+            Some(ImageLength::Percent(percent)) => {
+                container = container.w(relative(percent / 100.0));
+                element = element.w_full();
             }
-            
-            return element;
+            Some(ImageLength::Pixels(width)) => element = element.w(px(width)),
+            None => {}
         }
-        
-        // 使用自定义的 Markdown 渲染器
-        render_markdown_preview(&content, &self.syntax_highlighter)
+        if let Some(ImageLength::Pixels(height)) = image.height {
+            element = element.h(px(height));
+        }
+
+        let preview = self.preview.clone();
+        container
+            .on_click(move |_, _window, cx| {
+                let source = source.clone();
+                let _ = preview.update(cx, |preview, cx| {
+                    preview.zoomed = Some(source);
+                    cx.notify();
+                });
+            })
+            .child(element)
+            .into_any_element()
     }
+
+    /// 网络图片的占位符，点击时发出 [`PreviewEvent::OpenLink`]
+    fn remote_image(&mut self, label: &str, url: String, colors: &PreviewColors) -> AnyElement {
+        let preview = self.preview.clone();
+        div()
+            .id(("preview_image", self.next_id()))
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(rgb(colors.border))
+            .text_color(rgb(colors.link))
+            .cursor_pointer()
+            .child(format!("🌐 网络图片: {}", label))
+            .on_click(move |_, _window, cx| {
+                let event = PreviewEvent::OpenLink(url.clone());
+                let _ = preview.update(cx, |_preview, cx| cx.emit(event));
+            })
+            .into_any_element()
+    }
+}
+
+/// 无法显示的图片，显示图片的说明和地址
+fn broken_image(label: &str, colors: &PreviewColors) -> AnyElement {
+    div()
+        .px_2()
+        .py_1()
+        .border_1()
+        .border_color(rgb(colors.border))
+        .text_color(rgb(colors.muted))
+        .child(format!("🖼 图片无法显示: {}", label))
+        .into_any_element()
+}
+
+/// 本地或内嵌图片转换为 GPUI 的图片来源，网络图片和不支持的内嵌格式返回 `None`
+fn image_source(location: ImageLocation) -> Option<ImageSource> {
+    Some(match location {
+        ImageLocation::File(path) => ImageSource::from(path),
+        ImageLocation::Remote(_) => return None,
+        ImageLocation::Embedded(mime, data) => {
+            ImageSource::Image(Arc::new(Image::from_bytes(ImageFormat::from_mime_type(&mime)?, data)))
+        }
+    })
 }
 
 /// 渲染 Markdown 预览
-fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> Div {
+fn render_markdown_preview(
+    markdown: &str,
+    highlighter: &SyntaxHighlighter,
+    highlights: &[PreviewHighlight],
+    style: &PreviewStyle,
+    context: &mut RenderContext,
+) -> Div {
+    let colors = &style.colors;
     if markdown.is_empty() {
         return div()
             .text_sm()
             .p_4()
-            .text_color(rgb(0x999999))
+            .text_color(rgb(colors.muted))
             .text_center()
             .child("预览区域");
     }
     
     // 创建渲染元素 - 使用可变绑定
-    let mut element = div().text_sm().p_4();
+    let mut element = Blocks::new(div().p_4());
     let mut in_code_block = false;
     let mut code_block_content = String::new();
     let mut in_table = false;
     let mut table_rows = Vec::new();
     let mut code_block_language = String::new();
+    let mut in_math_block = false;
+    let mut math_content = String::new();
     
+    // 按行分组的搜索高亮
+    let mut line_highlights: HashMap<usize, Vec<&PreviewHighlight>> = HashMap::new();
+    for highlight in highlights {
+        line_highlights.entry(highlight.line_number).or_default().push(highlight);
+    }
+
     // 将 markdown 转换为 owned 字符串以避免生命周期问题
     let markdown_owned = markdown.to_string();
-    let mut lines = markdown_owned.lines();
+    let mut lines = markdown_owned.lines().enumerate();
     
-    while let Some(line) = lines.next() {
+    while let Some((line_index, line)) = lines.next() {
         let trimmed = line.trim();
+        // trimmed 在原始行中的起始位置
+        let indent = line.len() - line.trim_start().len();
+        let marks = line_highlights.get(&(line_index + 1)).map(Vec::as_slice).unwrap_or(&[]);
+        // 代码块和表格中的行对应整个代码块或表格（在最后一行之后加入）
+        context.line_items.push(element.count);
         
         // 处理代码块
         if trimmed.starts_with("```") {
@@ -98,13 +356,22 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
                 // 结束代码块
                 in_code_block = false;
                 let code_content = code_block_content.trim().to_string();
-                
+
+                if code_block_language == "mermaid" {
+                    let diagram_type = DiagramType::detect(code_content.lines().next().unwrap_or_default());
+                    let svg = MermaidRenderer::render(&code_content, diagram_type);
+                    element = element.child(div().mb_3().child(svg_image(svg).max_w_full()));
+                    code_block_content.clear();
+                    code_block_language.clear();
+                    continue;
+                }
+
                 // 使用语法高亮
                 let highlighted = if !code_block_language.is_empty() {
                     highlighter.highlight(&code_content, &code_block_language)
                 } else {
-                    format!("<pre style=\"background-color: #f5f5f5; padding: 1em; border-radius: 4px;\">{}</pre>", 
-                           html_escape(&code_content))
+                    format!("<pre style=\"background-color: {}; padding: 1em; border-radius: 4px;\">{}</pre>", 
+                           css_color(colors.code_background), html_escape(&code_content))
                 };
                 
                 element = element.child(
@@ -131,6 +398,23 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             code_block_content.push('\n');
             continue;
         }
+
+        // 块级公式中的行对应整个公式（在结束的 `$$` 之后加入）
+        if in_math_block {
+            match trimmed.strip_suffix("$$") {
+                Some(rest) => {
+                    math_content.push_str(rest);
+                    in_math_block = false;
+                    element = element.child(math_block(&math_content, style));
+                    math_content.clear();
+                }
+                None => {
+                    math_content.push_str(line);
+                    math_content.push('\n');
+                }
+            }
+            continue;
+        }
         
         // 处理表格
         if trimmed.starts_with('|') && trimmed.ends_with('|') {
@@ -143,13 +427,26 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
         } else if in_table {
             // 表格结束，渲染表格
             in_table = false;
-            element = element.child(render_table(&table_rows));
+            element = element.child(render_table(&table_rows, style));
             table_rows.clear();
         }
+
+        // 处理单独成行的块级公式 `$$...$$`，公式可以跨越多行
+        if let Some(rest) = trimmed.strip_prefix("$$") {
+            if let Some(formula) = rest.strip_suffix("$$") {
+                element = element.child(math_block(formula, style));
+                continue;
+            }
+            if !rest.contains("$$") {
+                in_math_block = true;
+                math_content = format!("{}\n", rest);
+                continue;
+            }
+        }
         
         // 处理任务列表
         if trimmed.starts_with("- [ ] ") {
-            let content = trimmed[6..].to_string();
+            let content = line_content(style, "", &trimmed[6..], indent + 6, marks, context);
             element = element.child(
                 div()
                     .ml_4()
@@ -163,7 +460,7 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
                                     .w_4()
                                     .h_4()
                                     .border_1()
-                                    .border_color(rgb(0x999999))
+                                    .border_color(rgb(colors.muted))
                                     .mr_2()
                             )
                             .child(content)
@@ -171,7 +468,7 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
             continue;
         } else if trimmed.starts_with("- [x] ") {
-            let content = trimmed[6..].to_string();
+            let content = line_content(style, "", &trimmed[6..], indent + 6, marks, context);
             element = element.child(
                 div()
                     .ml_4()
@@ -184,7 +481,7 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
                                 div()
                                     .w_4()
                                     .h_4()
-                                    .bg(rgb(0x0066cc))
+                                    .bg(rgb(colors.link))
                                     .mr_2()
                             )
                             .child(content)
@@ -193,34 +490,12 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             continue;
         }
         
-        // 处理图片
-        if trimmed.starts_with("![") && trimmed.contains("](") {
-            if let Some(start) = trimmed.find('[') {
-                if let Some(middle) = trimmed.find("](") {
-                    if let Some(end) = trimmed.find(')') {
-                        let alt_text = &trimmed[start + 1..middle];
-                        let url = &trimmed[middle + 2..end];
-                        element = element.child(
-                            div()
-                                .mb_3()
-                                .child(
-                                    div()
-                                        .text_color(rgb(0x0066cc))
-                                        .child(format!("🖼️ 图片: {} ({})", alt_text, url))
-                                )
-                        );
-                        continue;
-                    }
-                }
-            }
-        }
-        
         // 处理其他 Markdown 语法 - 使用 owned 字符串
         if trimmed.starts_with("# ") {
             // H1
-            let content = trimmed[2..].to_string();
+            let content = highlighted_text(style, trimmed[2..].to_string(), indent + 2, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_xl()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
@@ -228,9 +503,9 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("## ") {
             // H2
-            let content = trimmed[3..].to_string();
+            let content = highlighted_text(style, trimmed[3..].to_string(), indent + 3, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_lg()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
@@ -239,9 +514,9 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("### ") {
             // H3
-            let content = trimmed[4..].to_string();
+            let content = highlighted_text(style, trimmed[4..].to_string(), indent + 4, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_base()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
@@ -250,9 +525,9 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("#### ") {
             // H4
-            let content = trimmed[5..].to_string();
+            let content = highlighted_text(style, trimmed[5..].to_string(), indent + 5, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_sm()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
@@ -261,9 +536,9 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("##### ") {
             // H5
-            let content = trimmed[6..].to_string();
+            let content = highlighted_text(style, trimmed[6..].to_string(), indent + 6, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_xs()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
@@ -272,19 +547,19 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("###### ") {
             // H6
-            let content = trimmed[7..].to_string();
+            let content = highlighted_text(style, trimmed[7..].to_string(), indent + 7, 0, marks);
             element = element.child(
-                div()
+                heading(style)
                     .text_xs()
                     .font_weight(FontWeight::BOLD)
                     .mb_2()
                     .mt_2()
-                    .text_color(rgb(0x666666))
+                    .text_color(rgb(colors.muted))
                     .child(content)
             );
         } else if trimmed.starts_with("- ") {
             // 无序列表
-            let content = format!("• {}", &trimmed[2..]);
+            let content = line_content(style, "• ", &trimmed[2..], indent + 2, marks, context);
             element = element.child(
                 div()
                     .ml_4()
@@ -293,22 +568,21 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             );
         } else if trimmed.starts_with("1. ") || trimmed.starts_with("2. ") || trimmed.starts_with("3. ") {
             // 有序列表
-            if let Some(dot_pos) = trimmed.find('.') {
-                let content = format!("{}. {}", &trimmed[..dot_pos], &trimmed[dot_pos + 2..]);
-                element = element.child(
-                    div()
-                        .ml_4()
-                        .mb_1()
-                        .child(content)
-                );
-            }
+            let content = line_content(style, "", trimmed, indent, marks, context);
+            element = element.child(
+                div()
+                    .ml_4()
+                    .mb_1()
+                    .child(content)
+            );
         } else if trimmed.starts_with("> ") {
             // 引用
-            let content = trimmed[2..].to_string();
+            let content = line_content(style, "", &trimmed[2..], indent + 2, marks, context);
             element = element.child(
                 div()
                     .border_l_4()
-                    .border_color(rgb(0xdddddd))
+                    .border_color(rgb(colors.quote_border))
+                    .text_color(rgb(colors.quote_text))
                     .pl_3()
                     .ml_2()
                     .mb_3()
@@ -320,7 +594,7 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
             element = element.child(
                 div()
                     .mb_3()
-                    .child(line.to_string())
+                    .child(line_content(style, "", line, 0, marks, context))
             );
         } else {
             // 空行
@@ -328,12 +602,229 @@ fn render_markdown_preview(markdown: &str, highlighter: &SyntaxHighlighter) -> D
         }
     }
     
-    // 处理剩余的表格
+    // 处理剩余的表格和没有结束的块级公式
     if in_table && !table_rows.is_empty() {
-        element = element.child(render_table(&table_rows));
+        element = element.child(render_table(&table_rows, style));
+    }
+    if in_math_block {
+        element = element.child(math_block(&math_content, style));
     }
     
-    element
+    element.element
+}
+
+/// 预览中按顺序排列的块，记录块的数量以便按序号滚动
+struct Blocks {
+    element: Div,
+    count: usize,
+}
+
+impl Blocks {
+    fn new(element: Div) -> Self {
+        Self { element, count: 0 }
+    }
+
+    /// 添加一个块
+    fn child(mut self, child: impl IntoElement) -> Self {
+        self.element = self.element.child(child);
+        self.count += 1;
+        self
+    }
+}
+
+/// 为预览文本加上搜索高亮
+///
+/// `text` 从第 `prefix_len` 个字节开始对应原始行中从 `source_start` 开始的内容，
+/// 之前的部分（如列表符号）不参与高亮
+fn highlighted_text(style: &PreviewStyle, text: String, source_start: usize, prefix_len: usize, marks: &[&PreviewHighlight]) -> StyledText {
+    let highlights = mark_highlights(style, marks, source_start..usize::MAX, prefix_len, &text);
+    StyledText::new(text).with_highlights(highlights)
+}
+
+/// 原始行中 `source` 范围内的搜索高亮，转换为从 `text` 第 `offset` 个字节开始的位置
+fn mark_highlights(
+    style: &PreviewStyle,
+    marks: &[&PreviewHighlight],
+    source: Range<usize>,
+    offset: usize,
+    text: &str,
+) -> Vec<(Range<usize>, HighlightStyle)> {
+    marks
+        .iter()
+        .filter(|mark| mark.range.end > source.start && mark.range.start < source.end)
+        .filter_map(|mark| {
+            let start = mark.range.start.max(source.start) - source.start + offset;
+            let end = (mark.range.end.min(source.end) - source.start + offset).min(text.len());
+            (start < end && text.is_char_boundary(start) && text.is_char_boundary(end)).then(|| {
+                let color = if mark.current { style.colors.current_highlight } else { style.colors.highlight };
+                (start..end, HighlightStyle { background_color: Some(rgb(color).into()), ..Default::default() })
+            })
+        })
+        .collect()
+}
+
+/// 渲染包含链接的文字：链接只显示链接文字，点击时发出 [`PreviewEvent::OpenLink`] 或
+/// [`PreviewEvent::OpenWikiLink`]，指向的笔记不存在的 wiki 链接用次要颜色显示
+///
+/// 参数与 [`highlighted_text`] 相同，没有链接时直接返回高亮后的文字
+fn linked_text(
+    style: &PreviewStyle,
+    prefix: &str,
+    text: &str,
+    source_start: usize,
+    marks: &[&PreviewHighlight],
+    context: &mut RenderContext,
+) -> AnyElement {
+    // 链接的范围、显示文字的范围、点击时发出的事件以及目标是否存在
+    let mut links: Vec<(Range<usize>, Range<usize>, PreviewEvent, bool)> = find_inline_links(text)
+        .into_iter()
+        .map(|link| (link.range, link.label, PreviewEvent::OpenLink(link.dest), true))
+        .collect();
+    for link in find_wiki_links(text) {
+        let exists = link.note.is_empty() || resolve_wiki_link(&context.notes, &context.base_dir, &link.note).is_some();
+        links.push((link.range.clone(), link.label.clone(), PreviewEvent::OpenWikiLink(link), exists));
+    }
+    if links.is_empty() {
+        let content = format!("{}{}", prefix, text);
+        return highlighted_text(style, content, source_start, prefix.len(), marks).into_any_element();
+    }
+    links.sort_by_key(|(range, ..)| range.start);
+
+    // 显示的文字由原始行中的若干段组成，记录每段的显示位置和原始范围
+    let mut content = prefix.to_string();
+    let mut pieces = Vec::new();
+    let mut link_ranges = Vec::new();
+    let mut events = Vec::new();
+    let mut missing = Vec::new();
+    let mut position = 0;
+    for (range, label, event, exists) in links {
+        if range.start < position {
+            continue;
+        }
+        pieces.push((content.len(), position..range.start));
+        content.push_str(&text[position..range.start]);
+        let start = content.len();
+        pieces.push((start, label.clone()));
+        content.push_str(&text[label]);
+        if !exists {
+            missing.push(link_ranges.len());
+        }
+        link_ranges.push(start..content.len());
+        events.push(event);
+        position = range.end;
+    }
+    pieces.push((content.len(), position..text.len()));
+    content.push_str(&text[position..]);
+
+    let mut search: Vec<(Range<usize>, HighlightStyle)> = pieces
+        .into_iter()
+        .flat_map(|(offset, piece)| {
+            let source = source_start + piece.start..source_start + piece.end;
+            mark_highlights(style, marks, source, offset, &content)
+        })
+        .collect();
+    search.sort_by_key(|(range, _)| range.start);
+    let link_style = |color: u32| HighlightStyle {
+        color: Some(rgb(color).into()),
+        underline: Some(UnderlineStyle { thickness: px(1.0), ..Default::default() }),
+        ..Default::default()
+    };
+    let links = link_ranges.iter().enumerate().map(|(index, range)| {
+        let color = if missing.contains(&index) { style.colors.muted } else { style.colors.link };
+        (range.clone(), link_style(color))
+    });
+    let highlights: Vec<_> = combine_highlights(links, search).collect();
+
+    let preview = context.preview.clone();
+    let id = context.next_id();
+    InteractiveText::new(("preview_text", id), StyledText::new(content).with_highlights(highlights))
+        .on_click(link_ranges, move |index, _window, cx| {
+            let event = events[index].clone();
+            let _ = preview.update(cx, |_preview, cx| cx.emit(event));
+        })
+        .into_any_element()
+}
+
+/// 渲染一行中的行内内容，包含图片或公式时文字、图片和公式按原来的顺序排列
+///
+/// `prefix` 是显示在开头、不参与高亮的内容（如列表符号），`text` 对应原始行中从 `source_start` 开始的内容
+fn line_content(
+    style: &PreviewStyle,
+    prefix: &str,
+    text: &str,
+    source_start: usize,
+    marks: &[&PreviewHighlight],
+    context: &mut RenderContext,
+) -> AnyElement {
+    let parts = split_images(text);
+    let has_images = parts.iter().any(|part| matches!(part, LinePart::Image(_)));
+    if !has_images && LatexRenderer::find_math(text).is_empty() {
+        return linked_text(style, prefix, text, source_start, marks, context);
+    }
+
+    let mut element = div().flex().flex_wrap().items_end();
+    if !prefix.is_empty() {
+        element = element.child(prefix.to_string());
+    }
+    for part in parts {
+        match part {
+            LinePart::Text(range) => {
+                // 公式之间的文字仍然支持链接和搜索高亮
+                let part = &text[range.clone()];
+                let mut position = 0;
+                for span in LatexRenderer::find_math(part) {
+                    if span.range.start > position {
+                        let start = source_start + range.start + position;
+                        element = element.child(
+                            linked_text(style, "", &part[position..span.range.start], start, marks, context)
+                        );
+                    }
+                    element = element.child(math_element(&span.formula, span.display, style));
+                    position = span.range.end;
+                }
+                if position < part.len() {
+                    let start = source_start + range.start + position;
+                    element = element.child(linked_text(style, "", &part[position..], start, marks, context));
+                }
+            }
+            LinePart::Image(image) => element = element.child(context.image(&image, style)),
+        }
+    }
+    element.into_any_element()
+}
+
+/// 渲染公式：能排版时显示为 SVG，否则用代码字体显示公式源码
+fn math_element(formula: &str, display: bool, style: &PreviewStyle) -> AnyElement {
+    let colors = &style.colors;
+    match LatexRenderer::render_svg(formula, display) {
+        // 图片中的 `currentColor` 不会继承预览的文字颜色
+        Some(svg) => svg_image(svg.replace("currentColor", &css_color(colors.text))).into_any_element(),
+        None => div()
+            .px_1()
+            .bg(rgb(colors.code_background))
+            .font_family(style.code_font_family.clone())
+            .child(formula.trim().to_string())
+            .into_any_element(),
+    }
+}
+
+/// 块级公式，居中显示
+fn math_block(formula: &str, style: &PreviewStyle) -> Div {
+    div().mb_3().flex().justify_center().child(math_element(formula, true, style))
+}
+
+/// 把 SVG 显示为图片
+fn svg_image(svg: String) -> Img {
+    img(ImageSource::Image(Arc::new(Image::from_bytes(ImageFormat::Svg, svg.into_bytes()))))
+}
+
+/// 标题容器，使用主题的标题颜色和字体
+fn heading(style: &PreviewStyle) -> Div {
+    let element = div().text_color(rgb(style.colors.heading));
+    match &style.heading_font_family {
+        Some(font_family) => element.font_family(font_family.clone()),
+        None => element,
+    }
 }
 
 /// HTML 转义函数
@@ -346,8 +837,9 @@ fn html_escape(text: &str) -> String {
 }
 
 /// 渲染表格
-fn render_table(rows: &[String]) -> Div {
-    let mut table_element = div().mb_3().border_1().border_color(rgb(0xdddddd));
+fn render_table(rows: &[String], style: &PreviewStyle) -> Div {
+    let colors = &style.colors;
+    let mut table_element = div().mb_3().border_1().border_color(rgb(colors.border));
     
     for (i, row) in rows.iter().enumerate() {
         let mut row_element = div().flex();
@@ -360,8 +852,8 @@ fn render_table(rows: &[String]) -> Div {
                     .flex_1()
                     .p_2()
                     .border_r(px(1.0))
-                    .border_color(rgb(0xdddddd))
-                    .bg(if i == 0 { rgb(0xf5f5f5) } else { rgb(0xffffff) })
+                    .border_color(rgb(colors.border))
+                    .bg(if i == 0 { rgb(colors.table_header_background) } else { rgb(colors.background) })
                     .font_weight(if i == 0 { FontWeight::BOLD } else { FontWeight::NORMAL })
                     .child(cell_content)
             );